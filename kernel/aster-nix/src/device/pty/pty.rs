@@ -6,7 +6,7 @@ use ringbuf::{ring_buffer::RbBase, HeapRb, Rb};
 
 use crate::{
     device::tty::{line_discipline::LineDiscipline, new_job_control_and_ldisc},
-    events::IoEvents,
+    events::{IoEvents, Observer},
     fs::{
         device::{Device, DeviceId, DeviceType},
         devpts::DevPts,
@@ -86,6 +86,21 @@ impl PtyMaster {
         poll_status
     }
 
+    /// Registers the observer of the slave, whose input is the output of the master.
+    fn slave_register_observer(&self, observer: Weak<dyn Observer<IoEvents>>, mask: IoEvents) {
+        self.output.register_observer(observer.clone(), mask);
+        self.pollee.register_observer(observer, mask);
+    }
+
+    fn slave_unregister_observer(
+        &self,
+        observer: &Weak<dyn Observer<IoEvents>>,
+    ) -> Option<Weak<dyn Observer<IoEvents>>> {
+        let output_observer = self.output.unregister_observer(observer);
+        let input_observer = self.pollee.unregister_observer(observer);
+        output_observer.or(input_observer)
+    }
+
     pub(super) fn slave_buf_len(&self) -> usize {
         self.output.buffer_len()
     }
@@ -189,9 +204,9 @@ impl FileIo for PtyMaster {
                 };
 
                 let fd = {
-                    let mut file_table = current.file_table().lock();
+                    let file_table = current.file_table();
                     // TODO: deal with the O_CLOEXEC flag
-                    file_table.insert(slave, FdFlags::empty())
+                    file_table.insert(slave, FdFlags::empty())?
                 };
                 Ok(fd)
             }
@@ -262,6 +277,25 @@ impl FileIo for PtyMaster {
 
         poll_status
     }
+
+    fn register_observer(
+        &self,
+        observer: Weak<dyn Observer<IoEvents>>,
+        mask: IoEvents,
+    ) -> Result<()> {
+        self.pollee.register_observer(observer.clone(), mask);
+        self.output.register_observer(observer, mask);
+        Ok(())
+    }
+
+    fn unregister_observer(
+        &self,
+        observer: &Weak<dyn Observer<IoEvents>>,
+    ) -> Option<Weak<dyn Observer<IoEvents>>> {
+        let input_observer = self.pollee.unregister_observer(observer);
+        let output_observer = self.output.unregister_observer(observer);
+        input_observer.or(output_observer)
+    }
 }
 
 impl Terminal for PtyMaster {
@@ -352,6 +386,22 @@ impl FileIo for PtySlave {
         self.master().slave_poll(mask, poller)
     }
 
+    fn register_observer(
+        &self,
+        observer: Weak<dyn Observer<IoEvents>>,
+        mask: IoEvents,
+    ) -> Result<()> {
+        self.master().slave_register_observer(observer, mask);
+        Ok(())
+    }
+
+    fn unregister_observer(
+        &self,
+        observer: &Weak<dyn Observer<IoEvents>>,
+    ) -> Option<Weak<dyn Observer<IoEvents>>> {
+        self.master().slave_unregister_observer(observer)
+    }
+
     fn ioctl(&self, cmd: IoctlCmd, arg: usize) -> Result<i32> {
         match cmd {
             IoctlCmd::TCGETS
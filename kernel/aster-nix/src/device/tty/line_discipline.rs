@@ -9,7 +9,7 @@ use ringbuf::{ring_buffer::RbBase, Rb, StaticRb};
 
 use super::termio::{KernelTermios, WinSize, CC_C_CHAR};
 use crate::{
-    events::IoEvents,
+    events::{IoEvents, Observer},
     prelude::*,
     process::signal::{
         constants::{SIGINT, SIGQUIT},
@@ -292,6 +292,17 @@ impl LineDiscipline {
         self.pollee.poll(mask, poller)
     }
 
+    pub fn register_observer(&self, observer: Weak<dyn Observer<IoEvents>>, mask: IoEvents) {
+        self.pollee.register_observer(observer, mask);
+    }
+
+    pub fn unregister_observer(
+        &self,
+        observer: &Weak<dyn Observer<IoEvents>>,
+    ) -> Option<Weak<dyn Observer<IoEvents>>> {
+        self.pollee.unregister_observer(observer)
+    }
+
     /// returns immediately with the lesser of the number of bytes available or the number of bytes requested.
     /// If no bytes are available, completes immediately, returning 0.
     fn poll_read(&self, dst: &mut [u8]) -> usize {
@@ -7,7 +7,7 @@ use spin::Once;
 
 use self::{driver::TtyDriver, line_discipline::LineDiscipline};
 use crate::{
-    events::IoEvents,
+    events::{IoEvents, Observer},
     fs::{
         device::{Device, DeviceId, DeviceType},
         inode_handle::FileIo,
@@ -91,6 +91,22 @@ impl FileIo for Tty {
         self.ldisc.poll(mask, poller)
     }
 
+    fn register_observer(
+        &self,
+        observer: Weak<dyn Observer<IoEvents>>,
+        mask: IoEvents,
+    ) -> Result<()> {
+        self.ldisc.register_observer(observer, mask);
+        Ok(())
+    }
+
+    fn unregister_observer(
+        &self,
+        observer: &Weak<dyn Observer<IoEvents>>,
+    ) -> Option<Weak<dyn Observer<IoEvents>>> {
+        self.ldisc.unregister_observer(observer)
+    }
+
     fn ioctl(&self, cmd: IoctlCmd, arg: usize) -> Result<i32> {
         match cmd {
             IoctlCmd::TCGETS => {
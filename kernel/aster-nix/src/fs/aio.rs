@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The contexts of the legacy Linux asynchronous I/O (AIO).
+//!
+//! An AIO context is created by `io_setup`. It collects the completion events
+//! of the requests submitted by `io_submit`, which are reaped by `io_getevents`.
+//!
+//! The reads and writes of the files opened with `O_DIRECT` are submitted to
+//! the block device without waiting, and their events are queued when the I/O
+//! completes, which may be in the interrupt context. As in Linux, the other
+//! requests are executed when they are submitted.
+
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use ostd::sync::WaitQueue;
+
+use crate::{prelude::*, process::signal::Pauser};
+
+/// The ID of an AIO context, i.e., `aio_context_t` in Linux.
+pub type AioContextId = u64;
+
+/// The completion event of an AIO request, i.e., `struct io_event` in Linux.
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+pub struct IoEvent {
+    /// The `aio_data` field of the request.
+    pub data: u64,
+    /// The user-space address of the request.
+    pub obj: u64,
+    /// The result of the request, or the negated error number if it fails.
+    pub res: i64,
+    pub res2: i64,
+}
+
+/// An AIO context.
+pub struct AioContext {
+    /// The maximum number of the requests whose events have not been reaped.
+    max_events: usize,
+    /// The state of the requests, which is also updated in the interrupt context.
+    inner: SpinLock<Inner>,
+    pauser: Arc<Pauser>,
+    /// Wakes up `destroy` when a request completes.
+    completion_queue: WaitQueue,
+    is_destroyed: AtomicBool,
+}
+
+struct Inner {
+    /// The completion events that have not been reaped.
+    events: VecDeque<IoEvent>,
+    /// The user-space addresses of the requests that are being executed.
+    in_flight: Vec<u64>,
+}
+
+impl AioContext {
+    /// Creates a context that can hold `max_events` events.
+    pub fn new(max_events: usize) -> Arc<Self> {
+        Arc::new(Self {
+            max_events,
+            inner: SpinLock::new(Inner {
+                events: VecDeque::new(),
+                in_flight: Vec::new(),
+            }),
+            pauser: Pauser::new(),
+            completion_queue: WaitQueue::new(),
+            is_destroyed: AtomicBool::new(false),
+        })
+    }
+
+    /// Returns the maximum number of events that the context can hold.
+    pub fn max_events(&self) -> usize {
+        self.max_events
+    }
+
+    /// Reserves room for the event of the request at `obj` that is about to be executed.
+    ///
+    /// Each successful reservation must be followed by a call to [`Self::complete`].
+    ///
+    /// # Errors
+    ///
+    /// This method returns `EAGAIN` if the context is full, or `EINVAL` if it is destroyed.
+    pub fn reserve(&self, obj: u64) -> Result<()> {
+        let mut inner = self.inner.lock_irq_disabled();
+        if self.is_destroyed.load(Ordering::Acquire) {
+            return_errno_with_message!(Errno::EINVAL, "the AIO context is destroyed");
+        }
+        if inner.events.len() + inner.in_flight.len() >= self.max_events {
+            return_errno_with_message!(Errno::EAGAIN, "the AIO context is full");
+        }
+        inner.in_flight.push(obj);
+        Ok(())
+    }
+
+    /// Completes a request whose room has been reserved with `event`.
+    ///
+    /// This method can be called in the interrupt context.
+    pub fn complete(&self, event: IoEvent) {
+        let mut inner = self.inner.lock_irq_disabled();
+        let pos = inner.in_flight.iter().position(|obj| *obj == event.obj);
+        debug_assert!(pos.is_some());
+        if let Some(pos) = pos {
+            inner.in_flight.swap_remove(pos);
+        }
+        inner.events.push_back(event);
+        drop(inner);
+
+        self.pauser.resume_all();
+        self.completion_queue.wake_all();
+    }
+
+    /// Returns whether the request at `obj` is being executed.
+    pub fn is_in_flight(&self, obj: u64) -> bool {
+        self.inner.lock_irq_disabled().in_flight.contains(&obj)
+    }
+
+    /// Waits until there are at least `min_nr` events, and reaps at most `nr` events.
+    ///
+    /// If the `timeout` expires, the events that are available are reaped.
+    pub fn get_events(
+        &self,
+        min_nr: usize,
+        nr: usize,
+        timeout: Option<&Duration>,
+    ) -> Result<Vec<IoEvent>> {
+        let cond = || {
+            if self.is_destroyed.load(Ordering::Acquire) {
+                return Some(Err(Error::with_message(
+                    Errno::EINVAL,
+                    "the AIO context is destroyed",
+                )));
+            }
+            let mut inner = self.inner.lock_irq_disabled();
+            if inner.events.len() < min_nr {
+                return None;
+            }
+            Some(Ok(Self::pop_events(&mut inner, nr)))
+        };
+
+        let res = if let Some(timeout) = timeout {
+            self.pauser.pause_until_or_timeout(cond, timeout)
+        } else {
+            self.pauser.pause_until(cond)
+        };
+        match res {
+            Ok(res) => res,
+            Err(err) if err.error() == Errno::ETIME => {
+                Ok(Self::pop_events(&mut self.inner.lock_irq_disabled(), nr))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn pop_events(inner: &mut Inner, nr: usize) -> Vec<IoEvent> {
+        let nr = nr.min(inner.events.len());
+        inner.events.drain(..nr).collect()
+    }
+
+    /// Destroys the context, which wakes up the waiters with `EINVAL`.
+    ///
+    /// This method returns after the requests in flight complete, since their I/O may
+    /// still transfer data to or from the user buffers.
+    pub fn destroy(&self) {
+        self.is_destroyed.store(true, Ordering::Release);
+        self.pauser.resume_all();
+
+        self.completion_queue.wait_until(|| {
+            let inner = self.inner.lock_irq_disabled();
+            inner.in_flight.is_empty().then_some(())
+        });
+    }
+}
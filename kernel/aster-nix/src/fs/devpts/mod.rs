@@ -5,7 +5,7 @@
 use core::time::Duration;
 
 use aster_util::slot_vec::SlotVec;
-use id_alloc::IdAlloc;
+use id_alloc::AtomicBitmap;
 
 use self::{ptmx::Ptmx, slave::PtySlaveInode};
 use crate::{
@@ -14,7 +14,7 @@ use crate::{
         device::{Device, DeviceId, DeviceType},
         utils::{
             DirentVisitor, FileSystem, FsFlags, Inode, InodeMode, InodeType, IoctlCmd, Metadata,
-            SuperBlock, NAME_MAX,
+            RenameFlags, SuperBlock, NAME_MAX,
         },
     },
     prelude::*,
@@ -43,7 +43,7 @@ const MAX_PTY_NUM: usize = 4096;
 pub struct DevPts {
     sb: SuperBlock,
     root: Arc<RootInode>,
-    index_alloc: Mutex<IdAlloc>,
+    index_alloc: AtomicBitmap,
     this: Weak<Self>,
 }
 
@@ -52,7 +52,7 @@ impl DevPts {
         Arc::new_cyclic(|weak_self| Self {
             sb: SuperBlock::new(DEVPTS_MAGIC, BLOCK_SIZE, NAME_MAX),
             root: RootInode::new(weak_self.clone()),
-            index_alloc: Mutex::new(IdAlloc::with_capacity(MAX_PTY_NUM)),
+            index_alloc: AtomicBitmap::new(MAX_PTY_NUM),
             this: weak_self.clone(),
         })
     }
@@ -61,8 +61,7 @@ impl DevPts {
     fn create_master_slave_pair(&self) -> Result<(Arc<PtyMaster>, Arc<PtySlaveInode>)> {
         let index = self
             .index_alloc
-            .lock()
-            .alloc()
+            .set_next_zero(0)
             .ok_or_else(|| Error::with_message(Errno::EIO, "cannot alloc index"))?;
 
         let (master, slave) = crate::device::new_pty_pair(index as u32, self.root.ptmx.clone())?;
@@ -79,7 +78,7 @@ impl DevPts {
     pub fn remove_slave(&self, index: u32) -> Option<Arc<PtySlaveInode>> {
         let removed_slave = self.root.remove_slave(&index.to_string());
         if removed_slave.is_some() {
-            self.index_alloc.lock().free(index as usize);
+            self.index_alloc.test_and_clear(index as usize);
         }
         removed_slave
     }
@@ -101,6 +100,10 @@ impl FileSystem for DevPts {
     fn flags(&self) -> FsFlags {
         FsFlags::empty()
     }
+
+    fn type_name(&self) -> &'static str {
+        "devpts"
+    }
 }
 
 struct RootInode {
@@ -291,7 +294,13 @@ impl Inode for RootInode {
         Ok(inode)
     }
 
-    fn rename(&self, old_name: &str, target: &Arc<dyn Inode>, new_name: &str) -> Result<()> {
+    fn rename(
+        &self,
+        old_name: &str,
+        target: &Arc<dyn Inode>,
+        new_name: &str,
+        flags: RenameFlags,
+    ) -> Result<()> {
         Err(Error::new(Errno::EPERM))
     }
 
@@ -66,7 +66,7 @@ impl EpollFile {
         self.warn_unsupported_flags(&ep_flags);
 
         let current = current!();
-        let file_table = current.file_table().lock();
+        let file_table = current.file_table();
         let file_table_entry = file_table.get_entry(fd)?;
         let file = file_table_entry.file();
         let weak_file = Arc::downgrade(file);
@@ -83,7 +83,6 @@ impl EpollFile {
         // Register self to the file table entry
         file_table_entry.register_observer(self.weak_self.clone() as _);
         let file = file.clone();
-        drop(file_table);
         drop(interest);
 
         // Add the new entry to the ready list if the file is ready
@@ -154,7 +153,7 @@ impl EpollFile {
 
     fn unregister_from_file_table_entry(&self, fd: FileDesc) {
         let current = current!();
-        let file_table = current.file_table().lock();
+        let file_table = current.file_table();
         if let Ok(entry) = file_table.get_entry(fd) {
             entry.unregister_observer(&(self.weak_self.clone() as _));
         }
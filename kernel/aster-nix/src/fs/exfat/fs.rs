@@ -403,12 +403,22 @@ impl FileSystem for ExfatFS {
     }
 
     fn sb(&self) -> SuperBlock {
-        SuperBlock::new(BOOT_SIGNATURE as u64, self.sector_size(), MAX_NAME_LENGTH)
+        let mut sb = SuperBlock::new(BOOT_SIGNATURE as u64, self.cluster_size(), MAX_NAME_LENGTH);
+        // The blocks are the clusters in the cluster heap. There is no inode table in exFAT,
+        // so the number of inodes is left zero as in Linux.
+        sb.blocks = (self.super_block.num_clusters - EXFAT_RESERVED_CLUSTERS) as usize;
+        sb.bfree = self.num_free_clusters() as usize;
+        sb.bavail = sb.bfree;
+        sb
     }
 
     fn flags(&self) -> FsFlags {
         FsFlags::DENTRY_UNEVICTABLE
     }
+
+    fn type_name(&self) -> &'static str {
+        "exfat"
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -32,7 +32,7 @@ use crate::{
         exfat::{dentry::ExfatDentryIterator, fat::ExfatChain, fs::ExfatFS},
         utils::{
             DirentVisitor, Inode, InodeMode, InodeType, IoctlCmd, Metadata, PageCache,
-            PageCacheBackend,
+            PageCacheBackend, RenameFlags,
         },
     },
     prelude::*,
@@ -1600,7 +1600,16 @@ impl Inode for ExfatInode {
         Ok(inode)
     }
 
-    fn rename(&self, old_name: &str, target: &Arc<dyn Inode>, new_name: &str) -> Result<()> {
+    fn rename(
+        &self,
+        old_name: &str,
+        target: &Arc<dyn Inode>,
+        new_name: &str,
+        flags: RenameFlags,
+    ) -> Result<()> {
+        if !(flags - RenameFlags::NOREPLACE).is_empty() {
+            return_errno_with_message!(Errno::EINVAL, "the rename flags are not supported");
+        }
         if old_name == "." || old_name == ".." || new_name == "." || new_name == ".." {
             return_errno!(Errno::EISDIR);
         }
@@ -1622,6 +1631,9 @@ impl Inode for ExfatInode {
         let up_old_name = fs.upcase_table().lock().str_to_upcase(old_name)?;
         let up_new_name = fs.upcase_table().lock().str_to_upcase(new_name)?;
         if self.inner.read().ino == target_.inner.read().ino && up_old_name.eq(&up_new_name) {
+            if flags.contains(RenameFlags::NOREPLACE) {
+                return_errno_with_message!(Errno::EEXIST, "the new name exists");
+            }
             return Ok(());
         }
 
@@ -1637,6 +1649,10 @@ impl Inode for ExfatInode {
             .lookup_by_name(new_name, false, &fs_guard);
         // Check for the corner cases.
         if let Ok(ref exist_inode) = lookup_exist_result {
+            // The file system is locked, so no entry can be created concurrently.
+            if flags.contains(RenameFlags::NOREPLACE) {
+                return_errno_with_message!(Errno::EEXIST, "the new name exists");
+            }
             check_corner_cases_for_rename(&old_inode, exist_inode)?;
         }
 
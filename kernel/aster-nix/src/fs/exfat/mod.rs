@@ -33,7 +33,10 @@ mod test {
                 constants::{EXFAT_RESERVED_CLUSTERS, MAX_NAME_LENGTH},
                 ExfatFS, ExfatMountOptions,
             },
-            utils::{generate_random_operation, new_fs_in_memory, Inode, InodeMode, InodeType},
+            utils::{
+                generate_random_operation, new_fs_in_memory, Inode, InodeMode, InodeType,
+                RenameFlags,
+            },
         },
         prelude::*,
     };
@@ -464,7 +467,7 @@ mod test {
         let _ = a_inode.write_at(0, &buf);
 
         let new_name = "HELLO.TXT";
-        let rename_result = root.rename(file_name, &root.clone(), new_name);
+        let rename_result = root.rename(file_name, &root.clone(), new_name, RenameFlags::empty());
         assert!(
             rename_result.is_ok(),
             "Failed to rename: {:?}",
@@ -510,7 +513,12 @@ mod test {
         let sub_folder = create_folder(root.clone(), sub_folder_name);
         let sub_file_name = "A.TXT";
         create_file(sub_folder.clone(), sub_file_name);
-        let rename_result = sub_folder.rename(sub_file_name, &root.clone(), sub_file_name);
+        let rename_result = sub_folder.rename(
+            sub_file_name,
+            &root.clone(),
+            sub_file_name,
+            RenameFlags::empty(),
+        );
         assert!(
             rename_result.is_ok(),
             "Fs failed to rename file between different directories: {:?}",
@@ -533,16 +541,23 @@ mod test {
         );
 
         // test rename file when the new_name is exist
-        let rename_file_to_itself = root.rename(new_name, &root.clone(), new_name);
+        let rename_file_to_itself =
+            root.rename(new_name, &root.clone(), new_name, RenameFlags::empty());
         assert!(rename_file_to_itself.is_ok(), "Fail to rename to itself");
 
-        let rename_file_to_an_exist_folder = root.rename(new_name, &root.clone(), sub_folder_name);
+        let rename_file_to_an_exist_folder = root.rename(
+            new_name,
+            &root.clone(),
+            sub_folder_name,
+            RenameFlags::empty(),
+        );
         assert!(
             rename_file_to_an_exist_folder.is_err(),
             "Fs deal with rename a file to an exist directory incorrectly"
         );
 
-        let rename_file_to_an_exist_file = root.rename(new_name, &root.clone(), sub_file_name);
+        let rename_file_to_an_exist_file =
+            root.rename(new_name, &root.clone(), sub_file_name, RenameFlags::empty());
         assert!(
             rename_file_to_an_exist_file.is_ok(),
             "Fail to rename a file to another exist file",
@@ -570,7 +585,12 @@ mod test {
 
         // Test rename a folder, the sub-directories should remain.
         let new_folder_name = "NEW_FOLDER";
-        let rename_result = root.rename(old_folder_name, &root.clone(), new_folder_name);
+        let rename_result = root.rename(
+            old_folder_name,
+            &root.clone(),
+            new_folder_name,
+            RenameFlags::empty(),
+        );
 
         assert!(
             rename_result.is_ok(),
@@ -596,19 +616,31 @@ mod test {
         let exist_file_name = "EXIST_FILE.TXT";
         create_file(root.clone(), exist_file_name);
 
-        let rename_dir_to_an_exist_file =
-            root.rename(new_folder_name, &root.clone(), exist_file_name);
+        let rename_dir_to_an_exist_file = root.rename(
+            new_folder_name,
+            &root.clone(),
+            exist_file_name,
+            RenameFlags::empty(),
+        );
 
         assert!(rename_dir_to_an_exist_file.is_err());
 
-        let rename_dir_to_an_exist_no_empty_folder =
-            root.rename(new_folder_name, &root.clone(), exist_folder_name);
+        let rename_dir_to_an_exist_no_empty_folder = root.rename(
+            new_folder_name,
+            &root.clone(),
+            exist_folder_name,
+            RenameFlags::empty(),
+        );
         assert!(rename_dir_to_an_exist_no_empty_folder.is_err());
 
         let _ = exist_folder.unlink(child_file_name);
 
-        let rename_dir_to_an_exist_empty_folder =
-            root.rename(new_folder_name, &root.clone(), exist_folder_name);
+        let rename_dir_to_an_exist_empty_folder = root.rename(
+            new_folder_name,
+            &root.clone(),
+            exist_folder_name,
+            RenameFlags::empty(),
+        );
         assert!(rename_dir_to_an_exist_empty_folder.is_ok());
     }
 
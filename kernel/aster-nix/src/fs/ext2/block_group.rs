@@ -5,7 +5,7 @@ use id_alloc::IdAlloc;
 use super::{
     block_ptr::Ext2Bid,
     fs::Ext2,
-    inode::{Inode, InodeDesc, RawInode},
+    inode::{Inode, InodeDesc, RawInode, RawInodeExtra},
     prelude::*,
     super_block::SuperBlock,
 };
@@ -126,14 +126,17 @@ impl BlockGroup {
     /// This method may load the raw inode metadata from block device.
     fn load_inode(&self, inode_idx: u32) -> Result<Arc<Inode>> {
         let fs = self.fs();
-        let raw_inode = {
-            let offset = (inode_idx as usize) * fs.inode_size();
-            self.raw_inodes_cache
-                .pages()
-                .read_val::<RawInode>(offset)
-                .unwrap()
-        };
-        let inode_desc = Dirty::new(InodeDesc::try_from(raw_inode)?);
+        let offset = (inode_idx as usize) * fs.inode_size();
+        let raw_inode = self
+            .raw_inodes_cache
+            .pages()
+            .read_val::<RawInode>(offset)
+            .unwrap();
+        let mut inode_desc = InodeDesc::try_from(raw_inode)?;
+        if let Some(raw_inode_extra) = self.read_raw_inode_extra(offset) {
+            raw_inode_extra.load_times(&mut inode_desc);
+        }
+        let inode_desc = Dirty::new(inode_desc);
         let ino = inode_idx + self.idx as u32 * fs.inodes_per_group() + 1;
 
         Ok(Inode::new(ino, self.idx, inode_desc, Arc::downgrade(&fs)))
@@ -209,12 +212,38 @@ impl BlockGroup {
     }
 
     /// Writes back the raw inode metadata to the raw inode metadata cache.
-    pub fn sync_raw_inode(&self, inode_idx: u32, raw_inode: &RawInode) {
+    ///
+    /// If the inodes are large enough, the nanoseconds of the timestamps are also written
+    /// back to the extra fields.
+    pub fn sync_raw_inode(&self, inode_idx: u32, inode: &InodeDesc) {
         let offset = (inode_idx as usize) * self.fs().inode_size();
         self.raw_inodes_cache
             .pages()
-            .write_val(offset, raw_inode)
+            .write_val(offset, &RawInode::from(inode))
+            .unwrap();
+        if let Some(mut raw_inode_extra) = self.read_raw_inode_extra(offset) {
+            raw_inode_extra.store_times(inode);
+            self.raw_inodes_cache
+                .pages()
+                .write_val(offset + core::mem::size_of::<RawInode>(), &raw_inode_extra)
+                .unwrap();
+        }
+    }
+
+    /// Reads the extra fields of the raw inode at `offset`.
+    ///
+    /// Returns `None` if the inodes are too small to hold the extra fields.
+    fn read_raw_inode_extra(&self, offset: usize) -> Option<RawInodeExtra> {
+        let raw_inode_size = core::mem::size_of::<RawInode>();
+        if self.fs().inode_size() < raw_inode_size + core::mem::size_of::<RawInodeExtra>() {
+            return None;
+        }
+        let raw_inode_extra = self
+            .raw_inodes_cache
+            .pages()
+            .read_val::<RawInodeExtra>(offset + raw_inode_size)
             .unwrap();
+        Some(raw_inode_extra)
     }
 
     /// Writes back the metadata of this group.
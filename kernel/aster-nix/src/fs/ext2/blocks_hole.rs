@@ -2,21 +2,19 @@
 
 #![allow(dead_code)]
 
-use bitvec::prelude::BitVec;
+use id_alloc::Bitmap;
 
-/// A blocks hole descriptor implemented by the `BitVec`.
+/// A blocks hole descriptor implemented by the `Bitmap`.
 ///
 /// The true bit implies that the block is a hole, and conversely.
-pub(super) struct BlocksHoleDesc(BitVec);
+pub(super) struct BlocksHoleDesc(Bitmap);
 
 impl BlocksHoleDesc {
     /// Constructs a blocks hole descriptor with initial size.
     ///
     /// The `initial_size` usually is the number of blocks for a file.
     pub fn new(initial_size: usize) -> Self {
-        let mut bit_vec = BitVec::with_capacity(initial_size);
-        bit_vec.resize(initial_size, false);
-        Self(bit_vec)
+        Self(Bitmap::new(initial_size))
     }
 
     /// Returns the size.
@@ -37,7 +35,7 @@ impl BlocksHoleDesc {
     ///
     /// If the `idx` is out of bounds, this method will panic.
     pub fn is_hole(&self, idx: usize) -> bool {
-        self.0[idx]
+        self.0.get(idx)
     }
 
     /// Marks the block `idx` as a hole.
@@ -77,6 +77,11 @@ impl DirEntry {
         FileType::from(DirEntryFileType::try_from(self.header.file_type).unwrap())
     }
 
+    /// Modifies the type.
+    pub fn set_type(&mut self, file_type: FileType) {
+        self.header.file_type = DirEntryFileType::from(file_type) as _;
+    }
+
     /// Returns the distance to the next entry.
     pub fn record_len(&self) -> usize {
         self.header.record_len as _
@@ -5,7 +5,7 @@
 use super::{
     block_group::{BlockGroup, RawGroupDescriptor},
     block_ptr::Ext2Bid,
-    inode::{FilePerm, FileType, Inode, InodeDesc, RawInode},
+    inode::{FilePerm, FileType, Inode, InodeDesc},
     prelude::*,
     super_block::{RawSuperBlock, SuperBlock, SUPER_BLOCK_OFFSET},
 };
@@ -194,7 +194,7 @@ impl Ext2 {
     pub(super) fn sync_inode(&self, ino: u32, inode: &InodeDesc) -> Result<()> {
         let (_, block_group) = self.block_group_of_ino(ino)?;
         let inode_idx = self.inode_idx(ino);
-        block_group.sync_raw_inode(inode_idx, &RawInode::from(inode));
+        block_group.sync_raw_inode(inode_idx, inode);
         Ok(())
     }
 
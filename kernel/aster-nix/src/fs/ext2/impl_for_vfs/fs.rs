@@ -28,6 +28,10 @@ impl FileSystem for Ext2 {
     fn flags(&self) -> FsFlags {
         FsFlags::empty()
     }
+
+    fn type_name(&self) -> &'static str {
+        "ext2"
+    }
 }
 
 impl From<RwMutexReadGuard<'_, Dirty<Ext2SuperBlock>>> for SuperBlock {
@@ -37,10 +41,18 @@ impl From<RwMutexReadGuard<'_, Dirty<Ext2SuperBlock>>> for SuperBlock {
             bsize: ext2_sb.block_size(),
             blocks: ext2_sb.total_blocks() as _,
             bfree: ext2_sb.free_blocks_count() as _,
-            bavail: ext2_sb.free_blocks_count() as _,
+            bavail: ext2_sb
+                .free_blocks_count()
+                .saturating_sub(ext2_sb.reserved_blocks_count()) as _,
             files: ext2_sb.total_inodes() as _,
             ffree: ext2_sb.free_inodes_count() as _,
-            fsid: 0, // TODO
+            fsid: {
+                // Like Linux, the ID is folded from the UUID.
+                let uuid = ext2_sb.uuid();
+                let (high, low) = uuid.split_at(8);
+                u64::from_le_bytes(high.try_into().unwrap())
+                    ^ u64::from_le_bytes(low.try_into().unwrap())
+            },
             namelen: NAME_MAX,
             frsize: ext2_sb.fragment_size(),
             flags: 0, // TODO
@@ -4,13 +4,17 @@
 
 use core::time::Duration;
 
+use aster_block::bio::BioWaiter;
 use aster_rights::Full;
+use ostd::mm::Frame;
 
 use crate::{
     fs::{
         device::Device,
         ext2::{FilePerm, FileType, Inode as Ext2Inode},
-        utils::{DirentVisitor, FileSystem, Inode, InodeMode, InodeType, IoctlCmd, Metadata},
+        utils::{
+            DirentVisitor, FileSystem, Inode, InodeMode, InodeType, IoctlCmd, Metadata, RenameFlags,
+        },
     },
     prelude::*,
     process::{Gid, Uid},
@@ -124,6 +128,14 @@ impl Inode for Ext2Inode {
         self.write_direct_at(offset, buf)
     }
 
+    fn read_direct_async(&self, offset: usize, frames: &[Frame]) -> Result<(usize, BioWaiter)> {
+        self.read_direct_async(offset, frames)
+    }
+
+    fn write_direct_async(&self, offset: usize, frames: &[Frame]) -> Result<BioWaiter> {
+        self.write_direct_async(offset, frames)
+    }
+
     fn create(&self, name: &str, type_: InodeType, mode: InodeMode) -> Result<Arc<dyn Inode>> {
         Ok(self.create(name, type_.into(), mode.into())?)
     }
@@ -157,11 +169,32 @@ impl Inode for Ext2Inode {
         self.rmdir(name)
     }
 
-    fn rename(&self, old_name: &str, target: &Arc<dyn Inode>, new_name: &str) -> Result<()> {
+    fn rename(
+        &self,
+        old_name: &str,
+        target: &Arc<dyn Inode>,
+        new_name: &str,
+        flags: RenameFlags,
+    ) -> Result<()> {
         let target = target
             .downcast_ref::<Ext2Inode>()
             .ok_or_else(|| Error::with_message(Errno::EXDEV, "not same fs"))?;
-        self.rename(old_name, target, new_name)
+
+        if flags.contains(RenameFlags::EXCHANGE) {
+            if flags != RenameFlags::EXCHANGE {
+                return_errno_with_message!(Errno::EINVAL, "invalid flags to exchange");
+            }
+            return self.exchange(old_name, target, new_name);
+        }
+        if flags.contains(RenameFlags::WHITEOUT) {
+            return_errno_with_message!(Errno::EINVAL, "whiteout is not supported");
+        }
+        self.rename(
+            old_name,
+            target,
+            new_name,
+            flags.contains(RenameFlags::NOREPLACE),
+        )
     }
 
     fn read_link(&self) -> Result<String> {
@@ -244,7 +244,7 @@ impl Inode {
     }
 
     /// Rename within its own directory.
-    fn rename_within(&self, old_name: &str, new_name: &str) -> Result<()> {
+    fn rename_within(&self, old_name: &str, new_name: &str, no_replace: bool) -> Result<()> {
         let self_inner = self.inner.upread();
         if self_inner.file_type() != FileType::Dir {
             return_errno!(Errno::ENOTDIR);
@@ -266,6 +266,9 @@ impl Inode {
             self_inner.rename_entry_at(old_name, new_name, src_offset)?;
             return Ok(());
         };
+        if no_replace {
+            return_errno_with_message!(Errno::EEXIST, "the new name already exists");
+        }
         if src_inode.ino == dst_ino {
             // Same inode, do nothing
             return Ok(());
@@ -328,7 +331,13 @@ impl Inode {
         Ok(())
     }
 
-    pub fn rename(&self, old_name: &str, target: &Inode, new_name: &str) -> Result<()> {
+    pub fn rename(
+        &self,
+        old_name: &str,
+        target: &Inode,
+        new_name: &str,
+        no_replace: bool,
+    ) -> Result<()> {
         if old_name == "." || old_name == ".." || new_name == "." || new_name == ".." {
             return_errno!(Errno::EISDIR);
         }
@@ -338,7 +347,7 @@ impl Inode {
 
         // Rename inside the inode
         if self.ino == target.ino {
-            return self.rename_within(old_name, new_name);
+            return self.rename_within(old_name, new_name, no_replace);
         }
 
         let (self_inner, target_inner) = read_lock_two_inodes(self, target);
@@ -378,6 +387,9 @@ impl Inode {
             if self_inner.hard_links() == 0 || target_inner.hard_links() == 0 {
                 return_errno_with_message!(Errno::ENOENT, "dir removed");
             }
+            if no_replace && target_inner.get_entry_ino(new_name).is_some() {
+                return_errno_with_message!(Errno::EEXIST, "the new name already exists");
+            }
             let (src_offset, new_src_ino) = self_inner
                 .get_entry(old_name)
                 .map(|(offset, entry)| (offset, entry.ino()))
@@ -400,6 +412,9 @@ impl Inode {
             }
             return Ok(());
         };
+        if no_replace {
+            return_errno_with_message!(Errno::EEXIST, "the new name already exists");
+        }
         if src_inode.ino == dst_ino {
             // Same inode, do nothing
             return Ok(());
@@ -479,6 +494,149 @@ impl Inode {
         Ok(())
     }
 
+    /// Exchange within its own directory.
+    fn exchange_within(&self, old_name: &str, new_name: &str) -> Result<()> {
+        let mut self_inner = self.inner.write();
+        if self_inner.file_type() != FileType::Dir {
+            return_errno!(Errno::ENOTDIR);
+        }
+        if self_inner.hard_links() == 0 {
+            return_errno_with_message!(Errno::ENOENT, "dir removed");
+        }
+
+        let (src_offset, mut src_entry) = self_inner
+            .get_entry(old_name)
+            .ok_or(Error::new(Errno::ENOENT))?;
+        let (dst_offset, mut dst_entry) = self_inner
+            .get_entry(new_name)
+            .ok_or(Error::new(Errno::ENOENT))?;
+        if src_entry.ino() == dst_entry.ino() {
+            // Same inode, do nothing
+            return Ok(());
+        }
+
+        let (src_ino, src_inode_typ) = (src_entry.ino(), src_entry.type_());
+        src_entry.set_ino(dst_entry.ino());
+        src_entry.set_type(dst_entry.type_());
+        dst_entry.set_ino(src_ino);
+        dst_entry.set_type(src_inode_typ);
+        self_inner.write_entry_at(src_offset, &src_entry)?;
+        self_inner.write_entry_at(dst_offset, &dst_entry)?;
+
+        Ok(())
+    }
+
+    /// Atomically exchanges the entry `old_name` in this directory with the entry
+    /// `new_name` in the `target` directory. Both entries must exist.
+    pub fn exchange(&self, old_name: &str, target: &Inode, new_name: &str) -> Result<()> {
+        if old_name == "." || old_name == ".." || new_name == "." || new_name == ".." {
+            return_errno!(Errno::EISDIR);
+        }
+
+        // Exchange inside the inode
+        if self.ino == target.ino {
+            return self.exchange_within(old_name, new_name);
+        }
+
+        let (self_inner, target_inner) = read_lock_two_inodes(self, target);
+        if self_inner.file_type() != FileType::Dir || target_inner.file_type() != FileType::Dir {
+            return_errno!(Errno::ENOTDIR);
+        }
+        if self_inner.hard_links() == 0 || target_inner.hard_links() == 0 {
+            return_errno_with_message!(Errno::ENOENT, "dir removed");
+        }
+
+        let fs = self.fs();
+        let (src_inode, src_inode_typ) = {
+            let (_, entry) = self_inner
+                .get_entry(old_name)
+                .ok_or(Error::new(Errno::ENOENT))?;
+            (fs.lookup_inode(entry.ino())?, entry.type_())
+        };
+        let (dst_inode, dst_inode_typ) = {
+            let (_, entry) = target_inner
+                .get_entry(new_name)
+                .ok_or(Error::new(Errno::ENOENT))?;
+            (fs.lookup_inode(entry.ino())?, entry.type_())
+        };
+        if src_inode.ino == dst_inode.ino {
+            // Same inode, do nothing
+            return Ok(());
+        }
+        // Avoid moving a directory into itself
+        if src_inode.ino == target.ino || dst_inode.ino == self.ino {
+            return_errno!(Errno::EINVAL);
+        }
+        let src_is_dir = src_inode_typ == FileType::Dir;
+        let dst_is_dir = dst_inode_typ == FileType::Dir;
+        drop(self_inner);
+        drop(target_inner);
+
+        let mut inodes = vec![self, target];
+        if src_is_dir {
+            inodes.push(&src_inode);
+        }
+        if dst_is_dir {
+            inodes.push(&dst_inode);
+        }
+        let mut write_guards = write_lock_multiple_inodes(inodes);
+        let dst_guard = dst_is_dir.then(|| write_guards.pop().unwrap());
+        let src_guard = src_is_dir.then(|| write_guards.pop().unwrap());
+        let mut target_inner = write_guards.pop().unwrap();
+        let mut self_inner = write_guards.pop().unwrap();
+
+        // When we got the lock, the dirs may have been modified by another thread
+        if self_inner.hard_links() == 0 || target_inner.hard_links() == 0 {
+            return_errno_with_message!(Errno::ENOENT, "dir removed");
+        }
+
+        let (src_offset, mut src_entry) = self_inner
+            .get_entry(old_name)
+            .ok_or(Error::new(Errno::ENOENT))?;
+        if src_inode.ino != src_entry.ino() {
+            return_errno!(Errno::ENOENT);
+        }
+        let potential_new_src = fs.lookup_inode(src_inode.ino)?;
+        if !Arc::ptr_eq(&src_inode, &potential_new_src) {
+            return_errno!(Errno::ENOENT);
+        }
+
+        let (dst_offset, mut dst_entry) = target_inner
+            .get_entry(new_name)
+            .ok_or(Error::new(Errno::ENOENT))?;
+        if dst_inode.ino != dst_entry.ino() {
+            return_errno!(Errno::ENOENT);
+        }
+        let potential_new_dst = fs.lookup_inode(dst_inode.ino)?;
+        if !Arc::ptr_eq(&dst_inode, &potential_new_dst) {
+            return_errno!(Errno::ENOENT);
+        }
+
+        src_entry.set_ino(dst_inode.ino);
+        src_entry.set_type(dst_inode_typ);
+        dst_entry.set_ino(src_inode.ino);
+        dst_entry.set_type(src_inode_typ);
+        self_inner.write_entry_at(src_offset, &src_entry)?;
+        target_inner.write_entry_at(dst_offset, &dst_entry)?;
+
+        // A subdirectory holds a hard link to its parent through ".."
+        if src_is_dir && !dst_is_dir {
+            self_inner.dec_hard_links();
+            target_inner.inc_hard_links();
+        } else if !src_is_dir && dst_is_dir {
+            self_inner.inc_hard_links();
+            target_inner.dec_hard_links();
+        }
+        if let Some(mut src_inner) = src_guard {
+            src_inner.set_parent_ino(target.ino)?;
+        }
+        if let Some(mut dst_inner) = dst_guard {
+            dst_inner.set_parent_ino(self.ino)?;
+        }
+
+        Ok(())
+    }
+
     pub fn readdir_at(&self, offset: usize, visitor: &mut dyn DirentVisitor) -> Result<usize> {
         let inner = self.inner.read();
         if inner.file_type() != FileType::Dir {
@@ -604,6 +762,33 @@ impl Inode {
         Ok(buf.len())
     }
 
+    // The offset must be a multiple of the block size.
+    pub fn read_direct_async(&self, offset: usize, frames: &[Frame]) -> Result<(usize, BioWaiter)> {
+        let inner = self.inner.read();
+        if inner.file_type() != FileType::File {
+            return_errno!(Errno::EISDIR);
+        }
+        if !is_block_aligned(offset) {
+            return_errno_with_message!(Errno::EINVAL, "not block-aligned");
+        }
+
+        inner.read_direct_async(offset, frames)
+    }
+
+    // The offset must be a multiple of the block size.
+    pub fn write_direct_async(&self, offset: usize, frames: &[Frame]) -> Result<BioWaiter> {
+        let inner = self.inner.upread();
+        if inner.file_type() != FileType::File {
+            return_errno!(Errno::EISDIR);
+        }
+        if !is_block_aligned(offset) {
+            return_errno_with_message!(Errno::EINVAL, "not block aligned");
+        }
+
+        let mut inner = inner.upgrade();
+        inner.write_direct_async(offset, frames)
+    }
+
     fn init(&self, dir_ino: u32) -> Result<()> {
         let mut inner = self.inner.write();
         match inner.file_type() {
@@ -790,6 +975,28 @@ impl Inner {
         Ok(read_len)
     }
 
+    pub fn read_direct_async(&self, offset: usize, frames: &[Frame]) -> Result<(usize, BioWaiter)> {
+        let (offset, read_len) = {
+            let file_size = self.inode_impl.file_size();
+            let start = file_size.min(offset).align_down(BLOCK_SIZE);
+            let end = file_size
+                .min(offset + frames.len() * BLOCK_SIZE)
+                .align_down(BLOCK_SIZE);
+            (start, end - start)
+        };
+        self.page_cache.discard_range(offset..offset + read_len);
+
+        let mut bio_waiter = BioWaiter::new();
+        let bids = Bid::from_offset(offset)..Bid::from_offset(offset + read_len);
+        for (bid, frame) in bids.zip(frames) {
+            let waiter = self
+                .inode_impl
+                .read_block_async(bid.to_raw() as Ext2Bid, frame)?;
+            bio_waiter.concat(waiter);
+        }
+        Ok((read_len, bio_waiter))
+    }
+
     pub fn write_at(&self, offset: usize, buf: &[u8]) -> Result<()> {
         self.page_cache.pages().write_bytes(offset, buf)?;
         Ok(())
@@ -834,6 +1041,30 @@ impl Inner {
         Ok(())
     }
 
+    pub fn write_direct_async(&mut self, offset: usize, frames: &[Frame]) -> Result<BioWaiter> {
+        let file_size = self.inode_impl.file_size();
+        let end_offset = offset + frames.len() * BLOCK_SIZE;
+
+        let start = offset.min(file_size);
+        let end = end_offset.min(file_size);
+        self.page_cache.discard_range(start..end);
+
+        if end_offset > file_size {
+            self.page_cache.pages().resize(end_offset)?;
+            self.inode_impl.resize(end_offset)?;
+        }
+
+        let mut bio_waiter = BioWaiter::new();
+        let bids = Bid::from_offset(offset)..Bid::from_offset(end_offset);
+        for (bid, frame) in bids.zip(frames) {
+            let waiter = self
+                .inode_impl
+                .write_block_async(bid.to_raw() as Ext2Bid, frame)?;
+            bio_waiter.concat(waiter);
+        }
+        Ok(bio_waiter)
+    }
+
     pub fn write_link(&mut self, target: &str) -> Result<()> {
         if target.len() <= MAX_FAST_SYMLINK_LEN {
             return self.inode_impl.write_link(target);
@@ -920,6 +1151,10 @@ impl Inner {
         Ok(())
     }
 
+    pub fn write_entry_at(&mut self, offset: usize, entry: &DirEntry) -> Result<()> {
+        DirEntryWriter::new(&self.page_cache, offset).write_entry(entry)
+    }
+
     pub fn set_parent_ino(&mut self, parent_ino: u32) -> Result<()> {
         let (offset, mut entry) = self.get_entry("..").unwrap();
         entry.set_ino(parent_ino);
@@ -2082,6 +2317,50 @@ pub(super) struct Osd2 {
     reserved2: u32,
 }
 
+/// The extra fields of a large raw inode, which follow the first 128 bytes.
+///
+/// The layout is compatible with the large inodes of ext4. The extra time fields hold
+/// the nanoseconds of the timestamps, shifted left by 2 bits. The lower 2 bits extend
+/// the epoch, which is not supported.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug, Pod)]
+pub(super) struct RawInodeExtra {
+    /// Size of the extra fields in use.
+    pub extra_isize: u16,
+    /// Upper 16 bits of the inode checksum.
+    checksum_hi: u16,
+    /// Extra change time bits.
+    pub ctime_extra: u32,
+    /// Extra modification time bits.
+    pub mtime_extra: u32,
+    /// Extra access time bits.
+    pub atime_extra: u32,
+}
+
+impl RawInodeExtra {
+    /// Loads the nanoseconds of the timestamps from the extra time fields in use.
+    pub fn load_times(&self, inode: &mut InodeDesc) {
+        let extra_isize = self.extra_isize as usize;
+        if extra_isize >= 8 {
+            inode.ctime = Duration::new(inode.ctime.as_secs(), self.ctime_extra >> 2);
+        }
+        if extra_isize >= 12 {
+            inode.mtime = Duration::new(inode.mtime.as_secs(), self.mtime_extra >> 2);
+        }
+        if extra_isize >= 16 {
+            inode.atime = Duration::new(inode.atime.as_secs(), self.atime_extra >> 2);
+        }
+    }
+
+    /// Stores the nanoseconds of the timestamps in the extra time fields.
+    pub fn store_times(&mut self, inode: &InodeDesc) {
+        self.extra_isize = self.extra_isize.max(core::mem::size_of::<Self>() as u16);
+        self.ctime_extra = inode.ctime.subsec_nanos() << 2;
+        self.mtime_extra = inode.mtime.subsec_nanos() << 2;
+        self.atime_extra = inode.atime.subsec_nanos() << 2;
+    }
+}
+
 fn is_block_aligned(offset: usize) -> bool {
     offset % BLOCK_SIZE == 0
 }
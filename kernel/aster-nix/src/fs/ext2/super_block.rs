@@ -242,6 +242,16 @@ impl SuperBlock {
         self.feature_ro_compat
     }
 
+    /// Returns the number of blocks reserved for the superuser.
+    pub fn reserved_blocks_count(&self) -> u32 {
+        self.reserved_blocks_count
+    }
+
+    /// Returns the 128-bit UUID of the volume.
+    pub fn uuid(&self) -> [u8; 16] {
+        self.uuid
+    }
+
     /// Returns the number of free blocks.
     pub fn free_blocks_count(&self) -> u32 {
         self.free_blocks_count
@@ -8,6 +8,7 @@ use crate::{
     events::{IoEvents, Observer},
     fs::{
         device::Device,
+        pipe::Pipe,
         utils::{AccessMode, InodeMode, IoctlCmd, Metadata, SeekFrom, StatusFlags},
     },
     net::socket::Socket,
@@ -134,6 +135,11 @@ pub trait FileLike: Send + Sync + Any {
     fn as_device(&self) -> Option<Arc<dyn Device>> {
         None
     }
+
+    /// Returns the pipe if the file is either end of a pipe.
+    fn as_pipe(&self) -> Option<&Pipe> {
+        None
+    }
 }
 
 impl dyn FileLike {
@@ -2,9 +2,13 @@
 
 #![allow(unused_variables)]
 
-use core::sync::atomic::{AtomicU8, Ordering};
+use core::{
+    ops::RangeInclusive,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
-use aster_util::slot_vec::SlotVec;
+use id_alloc::AtomicBitmap;
+use ostd::sync::Rcu;
 
 use super::{
     file_handle::FileLike,
@@ -19,21 +23,30 @@ use crate::{
 
 pub type FileDesc = i32;
 
+/// The file descriptor table of a process.
+///
+/// Looking up a file descriptor takes no locks. The file descriptors are kept
+/// in an `FdArray` whose slots are published with RCU. An update modifies the
+/// slot and the bits of a file descriptor in place. Only when the array is full
+/// is it copied into a larger one, which is then published with RCU.
 pub struct FileTable {
-    table: SlotVec<FileTableEntry>,
+    fds: Rcu<Box<FdArray>>,
+    /// Serializes the updates of `fds`.
+    update_lock: Mutex<()>,
+    /// The number of processes that share the table.
+    ///
+    /// It is not the reference count, since a reference to the table is also
+    /// taken for a short time by whoever looks up the table, e.g. procfs.
+    nr_sharers: AtomicUsize,
     subject: Subject<FdEvents>,
 }
 
 impl FileTable {
-    pub const fn new() -> Self {
-        Self {
-            table: SlotVec::new(),
-            subject: Subject::new(),
-        }
+    pub fn new() -> Self {
+        Self::from_fd_array(FdArray::with_capacity(FdArray::INIT_NR_FDS))
     }
 
     pub fn new_with_stdio() -> Self {
-        let mut table = SlotVec::new();
         let fs_resolver = FsResolver::new();
         let tty_path = FsPath::new(AT_FDCWD, "/dev/console").expect("cannot find tty");
         let stdin = {
@@ -51,138 +64,204 @@ impl FileTable {
             let mode = InodeMode::S_IWUSR;
             fs_resolver.open(&tty_path, flags, mode.bits()).unwrap()
         };
-        table.put(FileTableEntry::new(Arc::new(stdin), FdFlags::empty()));
-        table.put(FileTableEntry::new(Arc::new(stdout), FdFlags::empty()));
-        table.put(FileTableEntry::new(Arc::new(stderr), FdFlags::empty()));
+
+        let fds = FdArray::with_capacity(FdArray::INIT_NR_FDS);
+        for (fd, file) in [stdin, stdout, stderr].into_iter().enumerate() {
+            fds.put_at(fd, FileTableEntry::new(Arc::new(file)), FdFlags::empty());
+        }
+        Self::from_fd_array(fds)
+    }
+
+    fn from_fd_array(fds: FdArray) -> Self {
         Self {
-            table,
+            fds: Rcu::new(Box::new(fds)),
+            update_lock: Mutex::new(()),
+            nr_sharers: AtomicUsize::new(1),
             subject: Subject::new(),
         }
     }
 
-    pub fn dup(&mut self, fd: FileDesc, new_fd: FileDesc, flags: FdFlags) -> Result<FileDesc> {
-        let file = self
-            .table
-            .get(fd as usize)
-            .map(|entry| entry.file.clone())
-            .ok_or(Error::with_message(Errno::ENOENT, "No such file"))?;
+    /// Shares the table with one more process, e.g. on `clone` with `CLONE_FILES`.
+    pub fn share(self: &Arc<Self>) -> Arc<Self> {
+        self.nr_sharers.fetch_add(1, Ordering::Relaxed);
+        self.clone()
+    }
 
-        // Get the lowest-numbered available fd equal to or greater than `new_fd`.
-        let get_min_free_fd = || -> usize {
-            let new_fd = new_fd as usize;
-            if self.table.get(new_fd).is_none() {
-                return new_fd;
-            }
+    /// Returns whether the table is shared by more than one process.
+    pub fn is_shared(&self) -> bool {
+        self.nr_sharers.load(Ordering::Relaxed) > 1
+    }
 
-            for idx in new_fd + 1..self.table.slots_len() {
-                if self.table.get(idx).is_none() {
-                    return idx;
-                }
-            }
-            self.table.slots_len()
-        };
+    /// Copies the table for a process that stops sharing it.
+    ///
+    /// Returns `None` if the process is the only sharer, which keeps using the table.
+    /// The caller must not unshare the same table for a process more than once.
+    pub fn unshare(&self) -> Option<Self> {
+        if !self.is_shared() {
+            return None;
+        }
+
+        let new_table = self.clone();
+        // The files of the table are kept open by the copy, so nothing is closed
+        // even if the other sharers have exited in the meantime.
+        self.nr_sharers.fetch_sub(1, Ordering::Release);
+        Some(new_table)
+    }
+
+    /// Releases the table for a process that exits.
+    ///
+    /// The files are closed and returned only if no other process shares the table.
+    pub fn release(&self) -> Vec<Arc<dyn FileLike>> {
+        if self.nr_sharers.fetch_sub(1, Ordering::AcqRel) > 1 {
+            return Vec::new();
+        }
+        self.close_all()
+    }
+
+    /// Duplicates `fd` to the lowest-numbered available fd equal to or greater than `new_fd`.
+    ///
+    /// The new fd must be less than `nofile_limit`, or this method fails with `EMFILE`.
+    pub fn dup(
+        &self,
+        fd: FileDesc,
+        new_fd: FileDesc,
+        flags: FdFlags,
+        nofile_limit: u64,
+    ) -> Result<FileDesc> {
+        if new_fd < 0 {
+            return_errno_with_message!(Errno::EINVAL, "the new fd is negative");
+        }
 
-        let min_free_fd = get_min_free_fd();
-        let entry = FileTableEntry::new(file, flags);
-        self.table.put_at(min_free_fd, entry);
-        Ok(min_free_fd as FileDesc)
+        let _guard = self.update_lock.lock();
+        let file = self.fds.get().get(fd)?.file.clone();
+        let max_nr_fds = usize::try_from(nofile_limit).unwrap_or(usize::MAX);
+        let new_fd = self.lowest_free_fd(new_fd as usize, max_nr_fds)?;
+        self.fds
+            .get()
+            .put_at(new_fd, FileTableEntry::new(file), flags);
+        Ok(new_fd as FileDesc)
     }
 
-    pub fn insert(&mut self, item: Arc<dyn FileLike>, flags: FdFlags) -> FileDesc {
-        let entry = FileTableEntry::new(item, flags);
-        self.table.put(entry) as FileDesc
+    pub fn insert(&self, item: Arc<dyn FileLike>, flags: FdFlags) -> Result<FileDesc> {
+        let _guard = self.update_lock.lock();
+        let fd = self.lowest_free_fd(0, usize::MAX)?;
+        self.fds.get().put_at(fd, FileTableEntry::new(item), flags);
+        Ok(fd as FileDesc)
     }
 
+    /// Installs the file at `fd`, and returns the file that is replaced, if any.
     pub fn insert_at(
-        &mut self,
+        &self,
         fd: FileDesc,
         item: Arc<dyn FileLike>,
         flags: FdFlags,
-    ) -> Option<Arc<dyn FileLike>> {
-        let entry = FileTableEntry::new(item, flags);
-        let entry = self.table.put_at(fd as usize, entry);
-        if entry.is_some() {
-            let events = FdEvents::Close(fd);
-            self.notify_fd_events(&events);
-            entry.as_ref().unwrap().notify_fd_events(&events);
+    ) -> Result<Option<Arc<dyn FileLike>>> {
+        if fd < 0 {
+            return_errno_with_message!(Errno::EBADF, "the fd is negative");
         }
-        entry.map(|e| e.file)
+
+        let old_entry = {
+            let _guard = self.update_lock.lock();
+            self.reserve(fd as usize + 1);
+            self.fds
+                .get()
+                .put_at(fd as usize, FileTableEntry::new(item), flags)
+        };
+        Ok(old_entry.map(|entry| self.on_entry_closed(fd, entry)))
     }
 
-    pub fn close_file(&mut self, fd: FileDesc) -> Option<Arc<dyn FileLike>> {
-        let entry = self.table.remove(fd as usize);
-        if entry.is_some() {
-            let events = FdEvents::Close(fd);
-            self.notify_fd_events(&events);
-            entry.as_ref().unwrap().notify_fd_events(&events);
-        }
-        entry.map(|e| e.file)
-    }
-
-    pub fn close_all(&mut self) -> Vec<Arc<dyn FileLike>> {
-        let mut closed_files = Vec::new();
-        let closed_fds: Vec<FileDesc> = self
-            .table
-            .idxes_and_items()
-            .map(|(idx, _)| idx as FileDesc)
-            .collect();
-        for fd in closed_fds {
-            let entry = self.table.remove(fd as usize).unwrap();
-            let events = FdEvents::Close(fd);
-            self.notify_fd_events(&events);
-            entry.notify_fd_events(&events);
-            closed_files.push(entry.file);
-        }
-        closed_files
-    }
-
-    pub fn close_files_on_exec(&mut self) -> Vec<Arc<dyn FileLike>> {
-        let mut closed_files = Vec::new();
-        let closed_fds: Vec<FileDesc> = self
-            .table
-            .idxes_and_items()
-            .filter_map(|(idx, entry)| {
-                if entry.flags().contains(FdFlags::CLOEXEC) {
-                    Some(idx as FileDesc)
-                } else {
-                    None
-                }
-            })
-            .collect();
-        for fd in closed_fds {
-            let entry = self.table.remove(fd as usize).unwrap();
-            let events = FdEvents::Close(fd);
-            self.notify_fd_events(&events);
-            entry.notify_fd_events(&events);
-            closed_files.push(entry.file);
-        }
-        closed_files
+    pub fn close_file(&self, fd: FileDesc) -> Option<Arc<dyn FileLike>> {
+        let old_entry = {
+            let _guard = self.update_lock.lock();
+            self.fds.get().remove(fd).ok()?
+        };
+        Some(self.on_entry_closed(fd, old_entry))
+    }
+
+    pub fn close_all(&self) -> Vec<Arc<dyn FileLike>> {
+        self.close_if(|_, _| true)
+    }
+
+    pub fn close_files_on_exec(&self) -> Vec<Arc<dyn FileLike>> {
+        self.close_if(|fds, fd| fds.close_on_exec.get(fd))
+    }
+
+    /// Closes the file descriptors in `range`, and returns the closed files.
+    pub fn close_range(&self, range: RangeInclusive<usize>) -> Vec<Arc<dyn FileLike>> {
+        self.close_if(|_, fd| range.contains(&fd))
     }
 
-    pub fn get_file(&self, fd: FileDesc) -> Result<&Arc<dyn FileLike>> {
-        self.table
-            .get(fd as usize)
-            .map(|entry| &entry.file)
-            .ok_or(Error::with_message(Errno::EBADF, "fd not exits"))
+    fn close_if(&self, should_close: impl Fn(&FdArray, usize) -> bool) -> Vec<Arc<dyn FileLike>> {
+        let closed_entries = {
+            let _guard = self.update_lock.lock();
+            let fds = self.fds.get();
+            fds.iter_open_fds()
+                .filter(|&fd| should_close(&fds, fd))
+                .filter_map(|fd| {
+                    let fd = fd as FileDesc;
+                    fds.remove(fd).ok().map(|entry| (fd, entry))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        closed_entries
+            .into_iter()
+            .map(|(fd, entry)| self.on_entry_closed(fd, entry))
+            .collect()
+    }
+
+    pub fn get_file(&self, fd: FileDesc) -> Result<Arc<dyn FileLike>> {
+        self.fds.get().get(fd).map(|entry| entry.file.clone())
     }
 
     pub fn get_socket(&self, sockfd: FileDesc) -> Result<Arc<dyn Socket>> {
-        let file_like = self.get_file(sockfd)?.clone();
+        let file_like = self.get_file(sockfd)?;
         file_like
             .as_socket()
             .ok_or_else(|| Error::with_message(Errno::ENOTSOCK, "the fd is not a socket"))
     }
 
-    pub fn get_entry(&self, fd: FileDesc) -> Result<&FileTableEntry> {
-        self.table
-            .get(fd as usize)
-            .ok_or(Error::with_message(Errno::EBADF, "fd not exits"))
+    pub fn get_entry(&self, fd: FileDesc) -> Result<Arc<FileTableEntry>> {
+        self.fds.get().get(fd)
+    }
+
+    pub fn fd_flags(&self, fd: FileDesc) -> Result<FdFlags> {
+        let fds = self.fds.get();
+        fds.get(fd)?;
+        if fds.close_on_exec.get(fd as usize) {
+            Ok(FdFlags::CLOEXEC)
+        } else {
+            Ok(FdFlags::empty())
+        }
+    }
+
+    pub fn set_fd_flags(&self, fd: FileDesc, flags: FdFlags) -> Result<()> {
+        let _guard = self.update_lock.lock();
+        let fds = self.fds.get();
+        fds.get(fd)?;
+        fds.set_cloexec(fd as usize, flags.contains(FdFlags::CLOEXEC));
+        Ok(())
     }
 
-    pub fn fds_and_files(&self) -> impl Iterator<Item = (FileDesc, &'_ Arc<dyn FileLike>)> {
-        self.table
-            .idxes_and_items()
-            .map(|(idx, entry)| (idx as FileDesc, &entry.file))
+    /// Sets the close-on-exec flag of the file descriptors in `range`.
+    pub fn set_cloexec_range(&self, range: RangeInclusive<usize>) {
+        let _guard = self.update_lock.lock();
+        let fds = self.fds.get();
+        for fd in fds.iter_open_fds().filter(|fd| range.contains(fd)) {
+            fds.set_cloexec(fd, true);
+        }
+    }
+
+    /// Returns a snapshot of the file descriptors and the files.
+    pub fn fds_and_files(&self) -> Vec<(FileDesc, Arc<dyn FileLike>)> {
+        let fds = self.fds.get();
+        fds.iter_open_fds()
+            .filter_map(|fd| {
+                let fd = fd as FileDesc;
+                // The fd may be closed concurrently.
+                fds.get(fd).ok().map(|entry| (fd, entry.file.clone()))
+            })
+            .collect()
     }
 
     pub fn register_observer(&self, observer: Weak<dyn Observer<FdEvents>>) {
@@ -193,6 +272,47 @@ impl FileTable {
         self.subject.unregister_observer(observer);
     }
 
+    /// Returns the lowest-numbered unused fd equal to or greater than `from`,
+    /// growing the file descriptors if there is no such fd.
+    ///
+    /// The fd must be less than `max_nr_fds`, or this method fails with `EMFILE`.
+    ///
+    /// The caller must hold `update_lock`.
+    fn lowest_free_fd(&self, from: usize, max_nr_fds: usize) -> Result<usize> {
+        let fd = {
+            let fds = self.fds.get();
+            fds.open_fds
+                .find_next_zero(from)
+                .unwrap_or_else(|| from.max(fds.capacity()))
+        };
+        if fd >= max_nr_fds || fd > FileDesc::MAX as usize {
+            return_errno_with_message!(Errno::EMFILE, "no more fds are available");
+        }
+        self.reserve(fd + 1);
+        Ok(fd)
+    }
+
+    /// Makes the file descriptors large enough to hold `nr_fds` file descriptors.
+    ///
+    /// The caller must hold `update_lock`.
+    fn reserve(&self, nr_fds: usize) {
+        let new_fds = {
+            let fds = self.fds.get();
+            if nr_fds <= fds.capacity() {
+                return;
+            }
+            fds.clone_with_capacity(nr_fds.next_power_of_two(), Arc::clone)
+        };
+        self.fds.replace(Box::new(new_fds)).delay();
+    }
+
+    fn on_entry_closed(&self, fd: FileDesc, entry: Arc<FileTableEntry>) -> Arc<dyn FileLike> {
+        let events = FdEvents::Close(fd);
+        self.notify_fd_events(&events);
+        entry.notify_fd_events(&events);
+        entry.file.clone()
+    }
+
     fn notify_fd_events(&self, events: &FdEvents) {
         self.subject.notify_observers(events);
     }
@@ -206,10 +326,16 @@ impl Default for FileTable {
 
 impl Clone for FileTable {
     fn clone(&self) -> Self {
-        Self {
-            table: self.table.clone(),
-            subject: Subject::new(),
-        }
+        let _guard = self.update_lock.lock();
+
+        // The entries are not shared, since the observers of an entry belong to this table.
+        let fds = self.fds.get();
+        let new_fds = fds.clone_with_capacity(fds.capacity(), |entry| {
+            Arc::new(FileTableEntry::clone(entry))
+        });
+        drop(fds);
+
+        Self::from_fd_array(new_fds)
     }
 }
 
@@ -220,6 +346,120 @@ impl Drop for FileTable {
     }
 }
 
+/// The file descriptors in a [`FileTable`].
+///
+/// The array has a fixed capacity. Its slots and bits are updated in place by
+/// the writers, which are serialized by the update lock of the table.
+///
+/// The array is not an `XArray` because modifying an `XArray` requires a
+/// mutable reference. The lookups would then have to take the lock of the
+/// table as well.
+struct FdArray {
+    entries: Box<[FdSlot]>,
+    /// The bitmap of the file descriptors in use.
+    open_fds: AtomicBitmap,
+    /// The bitmap of the file descriptors to close on exec.
+    close_on_exec: AtomicBitmap,
+}
+
+/// The slot of a file descriptor, which holds the entry if the fd is in use.
+type FdSlot = Rcu<Option<Box<Arc<FileTableEntry>>>>;
+
+impl FdArray {
+    /// The initial number of file descriptors that the array can hold.
+    const INIT_NR_FDS: usize = 64;
+
+    fn with_capacity(nr_fds: usize) -> Self {
+        let entries = (0..nr_fds)
+            .map(|_| Rcu::new(None))
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        Self {
+            entries,
+            open_fds: AtomicBitmap::new(nr_fds),
+            close_on_exec: AtomicBitmap::new(nr_fds),
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn get(&self, fd: FileDesc) -> Result<Arc<FileTableEntry>> {
+        usize::try_from(fd)
+            .ok()
+            .and_then(|fd| self.entries.get(fd))
+            .and_then(|slot| slot.try_get().map(|entry| Arc::clone(&*entry)))
+            .ok_or_else(|| Error::with_message(Errno::EBADF, "the fd does not exist"))
+    }
+
+    fn iter_open_fds(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut from = 0;
+        core::iter::from_fn(move || {
+            let fd = self.open_fds.find_next_one(from)?;
+            from = fd + 1;
+            Some(fd)
+        })
+    }
+
+    /// Puts the entry at `fd`, and returns the entry that is replaced, if any.
+    ///
+    /// The `fd` must be less than the capacity.
+    fn put_at(
+        &self,
+        fd: usize,
+        entry: FileTableEntry,
+        flags: FdFlags,
+    ) -> Option<Arc<FileTableEntry>> {
+        let old_entry = self.get(fd as FileDesc).ok();
+
+        self.set_cloexec(fd, flags.contains(FdFlags::CLOEXEC));
+        self.entries[fd]
+            .replace(Some(Box::new(Arc::new(entry))))
+            .delay();
+        self.open_fds.test_and_set(fd);
+        old_entry
+    }
+
+    fn remove(&self, fd: FileDesc) -> Result<Arc<FileTableEntry>> {
+        let entry = self.get(fd)?;
+
+        let fd = fd as usize;
+        self.open_fds.test_and_clear(fd);
+        self.close_on_exec.test_and_clear(fd);
+        self.entries[fd].replace(None).delay();
+        Ok(entry)
+    }
+
+    fn set_cloexec(&self, fd: usize, is_cloexec: bool) {
+        if is_cloexec {
+            self.close_on_exec.test_and_set(fd);
+        } else {
+            self.close_on_exec.test_and_clear(fd);
+        }
+    }
+
+    /// Copies the file descriptors into a new array that can hold `nr_fds` file
+    /// descriptors, where the entries are copied with `clone_entry`.
+    fn clone_with_capacity(
+        &self,
+        nr_fds: usize,
+        clone_entry: impl Fn(&Arc<FileTableEntry>) -> Arc<FileTableEntry>,
+    ) -> Self {
+        let mut new_fds = Self::with_capacity(nr_fds);
+        for fd in self.iter_open_fds() {
+            let Ok(entry) = self.get(fd as FileDesc) else {
+                continue;
+            };
+            let is_cloexec = self.close_on_exec.get(fd);
+            new_fds.entries[fd] = Rcu::new(Some(Box::new(clone_entry(&entry))));
+            new_fds.set_cloexec(fd, is_cloexec);
+            new_fds.open_fds.test_and_set(fd);
+        }
+        new_fds
+    }
+}
+
 #[derive(Copy, Clone)]
 pub enum FdEvents {
     Close(FileDesc),
@@ -230,15 +470,13 @@ impl Events for FdEvents {}
 
 pub struct FileTableEntry {
     file: Arc<dyn FileLike>,
-    flags: AtomicU8,
     subject: Subject<FdEvents>,
 }
 
 impl FileTableEntry {
-    pub fn new(file: Arc<dyn FileLike>, flags: FdFlags) -> Self {
+    pub fn new(file: Arc<dyn FileLike>) -> Self {
         Self {
             file,
-            flags: AtomicU8::new(flags.bits()),
             subject: Subject::new(),
         }
     }
@@ -247,14 +485,6 @@ impl FileTableEntry {
         &self.file
     }
 
-    pub fn flags(&self) -> FdFlags {
-        FdFlags::from_bits(self.flags.load(Ordering::Relaxed)).unwrap()
-    }
-
-    pub fn set_flags(&self, flags: FdFlags) {
-        self.flags.store(flags.bits(), Ordering::Relaxed);
-    }
-
     pub fn register_observer(&self, epoll: Weak<dyn Observer<FdEvents>>) {
         self.subject.register_observer(epoll, ());
     }
@@ -272,7 +502,6 @@ impl Clone for FileTableEntry {
     fn clone(&self) -> Self {
         Self {
             file: self.file.clone(),
-            flags: AtomicU8::new(self.flags.load(Ordering::Relaxed)),
             subject: Subject::new(),
         }
     }
@@ -5,7 +5,7 @@ use alloc::str;
 use super::{
     file_table::FileDesc,
     inode_handle::InodeHandle,
-    path::Dentry,
+    path::{check_follow_link, check_open_existing, Dentry, PerMountFlags},
     rootfs::root_mount,
     utils::{AccessMode, CreationFlags, InodeMode, InodeType, StatusFlags, PATH_MAX, SYMLINKS_MAX},
 };
@@ -92,6 +92,12 @@ impl FsResolver {
                         "O_DIRECTORY is specified but file is not a directory"
                     );
                 }
+                if creation_flags.contains(CreationFlags::O_CREAT) {
+                    // The directory that contains the file itself, after following the links
+                    if let Some(dir_dentry) = dentry.effective_parent() {
+                        check_open_existing(&dir_dentry, &dentry)?;
+                    }
+                }
                 dentry
             }
             Err(e)
@@ -114,6 +120,16 @@ impl FsResolver {
             Err(e) => return Err(e),
         };
 
+        let mount_flags = dentry.mount_node().flags();
+        if mount_flags.contains(PerMountFlags::NODEV) && dentry.inode().as_device().is_some() {
+            return_errno_with_message!(Errno::EACCES, "the mount does not allow devices");
+        }
+        if dentry.type_() == InodeType::File
+            && (access_mode.is_writable() || creation_flags.contains(CreationFlags::O_TRUNC))
+        {
+            dentry.mount_node().check_writable()?;
+        }
+
         if creation_flags.contains(CreationFlags::O_TRUNC) {
             dentry.resize(0)?;
         }
@@ -201,6 +217,7 @@ impl FsResolver {
                 if follows >= SYMLINKS_MAX {
                     return_errno_with_message!(Errno::ELOOP, "too many symlinks");
                 }
+                check_follow_link(&dentry, &next_dentry)?;
                 let link_path_remain = {
                     let mut tmp_link_path = next_dentry.inode().read_link()?;
                     if tmp_link_path.is_empty() {
@@ -239,9 +256,8 @@ impl FsResolver {
     /// Lookup dentry from the giving fd
     pub fn lookup_from_fd(&self, fd: FileDesc) -> Result<Arc<Dentry>> {
         let current = current!();
-        let file_table = current.file_table().lock();
-        let inode_handle = file_table
-            .get_file(fd)?
+        let file = current.file_table().get_file(fd)?;
+        let inode_handle = file
             .downcast_ref::<InodeHandle>()
             .ok_or(Error::with_message(Errno::EBADF, "not inode"))?;
         Ok(inode_handle.dentry().clone())
@@ -292,6 +308,7 @@ impl FsResolver {
         loop {
             match dir_dentry.lookup(base_name.trim_end_matches('/')) {
                 Ok(dentry) if dentry.type_() == InodeType::SymLink => {
+                    check_follow_link(&dir_dentry, &dentry)?;
                     let link = {
                         let mut link = dentry.inode().read_link()?;
                         if link.is_empty() {
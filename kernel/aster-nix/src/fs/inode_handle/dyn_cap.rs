@@ -29,6 +29,7 @@ impl InodeHandle<Rights> {
             None
         };
 
+        dentry.mount_node().stats().add_open();
         let inner = Arc::new(InodeHandle_ {
             dentry,
             file_io,
@@ -61,6 +62,20 @@ impl InodeHandle<Rights> {
         }
         self.0.readdir(visitor)
     }
+
+    pub fn read_direct_async(&self, offset: usize, frames: &[Frame]) -> Result<(usize, BioWaiter)> {
+        if !self.1.contains(Rights::READ) {
+            return_errno_with_message!(Errno::EBADF, "File is not readable");
+        }
+        self.0.read_direct_async(offset, frames)
+    }
+
+    pub fn write_direct_async(&self, offset: usize, frames: &[Frame]) -> Result<BioWaiter> {
+        if !self.1.contains(Rights::WRITE) {
+            return_errno_with_message!(Errno::EBADF, "File is not writable");
+        }
+        self.0.write_direct_async(offset, frames)
+    }
 }
 
 impl Clone for InodeHandle<Rights> {
@@ -83,6 +98,15 @@ impl FileLike for InodeHandle<Rights> {
     fn group(&self) -> Result<Gid>;
     fn set_group(&self, gid: Gid) -> Result<()>;
     fn seek(&self, seek_from: SeekFrom) -> Result<usize>;
+    fn register_observer(
+        &self,
+        observer: Weak<dyn Observer<IoEvents>>,
+        mask: IoEvents,
+    ) -> Result<()>;
+    fn unregister_observer(
+        &self,
+        observer: &Weak<dyn Observer<IoEvents>>,
+    ) -> Option<Weak<dyn Observer<IoEvents>>>;
 
     fn read(&self, buf: &mut [u8]) -> Result<usize> {
         if !self.1.contains(Rights::READ) {
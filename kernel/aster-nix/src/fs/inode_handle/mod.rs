@@ -9,11 +9,13 @@ mod static_cap;
 
 use core::sync::atomic::{AtomicU32, Ordering};
 
+use aster_block::bio::BioWaiter;
 use aster_rights::Rights;
 use inherit_methods_macro::inherit_methods;
+use ostd::mm::Frame;
 
 use crate::{
-    events::IoEvents,
+    events::{IoEvents, Observer},
     fs::{
         device::Device,
         file_handle::FileLike,
@@ -74,19 +76,21 @@ impl InodeHandle_ {
 
     pub fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize> {
         if let Some(ref file_io) = self.file_io {
-            todo!("support read_at for FileIo");
+            return file_io.read_at(offset, buf);
         }
 
-        if self.status_flags().contains(StatusFlags::O_DIRECT) {
-            self.dentry.inode().read_direct_at(offset, buf)
+        let len = if self.status_flags().contains(StatusFlags::O_DIRECT) {
+            self.dentry.inode().read_direct_at(offset, buf)?
         } else {
-            self.dentry.inode().read_at(offset, buf)
-        }
+            self.dentry.inode().read_at(offset, buf)?
+        };
+        self.dentry.mount_node().stats().add_read_bytes(len);
+        Ok(len)
     }
 
     pub fn write_at(&self, mut offset: usize, buf: &[u8]) -> Result<usize> {
         if let Some(ref file_io) = self.file_io {
-            todo!("support write_at for FileIo");
+            return file_io.write_at(offset, buf);
         }
 
         if self.status_flags().contains(StatusFlags::O_APPEND) {
@@ -94,11 +98,51 @@ impl InodeHandle_ {
             offset = self.dentry.size();
         }
 
-        if self.status_flags().contains(StatusFlags::O_DIRECT) {
-            self.dentry.inode().write_direct_at(offset, buf)
+        let len = if self.status_flags().contains(StatusFlags::O_DIRECT) {
+            self.dentry.inode().write_direct_at(offset, buf)?
         } else {
-            self.dentry.inode().write_at(offset, buf)
+            self.dentry.inode().write_at(offset, buf)?
+        };
+        self.dentry.mount_node().stats().add_written_bytes(len);
+        Ok(len)
+    }
+
+    /// Reads the file at `offset` into `frames` asynchronously.
+    ///
+    /// See [`Inode::read_direct_async`] for the details. Only the files opened with
+    /// `O_DIRECT` support asynchronous I/O.
+    ///
+    /// [`Inode::read_direct_async`]: crate::fs::utils::Inode::read_direct_async
+    pub fn read_direct_async(&self, offset: usize, frames: &[Frame]) -> Result<(usize, BioWaiter)> {
+        self.check_direct_async()?;
+        self.dentry.inode().read_direct_async(offset, frames)
+    }
+
+    /// Writes `frames` to the file at `offset` asynchronously.
+    ///
+    /// See [`Inode::write_direct_async`] for the details. Only the files opened with
+    /// `O_DIRECT` support asynchronous I/O.
+    ///
+    /// [`Inode::write_direct_async`]: crate::fs::utils::Inode::write_direct_async
+    pub fn write_direct_async(&self, mut offset: usize, frames: &[Frame]) -> Result<BioWaiter> {
+        self.check_direct_async()?;
+
+        if self.status_flags().contains(StatusFlags::O_APPEND) {
+            // If the file has the O_APPEND flag, the offset is ignored
+            offset = self.dentry.size();
+        }
+
+        self.dentry.inode().write_direct_async(offset, frames)
+    }
+
+    fn check_direct_async(&self) -> Result<()> {
+        if self.file_io.is_some() || !self.status_flags().contains(StatusFlags::O_DIRECT) {
+            return_errno_with_message!(
+                Errno::EOPNOTSUPP,
+                "asynchronous I/O requires a file opened with O_DIRECT"
+            );
         }
+        Ok(())
     }
 
     pub fn read_to_end(&self, buf: &mut Vec<u8>) -> Result<usize> {
@@ -191,6 +235,26 @@ impl InodeHandle_ {
 
         self.dentry.inode().ioctl(cmd, arg)
     }
+
+    fn register_observer(
+        &self,
+        observer: Weak<dyn Observer<IoEvents>>,
+        mask: IoEvents,
+    ) -> Result<()> {
+        if let Some(ref file_io) = self.file_io {
+            return file_io.register_observer(observer, mask);
+        }
+
+        return_errno_with_message!(Errno::EPERM, "the inode does not support observers");
+    }
+
+    fn unregister_observer(
+        &self,
+        observer: &Weak<dyn Observer<IoEvents>>,
+    ) -> Option<Weak<dyn Observer<IoEvents>>> {
+        let file_io = self.file_io.as_ref()?;
+        file_io.unregister_observer(observer)
+    }
 }
 
 #[inherit_methods(from = "self.dentry")]
@@ -223,14 +287,42 @@ impl<R> InodeHandle<R> {
     }
 }
 
+/// The file operations of a special file, such as a device.
+///
+/// The operations mirror those of [`FileLike`], so an opened special file
+/// behaves the same as any other file to epoll, splice, and so on.
 pub trait FileIo: Send + Sync + 'static {
     fn read(&self, buf: &mut [u8]) -> Result<usize>;
 
     fn write(&self, buf: &[u8]) -> Result<usize>;
 
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<usize> {
+        return_errno_with_message!(Errno::ESPIPE, "read_at is not supported");
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> Result<usize> {
+        return_errno_with_message!(Errno::ESPIPE, "write_at is not supported");
+    }
+
     fn poll(&self, mask: IoEvents, poller: Option<&Poller>) -> IoEvents;
 
     fn ioctl(&self, cmd: IoctlCmd, arg: usize) -> Result<i32> {
         return_errno_with_message!(Errno::EINVAL, "ioctl is not supported");
     }
+
+    fn register_observer(
+        &self,
+        observer: Weak<dyn Observer<IoEvents>>,
+        mask: IoEvents,
+    ) -> Result<()> {
+        // As in Linux, epoll rejects the files that cannot be polled with EPERM.
+        return_errno_with_message!(Errno::EPERM, "the file does not support observers")
+    }
+
+    fn unregister_observer(
+        &self,
+        observer: &Weak<dyn Observer<IoEvents>>,
+    ) -> Option<Weak<dyn Observer<IoEvents>>> {
+        None
+    }
 }
@@ -1,4 +1,5 @@
 // SPDX-License-Identifier: MPL-2.0
+pub mod aio;
 pub mod device;
 pub mod devpts;
 pub mod epoll;
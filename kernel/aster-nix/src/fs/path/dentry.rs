@@ -13,8 +13,8 @@ use inherit_methods_macro::inherit_methods;
 use crate::{
     fs::{
         device::Device,
-        path::mount::MountNode,
-        utils::{FileSystem, Inode, InodeMode, InodeType, Metadata, NAME_MAX},
+        path::{mount::MountNode, protected::check_link},
+        utils::{FileSystem, Inode, InodeMode, InodeType, Metadata, RenameFlags, NAME_MAX},
     },
     prelude::*,
     process::{Gid, Uid},
@@ -247,7 +247,13 @@ impl Dentry_ {
     }
 
     /// Rename a Dentry_ to the new Dentry_ by renaming inode.
-    pub fn rename(&self, old_name: &str, new_dir: &Arc<Self>, new_name: &str) -> Result<()> {
+    pub fn rename(
+        &self,
+        old_name: &str,
+        new_dir: &Arc<Self>,
+        new_name: &str,
+        flags: RenameFlags,
+    ) -> Result<()> {
         if old_name == "." || old_name == ".." || new_name == "." || new_name == ".." {
             return_errno_with_message!(Errno::EISDIR, "old_name or new_name is a directory");
         }
@@ -255,15 +261,19 @@ impl Dentry_ {
             return_errno!(Errno::ENOTDIR);
         }
 
+        if flags.contains(RenameFlags::EXCHANGE) {
+            return self.exchange(old_name, new_dir, new_name, flags);
+        }
+
         // Self and new_dir are same Dentry_, just modify name
         if Arc::ptr_eq(&self.this(), new_dir) {
-            if old_name == new_name {
+            if old_name == new_name && !flags.contains(RenameFlags::NOREPLACE) {
                 return Ok(());
             }
             let mut children = self.children.lock();
             let old_dentry = children.find_dentry_with_checking_mountpoint(old_name)?;
             let _ = children.find_dentry_with_checking_mountpoint(new_name)?;
-            self.inode.rename(old_name, &self.inode, new_name)?;
+            self.inode.rename(old_name, &self.inode, new_name, flags)?;
             match old_dentry.as_ref() {
                 Some(dentry) => {
                     children.delete_dentry(old_name);
@@ -280,7 +290,8 @@ impl Dentry_ {
                 write_lock_children_on_two_dentries(self, new_dir);
             let old_dentry = self_children.find_dentry_with_checking_mountpoint(old_name)?;
             let _ = new_dir_children.find_dentry_with_checking_mountpoint(new_name)?;
-            self.inode.rename(old_name, &new_dir.inode, new_name)?;
+            self.inode
+                .rename(old_name, &new_dir.inode, new_name, flags)?;
             match old_dentry.as_ref() {
                 Some(dentry) => {
                     self_children.delete_dentry(old_name);
@@ -294,6 +305,54 @@ impl Dentry_ {
         }
         Ok(())
     }
+
+    /// Exchange the child `old_name` of this Dentry_ with the child `new_name` of `new_dir`
+    /// by exchanging the entries of the inodes.
+    fn exchange(
+        &self,
+        old_name: &str,
+        new_dir: &Arc<Self>,
+        new_name: &str,
+        flags: RenameFlags,
+    ) -> Result<()> {
+        if Arc::ptr_eq(&self.this(), new_dir) {
+            if old_name == new_name {
+                return Ok(());
+            }
+            let mut children = self.children.lock();
+            let old_dentry = children.find_dentry_with_checking_mountpoint(old_name)?;
+            let new_dentry = children.find_dentry_with_checking_mountpoint(new_name)?;
+            self.inode.rename(old_name, &self.inode, new_name, flags)?;
+            children.delete_dentry(old_name);
+            children.delete_dentry(new_name);
+            if let Some(dentry) = old_dentry.as_ref() {
+                dentry.set_name_and_parent(new_name, self.this());
+                children.insert_dentry(dentry);
+            }
+            if let Some(dentry) = new_dentry.as_ref() {
+                dentry.set_name_and_parent(old_name, self.this());
+                children.insert_dentry(dentry);
+            }
+        } else {
+            let (mut self_children, mut new_dir_children) =
+                write_lock_children_on_two_dentries(self, new_dir);
+            let old_dentry = self_children.find_dentry_with_checking_mountpoint(old_name)?;
+            let new_dentry = new_dir_children.find_dentry_with_checking_mountpoint(new_name)?;
+            self.inode
+                .rename(old_name, &new_dir.inode, new_name, flags)?;
+            self_children.delete_dentry(old_name);
+            new_dir_children.delete_dentry(new_name);
+            if let Some(dentry) = old_dentry.as_ref() {
+                dentry.set_name_and_parent(new_name, new_dir.this());
+                new_dir_children.insert_dentry(dentry);
+            }
+            if let Some(dentry) = new_dentry.as_ref() {
+                dentry.set_name_and_parent(old_name, self.this());
+                self_children.insert_dentry(dentry);
+            }
+        }
+        Ok(())
+    }
 }
 
 #[inherit_methods(from = "self.inode")]
@@ -450,6 +509,7 @@ impl Dentry {
 
     /// Crete a new Dentry to represent the child directory of a file system.
     pub fn new_fs_child(&self, name: &str, type_: InodeType, mode: InodeMode) -> Result<Arc<Self>> {
+        self.mount_node.check_writable()?;
         let new_child_dentry = self.inner.create(name, type_, mode)?;
         Ok(Self::new(self.mount_node.clone(), new_child_dentry.clone()))
     }
@@ -542,7 +602,7 @@ impl Dentry {
     ///
     /// If it is the root of mount, it will go up to the mountpoint to get the parent
     /// of the mountpoint recursively.
-    fn effective_parent(&self) -> Option<Arc<Self>> {
+    pub fn effective_parent(&self) -> Option<Arc<Self>> {
         if !self.inner.is_root_of_mount() {
             return Some(Self::new(
                 self.mount_node.clone(),
@@ -624,6 +684,7 @@ impl Dentry {
 
     /// Create a Dentry by making a device inode.
     pub fn mknod(&self, name: &str, mode: InodeMode, device: Arc<dyn Device>) -> Result<Arc<Self>> {
+        self.mount_node.check_writable()?;
         let inner = self.inner.mknod(name, mode, device)?;
         Ok(Self::new(self.mount_node.clone(), inner.clone()))
     }
@@ -633,25 +694,38 @@ impl Dentry {
         if !Arc::ptr_eq(&old.mount_node, &self.mount_node) {
             return_errno_with_message!(Errno::EXDEV, "cannot cross mount");
         }
+        self.mount_node.check_writable()?;
+        check_link(old)?;
         self.inner.link(&old.inner, name)
     }
 
     /// Delete a Dentry by unlinking inode.
     pub fn unlink(&self, name: &str) -> Result<()> {
+        self.mount_node.check_writable()?;
         self.inner.unlink(name)
     }
 
     /// Delete a directory Dentry by rmdiring inode.
     pub fn rmdir(&self, name: &str) -> Result<()> {
+        self.mount_node.check_writable()?;
         self.inner.rmdir(name)
     }
 
     /// Rename a Dentry to the new Dentry by renaming inode.
-    pub fn rename(&self, old_name: &str, new_dir: &Arc<Self>, new_name: &str) -> Result<()> {
+    ///
+    /// With `RenameFlags::EXCHANGE`, the two Dentries are exchanged instead.
+    pub fn rename(
+        &self,
+        old_name: &str,
+        new_dir: &Arc<Self>,
+        new_name: &str,
+        flags: RenameFlags,
+    ) -> Result<()> {
         if !Arc::ptr_eq(&self.mount_node, &new_dir.mount_node) {
             return_errno_with_message!(Errno::EXDEV, "cannot cross mount");
         }
-        self.inner.rename(old_name, &new_dir.inner, new_name)
+        self.mount_node.check_writable()?;
+        self.inner.rename(old_name, &new_dir.inner, new_name, flags)
     }
 
     /// Bind mount the Dentry to the destination Dentry.
@@ -3,7 +3,11 @@
 //! Form file paths within and across FSes with dentries and mount points.
 
 pub use dentry::{Dentry, DentryKey};
-pub use mount::MountNode;
+pub use mount::{MountNode, MountStats, PerMountFlags};
+pub use protected::{PROTECTED_FIFOS, PROTECTED_HARDLINKS, PROTECTED_REGULAR, PROTECTED_SYMLINKS};
+
+pub(in crate::fs) use protected::{check_follow_link, check_open_existing};
 
 mod dentry;
 mod mount;
+mod protected;
@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use core::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
 use crate::{
     fs::{
         path::dentry::{Dentry, DentryKey, Dentry_},
@@ -21,6 +23,10 @@ pub struct MountNode {
     parent: RwLock<Option<Weak<MountNode>>>,
     /// Child mount nodes which are mounted on one dentry of self.
     children: Mutex<BTreeMap<DentryKey, Arc<Self>>>,
+    /// The flags of this mount, stored as the bits of `PerMountFlags`.
+    flags: AtomicU32,
+    /// The statistics of the file operations through this mount.
+    stats: MountStats,
     /// Reference to self.
     this: Weak<Self>,
 }
@@ -51,6 +57,8 @@ impl MountNode {
             mountpoint_dentry: RwLock::new(None),
             parent: RwLock::new(parent_mount),
             children: Mutex::new(BTreeMap::new()),
+            flags: AtomicU32::new(0),
+            stats: MountStats::default(),
             fs,
             this: weak_self.clone(),
         })
@@ -99,7 +107,7 @@ impl MountNode {
 
     /// Clone a mount node with the an root `Dentry_`.
     ///
-    /// The new mount node will have the same fs as the original one and
+    /// The new mount node will have the same fs and flags as the original one and
     /// have no parent and children. We should set the parent and children manually.
     fn clone_mount_node(&self, root_dentry: &Arc<Dentry_>) -> Arc<Self> {
         Arc::new_cyclic(|weak_self| Self {
@@ -107,6 +115,8 @@ impl MountNode {
             mountpoint_dentry: RwLock::new(None),
             parent: RwLock::new(None),
             children: Mutex::new(BTreeMap::new()),
+            flags: AtomicU32::new(self.flags.load(Ordering::Relaxed)),
+            stats: MountStats::default(),
             fs: self.fs.clone(),
             this: weak_self.clone(),
         })
@@ -198,6 +208,18 @@ impl MountNode {
         self.children.lock().get(&mountpoint.key()).cloned()
     }
 
+    /// Returns this mount node and all its descendant mount nodes in pre-order.
+    pub fn collect_mount_nodes(&self) -> Vec<Arc<Self>> {
+        let mut mount_nodes = Vec::new();
+        let mut stack = vec![self.this()];
+        while let Some(mount_node) = stack.pop() {
+            // Push the children in reverse order, so that they are visited in order.
+            stack.extend(mount_node.children.lock().values().rev().cloned());
+            mount_nodes.push(mount_node);
+        }
+        mount_nodes
+    }
+
     /// Get the root `Dentry_` of this mount node.
     pub fn root_dentry(&self) -> &Arc<Dentry_> {
         &self.root_dentry
@@ -252,6 +274,85 @@ impl MountNode {
     pub fn fs(&self) -> &Arc<dyn FileSystem> {
         &self.fs
     }
+
+    /// Returns the flags of this mount.
+    pub fn flags(&self) -> PerMountFlags {
+        PerMountFlags::from_bits_truncate(self.flags.load(Ordering::Relaxed))
+    }
+
+    /// Sets the flags of this mount.
+    pub fn set_flags(&self, flags: PerMountFlags) {
+        self.flags.store(flags.bits(), Ordering::Relaxed);
+    }
+
+    /// Checks whether the files can be modified through this mount.
+    pub fn check_writable(&self) -> Result<()> {
+        if self.flags().contains(PerMountFlags::RDONLY) {
+            return_errno_with_message!(Errno::EROFS, "the mount is read-only");
+        }
+        Ok(())
+    }
+
+    /// Returns the statistics of the file operations through this mount.
+    pub fn stats(&self) -> &MountStats {
+        &self.stats
+    }
+}
+
+bitflags! {
+    /// The flags of a mount, which apply to the files accessed through the mount.
+    ///
+    /// The values are the same as the `ST_*` flags reported by `statfs` in Linux.
+    pub struct PerMountFlags: u32 {
+        /// The files cannot be modified.
+        const RDONLY = 1 << 0;
+        /// The set-user-ID and set-group-ID bits are ignored by `execve`.
+        const NOSUID = 1 << 1;
+        /// The device files cannot be opened.
+        const NODEV = 1 << 2;
+        /// The files cannot be executed.
+        const NOEXEC = 1 << 3;
+    }
+}
+
+/// The statistics of the file operations through a mount.
+#[derive(Debug, Default)]
+pub struct MountStats {
+    opens: AtomicU64,
+    read_bytes: AtomicU64,
+    written_bytes: AtomicU64,
+}
+
+impl MountStats {
+    /// Counts a file that is opened.
+    pub fn add_open(&self) {
+        self.opens.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts the bytes that are read from a file.
+    pub fn add_read_bytes(&self, len: usize) {
+        self.read_bytes.fetch_add(len as u64, Ordering::Relaxed);
+    }
+
+    /// Counts the bytes that are written to a file.
+    pub fn add_written_bytes(&self, len: usize) {
+        self.written_bytes.fetch_add(len as u64, Ordering::Relaxed);
+    }
+
+    /// Returns the number of the files that have been opened.
+    pub fn opens(&self) -> u64 {
+        self.opens.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of the bytes that have been read from the files.
+    pub fn read_bytes(&self) -> u64 {
+        self.read_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of the bytes that have been written to the files.
+    pub fn written_bytes(&self) -> u64 {
+        self.written_bytes.load(Ordering::Relaxed)
+    }
 }
 
 impl Debug for MountNode {
@@ -260,6 +361,7 @@ impl Debug for MountNode {
             .field("root", &self.root_dentry)
             .field("mountpoint", &self.mountpoint_dentry)
             .field("fs", &self.fs)
+            .field("flags", &self.flags())
             .finish()
     }
 }
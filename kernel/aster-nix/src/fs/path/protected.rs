@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Protection against the link and file attacks in shared directories.
+//!
+//! In a world-writable sticky directory like `/tmp`, a user can plant a symlink,
+//! a hard link, a FIFO or a regular file in advance, which tricks a privileged
+//! process into following or opening it. The protection works the same as the
+//! `protected_symlinks`, `protected_hardlinks`, `protected_fifos`, and
+//! `protected_regular` sysctls in Linux, all of which are enabled by default.
+//! They can be changed through the files of the same names in `/proc/sys/fs`.
+
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use super::Dentry;
+use crate::{
+    fs::utils::{InodeMode, InodeType},
+    prelude::*,
+    process::{credentials, credentials::capabilities::CapSet},
+};
+
+/// Whether a symlink in a world-writable sticky directory can only be followed
+/// by its owner, or if it is owned by the owner of the directory.
+pub static PROTECTED_SYMLINKS: AtomicBool = AtomicBool::new(true);
+
+/// Whether a user can only create hard links to the files that they own, or
+/// that they can read and write.
+pub static PROTECTED_HARDLINKS: AtomicBool = AtomicBool::new(true);
+
+/// Whether opening an existing FIFO with `O_CREAT` in a sticky directory is
+/// restricted if the FIFO is owned by neither the user nor the owner of the
+/// directory.
+///
+/// If the value is 1, world-writable sticky directories are protected. If the
+/// value is 2, group-writable sticky directories are protected as well. Zero
+/// means no protection.
+pub static PROTECTED_FIFOS: AtomicU8 = AtomicU8::new(1);
+
+/// The same as [`PROTECTED_FIFOS`], but for regular files.
+pub static PROTECTED_REGULAR: AtomicU8 = AtomicU8::new(1);
+
+/// Checks whether the symlink `link` in the directory `dir` can be followed.
+pub(in crate::fs) fn check_follow_link(dir: &Dentry, link: &Dentry) -> Result<()> {
+    if !PROTECTED_SYMLINKS.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+
+    let dir_mode = dir.mode()?;
+    if !dir_mode.has_sticky_bit() || !dir_mode.contains(InodeMode::S_IWOTH) {
+        return Ok(());
+    }
+    let link_owner = link.owner()?;
+    if credentials().fsuid() == link_owner || dir.owner()? == link_owner {
+        return Ok(());
+    }
+
+    return_errno_with_message!(
+        Errno::EACCES,
+        "the symlink in a sticky directory is not owned by the follower"
+    );
+}
+
+/// Checks whether a hard link to `target` can be created.
+pub(in crate::fs) fn check_link(target: &Dentry) -> Result<()> {
+    if !PROTECTED_HARDLINKS.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+
+    let credentials = credentials();
+    if target.owner()? == credentials.fsuid()
+        || credentials.effective_capset().contains(CapSet::FOWNER)
+    {
+        return Ok(());
+    }
+
+    // A file that is not owned by the user is only safe to link to if it is an
+    // ordinary file that the user can read and write.
+    let mode = target.mode()?;
+    let is_safe_source = target.type_() == InodeType::File
+        && !mode.has_set_uid()
+        && !(mode.has_set_gid() && mode.contains(InodeMode::S_IXGRP))
+        && is_readable_and_writable(target, mode)?;
+    if !is_safe_source {
+        return_errno_with_message!(Errno::EPERM, "the link target is not owned by the user");
+    }
+    Ok(())
+}
+
+/// Checks whether the existing `dentry` in the directory `dir` can be opened with `O_CREAT`.
+pub(in crate::fs) fn check_open_existing(dir: &Dentry, dentry: &Dentry) -> Result<()> {
+    let protection = match dentry.type_() {
+        InodeType::NamedPipe => PROTECTED_FIFOS.load(Ordering::Relaxed),
+        InodeType::File => PROTECTED_REGULAR.load(Ordering::Relaxed),
+        _ => return Ok(()),
+    };
+    if protection == 0 {
+        return Ok(());
+    }
+
+    let dir_mode = dir.mode()?;
+    if !dir_mode.has_sticky_bit() {
+        return Ok(());
+    }
+    let owner = dentry.owner()?;
+    if owner == dir.owner()? || owner == credentials().fsuid() {
+        return Ok(());
+    }
+
+    if dir_mode.contains(InodeMode::S_IWOTH)
+        || (protection >= 2 && dir_mode.contains(InodeMode::S_IWGRP))
+    {
+        return_errno_with_message!(
+            Errno::EACCES,
+            "the file in a sticky directory is not owned by the user"
+        );
+    }
+    Ok(())
+}
+
+/// Returns whether a user other than the owner can read and write the file.
+fn is_readable_and_writable(dentry: &Dentry, mode: InodeMode) -> Result<bool> {
+    let credentials = credentials();
+    if credentials
+        .effective_capset()
+        .contains(CapSet::DAC_OVERRIDE)
+    {
+        return Ok(true);
+    }
+
+    let group = dentry.group()?;
+    let is_accessible = if credentials.fsgid() == group || credentials.groups().contains(&group) {
+        mode.contains(InodeMode::S_IRGRP | InodeMode::S_IWGRP)
+    } else {
+        mode.contains(InodeMode::S_IROTH | InodeMode::S_IWOTH)
+    };
+    Ok(is_accessible)
+}
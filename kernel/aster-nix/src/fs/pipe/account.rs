@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Per-user accounting of the pages held by pipes.
+//!
+//! The limits work the same as the `pipe-max-size`, `pipe-user-pages-soft`,
+//! and `pipe-user-pages-hard` sysctls in Linux, and can be changed through
+//! the files of the same names under `/proc/sys/fs`.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{
+    prelude::*,
+    process::{credentials, credentials::capabilities::CapSet, Uid},
+};
+
+/// The maximum capacity, in bytes, that an unprivileged user can set a pipe to.
+pub static PIPE_MAX_SIZE: AtomicUsize = AtomicUsize::new(1024 * 1024);
+
+/// The number of pages a user can hold in pipes before the new pipes of
+/// the user are created with the minimum capacity. Zero means no limit.
+pub static PIPE_USER_PAGES_SOFT: AtomicUsize = AtomicUsize::new(16384);
+
+/// The number of pages a user can hold in pipes before creating or
+/// enlarging the pipes of the user fails. Zero means no limit.
+pub static PIPE_USER_PAGES_HARD: AtomicUsize = AtomicUsize::new(0);
+
+/// Rounds up `size` to a valid pipe capacity in bytes.
+///
+/// As in Linux, the capacity is a power of two number of pages, and a size
+/// larger than 2 GiB is invalid.
+pub fn round_pipe_size(size: usize) -> Result<usize> {
+    if size > 1 << 31 {
+        return_errno_with_message!(Errno::EINVAL, "the pipe size is too large");
+    }
+    Ok(size.div_ceil(PAGE_SIZE).max(1).next_power_of_two() * PAGE_SIZE)
+}
+
+/// The default capacity of a pipe in pages, which is 64 KiB as in Linux.
+const DEFAULT_PIPE_BUFS: usize = 16;
+
+/// The capacity of a pipe in pages if the user has too many pipe pages.
+const MIN_DEFAULT_PIPE_BUFS: usize = 2;
+
+/// The number of pages held by the pipes of each user.
+static USER_PIPE_BUFS: SpinLock<BTreeMap<Uid, usize>> = SpinLock::new(BTreeMap::new());
+
+/// The pages that are accounted to the user who creates a pipe.
+///
+/// The pages are unaccounted when this object is dropped.
+pub(super) struct PipeBufsAccount {
+    uid: Uid,
+    nr_bufs: usize,
+}
+
+impl PipeBufsAccount {
+    /// Accounts the pages of a new pipe to the current user.
+    pub fn new() -> Result<Self> {
+        let mut account = Self {
+            uid: credentials().ruid(),
+            nr_bufs: 0,
+        };
+
+        let mut user_bufs = account.set_nr_bufs(DEFAULT_PIPE_BUFS);
+        if too_many_bufs_soft(user_bufs) && is_unprivileged_user() {
+            user_bufs = account.set_nr_bufs(MIN_DEFAULT_PIPE_BUFS);
+        }
+        if too_many_bufs_hard(user_bufs) && is_unprivileged_user() {
+            return_errno_with_message!(Errno::ENFILE, "the user has too many pipe pages");
+        }
+
+        Ok(account)
+    }
+
+    /// Returns the number of pages, i.e., the capacity of the pipe.
+    pub fn nr_bufs(&self) -> usize {
+        self.nr_bufs
+    }
+
+    /// Changes the number of pages when the pipe is resized.
+    pub fn resize(&mut self, nr_bufs: usize) -> Result<()> {
+        if nr_bufs * PAGE_SIZE > PIPE_MAX_SIZE.load(Ordering::Relaxed)
+            && !is_capable(CapSet::SYS_RESOURCE)
+        {
+            return_errno_with_message!(Errno::EPERM, "the pipe size exceeds the maximum");
+        }
+
+        let old_nr_bufs = self.nr_bufs;
+        let user_bufs = self.set_nr_bufs(nr_bufs);
+        if nr_bufs > old_nr_bufs
+            && (too_many_bufs_hard(user_bufs) || too_many_bufs_soft(user_bufs))
+            && is_unprivileged_user()
+        {
+            self.set_nr_bufs(old_nr_bufs);
+            return_errno_with_message!(Errno::EPERM, "the user has too many pipe pages");
+        }
+
+        Ok(())
+    }
+
+    /// Sets the number of pages and returns the total number of pages of the user.
+    fn set_nr_bufs(&mut self, nr_bufs: usize) -> usize {
+        let mut all_user_bufs = USER_PIPE_BUFS.lock_irq_disabled();
+        let user_bufs = all_user_bufs.entry(self.uid).or_insert(0);
+        *user_bufs = *user_bufs - self.nr_bufs + nr_bufs;
+        self.nr_bufs = nr_bufs;
+
+        let user_bufs = *user_bufs;
+        if user_bufs == 0 {
+            all_user_bufs.remove(&self.uid);
+        }
+        user_bufs
+    }
+}
+
+impl Drop for PipeBufsAccount {
+    fn drop(&mut self) {
+        self.set_nr_bufs(0);
+    }
+}
+
+fn too_many_bufs_soft(user_bufs: usize) -> bool {
+    let soft_limit = PIPE_USER_PAGES_SOFT.load(Ordering::Relaxed);
+    soft_limit != 0 && user_bufs > soft_limit
+}
+
+fn too_many_bufs_hard(user_bufs: usize) -> bool {
+    let hard_limit = PIPE_USER_PAGES_HARD.load(Ordering::Relaxed);
+    hard_limit != 0 && user_bufs > hard_limit
+}
+
+fn is_unprivileged_user() -> bool {
+    !is_capable(CapSet::SYS_RESOURCE) && !is_capable(CapSet::SYS_ADMIN)
+}
+
+fn is_capable(cap: CapSet) -> bool {
+    credentials().effective_capset().contains(cap)
+}
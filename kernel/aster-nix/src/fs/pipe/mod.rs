@@ -0,0 +1,504 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#![allow(dead_code)]
+
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use self::{account::PipeBufsAccount, page_ring::PageRing};
+use super::{
+    file_handle::FileLike,
+    utils::{AccessMode, InodeMode, InodeType, Metadata, StatusFlags},
+};
+use crate::{
+    events::{IoEvents, Observer},
+    prelude::*,
+    process::{
+        signal::{Pollee, Poller},
+        Gid, Uid,
+    },
+    time::clocks::RealTimeCoarseClock,
+};
+
+mod account;
+mod page_ring;
+
+pub use self::account::{
+    round_pipe_size, PIPE_MAX_SIZE, PIPE_USER_PAGES_HARD, PIPE_USER_PAGES_SOFT,
+};
+
+/// The maximum number of bytes that are written into a pipe atomically.
+const PIPE_BUF: usize = PAGE_SIZE;
+
+/// Creates a pipe and returns its read end and write end.
+///
+/// If `flags` contains `O_DIRECT`, the pipe works in the packet mode,
+/// where each write is read as a separate packet. As in Linux, the flag
+/// only appears in the status flags of the write end.
+pub fn new_pipe_pair(flags: StatusFlags) -> Result<(Arc<PipeReader>, Arc<PipeWriter>)> {
+    check_status_flags(flags)?;
+
+    let pipe = Arc::new(Pipe::new()?);
+    let reader = PipeReader {
+        pipe: pipe.clone(),
+        status_flags: AtomicU32::new((flags - StatusFlags::O_DIRECT).bits()),
+    };
+    let writer = PipeWriter {
+        pipe,
+        status_flags: AtomicU32::new(flags.bits()),
+    };
+    Ok((Arc::new(reader), Arc::new(writer)))
+}
+
+pub struct PipeReader {
+    pipe: Arc<Pipe>,
+    status_flags: AtomicU32,
+}
+
+impl PipeReader {
+    /// Returns the pipe that this end belongs to.
+    pub fn pipe(&self) -> &Pipe {
+        &self.pipe
+    }
+
+    fn try_read(&self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let mut ring = self.pipe.ring.lock();
+        let read_len = ring.read(&mut VmWriter::from(buf));
+        self.pipe.update_pollee(&ring);
+
+        if read_len > 0 || self.pipe.is_writer_closed() {
+            Ok(read_len)
+        } else {
+            return_errno_with_message!(Errno::EAGAIN, "try read later");
+        }
+    }
+}
+
+impl FileLike for PipeReader {
+    fn read(&self, buf: &mut [u8]) -> Result<usize> {
+        let is_nonblocking = self.status_flags().contains(StatusFlags::O_NONBLOCK);
+        wait_for_io(&self.pipe.read_pollee, IoEvents::IN, is_nonblocking, || {
+            self.try_read(buf)
+        })
+    }
+
+    fn poll(&self, mask: IoEvents, poller: Option<&Poller>) -> IoEvents {
+        self.pipe.read_pollee.poll(mask, poller)
+    }
+
+    fn status_flags(&self) -> StatusFlags {
+        StatusFlags::from_bits(self.status_flags.load(Ordering::Relaxed)).unwrap()
+    }
+
+    fn set_status_flags(&self, new_flags: StatusFlags) -> Result<()> {
+        check_status_flags(new_flags)?;
+        self.status_flags.store(new_flags.bits(), Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn access_mode(&self) -> AccessMode {
+        AccessMode::O_RDONLY
+    }
+
+    fn metadata(&self) -> Metadata {
+        self.pipe.metadata(InodeMode::from_bits_truncate(0o400))
+    }
+
+    fn register_observer(
+        &self,
+        observer: Weak<dyn Observer<IoEvents>>,
+        mask: IoEvents,
+    ) -> Result<()> {
+        self.pipe.read_pollee.register_observer(observer, mask);
+        Ok(())
+    }
+
+    fn unregister_observer(
+        &self,
+        observer: &Weak<dyn Observer<IoEvents>>,
+    ) -> Option<Weak<dyn Observer<IoEvents>>> {
+        self.pipe.read_pollee.unregister_observer(observer)
+    }
+
+    fn as_pipe(&self) -> Option<&Pipe> {
+        Some(&self.pipe)
+    }
+}
+
+impl Drop for PipeReader {
+    fn drop(&mut self) {
+        let _ring = self.pipe.ring.lock();
+        self.pipe.is_reader_closed.store(true, Ordering::Release);
+
+        // POLLERR is also set for a file descriptor referring to the write end of a pipe
+        // when the read end has been closed.
+        self.pipe.write_pollee.add_events(IoEvents::ERR);
+    }
+}
+
+pub struct PipeWriter {
+    pipe: Arc<Pipe>,
+    status_flags: AtomicU32,
+}
+
+impl PipeWriter {
+    /// Returns the pipe that this end belongs to.
+    pub fn pipe(&self) -> &Pipe {
+        &self.pipe
+    }
+
+    fn try_write(&self, buf: &[u8], is_packet: bool) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let mut ring = self.pipe.ring.lock();
+        if self.pipe.is_reader_closed() {
+            return_errno_with_message!(Errno::EPIPE, "the read end is closed");
+        }
+
+        // Writes of at most `PIPE_BUF` bytes must not be interleaved with other writes.
+        if buf.len() <= PIPE_BUF && ring.free_space() < buf.len() {
+            return_errno_with_message!(Errno::EAGAIN, "try write later");
+        }
+
+        let written_len = ring.write(&mut VmReader::from(buf), is_packet)?;
+        self.pipe.update_pollee(&ring);
+
+        if written_len > 0 {
+            Ok(written_len)
+        } else {
+            return_errno_with_message!(Errno::EAGAIN, "try write later");
+        }
+    }
+}
+
+impl FileLike for PipeWriter {
+    fn write(&self, buf: &[u8]) -> Result<usize> {
+        let status_flags = self.status_flags();
+        let is_nonblocking = status_flags.contains(StatusFlags::O_NONBLOCK);
+        let is_packet = status_flags.contains(StatusFlags::O_DIRECT);
+        wait_for_io(
+            &self.pipe.write_pollee,
+            IoEvents::OUT,
+            is_nonblocking,
+            || self.try_write(buf, is_packet),
+        )
+    }
+
+    fn poll(&self, mask: IoEvents, poller: Option<&Poller>) -> IoEvents {
+        self.pipe.write_pollee.poll(mask, poller)
+    }
+
+    fn status_flags(&self) -> StatusFlags {
+        StatusFlags::from_bits(self.status_flags.load(Ordering::Relaxed)).unwrap()
+    }
+
+    fn set_status_flags(&self, new_flags: StatusFlags) -> Result<()> {
+        check_status_flags(new_flags)?;
+        self.status_flags.store(new_flags.bits(), Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn access_mode(&self) -> AccessMode {
+        AccessMode::O_WRONLY
+    }
+
+    fn metadata(&self) -> Metadata {
+        self.pipe.metadata(InodeMode::from_bits_truncate(0o200))
+    }
+
+    fn register_observer(
+        &self,
+        observer: Weak<dyn Observer<IoEvents>>,
+        mask: IoEvents,
+    ) -> Result<()> {
+        self.pipe.write_pollee.register_observer(observer, mask);
+        Ok(())
+    }
+
+    fn unregister_observer(
+        &self,
+        observer: &Weak<dyn Observer<IoEvents>>,
+    ) -> Option<Weak<dyn Observer<IoEvents>>> {
+        self.pipe.write_pollee.unregister_observer(observer)
+    }
+
+    fn as_pipe(&self) -> Option<&Pipe> {
+        Some(&self.pipe)
+    }
+}
+
+impl Drop for PipeWriter {
+    fn drop(&mut self) {
+        let _ring = self.pipe.ring.lock();
+        self.pipe.is_writer_closed.store(true, Ordering::Release);
+
+        // When reading from a channel such as a pipe or a stream socket,
+        // POLLHUP merely indicates that the peer closed its end of the channel.
+        self.pipe.read_pollee.add_events(IoEvents::HUP);
+    }
+}
+
+/// The state shared by both ends of a pipe.
+pub struct Pipe {
+    ring: Mutex<PageRing>,
+    account: Mutex<PipeBufsAccount>,
+    read_pollee: Pollee,
+    write_pollee: Pollee,
+    is_reader_closed: AtomicBool,
+    is_writer_closed: AtomicBool,
+}
+
+impl Pipe {
+    fn new() -> Result<Self> {
+        let account = PipeBufsAccount::new()?;
+        Ok(Self {
+            ring: Mutex::new(PageRing::new(account.nr_bufs())),
+            account: Mutex::new(account),
+            read_pollee: Pollee::new(IoEvents::empty()),
+            write_pollee: Pollee::new(IoEvents::OUT),
+            is_reader_closed: AtomicBool::new(false),
+            is_writer_closed: AtomicBool::new(false),
+        })
+    }
+
+    /// Returns the capacity of the pipe in bytes.
+    pub fn capacity(&self) -> usize {
+        self.ring.lock().max_bufs() * PAGE_SIZE
+    }
+
+    /// Resizes the pipe to hold at least `size` bytes, and returns the new capacity.
+    ///
+    /// The capacity is rounded up to a power of two number of pages.
+    pub fn set_capacity(&self, size: usize) -> Result<usize> {
+        let nr_bufs = round_pipe_size(size)? / PAGE_SIZE;
+
+        let mut account = self.account.lock();
+        let mut ring = self.ring.lock();
+        if ring.max_bufs() == nr_bufs {
+            return Ok(nr_bufs * PAGE_SIZE);
+        }
+        let old_nr_bufs = ring.max_bufs();
+        ring.set_max_bufs(nr_bufs)?;
+        if let Err(err) = account.resize(nr_bufs) {
+            // The ring can always hold its data with the old capacity.
+            ring.set_max_bufs(old_nr_bufs).unwrap();
+            return Err(err);
+        }
+        self.update_pollee(&ring);
+
+        Ok(nr_bufs * PAGE_SIZE)
+    }
+
+    /// Moves at most `len` bytes of data from this pipe to `dst`, and returns
+    /// the number of bytes moved.
+    ///
+    /// This is the pipe-to-pipe case of `splice`, where the pages holding the
+    /// data are handed over to `dst` without copying the data.
+    pub fn splice_to(&self, dst: &Pipe, len: usize, is_nonblocking: bool) -> Result<usize> {
+        self.transfer_to(dst, len, is_nonblocking, |src_ring, dst_ring| {
+            let mut moved_len = 0;
+            while moved_len < len && !dst_ring.is_full() {
+                let Some(buf) = src_ring.pop_buffer(len - moved_len) else {
+                    break;
+                };
+                moved_len += buf.len();
+                // The ring is not full, so the buffer can always be pushed.
+                dst_ring.push_buffer(buf).unwrap();
+            }
+            moved_len
+        })
+    }
+
+    /// Duplicates at most `len` bytes of data from this pipe to `dst` without
+    /// consuming the data, and returns the number of bytes duplicated.
+    ///
+    /// This is how `tee` works, where the pages holding the data are shared
+    /// by both pipes without copying the data.
+    pub fn tee_to(&self, dst: &Pipe, len: usize, is_nonblocking: bool) -> Result<usize> {
+        self.transfer_to(dst, len, is_nonblocking, |src_ring, dst_ring| {
+            let mut dup_len = 0;
+            for buf in src_ring.dup_buffers(len) {
+                let buf_len = buf.len();
+                if dst_ring.push_buffer(buf).is_err() {
+                    break;
+                }
+                dup_len += buf_len;
+            }
+            dup_len
+        })
+    }
+
+    /// Waits until this pipe has data and `dst` has room, and then transfers
+    /// the data with the rings of both pipes locked.
+    ///
+    /// If this pipe is empty and the write end is closed, zero is returned.
+    fn transfer_to<F>(
+        &self,
+        dst: &Pipe,
+        len: usize,
+        is_nonblocking: bool,
+        mut transfer: F,
+    ) -> Result<usize>
+    where
+        F: FnMut(&mut PageRing, &mut PageRing) -> usize,
+    {
+        if core::ptr::eq(self, dst) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the data cannot be transferred to the same pipe"
+            );
+        }
+        if len == 0 {
+            return Ok(0);
+        }
+
+        loop {
+            let has_data = wait_for_io(&self.read_pollee, IoEvents::IN, is_nonblocking, || {
+                let ring = self.ring.lock();
+                if !ring.is_empty() {
+                    Ok(true)
+                } else if self.is_writer_closed() {
+                    Ok(false)
+                } else {
+                    return_errno_with_message!(Errno::EAGAIN, "try transfer later");
+                }
+            })?;
+            if !has_data {
+                return Ok(0);
+            }
+
+            wait_for_io(&dst.write_pollee, IoEvents::OUT, is_nonblocking, || {
+                let ring = dst.ring.lock();
+                if dst.is_reader_closed() {
+                    return_errno_with_message!(Errno::EPIPE, "the read end is closed");
+                }
+                if ring.is_full() {
+                    return_errno_with_message!(Errno::EAGAIN, "try transfer later");
+                }
+                Ok(())
+            })?;
+
+            // Lock the rings in a fixed order, so that the transfers in the opposite
+            // directions between the same pipes cannot deadlock.
+            let (mut src_ring, mut dst_ring) = if (self as *const Pipe) < (dst as *const Pipe) {
+                let src_ring = self.ring.lock();
+                (src_ring, dst.ring.lock())
+            } else {
+                let dst_ring = dst.ring.lock();
+                (self.ring.lock(), dst_ring)
+            };
+            if dst.is_reader_closed() {
+                return_errno_with_message!(Errno::EPIPE, "the read end is closed");
+            }
+
+            let transferred_len = transfer(&mut src_ring, &mut dst_ring);
+            self.update_pollee(&src_ring);
+            dst.update_pollee(&dst_ring);
+
+            // The data or the room may have been taken by others after the waits.
+            if transferred_len > 0 {
+                return Ok(transferred_len);
+            }
+            if is_nonblocking {
+                return_errno_with_message!(Errno::EAGAIN, "try transfer later");
+            }
+        }
+    }
+
+    fn is_reader_closed(&self) -> bool {
+        self.is_reader_closed.load(Ordering::Acquire)
+    }
+
+    fn is_writer_closed(&self) -> bool {
+        self.is_writer_closed.load(Ordering::Acquire)
+    }
+
+    /// Updates the events of both ends.
+    ///
+    /// This method must be called with the ring locked, so that the pollees
+    /// always reflect the _true_ state of the ring regardless of any race conditions.
+    fn update_pollee(&self, ring: &PageRing) {
+        if ring.is_empty() {
+            self.read_pollee.del_events(IoEvents::IN);
+        } else {
+            self.read_pollee.add_events(IoEvents::IN);
+        }
+
+        if ring.is_full() {
+            self.write_pollee.del_events(IoEvents::OUT);
+        } else {
+            self.write_pollee.add_events(IoEvents::OUT);
+        }
+    }
+
+    fn metadata(&self, mode: InodeMode) -> Metadata {
+        let now = RealTimeCoarseClock::get().read_time();
+        Metadata {
+            dev: 0,
+            ino: 0,
+            size: 0,
+            blk_size: 0,
+            blocks: 0,
+            atime: now,
+            mtime: now,
+            ctime: now,
+            type_: InodeType::NamedPipe,
+            mode,
+            nlinks: 1,
+            uid: Uid::new_root(),
+            gid: Gid::new_root(),
+            rdev: 0,
+        }
+    }
+}
+
+fn check_status_flags(flags: StatusFlags) -> Result<()> {
+    let valid_flags: StatusFlags = StatusFlags::O_NONBLOCK | StatusFlags::O_DIRECT;
+    if !valid_flags.contains(flags) {
+        return_errno_with_message!(Errno::EINVAL, "invalid flags");
+    }
+    Ok(())
+}
+
+/// Tries the I/O operation until it succeeds, fails, or would block in the non-blocking mode.
+fn wait_for_io<T>(
+    pollee: &Pollee,
+    mask: IoEvents,
+    is_nonblocking: bool,
+    mut try_io: impl FnMut() -> Result<T>,
+) -> Result<T> {
+    // Fast path
+    let res = try_io();
+    if should_io_return(&res, is_nonblocking) {
+        return res;
+    }
+
+    // Slow path
+    let poller = Poller::new();
+    loop {
+        let res = try_io();
+        if should_io_return(&res, is_nonblocking) {
+            return res;
+        }
+        let events = pollee.poll(mask, Some(&poller));
+        if events.is_empty() {
+            poller.wait()?;
+        }
+    }
+}
+
+fn should_io_return<T>(res: &Result<T>, is_nonblocking: bool) -> bool {
+    if is_nonblocking {
+        return true;
+    }
+    match res {
+        Ok(_) => true,
+        Err(e) if e.error() == Errno::EAGAIN => false,
+        Err(_) => true,
+    }
+}
@@ -0,0 +1,337 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::collections::VecDeque;
+
+use ostd::mm::{Frame, FrameAllocOptions};
+
+use crate::prelude::*;
+
+/// A ring of page buffers, which holds the data in a pipe.
+///
+/// The data written into the ring is copied into pages only once. After that,
+/// the pages are owned by the ring and are handed over as they are. Readers
+/// copy the data out of the pages, while `splice` moves buffers between rings
+/// and `tee` shares them, both without copying the data.
+pub(super) struct PageRing {
+    bufs: VecDeque<PipeBuffer>,
+    max_bufs: usize,
+    len: usize,
+}
+
+impl PageRing {
+    /// Creates an empty ring that holds at most `max_bufs` buffers.
+    pub fn new(max_bufs: usize) -> Self {
+        Self {
+            bufs: VecDeque::with_capacity(max_bufs),
+            max_bufs,
+            len: 0,
+        }
+    }
+
+    /// Returns the maximum number of buffers in the ring.
+    pub fn max_bufs(&self) -> usize {
+        self.max_bufs
+    }
+
+    /// Changes the maximum number of buffers in the ring.
+    ///
+    /// This method fails with `EBUSY` if the ring holds more buffers than `max_bufs`.
+    pub fn set_max_bufs(&mut self, max_bufs: usize) -> Result<()> {
+        if self.bufs.len() > max_bufs {
+            return_errno_with_message!(Errno::EBUSY, "the pipe holds too much data");
+        }
+
+        self.max_bufs = max_bufs;
+        Ok(())
+    }
+
+    /// Returns the number of bytes in the ring.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the ring has no data.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns whether all the buffers of the ring are in use.
+    ///
+    /// As in Linux, the ring is full even if more data can be appended to the
+    /// last buffer. The writers waiting for room are woken up only when a whole
+    /// buffer is available.
+    pub fn is_full(&self) -> bool {
+        self.bufs.len() >= self.max_bufs
+    }
+
+    /// Returns the number of bytes that can be written without blocking.
+    pub fn free_space(&self) -> usize {
+        let free_bufs = self.max_bufs.saturating_sub(self.bufs.len());
+        let tail_room = match self.bufs.back() {
+            Some(last) if last.can_merge => last.tail_room(),
+            _ => 0,
+        };
+        free_bufs * PAGE_SIZE + tail_room
+    }
+
+    /// Copies data from `reader` into the ring.
+    ///
+    /// The data is appended to the last buffer if possible. New pages are
+    /// allocated for the rest of the data until the ring is full.
+    ///
+    /// If `is_packet` is true, the data is written as packets instead, each
+    /// of which has its own page and is read as a whole.
+    pub fn write(&mut self, reader: &mut VmReader, is_packet: bool) -> Result<usize> {
+        let mut written_len = 0;
+
+        if !is_packet {
+            if let Some(last) = self.bufs.back_mut().filter(|buf| buf.can_merge) {
+                written_len += last.append(reader);
+            }
+        }
+
+        while reader.has_remain() && self.bufs.len() < self.max_bufs {
+            let frame = match FrameAllocOptions::new(1).uninit(true).alloc_single() {
+                Ok(frame) => frame,
+                Err(_) if written_len > 0 => break,
+                Err(err) => return Err(err.into()),
+            };
+            let mut buf = PipeBuffer::new(frame, is_packet);
+            written_len += buf.append(reader);
+            self.bufs.push_back(buf);
+        }
+
+        self.len += written_len;
+        Ok(written_len)
+    }
+
+    /// Copies data from the ring into `writer` and releases the consumed pages.
+    ///
+    /// At most one packet is read. If `writer` is too small for the packet,
+    /// the rest of the packet is discarded.
+    pub fn read(&mut self, writer: &mut VmWriter) -> usize {
+        let mut read_len = 0;
+
+        while writer.has_avail() {
+            let Some(first) = self.bufs.front_mut() else {
+                break;
+            };
+            read_len += first.consume(writer);
+            if first.is_packet {
+                self.len -= first.len;
+                self.bufs.pop_front();
+                break;
+            }
+            if first.is_empty() {
+                self.bufs.pop_front();
+            }
+        }
+
+        self.len -= read_len;
+        read_len
+    }
+
+    /// Takes at most `max_len` bytes of data at the front of the ring out as a buffer.
+    ///
+    /// The returned buffer can be pushed into another ring with
+    /// [`PageRing::push_buffer`] without copying the data.
+    pub fn pop_buffer(&mut self, max_len: usize) -> Option<PipeBuffer> {
+        let first = self.bufs.front_mut()?;
+        let buf = if first.len > max_len {
+            first.split_to(max_len)
+        } else {
+            self.bufs.pop_front().unwrap()
+        };
+
+        self.len -= buf.len;
+        Some(buf)
+    }
+
+    /// Pushes a buffer into the ring without copying the data.
+    ///
+    /// If the ring is full, this method returns `Err` containing the buffer.
+    pub fn push_buffer(&mut self, buf: PipeBuffer) -> core::result::Result<(), PipeBuffer> {
+        if buf.is_empty() {
+            return Ok(());
+        }
+        if self.bufs.len() >= self.max_bufs {
+            return Err(buf);
+        }
+
+        self.len += buf.len;
+        self.bufs.push_back(buf);
+        Ok(())
+    }
+
+    /// Returns buffers that share the pages of at most `max_len` bytes of data
+    /// at the front of the ring, without consuming the data.
+    pub fn dup_buffers(&mut self, max_len: usize) -> Vec<PipeBuffer> {
+        let mut dup_bufs = Vec::new();
+        let mut remain_len = max_len;
+
+        for buf in self.bufs.iter_mut() {
+            if remain_len == 0 {
+                break;
+            }
+            let mut dup_buf = buf.share();
+            dup_buf.len = dup_buf.len.min(remain_len);
+            remain_len -= dup_buf.len;
+            dup_bufs.push(dup_buf);
+        }
+
+        dup_bufs
+    }
+}
+
+/// A buffer in a pipe, which refers to a range of data in a page.
+#[derive(Debug)]
+pub struct PipeBuffer {
+    frame: Frame,
+    offset: usize,
+    len: usize,
+    /// Whether more data can be appended to the buffer.
+    ///
+    /// This is false if the page is shared with other buffers, in which case
+    /// appending data may overwrite the data of the other buffers.
+    can_merge: bool,
+    /// Whether the buffer is a packet, which is read as a whole.
+    is_packet: bool,
+}
+
+impl PipeBuffer {
+    fn new(frame: Frame, is_packet: bool) -> Self {
+        Self {
+            frame,
+            offset: 0,
+            len: 0,
+            can_merge: !is_packet,
+            is_packet,
+        }
+    }
+
+    /// Returns the number of bytes in the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the buffer has no data.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn tail_room(&self) -> usize {
+        PAGE_SIZE - self.offset - self.len
+    }
+
+    fn append(&mut self, reader: &mut VmReader) -> usize {
+        let mut page_writer = self.frame.writer().skip(self.offset + self.len);
+        let written_len = page_writer.write(reader);
+        self.len += written_len;
+        written_len
+    }
+
+    fn consume(&mut self, writer: &mut VmWriter) -> usize {
+        let mut page_reader = self.frame.reader().skip(self.offset).limit(self.len);
+        let read_len = writer.write(&mut page_reader);
+        self.offset += read_len;
+        self.len -= read_len;
+        read_len
+    }
+
+    /// Splits the first `len` bytes off into a new buffer sharing the same page.
+    fn split_to(&mut self, len: usize) -> PipeBuffer {
+        debug_assert!(len <= self.len);
+
+        let mut front = self.share();
+        front.len = len;
+        self.offset += len;
+        self.len -= len;
+        front
+    }
+
+    /// Returns a buffer that shares the same page and the same data.
+    fn share(&mut self) -> PipeBuffer {
+        self.can_merge = false;
+        PipeBuffer {
+            frame: self.frame.clone(),
+            offset: self.offset,
+            len: self.len,
+            can_merge: false,
+            is_packet: self.is_packet,
+        }
+    }
+}
+
+#[cfg(ktest)]
+mod test {
+    use ostd::prelude::*;
+
+    use super::*;
+
+    #[ktest]
+    fn write_and_read_across_pages() {
+        let mut ring = PageRing::new(2);
+        let data = vec![0xau8; PAGE_SIZE + 100];
+
+        let written_len = ring.write(&mut VmReader::from(data.as_slice()), false);
+        assert_eq!(written_len.unwrap(), data.len());
+        assert_eq!(ring.free_space(), PAGE_SIZE - 100);
+        let written_len = ring.write(&mut VmReader::from(data.as_slice()), false);
+        assert_eq!(written_len.unwrap(), PAGE_SIZE - 100);
+        assert!(ring.is_full());
+
+        let mut buf = vec![0u8; PAGE_SIZE * 2];
+        assert_eq!(
+            ring.read(&mut VmWriter::from(buf.as_mut_slice())),
+            PAGE_SIZE * 2
+        );
+        assert!(buf.iter().all(|byte| *byte == 0xa));
+        assert!(ring.is_empty());
+    }
+
+    #[ktest]
+    fn move_and_share_buffers() {
+        let mut src = PageRing::new(4);
+        let mut dst = PageRing::new(4);
+        src.write(&mut VmReader::from(b"hello, world".as_slice()), false)
+            .unwrap();
+
+        let shared = src.dup_buffers(5);
+        assert_eq!(shared.len(), 1);
+        assert_eq!(shared[0].len(), 5);
+        let moved = src.pop_buffer(7).unwrap();
+        assert_eq!(moved.len(), 7);
+        dst.push_buffer(moved).unwrap();
+
+        // The page is shared, so new data must not be merged into it.
+        dst.write(&mut VmReader::from(b"!".as_slice()), false)
+            .unwrap();
+        let mut buf = [0u8; 8];
+        assert_eq!(dst.read(&mut VmWriter::from(buf.as_mut_slice())), 8);
+        assert_eq!(&buf, b"hello, !");
+
+        let mut buf = [0u8; 5];
+        assert_eq!(src.read(&mut VmWriter::from(buf.as_mut_slice())), 5);
+        assert_eq!(&buf, b"world");
+    }
+
+    #[ktest]
+    fn read_packets() {
+        let mut ring = PageRing::new(4);
+        ring.write(&mut VmReader::from(b"first".as_slice()), true)
+            .unwrap();
+        ring.write(&mut VmReader::from(b"second".as_slice()), true)
+            .unwrap();
+        assert_eq!(ring.len(), 11);
+
+        let mut buf = [0u8; 16];
+        assert_eq!(ring.read(&mut VmWriter::from(buf.as_mut_slice())), 5);
+        assert_eq!(&buf[..5], b"first");
+
+        // The rest of a packet is discarded.
+        let mut buf = [0u8; 3];
+        assert_eq!(ring.read(&mut VmWriter::from(buf.as_mut_slice())), 3);
+        assert_eq!(&buf, b"sec");
+        assert!(ring.is_empty());
+    }
+}
@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::fmt::Write;
+
+use ostd::io_resource::{claimed_resources, IoResourceKind};
+
+use crate::{
+    fs::{
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    prelude::*,
+};
+
+/// Represents the inodes at `/proc/iomem` and `/proc/ioports`.
+pub struct IoResourceFileOps(IoResourceKind);
+
+impl IoResourceFileOps {
+    pub fn new_inode(kind: IoResourceKind, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(kind))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for IoResourceFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        // The same widths of the addresses as those of Linux.
+        let width = match self.0 {
+            IoResourceKind::Memory => 8,
+            IoResourceKind::Port => 4,
+        };
+
+        let mut output = String::new();
+        for info in claimed_resources(self.0) {
+            writeln!(
+                output,
+                "{:0width$x}-{:0width$x} : {}",
+                info.range.start,
+                info.range.end - 1,
+                info.name,
+                width = width
+            )
+            .unwrap();
+        }
+        Ok(output.into_bytes())
+    }
+}
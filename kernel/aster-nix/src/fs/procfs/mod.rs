@@ -2,9 +2,15 @@
 
 use core::sync::atomic::{AtomicU64, Ordering};
 
+use ostd::io_resource::IoResourceKind;
+
 use self::{
+    io_resource::IoResourceFileOps,
+    net::NetDirOps,
     pid::PidDirOps,
+    pressure::PressureDirOps,
     self_::SelfSymOps,
+    sys::SysDirOps,
     template::{DirOps, ProcDir, ProcDirBuilder, ProcSymBuilder, SymOps},
 };
 use crate::{
@@ -14,8 +20,12 @@ use crate::{
     process::{process_table, process_table::PidEvent, Pid},
 };
 
+mod io_resource;
+mod net;
 mod pid;
+mod pressure;
 mod self_;
+mod sys;
 mod template;
 
 /// Magic number.
@@ -61,6 +71,10 @@ impl FileSystem for ProcFS {
     fn flags(&self) -> FsFlags {
         FsFlags::empty()
     }
+
+    fn type_name(&self) -> &'static str {
+        "proc"
+    }
 }
 
 /// Represents the inode at `/proc`.
@@ -91,6 +105,16 @@ impl DirOps for RootDirOps {
     fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
         let child = if name == "self" {
             SelfSymOps::new_inode(this_ptr.clone())
+        } else if name == "pressure" {
+            PressureDirOps::new_inode(this_ptr.clone())
+        } else if name == "sys" {
+            SysDirOps::new_inode(this_ptr.clone())
+        } else if name == "net" {
+            NetDirOps::new_inode(this_ptr.clone())
+        } else if name == "iomem" {
+            IoResourceFileOps::new_inode(IoResourceKind::Memory, this_ptr.clone())
+        } else if name == "ioports" {
+            IoResourceFileOps::new_inode(IoResourceKind::Port, this_ptr.clone())
         } else if let Ok(pid) = name.parse::<Pid>() {
             let process_ref =
                 process_table::get_process(pid).ok_or_else(|| Error::new(Errno::ENOENT))?;
@@ -108,6 +132,16 @@ impl DirOps for RootDirOps {
         };
         let mut cached_children = this.cached_children().write();
         cached_children.put_entry_if_not_found("self", || SelfSymOps::new_inode(this_ptr.clone()));
+        cached_children
+            .put_entry_if_not_found("pressure", || PressureDirOps::new_inode(this_ptr.clone()));
+        cached_children.put_entry_if_not_found("sys", || SysDirOps::new_inode(this_ptr.clone()));
+        cached_children.put_entry_if_not_found("net", || NetDirOps::new_inode(this_ptr.clone()));
+        cached_children.put_entry_if_not_found("iomem", || {
+            IoResourceFileOps::new_inode(IoResourceKind::Memory, this_ptr.clone())
+        });
+        cached_children.put_entry_if_not_found("ioports", || {
+            IoResourceFileOps::new_inode(IoResourceKind::Port, this_ptr.clone())
+        });
 
         for process in process_table::process_table().iter() {
             let pid = process.pid().to_string();
@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::fmt::Write;
+
+use crate::{
+    fs::{
+        procfs::template::{DirOps, FileOps, ProcDir, ProcDirBuilder, ProcFileBuilder},
+        utils::{DirEntryVecExt, Inode},
+    },
+    net::IFACES,
+    prelude::*,
+};
+
+/// Represents the inode at `/proc/net`.
+pub struct NetDirOps;
+
+impl NetDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl DirOps for NetDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        if name != "dev" {
+            return_errno!(Errno::ENOENT);
+        }
+        Ok(DevFileOps::new_inode(this_ptr))
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<NetDirOps>>().unwrap().this()
+        };
+        let mut cached_children = this.cached_children().write();
+        cached_children.put_entry_if_not_found("dev", || DevFileOps::new_inode(this_ptr.clone()));
+    }
+}
+
+/// Represents the inode at `/proc/net/dev`.
+struct DevFileOps;
+
+impl DevFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl FileOps for DevFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let mut output = String::from(concat!(
+            "Inter-|   Receive                                                |  Transmit\n",
+            " face |bytes    packets errs drop fifo frame compressed multicast|",
+            "bytes    packets errs drop fifo colls carrier compressed\n",
+        ));
+
+        // The errors, the drops and the other columns are not tracked, so they are zeros.
+        for iface in IFACES.get().into_iter().flatten() {
+            let stats = iface.stats();
+            writeln!(
+                output,
+                "{:>6}:{:>8} {:>7}    0    0    0     0          0         0 \
+                 {:>8} {:>7}    0    0    0     0       0          0",
+                iface.name(),
+                stats.rx_bytes(),
+                stats.rx_packets(),
+                stats.tx_bytes(),
+                stats.tx_packets(),
+            )
+            .unwrap();
+        }
+        Ok(output.into_bytes())
+    }
+}
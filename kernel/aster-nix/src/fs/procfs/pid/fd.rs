@@ -23,7 +23,7 @@ impl FdDirOps {
             .parent(parent)
             .build()
             .unwrap();
-        let file_table = process_ref.file_table().lock();
+        let file_table = process_ref.file_table();
         let weak_ptr = Arc::downgrade(&fd_inode);
         file_table.register_observer(weak_ptr);
         fd_inode
@@ -49,11 +49,10 @@ impl DirOps for FdDirOps {
             let fd = name
                 .parse::<FileDesc>()
                 .map_err(|_| Error::new(Errno::ENOENT))?;
-            let file_table = self.0.file_table().lock();
+            let file_table = self.0.file_table();
             file_table
                 .get_file(fd)
                 .map_err(|_| Error::new(Errno::ENOENT))?
-                .clone()
         };
         Ok(FileSymOps::new_inode(file, this_ptr.clone()))
     }
@@ -63,7 +62,7 @@ impl DirOps for FdDirOps {
             let this = this_ptr.upgrade().unwrap();
             this.downcast_ref::<ProcDir<FdDirOps>>().unwrap().this()
         };
-        let file_table = self.0.file_table().lock();
+        let file_table = self.0.file_table();
         let mut cached_children = this.cached_children().write();
         for (fd, file) in file_table.fds_and_files() {
             cached_children.put_entry_if_not_found(&fd.to_string(), || {
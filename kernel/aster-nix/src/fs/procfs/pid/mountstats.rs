@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::fmt::Write;
+
+use crate::{
+    fs::{
+        path::Dentry,
+        procfs::template::{FileOps, ProcFileBuilder},
+        utils::Inode,
+    },
+    prelude::*,
+    Process,
+};
+
+/// Represents the inode at `/proc/[pid]/mountstats`.
+pub struct MountStatsFileOps(Arc<Process>);
+
+impl MountStatsFileOps {
+    pub fn new_inode(process_ref: Arc<Process>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(process_ref))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for MountStatsFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        let root_mount_node = self.0.fs().read().root().mount_node().clone();
+
+        let mut mountstats_output = String::new();
+        for mount_node in root_mount_node.collect_mount_nodes() {
+            let mount_path = Dentry::new_fs_root(mount_node.clone()).abs_path();
+            // The devices of mounts are not recorded, so they are reported as
+            // "no device" like the mounts without a device name in Linux.
+            writeln!(
+                mountstats_output,
+                "no device mounted on {} with fstype {}",
+                mount_path,
+                mount_node.fs().type_name()
+            )
+            .unwrap();
+
+            // Linux only reports the statistics of NFS mounts, whose format does not
+            // fit other filesystems, so the statistics here follow a simple format of
+            // their own: the number of opens, and the bytes read and written.
+            let stats = mount_node.stats();
+            writeln!(mountstats_output, "\topens:\t{}", stats.opens()).unwrap();
+            writeln!(
+                mountstats_output,
+                "\tbytes:\t{} {}",
+                stats.read_bytes(),
+                stats.written_bytes()
+            )
+            .unwrap();
+        }
+        Ok(mountstats_output.into_bytes())
+    }
+}
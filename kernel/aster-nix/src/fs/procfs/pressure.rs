@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    fs::{
+        procfs::template::{DirOps, FileOps, ProcDir, ProcDirBuilder, ProcFileBuilder},
+        utils::{DirEntryVecExt, Inode},
+    },
+    prelude::*,
+    sched::pressure::{pressure_of, PressureResource},
+};
+
+/// Represents the inode at `/proc/pressure`.
+pub struct PressureDirOps;
+
+impl PressureDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+const PRESSURE_FILES: [(&str, PressureResource); 3] = [
+    ("cpu", PressureResource::Cpu),
+    ("memory", PressureResource::Memory),
+    ("io", PressureResource::Io),
+];
+
+impl DirOps for PressureDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        let resource = PRESSURE_FILES
+            .iter()
+            .find_map(|(file_name, resource)| (*file_name == name).then_some(*resource))
+            .ok_or_else(|| Error::new(Errno::ENOENT))?;
+        Ok(PressureFileOps::new_inode(resource, this_ptr))
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<PressureDirOps>>()
+                .unwrap()
+                .this()
+        };
+        let mut cached_children = this.cached_children().write();
+        for (name, resource) in PRESSURE_FILES {
+            cached_children.put_entry_if_not_found(name, || {
+                PressureFileOps::new_inode(resource, this_ptr.clone())
+            });
+        }
+    }
+}
+
+/// Represents the inodes at `/proc/pressure/{cpu,memory,io}`.
+struct PressureFileOps(PressureResource);
+
+impl PressureFileOps {
+    pub fn new_inode(resource: PressureResource, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(resource))
+            .parent(parent)
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for PressureFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        Ok(pressure_of(self.0).render().into_bytes())
+    }
+
+    fn write(&self, _buf: &[u8]) -> Result<usize> {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the pressure triggers are not supported");
+    }
+}
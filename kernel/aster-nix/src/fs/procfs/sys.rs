@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::format;
+use core::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+
+use crate::{
+    fs::{
+        path::{PROTECTED_FIFOS, PROTECTED_HARDLINKS, PROTECTED_REGULAR, PROTECTED_SYMLINKS},
+        pipe::{round_pipe_size, PIPE_MAX_SIZE, PIPE_USER_PAGES_HARD, PIPE_USER_PAGES_SOFT},
+        procfs::template::{DirOps, FileOps, ProcDir, ProcDirBuilder, ProcFileBuilder},
+        utils::{DirEntryVecExt, Inode, InodeMode},
+    },
+    prelude::*,
+};
+
+/// Represents the inode at `/proc/sys`.
+pub struct SysDirOps;
+
+impl SysDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+impl DirOps for SysDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        if name != "fs" {
+            return_errno!(Errno::ENOENT);
+        }
+        Ok(FsDirOps::new_inode(this_ptr))
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<SysDirOps>>().unwrap().this()
+        };
+        let mut cached_children = this.cached_children().write();
+        cached_children.put_entry_if_not_found("fs", || FsDirOps::new_inode(this_ptr.clone()));
+    }
+}
+
+/// Represents the inode at `/proc/sys/fs`.
+struct FsDirOps;
+
+impl FsDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcDirBuilder::new(Self).parent(parent).build().unwrap()
+    }
+}
+
+static FS_SYSCTLS: [Sysctl; 7] = [
+    Sysctl {
+        name: "pipe-max-size",
+        mode: 0o644,
+        value: SysctlValue::Rounded(&PIPE_MAX_SIZE, round_pipe_size),
+    },
+    Sysctl {
+        name: "pipe-user-pages-hard",
+        mode: 0o644,
+        value: SysctlValue::Usize(&PIPE_USER_PAGES_HARD),
+    },
+    Sysctl {
+        name: "pipe-user-pages-soft",
+        mode: 0o644,
+        value: SysctlValue::Usize(&PIPE_USER_PAGES_SOFT),
+    },
+    Sysctl {
+        name: "protected_fifos",
+        mode: 0o600,
+        value: SysctlValue::Int(&PROTECTED_FIFOS, 2),
+    },
+    Sysctl {
+        name: "protected_hardlinks",
+        mode: 0o600,
+        value: SysctlValue::Bool(&PROTECTED_HARDLINKS),
+    },
+    Sysctl {
+        name: "protected_regular",
+        mode: 0o600,
+        value: SysctlValue::Int(&PROTECTED_REGULAR, 2),
+    },
+    Sysctl {
+        name: "protected_symlinks",
+        mode: 0o600,
+        value: SysctlValue::Bool(&PROTECTED_SYMLINKS),
+    },
+];
+
+impl DirOps for FsDirOps {
+    fn lookup_child(&self, this_ptr: Weak<dyn Inode>, name: &str) -> Result<Arc<dyn Inode>> {
+        let sysctl = FS_SYSCTLS
+            .iter()
+            .find(|sysctl| sysctl.name == name)
+            .ok_or_else(|| Error::new(Errno::ENOENT))?;
+        Ok(SysctlFileOps::new_inode(sysctl, this_ptr))
+    }
+
+    fn populate_children(&self, this_ptr: Weak<dyn Inode>) {
+        let this = {
+            let this = this_ptr.upgrade().unwrap();
+            this.downcast_ref::<ProcDir<FsDirOps>>().unwrap().this()
+        };
+        let mut cached_children = this.cached_children().write();
+        for sysctl in FS_SYSCTLS.iter() {
+            cached_children.put_entry_if_not_found(sysctl.name, || {
+                SysctlFileOps::new_inode(sysctl, this_ptr.clone())
+            });
+        }
+    }
+}
+
+/// A kernel parameter that can be read and written through `/proc/sys`.
+struct Sysctl {
+    name: &'static str,
+    mode: u16,
+    value: SysctlValue,
+}
+
+/// The value of a [`Sysctl`], which is a decimal integer in the file.
+enum SysctlValue {
+    /// A switch that is either 0 or 1.
+    Bool(&'static AtomicBool),
+    /// An integer from 0 to the maximum value.
+    Int(&'static AtomicU8, u8),
+    /// An integer of any value.
+    Usize(&'static AtomicUsize),
+    /// An integer that is validated and rounded by the function before it is stored.
+    Rounded(&'static AtomicUsize, fn(usize) -> Result<usize>),
+}
+
+impl SysctlValue {
+    fn get(&self) -> usize {
+        match self {
+            Self::Bool(value) => value.load(Ordering::Relaxed) as usize,
+            Self::Int(value, _) => value.load(Ordering::Relaxed) as usize,
+            Self::Usize(value) | Self::Rounded(value, _) => value.load(Ordering::Relaxed),
+        }
+    }
+
+    fn set(&self, new_value: usize) -> Result<()> {
+        match self {
+            Self::Bool(value) if new_value <= 1 => value.store(new_value == 1, Ordering::Relaxed),
+            Self::Int(value, max) if new_value <= *max as usize => {
+                value.store(new_value as u8, Ordering::Relaxed)
+            }
+            Self::Usize(value) => value.store(new_value, Ordering::Relaxed),
+            Self::Rounded(value, round) => value.store(round(new_value)?, Ordering::Relaxed),
+            _ => return_errno_with_message!(Errno::EINVAL, "the value is out of range"),
+        }
+        Ok(())
+    }
+}
+
+/// Represents the inodes of the sysctls, e.g., `/proc/sys/fs/protected_symlinks`.
+struct SysctlFileOps(&'static Sysctl);
+
+impl SysctlFileOps {
+    pub fn new_inode(sysctl: &'static Sysctl, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        ProcFileBuilder::new(Self(sysctl))
+            .parent(parent)
+            .mode(InodeMode::from_bits_truncate(sysctl.mode))
+            .build()
+            .unwrap()
+    }
+}
+
+impl FileOps for SysctlFileOps {
+    fn data(&self) -> Result<Vec<u8>> {
+        Ok(format!("{}\n", self.0.value.get()).into_bytes())
+    }
+
+    fn write(&self, buf: &[u8]) -> Result<usize> {
+        let new_value = core::str::from_utf8(buf)
+            .ok()
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the value is not an integer"))?;
+        self.0.value.set(new_value)?;
+        Ok(buf.len())
+    }
+}
@@ -8,7 +8,7 @@ use super::{
     sym::{ProcSym, SymOps},
 };
 use crate::{
-    fs::utils::{FileSystem, Inode},
+    fs::utils::{FileSystem, Inode, InodeMode},
     prelude::*,
 };
 
@@ -63,6 +63,7 @@ pub struct ProcFileBuilder<O: FileOps> {
     // Mandatory field
     file: O,
     // Optional fields
+    mode: InodeMode,
     optional_builder: Option<OptionalBuilder>,
 }
 
@@ -71,6 +72,7 @@ impl<O: FileOps> ProcFileBuilder<O> {
         let optional_builder: OptionalBuilder = Default::default();
         Self {
             file,
+            mode: InodeMode::from_bits_truncate(0o444),
             optional_builder: Some(optional_builder),
         }
     }
@@ -83,9 +85,15 @@ impl<O: FileOps> ProcFileBuilder<O> {
         self.optional_builder(|ob| ob.volatile())
     }
 
+    /// Sets the permission bits of the file, which are 0o444 by default.
+    pub fn mode(mut self, mode: InodeMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
     pub fn build(mut self) -> Result<Arc<ProcFile<O>>> {
         let (fs, _, _, is_volatile) = self.optional_builder.take().unwrap().build()?;
-        Ok(ProcFile::new(self.file, fs, is_volatile))
+        Ok(ProcFile::new(self.file, fs, is_volatile, self.mode))
     }
 
     fn optional_builder<F>(mut self, f: F) -> Self
@@ -11,7 +11,7 @@ use super::{Common, ProcFS};
 use crate::{
     fs::{
         device::Device,
-        utils::{DirentVisitor, FileSystem, Inode, InodeMode, InodeType, Metadata},
+        utils::{DirentVisitor, FileSystem, Inode, InodeMode, InodeType, Metadata, RenameFlags},
     },
     prelude::*,
     process::{Gid, Uid},
@@ -179,7 +179,13 @@ impl<D: DirOps + 'static> Inode for ProcDir<D> {
         Ok(inode)
     }
 
-    fn rename(&self, _old_name: &str, _target: &Arc<dyn Inode>, _new_name: &str) -> Result<()> {
+    fn rename(
+        &self,
+        _old_name: &str,
+        _target: &Arc<dyn Inode>,
+        _new_name: &str,
+        _flags: RenameFlags,
+    ) -> Result<()> {
         Err(Error::new(Errno::EPERM))
     }
 
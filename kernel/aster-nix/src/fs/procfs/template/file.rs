@@ -17,15 +17,11 @@ pub struct ProcFile<F: FileOps> {
 }
 
 impl<F: FileOps> ProcFile<F> {
-    pub fn new(file: F, fs: Weak<dyn FileSystem>, is_volatile: bool) -> Arc<Self> {
+    pub fn new(file: F, fs: Weak<dyn FileSystem>, is_volatile: bool, mode: InodeMode) -> Arc<Self> {
         let common = {
             let arc_fs = fs.upgrade().unwrap();
             let procfs = arc_fs.downcast_ref::<ProcFS>().unwrap();
-            let metadata = Metadata::new_file(
-                procfs.alloc_id(),
-                InodeMode::from_bits_truncate(0o444),
-                super::BLOCK_SIZE,
-            );
+            let metadata = Metadata::new_file(procfs.alloc_id(), mode, super::BLOCK_SIZE);
             Common::new(metadata, fs, is_volatile)
         };
         Arc::new(Self {
@@ -75,12 +71,12 @@ impl<F: FileOps + 'static> Inode for ProcFile<F> {
         self.read_at(offset, buf)
     }
 
-    fn write_at(&self, _offset: usize, _buf: &[u8]) -> Result<usize> {
-        Err(Error::new(Errno::EPERM))
+    fn write_at(&self, _offset: usize, buf: &[u8]) -> Result<usize> {
+        self.inner.write(buf)
     }
 
-    fn write_direct_at(&self, _offset: usize, _buf: &[u8]) -> Result<usize> {
-        Err(Error::new(Errno::EPERM))
+    fn write_direct_at(&self, offset: usize, buf: &[u8]) -> Result<usize> {
+        self.write_at(offset, buf)
     }
 
     fn read_link(&self) -> Result<String> {
@@ -102,4 +98,11 @@ impl<F: FileOps + 'static> Inode for ProcFile<F> {
 
 pub trait FileOps: Sync + Send {
     fn data(&self) -> Result<Vec<u8>>;
+
+    /// Writes the data to the file.
+    ///
+    /// Each write replaces the whole content, so the offset is ignored.
+    fn write(&self, _buf: &[u8]) -> Result<usize> {
+        Err(Error::new(Errno::EPERM))
+    }
 }
@@ -17,10 +17,11 @@ use super::*;
 use crate::{
     events::IoEvents,
     fs::{
-        device::Device,
+        device::{Device, DeviceId, DeviceType},
+        inode_handle::FileIo,
         utils::{
             CStr256, DirentVisitor, FileSystem, FsFlags, Inode, InodeMode, InodeType, IoctlCmd,
-            Metadata, PageCache, PageCacheBackend, SuperBlock,
+            Metadata, PageCache, PageCacheBackend, RenameFlags, SuperBlock,
         },
     },
     prelude::*,
@@ -86,6 +87,10 @@ impl FileSystem for RamFS {
     fn flags(&self) -> FsFlags {
         FsFlags::DENTRY_UNEVICTABLE
     }
+
+    fn type_name(&self) -> &'static str {
+        "ramfs"
+    }
 }
 
 struct RamInode {
@@ -454,6 +459,81 @@ impl RamInode {
             .ok_or(Error::new(Errno::ENOENT))?;
         Ok(inode)
     }
+
+    /// Exchanges the entry `old_name` in this directory with the entry `new_name` in the
+    /// `target` directory. Both entries must exist.
+    fn exchange(&self, old_name: &str, target: &RamInode, new_name: &str) -> Result<()> {
+        // Exchange in the same directory
+        if self.ino == target.ino {
+            let mut self_inode = self.node.write();
+            let self_dir = self_inode.inner.as_direntry_mut().unwrap();
+            let (src_idx, src_inode) = self_dir
+                .get_entry(old_name)
+                .ok_or(Error::new(Errno::ENOENT))?;
+            let (dst_idx, dst_inode) = self_dir
+                .get_entry(new_name)
+                .ok_or(Error::new(Errno::ENOENT))?;
+            self_dir.substitute_entry(src_idx, (CStr256::from(old_name), dst_inode));
+            self_dir.substitute_entry(dst_idx, (CStr256::from(new_name), src_inode));
+            return Ok(());
+        }
+
+        // Or exchange across different directories
+        let (mut self_inode, mut target_inode) = write_lock_two_inodes(self, target);
+        let self_inode_arc = self.this.upgrade().unwrap();
+        let target_inode_arc = target.this.upgrade().unwrap();
+        let self_dir = self_inode.inner.as_direntry_mut().unwrap();
+        let (src_idx, src_inode) = self_dir
+            .get_entry(old_name)
+            .ok_or(Error::new(Errno::ENOENT))?;
+        let target_dir = target_inode.inner.as_direntry_mut().unwrap();
+        let (dst_idx, dst_inode) = target_dir
+            .get_entry(new_name)
+            .ok_or(Error::new(Errno::ENOENT))?;
+        // Avoid making a directory a subdirectory of itself
+        if Arc::ptr_eq(&src_inode, &target_inode_arc) || Arc::ptr_eq(&dst_inode, &self_inode_arc) {
+            return_errno!(Errno::EINVAL);
+        }
+        let src_is_dir = src_inode.typ == InodeType::Dir;
+        let dst_is_dir = dst_inode.typ == InodeType::Dir;
+
+        self_dir.substitute_entry(src_idx, (CStr256::from(old_name), dst_inode.clone()));
+        target_dir.substitute_entry(dst_idx, (CStr256::from(new_name), src_inode.clone()));
+        // The ".." entry of a directory links to its parent.
+        match (src_is_dir, dst_is_dir) {
+            (true, false) => {
+                self_inode.dec_nlinks();
+                target_inode.inc_nlinks();
+            }
+            (false, true) => {
+                self_inode.inc_nlinks();
+                target_inode.dec_nlinks();
+            }
+            _ => {}
+        }
+        drop(self_inode);
+        drop(target_inode);
+
+        if src_is_dir {
+            src_inode
+                .node
+                .write()
+                .inner
+                .as_direntry_mut()
+                .unwrap()
+                .set_parent(target.this.clone());
+        }
+        if dst_is_dir {
+            dst_inode
+                .node
+                .write()
+                .inner
+                .as_direntry_mut()
+                .unwrap()
+                .set_parent(self.this.clone());
+        }
+        Ok(())
+    }
 }
 
 impl PageCacheBackend for RamInode {
@@ -816,7 +896,13 @@ impl Inode for RamInode {
         Ok(inode as _)
     }
 
-    fn rename(&self, old_name: &str, target: &Arc<dyn Inode>, new_name: &str) -> Result<()> {
+    fn rename(
+        &self,
+        old_name: &str,
+        target: &Arc<dyn Inode>,
+        new_name: &str,
+        flags: RenameFlags,
+    ) -> Result<()> {
         if old_name == "." || old_name == ".." {
             return_errno_with_message!(Errno::EISDIR, "old_name is . or ..");
         }
@@ -838,6 +924,25 @@ impl Inode for RamInode {
             return_errno_with_message!(Errno::ENOTDIR, "target is not dir");
         }
 
+        if flags.contains(RenameFlags::EXCHANGE) {
+            if flags != RenameFlags::EXCHANGE {
+                return_errno_with_message!(Errno::EINVAL, "invalid flags to exchange");
+            }
+            return self.exchange(old_name, target, new_name);
+        }
+        let no_replace = flags.contains(RenameFlags::NOREPLACE);
+        // The whiteout is a character device with the device number 0:0, which is left in
+        // place of the old name.
+        let whiteout = flags.contains(RenameFlags::WHITEOUT).then(|| {
+            RamInode::new_device(
+                &self.fs.upgrade().unwrap(),
+                InodeMode::empty(),
+                Uid::new_root(),
+                Gid::new_root(),
+                Arc::new(Whiteout),
+            )
+        });
+
         // Perform necessary checks to ensure that `dst_inode` can be replaced by `src_inode`.
         let check_replace_inode =
             |src_inode: &Arc<RamInode>, dst_inode: &Arc<RamInode>| -> Result<()> {
@@ -879,6 +984,9 @@ impl Inode for RamInode {
             let is_dir = src_inode.typ == InodeType::Dir;
 
             if let Some((dst_idx, dst_inode)) = self_dir.get_entry(new_name) {
+                if no_replace {
+                    return_errno_with_message!(Errno::EEXIST, "new_name exists");
+                }
                 check_replace_inode(&src_inode, &dst_inode)?;
                 self_dir.remove_entry(dst_idx);
                 self_dir.substitute_entry(src_idx, (CStr256::from(new_name), src_inode.clone()));
@@ -889,6 +997,11 @@ impl Inode for RamInode {
             } else {
                 self_dir.substitute_entry(src_idx, (CStr256::from(new_name), src_inode.clone()));
             }
+            if let Some(whiteout) = whiteout {
+                let self_dir = self_inode.inner.as_direntry_mut().unwrap();
+                self_dir.append_entry(old_name, whiteout);
+                self_inode.inc_size();
+            }
         }
         // Or rename across different directories
         else {
@@ -907,6 +1020,9 @@ impl Inode for RamInode {
 
             let target_dir = target_inode.inner.as_direntry_mut().unwrap();
             if let Some((dst_idx, dst_inode)) = target_dir.get_entry(new_name) {
+                if no_replace {
+                    return_errno_with_message!(Errno::EEXIST, "new_name exists");
+                }
                 // Avoid renaming a subdirectory to a directory.
                 if Arc::ptr_eq(&self_inode_arc, &dst_inode) {
                     return_errno!(Errno::ENOTEMPTY);
@@ -929,6 +1045,11 @@ impl Inode for RamInode {
                     target_inode.inc_nlinks();
                 }
             }
+            if let Some(whiteout) = whiteout {
+                let self_dir = self_inode.inner.as_direntry_mut().unwrap();
+                self_dir.append_entry(old_name, whiteout);
+                self_inode.inc_size();
+            }
             drop(self_inode);
             drop(target_inode);
             if is_dir {
@@ -1028,3 +1149,34 @@ fn write_lock_two_inodes<'a>(
         (this, other)
     }
 }
+
+/// The whiteout left in place of the old name by `RENAME_WHITEOUT`.
+struct Whiteout;
+
+impl Device for Whiteout {
+    fn type_(&self) -> DeviceType {
+        DeviceType::CharDevice
+    }
+
+    fn id(&self) -> DeviceId {
+        DeviceId::new(0, 0)
+    }
+
+    fn open(&self) -> Result<Option<Arc<dyn FileIo>>> {
+        return_errno_with_message!(Errno::ENXIO, "the whiteout cannot be opened");
+    }
+}
+
+impl FileIo for Whiteout {
+    fn read(&self, _buf: &mut [u8]) -> Result<usize> {
+        return_errno!(Errno::ENXIO);
+    }
+
+    fn write(&self, _buf: &[u8]) -> Result<usize> {
+        return_errno!(Errno::ENXIO);
+    }
+
+    fn poll(&self, _mask: IoEvents, _poller: Option<&Poller>) -> IoEvents {
+        IoEvents::empty()
+    }
+}
@@ -162,7 +162,10 @@ impl<T: Copy> Producer<T> {
         }
     }
 
-    fn try_write(&self, buf: &[T]) -> Result<usize> {
+    /// Writes the data without blocking.
+    ///
+    /// If no data can be written, this method fails with `EAGAIN`.
+    pub fn try_write(&self, buf: &[T]) -> Result<usize> {
         if self.is_shutdown() || self.is_peer_shutdown() {
             return_errno!(Errno::EPIPE);
         }
@@ -301,7 +304,10 @@ impl<T: Copy> Consumer<T> {
         }
     }
 
-    fn try_read(&self, buf: &mut [T]) -> Result<usize> {
+    /// Reads the data without blocking.
+    ///
+    /// If no data can be read, this method fails with `EAGAIN`.
+    pub fn try_read(&self, buf: &mut [T]) -> Result<usize> {
         if self.is_shutdown() {
             return_errno!(Errno::EPIPE);
         }
@@ -51,6 +51,9 @@ pub trait FileSystem: Any + Sync + Send {
     fn sb(&self) -> SuperBlock;
 
     fn flags(&self) -> FsFlags;
+
+    /// Returns the name of the filesystem type, e.g., `ext2`.
+    fn type_name(&self) -> &'static str;
 }
 
 impl dyn FileSystem {
@@ -4,10 +4,13 @@
 
 use core::time::Duration;
 
+use aster_block::bio::BioWaiter;
 use aster_rights::Full;
 use core2::io::{Error as IoError, ErrorKind as IoErrorKind, Result as IoResult, Write};
 
 use super::{DirentVisitor, FileSystem, IoctlCmd};
+use ostd::mm::Frame;
+
 use crate::{
     events::IoEvents,
     fs::device::{Device, DeviceType},
@@ -118,6 +121,18 @@ impl InodeMode {
     }
 }
 
+bitflags! {
+    /// The flags of renaming, i.e., `RENAME_*` in Linux.
+    pub struct RenameFlags: u32 {
+        /// Do not replace the entry at the new name.
+        const NOREPLACE = 1 << 0;
+        /// Exchange the entries at the old name and the new name.
+        const EXCHANGE = 1 << 1;
+        /// Create a whiteout object at the old name.
+        const WHITEOUT = 1 << 2;
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Metadata {
     pub dev: u64,
@@ -292,6 +307,23 @@ pub trait Inode: Any + Sync + Send {
         Err(Error::new(Errno::EISDIR))
     }
 
+    /// Reads the pages at `offset` into `frames` asynchronously, bypassing the page cache.
+    ///
+    /// The `offset` must be a multiple of the page size, and each frame receives one page.
+    /// The pages beyond the end of the file are not read. This method returns the number of
+    /// bytes that are read and a waiter for the completion of the I/O.
+    fn read_direct_async(&self, offset: usize, frames: &[Frame]) -> Result<(usize, BioWaiter)> {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "asynchronous I/O is not supported");
+    }
+
+    /// Writes `frames` to the pages at `offset` asynchronously, bypassing the page cache.
+    ///
+    /// The `offset` must be a multiple of the page size, and each frame holds one page.
+    /// This method returns a waiter for the completion of the I/O.
+    fn write_direct_async(&self, offset: usize, frames: &[Frame]) -> Result<BioWaiter> {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "asynchronous I/O is not supported");
+    }
+
     fn create(&self, name: &str, type_: InodeType, mode: InodeMode) -> Result<Arc<dyn Inode>> {
         Err(Error::new(Errno::ENOTDIR))
     }
@@ -324,7 +356,17 @@ pub trait Inode: Any + Sync + Send {
         Err(Error::new(Errno::ENOTDIR))
     }
 
-    fn rename(&self, old_name: &str, target: &Arc<dyn Inode>, new_name: &str) -> Result<()> {
+    /// Renames the entry `old_name` in this directory to `new_name` in the `target` directory.
+    ///
+    /// The `flags` are checked under the locks of the directories, so the rename is atomic
+    /// with respect to them. A file system returns `EINVAL` for the flags it does not support.
+    fn rename(
+        &self,
+        old_name: &str,
+        target: &Arc<dyn Inode>,
+        new_name: &str,
+        flags: RenameFlags,
+    ) -> Result<()> {
         Err(Error::new(Errno::ENOTDIR))
     }
 
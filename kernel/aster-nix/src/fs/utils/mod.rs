@@ -9,7 +9,7 @@ pub use dirent_visitor::DirentVisitor;
 pub use direntry_vec::DirEntryVecExt;
 pub use file_creation_mask::FileCreationMask;
 pub use fs::{FileSystem, FsFlags, SuperBlock};
-pub use inode::{Inode, InodeMode, InodeType, Metadata};
+pub use inode::{Inode, InodeMode, InodeType, Metadata, RenameFlags};
 pub use ioctl::IoctlCmd;
 pub use page_cache::{PageCache, PageCacheBackend};
 pub use random_test::{generate_random_operation, new_fs_in_memory};
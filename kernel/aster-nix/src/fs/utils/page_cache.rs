@@ -11,6 +11,7 @@ use ostd::mm::{Frame, FrameAllocOptions};
 
 use crate::{
     prelude::*,
+    sched::pressure::account_io_wait,
     vm::vmo::{get_page_idx_range, Pager, Vmo, VmoFlags, VmoOptions},
 };
 
@@ -198,7 +199,10 @@ impl ReadaheadState {
         &mut self,
         pages: &mut MutexGuard<LruCache<usize, Page>>,
     ) -> Result<()> {
-        if matches!(self.waiter.wait(), Some(BioStatus::Complete)) {
+        if matches!(
+            account_io_wait(|| self.waiter.wait()),
+            Some(BioStatus::Complete)
+        ) {
             let Some(window) = &self.ra_window else {
                 return_errno!(Errno::EINVAL)
             };
@@ -318,7 +322,7 @@ impl PageCacheManager {
         }
 
         for (idx, waiter) in indices_and_waiters.iter() {
-            if matches!(waiter.wait(), Some(BioStatus::Complete)) {
+            if matches!(account_io_wait(|| waiter.wait()), Some(BioStatus::Complete)) {
                 if let Some(page) = self.pages.lock().get_mut(idx) {
                     page.set_state(PageState::UpToDate)
                 }
@@ -494,7 +498,7 @@ impl dyn PageCacheBackend {
     /// Reads a page from the backend synchronously.
     fn read_page_sync(&self, idx: usize, frame: &Frame) -> Result<()> {
         let waiter = self.read_page(idx, frame)?;
-        match waiter.wait() {
+        match account_io_wait(|| waiter.wait()) {
             Some(BioStatus::Complete) => Ok(()),
             _ => return_errno!(Errno::EIO),
         }
@@ -502,7 +506,7 @@ impl dyn PageCacheBackend {
     /// Writes a page to the backend synchronously.
     fn write_page_sync(&self, idx: usize, frame: &Frame) -> Result<()> {
         let waiter = self.write_page(idx, frame)?;
-        match waiter.wait() {
+        match account_io_wait(|| waiter.wait()) {
             Some(BioStatus::Complete) => Ok(()),
             _ => return_errno!(Errno::EIO),
         }
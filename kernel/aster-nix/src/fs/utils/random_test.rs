@@ -7,7 +7,7 @@ use alloc::sync::Arc;
 use hashbrown::HashMap;
 use rand::{Rng, RngCore};
 
-use super::{Inode, InodeMode, InodeType};
+use super::{Inode, InodeMode, InodeType, RenameFlags};
 use crate::prelude::*;
 
 pub struct FileInMemory {
@@ -288,7 +288,8 @@ impl DirInMemory {
             self.name, old_name, self.name, new_name
         );
 
-        let rename_result = self.inode.rename(old_name, &self.inode, new_name);
+        let rename_result =
+            self.inode.rename(old_name, &self.inode, new_name, RenameFlags::empty());
         if old_name.eq(new_name) {
             assert!(rename_result.is_ok());
             info!(
@@ -1,10 +1,9 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::collections::btree_map::Entry;
 use core::sync::atomic::{AtomicU64, Ordering};
 
 use keyable_arc::KeyableWeak;
-use ostd::sync::WaitQueue;
+use ostd::{collections::rcu_hashmap::RcuHashMap, sync::WaitQueue};
 use smoltcp::{
     iface::{SocketHandle, SocketSet},
     phy::Device,
@@ -13,6 +12,7 @@ use smoltcp::{
 
 use super::{
     any_socket::{AnyBoundSocket, AnyRawSocket, AnyUnboundSocket, SocketFamily},
+    stats::{CountingDevice, IfaceStats},
     time::get_network_timestamp,
     util::BindPortConfig,
     Iface, Ipv4Address,
@@ -22,25 +22,33 @@ use crate::prelude::*;
 pub struct IfaceCommon {
     interface: SpinLock<smoltcp::iface::Interface>,
     sockets: SpinLock<SocketSet<'static>>,
-    used_ports: RwLock<BTreeMap<u16, usize>>,
+    /// The number of sockets bound to each port, which can be looked up without locking.
+    used_ports: RcuHashMap<u16, usize>,
+    /// The lock that serializes the updates of `used_ports`.
+    ///
+    /// The lookups do not take the lock. An update takes it and then looks up
+    /// the port again, since the port may have changed after a lookup.
+    used_ports_lock: SpinLock<()>,
     /// The time should do next poll. We stores the total milliseconds since system boots up.
     next_poll_at_ms: AtomicU64,
     bound_sockets: RwLock<BTreeSet<KeyableWeak<AnyBoundSocket>>>,
     /// The wait queue that background polling thread will sleep on
     polling_wait_queue: WaitQueue,
+    stats: IfaceStats,
 }
 
 impl IfaceCommon {
     pub(super) fn new(interface: smoltcp::iface::Interface) -> Self {
         let socket_set = SocketSet::new(Vec::new());
-        let used_ports = BTreeMap::new();
         Self {
             interface: SpinLock::new(interface),
             sockets: SpinLock::new(socket_set),
-            used_ports: RwLock::new(used_ports),
+            used_ports: RcuHashMap::new(),
+            used_ports_lock: SpinLock::new(()),
             next_poll_at_ms: AtomicU64::new(0),
             bound_sockets: RwLock::new(BTreeSet::new()),
             polling_wait_queue: WaitQueue::new(),
+            stats: IfaceStats::new(),
         }
     }
 
@@ -70,10 +78,14 @@ impl IfaceCommon {
 
     /// Alloc an unused port range from 49152 ~ 65535 (According to smoltcp docs)
     fn alloc_ephemeral_port(&self) -> Result<u16> {
-        let mut used_ports = self.used_ports.write();
         for port in IP_LOCAL_PORT_START..=IP_LOCAL_PORT_END {
-            if let Entry::Vacant(e) = used_ports.entry(port) {
-                e.insert(0);
+            if self.used_ports.contains_key(&port) {
+                continue;
+            }
+            // The port may have been taken since the lookup, so check it again.
+            let _guard = self.used_ports_lock.lock();
+            if !self.used_ports.contains_key(&port) {
+                self.used_ports.insert(port, 0);
                 return Ok(port);
             }
         }
@@ -81,26 +93,41 @@ impl IfaceCommon {
     }
 
     fn bind_port(&self, port: u16, can_reuse: bool) -> Result<()> {
-        let mut used_ports = self.used_ports.write();
-        if let Some(used_times) = used_ports.get_mut(&port) {
-            if *used_times == 0 || can_reuse {
-                *used_times += 1;
-            } else {
-                return_errno_with_message!(Errno::EADDRINUSE, "the address is already in use");
-            }
-        } else {
-            used_ports.insert(port, 1);
+        // Fail without locking if the port is known to be in use.
+        let is_used = self
+            .used_ports
+            .get(&port)
+            .is_some_and(|used_times| used_times != 0);
+        if is_used && !can_reuse {
+            return_errno_with_message!(Errno::EADDRINUSE, "the address is already in use");
         }
+
+        let _guard = self.used_ports_lock.lock();
+        match self.used_ports.get(&port) {
+            Some(used_times) if used_times != 0 && !can_reuse => {
+                return_errno_with_message!(Errno::EADDRINUSE, "the address is already in use")
+            }
+            Some(used_times) => self.used_ports.insert(port, used_times + 1),
+            None => self.used_ports.insert(port, 1),
+        };
         Ok(())
     }
 
     /// Release port number so the port can be used again. For reused port, the port may still be in use.
     pub(super) fn release_port(&self, port: u16) {
-        let mut used_ports = self.used_ports.write();
-        if let Some(used_times) = used_ports.remove(&port) {
-            if used_times != 1 {
-                used_ports.insert(port, used_times - 1);
+        if !self.used_ports.contains_key(&port) {
+            return;
+        }
+
+        let _guard = self.used_ports_lock.lock();
+        match self.used_ports.get(&port) {
+            Some(used_times) if used_times <= 1 => {
+                self.used_ports.remove(&port);
+            }
+            Some(used_times) => {
+                self.used_ports.insert(port, used_times - 1);
             }
+            None => (),
         }
     }
 
@@ -150,7 +177,8 @@ impl IfaceCommon {
         let timestamp = get_network_timestamp();
         let has_events = {
             let mut sockets = self.sockets.lock_irq_disabled();
-            interface.poll(timestamp, device, &mut sockets)
+            let mut device = CountingDevice::new(device, &self.stats);
+            interface.poll(timestamp, &mut device, &mut sockets)
             // drop sockets here to avoid deadlock
         };
         if has_events {
@@ -175,6 +203,10 @@ impl IfaceCommon {
         }
     }
 
+    pub(super) fn stats(&self) -> &IfaceStats {
+        &self.stats
+    }
+
     pub(super) fn next_poll_at_ms(&self) -> Option<u64> {
         let millis = self.next_poll_at_ms.load(Ordering::SeqCst);
         if millis == 0 {
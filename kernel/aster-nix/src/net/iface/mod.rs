@@ -9,6 +9,7 @@ use crate::prelude::*;
 mod any_socket;
 mod common;
 mod loopback;
+mod stats;
 mod time;
 mod util;
 mod virtio;
@@ -18,6 +19,7 @@ pub use any_socket::{
 };
 pub use loopback::IfaceLoopback;
 pub use smoltcp::wire::{EthernetAddress, IpAddress, IpEndpoint, Ipv4Address};
+pub use stats::IfaceStats;
 pub use util::{spawn_background_poll_thread, BindPortConfig};
 pub use virtio::IfaceVirtio;
 
@@ -66,6 +68,11 @@ pub trait Iface: internal::IfaceInternal + Send + Sync {
     fn polling_wait_queue(&self) -> &WaitQueue {
         self.common().polling_wait_queue()
     }
+
+    /// The statistics on the received and transmitted packets.
+    fn stats(&self) -> &IfaceStats {
+        self.common().stats()
+    }
 }
 
 mod internal {
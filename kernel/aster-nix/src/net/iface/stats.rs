@@ -0,0 +1,130 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::sync::PerCpuCounter;
+use smoltcp::{
+    phy::{self, Device, DeviceCapabilities},
+    time::Instant,
+};
+
+/// The statistics on the packets that an iface has received and transmitted.
+///
+/// The counters are updated whenever the iface is polled, possibly on all CPUs,
+/// but are only read by `/proc/net/dev`, so they are per-CPU counters.
+pub struct IfaceStats {
+    rx_packets: PerCpuCounter,
+    rx_bytes: PerCpuCounter,
+    tx_packets: PerCpuCounter,
+    tx_bytes: PerCpuCounter,
+}
+
+impl IfaceStats {
+    pub(super) fn new() -> Self {
+        Self {
+            rx_packets: PerCpuCounter::new(0),
+            rx_bytes: PerCpuCounter::new(0),
+            tx_packets: PerCpuCounter::new(0),
+            tx_bytes: PerCpuCounter::new(0),
+        }
+    }
+
+    /// Returns the number of received packets.
+    pub fn rx_packets(&self) -> u64 {
+        self.rx_packets.sum() as u64
+    }
+
+    /// Returns the number of received bytes.
+    pub fn rx_bytes(&self) -> u64 {
+        self.rx_bytes.sum() as u64
+    }
+
+    /// Returns the number of transmitted packets.
+    pub fn tx_packets(&self) -> u64 {
+        self.tx_packets.sum() as u64
+    }
+
+    /// Returns the number of transmitted bytes.
+    pub fn tx_bytes(&self) -> u64 {
+        self.tx_bytes.sum() as u64
+    }
+
+    fn count_rx(&self, len: usize) {
+        self.rx_packets.inc();
+        self.rx_bytes.add(len as i64);
+    }
+
+    fn count_tx(&self, len: usize) {
+        self.tx_packets.inc();
+        self.tx_bytes.add(len as i64);
+    }
+}
+
+/// A device that counts the packets passing through the inner device.
+pub(super) struct CountingDevice<'a, D: ?Sized> {
+    inner: &'a mut D,
+    stats: &'a IfaceStats,
+}
+
+impl<'a, D: Device + ?Sized> CountingDevice<'a, D> {
+    pub(super) fn new(inner: &'a mut D, stats: &'a IfaceStats) -> Self {
+        Self { inner, stats }
+    }
+}
+
+impl<'a, D: Device + ?Sized> Device for CountingDevice<'a, D> {
+    type RxToken<'b> = CountingToken<'b, D::RxToken<'b>> where Self: 'b;
+    type TxToken<'b> = CountingToken<'b, D::TxToken<'b>> where Self: 'b;
+
+    fn receive(&mut self, timestamp: Instant) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        let stats = self.stats;
+        let (rx_token, tx_token) = self.inner.receive(timestamp)?;
+        Some((
+            CountingToken::new(rx_token, stats),
+            CountingToken::new(tx_token, stats),
+        ))
+    }
+
+    fn transmit(&mut self, timestamp: Instant) -> Option<Self::TxToken<'_>> {
+        let stats = self.stats;
+        let tx_token = self.inner.transmit(timestamp)?;
+        Some(CountingToken::new(tx_token, stats))
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        self.inner.capabilities()
+    }
+}
+
+/// A token that counts the packet that it receives or transmits.
+pub(super) struct CountingToken<'a, T> {
+    inner: T,
+    stats: &'a IfaceStats,
+}
+
+impl<'a, T> CountingToken<'a, T> {
+    fn new(inner: T, stats: &'a IfaceStats) -> Self {
+        Self { inner, stats }
+    }
+}
+
+impl<'a, T: phy::RxToken> phy::RxToken for CountingToken<'a, T> {
+    fn consume<R, F>(self, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        let stats = self.stats;
+        self.inner.consume(|buffer| {
+            stats.count_rx(buffer.len());
+            f(buffer)
+        })
+    }
+}
+
+impl<'a, T: phy::TxToken> phy::TxToken for CountingToken<'a, T> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        self.stats.count_tx(len);
+        self.inner.consume(len, f)
+    }
+}
@@ -9,13 +9,16 @@ use super::{common::get_ephemeral_endpoint, UNSPECIFIED_LOCAL_ENDPOINT};
 use crate::{
     events::{IoEvents, Observer},
     fs::{file_handle::FileLike, utils::StatusFlags},
+    match_sock_option_mut, match_sock_option_ref,
     net::{
         iface::IpEndpoint,
         poll_ifaces,
         socket::{
+            options::{BusyPoll, SocketOption, Timestamping},
             util::{
-                copy_message_from_user, copy_message_to_user, create_message_buffer,
-                send_recv_flags::SendRecvFlags, socket_addr::SocketAddr, MessageHeader,
+                busy_poll, check_busy_poll, copy_message_from_user, copy_message_to_user,
+                create_message_buffer, options::SocketOptionSet, send_recv_flags::SendRecvFlags,
+                socket_addr::SocketAddr, MessageHeader, TimestampingFlags,
             },
             Socket,
         },
@@ -29,6 +32,7 @@ mod bound;
 mod unbound;
 
 pub struct DatagramSocket {
+    options: RwLock<SocketOptionSet>,
     inner: RwLock<Takeable<Inner>>,
     nonblocking: AtomicBool,
     pollee: Pollee,
@@ -78,6 +82,7 @@ impl DatagramSocket {
             let pollee = Pollee::new(IoEvents::empty());
             unbound_datagram.init_pollee(&pollee);
             Self {
+                options: RwLock::new(SocketOptionSet::new_udp()),
                 inner: RwLock::new(Takeable::new(Inner::Unbound(unbound_datagram))),
                 nonblocking: AtomicBool::new(nonblocking),
                 pollee,
@@ -143,11 +148,18 @@ impl DatagramSocket {
     }
 
     fn recv(&self, buf: &mut [u8], flags: SendRecvFlags) -> Result<(usize, SocketAddr)> {
-        if self.is_nonblocking() {
-            self.try_recv(buf, flags)
-        } else {
-            self.wait_events(IoEvents::IN, || self.try_recv(buf, flags))
+        if self.is_nonblocking() || flags.contains(SendRecvFlags::MSG_DONTWAIT) {
+            return self.try_recv(buf, flags);
         }
+
+        // `try_recv` polls the network interfaces, so the packets arriving within the
+        // busy-poll time are received without sleeping.
+        let busy_poll_time = self.options.read().busy_poll();
+        if let Some(result) = busy_poll(busy_poll_time, || self.try_recv(buf, flags)) {
+            return result;
+        }
+
+        self.wait_events(IoEvents::IN, || self.try_recv(buf, flags))
     }
 
     fn try_send(&self, buf: &[u8], remote: &IpEndpoint, flags: SendRecvFlags) -> Result<usize> {
@@ -362,12 +374,50 @@ impl Socket for DatagramSocket {
             copy_message_to_user(io_vecs, message)
         };
 
-        // TODO: Receive control message
+        // TODO: Receive other control messages
+        let control_message = self.options.read().timestamping().rx_control_message();
 
-        let message_header = MessageHeader::new(Some(peer_addr), None);
+        let message_header = MessageHeader::new(Some(peer_addr), control_message);
 
         Ok((copied_bytes, message_header))
     }
+
+    fn get_option(&self, option: &mut dyn SocketOption) -> Result<()> {
+        let options = self.options.read();
+
+        match_sock_option_mut!(option, {
+            socket_busy_poll: BusyPoll => {
+                let busy_poll = options.busy_poll();
+                socket_busy_poll.set(busy_poll);
+            },
+            socket_timestamping: Timestamping => {
+                let timestamping = options.timestamping();
+                socket_timestamping.set(timestamping.bits());
+            },
+            _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option to get is unknown")
+        });
+
+        Ok(())
+    }
+
+    fn set_option(&self, option: &dyn SocketOption) -> Result<()> {
+        let mut options = self.options.write();
+
+        match_sock_option_ref!(option, {
+            socket_busy_poll: BusyPoll => {
+                let busy_poll = socket_busy_poll.get().unwrap();
+                check_busy_poll(options.busy_poll(), *busy_poll)?;
+                options.set_busy_poll(*busy_poll);
+            },
+            socket_timestamping: Timestamping => {
+                let timestamping = socket_timestamping.get().unwrap();
+                options.set_timestamping(TimestampingFlags::parse(*timestamping)?);
+            },
+            _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option to be set is unknown")
+        });
+
+        Ok(())
+    }
 }
 
 impl Observer<()> for DatagramSocket {
@@ -20,15 +20,17 @@ use crate::{
         poll_ifaces,
         socket::{
             options::{
-                Error as SocketError, Linger, RecvBuf, ReuseAddr, ReusePort, SendBuf, SocketOption,
+                BusyPoll, Error as SocketError, Linger, RecvBuf, ReuseAddr, ReusePort, SendBuf,
+                SocketOption, Timestamping,
             },
             util::{
-                copy_message_from_user, copy_message_to_user, create_message_buffer,
+                busy_poll, check_busy_poll, copy_message_from_user, copy_message_to_user,
+                create_message_buffer,
                 options::{SocketOptionSet, MIN_RECVBUF, MIN_SENDBUF},
                 send_recv_flags::SendRecvFlags,
                 shutdown_cmd::SockShutdownCmd,
                 socket_addr::SocketAddr,
-                MessageHeader,
+                MessageHeader, TimestampingFlags,
             },
             Socket,
         },
@@ -277,11 +279,18 @@ impl StreamSocket {
     }
 
     fn recv(&self, buf: &mut [u8], flags: SendRecvFlags) -> Result<(usize, SocketAddr)> {
-        if self.is_nonblocking() {
-            self.try_recv(buf, flags)
-        } else {
-            self.wait_events(IoEvents::IN, || self.try_recv(buf, flags))
+        if self.is_nonblocking() || flags.contains(SendRecvFlags::MSG_DONTWAIT) {
+            return self.try_recv(buf, flags);
+        }
+
+        // `try_recv` polls the network interfaces, so the packets arriving within the
+        // busy-poll time are received without sleeping.
+        let busy_poll_time = self.options.read().socket.busy_poll();
+        if let Some(result) = busy_poll(busy_poll_time, || self.try_recv(buf, flags)) {
+            return result;
         }
+
+        self.wait_events(IoEvents::IN, || self.try_recv(buf, flags))
     }
 
     fn try_send(&self, buf: &[u8], flags: SendRecvFlags) -> Result<usize> {
@@ -312,7 +321,7 @@ impl StreamSocket {
     }
 
     fn send(&self, buf: &[u8], flags: SendRecvFlags) -> Result<usize> {
-        if self.is_nonblocking() {
+        if self.is_nonblocking() || flags.contains(SendRecvFlags::MSG_DONTWAIT) {
             self.try_send(buf, flags)
         } else {
             self.wait_events(IoEvents::OUT, || self.try_send(buf, flags))
@@ -566,11 +575,17 @@ impl Socket for StreamSocket {
             copy_message_to_user(io_vecs, message)
         };
 
-        // TODO: Receive control message
+        // TODO: Receive other control messages
+        let control_message = self
+            .options
+            .read()
+            .socket
+            .timestamping()
+            .rx_control_message();
 
         // According to <https://elixir.bootlin.com/linux/v6.0.9/source/net/ipv4/tcp.c#L2645>,
         // peer address is ignored for connected socket.
-        let message_header = MessageHeader::new(None, None);
+        let message_header = MessageHeader::new(None, control_message);
 
         Ok((copied_bytes, message_header))
     }
@@ -610,6 +625,14 @@ impl Socket for StreamSocket {
                 let reuse_port = options.socket.reuse_port();
                 socket_reuse_port.set(reuse_port);
             },
+            socket_busy_poll: BusyPoll => {
+                let busy_poll = options.socket.busy_poll();
+                socket_busy_poll.set(busy_poll);
+            },
+            socket_timestamping: Timestamping => {
+                let timestamping = options.socket.timestamping();
+                socket_timestamping.set(timestamping.bits());
+            },
             // TCP options:
             tcp_no_delay: NoDelay => {
                 let no_delay = options.tcp.no_delay();
@@ -675,6 +698,15 @@ impl Socket for StreamSocket {
                 let linger = socket_linger.get().unwrap();
                 options.socket.set_linger(*linger);
             },
+            socket_busy_poll: BusyPoll => {
+                let busy_poll = socket_busy_poll.get().unwrap();
+                check_busy_poll(options.socket.busy_poll(), *busy_poll)?;
+                options.socket.set_busy_poll(*busy_poll);
+            },
+            socket_timestamping: Timestamping => {
+                let timestamping = socket_timestamping.get().unwrap();
+                options.socket.set_timestamping(TimestampingFlags::parse(*timestamping)?);
+            },
             // TCP options:
             tcp_no_delay: NoDelay => {
                 let no_delay = tcp_no_delay.get().unwrap();
@@ -20,4 +20,6 @@ impl_socket_options!(
     pub struct Error(Option<crate::error::Error>);
     pub struct Linger(LingerOption);
     pub struct KeepAlive(bool);
+    pub struct BusyPoll(u32);
+    pub struct Timestamping(u32);
 );
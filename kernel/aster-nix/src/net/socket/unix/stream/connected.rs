@@ -33,6 +33,14 @@ impl Connected {
         self.local_endpoint.read(buf)
     }
 
+    pub(super) fn try_write(&self, buf: &[u8]) -> Result<usize> {
+        self.local_endpoint.try_write(buf)
+    }
+
+    pub(super) fn try_read(&self, buf: &mut [u8]) -> Result<usize> {
+        self.local_endpoint.try_read(buf)
+    }
+
     pub(super) fn shutdown(&self, cmd: SockShutdownCmd) -> Result<()> {
         self.local_endpoint.shutdown(cmd)
     }
@@ -86,6 +86,14 @@ impl Endpoint {
         self.0.writer.write(buf)
     }
 
+    pub(super) fn try_read(&self, buf: &mut [u8]) -> Result<usize> {
+        self.0.reader.try_read(buf)
+    }
+
+    pub(super) fn try_write(&self, buf: &[u8]) -> Result<usize> {
+        self.0.writer.try_write(buf)
+    }
+
     pub(super) fn shutdown(&self, cmd: SockShutdownCmd) -> Result<()> {
         if !self.is_connected() {
             return_errno_with_message!(Errno::ENOTCONN, "The socket is not connected.");
@@ -84,22 +84,30 @@ impl UnixStreamSocket {
         status_flags.intersection(SUPPORTED_FLAGS)
     }
 
-    fn send(&self, buf: &[u8], _flags: SendRecvFlags) -> Result<usize> {
+    fn send(&self, buf: &[u8], flags: SendRecvFlags) -> Result<usize> {
         let connected = match &*self.0.read() {
             State::Connected(connected) => connected.clone(),
             _ => return_errno_with_message!(Errno::ENOTCONN, "the socket is not connected"),
         };
 
-        connected.write(buf)
+        if flags.contains(SendRecvFlags::MSG_DONTWAIT) {
+            connected.try_write(buf)
+        } else {
+            connected.write(buf)
+        }
     }
 
-    fn recv(&self, buf: &mut [u8], _flags: SendRecvFlags) -> Result<usize> {
+    fn recv(&self, buf: &mut [u8], flags: SendRecvFlags) -> Result<usize> {
         let connected = match &*self.0.read() {
             State::Connected(connected) => connected.clone(),
             _ => return_errno_with_message!(Errno::ENOTCONN, "the socket is not connected"),
         };
 
-        connected.read(buf)
+        if flags.contains(SendRecvFlags::MSG_DONTWAIT) {
+            connected.try_read(buf)
+        } else {
+            connected.read(buf)
+        }
     }
 }
 
@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use crate::{
+    prelude::*,
+    process::{credentials, credentials::capabilities::CapSet, posix_thread::PosixThreadExt},
+    thread::Thread,
+    time::clocks::MonotonicClock,
+};
+
+/// Checks whether the busy-poll time (`SO_BUSY_POLL`) can be changed from `old` to `new`.
+///
+/// As in Linux, the time must not be negative, and raising it requires `CAP_NET_ADMIN`.
+pub(in crate::net) fn check_busy_poll(old: u32, new: u32) -> Result<()> {
+    if new > i32::MAX as u32 {
+        return_errno_with_message!(Errno::EINVAL, "the busy-poll time is negative");
+    }
+
+    if new > old && !credentials().effective_capset().contains(CapSet::NET_ADMIN) {
+        return_errno_with_message!(
+            Errno::EPERM,
+            "raising the busy-poll time requires CAP_NET_ADMIN"
+        );
+    }
+
+    Ok(())
+}
+
+/// Busy polls with `try_op` for `busy_poll` microseconds.
+///
+/// `try_op` is expected to poll the network interfaces itself and fail with `EAGAIN` if the
+/// operation would block. This method returns `None` if the operation still would block when
+/// the busy-poll time elapses, in which case the caller should go to sleep.
+///
+/// The CPU is yielded between the attempts, and the busy polling stops with `EINTR` if there
+/// is a pending signal.
+pub(in crate::net) fn busy_poll<F, R>(busy_poll: u32, mut try_op: F) -> Option<Result<R>>
+where
+    F: FnMut() -> Result<R>,
+{
+    if busy_poll == 0 {
+        return None;
+    }
+
+    let deadline = MonotonicClock::get().read_time() + Duration::from_micros(busy_poll as u64);
+    loop {
+        match try_op() {
+            Err(err) if err.error() == Errno::EAGAIN => (),
+            result => return Some(result),
+        }
+
+        if MonotonicClock::get().read_time() >= deadline {
+            return None;
+        }
+
+        let current_thread = current_thread!();
+        let posix_thread = current_thread.as_posix_thread().unwrap();
+        if posix_thread.has_pending() {
+            return Some(Err(Error::with_message(
+                Errno::EINTR,
+                "busy polling is interrupted by a signal",
+            )));
+        }
+
+        Thread::yield_now();
+    }
+}
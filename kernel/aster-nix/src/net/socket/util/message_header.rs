@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use core::time::Duration;
+
 use super::socket_addr::SocketAddr;
 use crate::{prelude::*, util::IoVec};
 
@@ -23,13 +25,21 @@ impl MessageHeader {
     pub fn addr(&self) -> Option<&SocketAddr> {
         self.addr.as_ref()
     }
+
+    /// Returns the control message.
+    pub fn control_message(&self) -> Option<&ControlMessage> {
+        self.control_message.as_ref()
+    }
 }
 
 /// Control message carried by MessageHeader.
 ///
-/// TODO: Implement the struct. The struct is empty now.
+/// TODO: Support more types of control messages.
 #[derive(Debug)]
-pub struct ControlMessage;
+pub enum ControlMessage {
+    /// The software timestamp of a received packet (`SCM_TIMESTAMPING`), as a real time.
+    Timestamping(Duration),
+}
 
 /// Copies a message from user space.
 ///
@@ -1,12 +1,16 @@
 // SPDX-License-Identifier: MPL-2.0
 
+mod busy_poll;
 mod message_header;
 pub mod options;
 pub mod send_recv_flags;
 pub mod shutdown_cmd;
 pub mod socket_addr;
+mod timestamping;
 
-pub use message_header::MessageHeader;
+pub(in crate::net) use busy_poll::{busy_poll, check_busy_poll};
 pub(in crate::net) use message_header::{
     copy_message_from_user, copy_message_to_user, create_message_buffer,
 };
+pub use message_header::{ControlMessage, MessageHeader};
+pub use timestamping::TimestampingFlags;
@@ -3,7 +3,10 @@
 use core::time::Duration;
 
 use crate::{
-    net::iface::{RECV_BUF_LEN, SEND_BUF_LEN},
+    net::{
+        iface::{RECV_BUF_LEN, SEND_BUF_LEN},
+        socket::util::TimestampingFlags,
+    },
     prelude::*,
 };
 
@@ -17,6 +20,9 @@ pub struct SocketOptionSet {
     send_buf: u32,
     recv_buf: u32,
     linger: LingerOption,
+    /// The time in microseconds to busy poll the network interfaces before sleeping
+    busy_poll: u32,
+    timestamping: TimestampingFlags,
 }
 
 impl SocketOptionSet {
@@ -29,8 +35,15 @@ impl SocketOptionSet {
             send_buf: SEND_BUF_LEN as u32,
             recv_buf: RECV_BUF_LEN as u32,
             linger: LingerOption::default(),
+            busy_poll: 0,
+            timestamping: TimestampingFlags::empty(),
         }
     }
+
+    /// Return the default socket level options for udp socket.
+    pub fn new_udp() -> Self {
+        Self::new_tcp()
+    }
 }
 
 pub const MIN_SENDBUF: u32 = 2304;
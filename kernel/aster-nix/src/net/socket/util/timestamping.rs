@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::message_header::ControlMessage;
+use crate::{prelude::*, time::clocks::RealTimeClock};
+
+bitflags! {
+    /// The flags of the `SO_TIMESTAMPING` socket option.
+    ///
+    /// The definition is from https://elixir.bootlin.com/linux/v6.0.9/source/include/uapi/linux/net_tstamp.h.
+    pub struct TimestampingFlags: u32 {
+        const TX_HARDWARE = 1 << 0;
+        const TX_SOFTWARE = 1 << 1;
+        const RX_HARDWARE = 1 << 2;
+        const RX_SOFTWARE = 1 << 3;
+        const SOFTWARE = 1 << 4;
+        const SYS_HARDWARE = 1 << 5;
+        const RAW_HARDWARE = 1 << 6;
+        const OPT_ID = 1 << 7;
+        const TX_SCHED = 1 << 8;
+        const TX_ACK = 1 << 9;
+        const OPT_CMSG = 1 << 10;
+        const OPT_TSONLY = 1 << 11;
+        const OPT_STATS = 1 << 12;
+        const OPT_PKTINFO = 1 << 13;
+        const OPT_TX_SWHW = 1 << 14;
+        const BIND_PHC = 1 << 15;
+    }
+}
+
+impl TimestampingFlags {
+    /// Parses the flags set by `setsockopt`.
+    pub fn parse(flags: u32) -> Result<Self> {
+        let flags = Self::from_bits(flags)
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid timestamping flags"))?;
+
+        if flags.contains(Self::BIND_PHC) {
+            return_errno_with_message!(Errno::EINVAL, "there is no PTP hardware clock to bind");
+        }
+
+        Ok(flags)
+    }
+
+    /// Returns the control message that carries the timestamps of a packet received now.
+    ///
+    /// Only software receive timestamps are supported. There are no hardware clocks, and
+    /// transmit timestamps are reported through the error queue, which is not supported.
+    ///
+    /// FIXME: The timestamp should be taken when the packet arrives at the network interface,
+    /// but the smoltcp sockets do not record the arrival time, so the time when the packet is
+    /// dequeued from the socket is used instead.
+    pub fn rx_control_message(&self) -> Option<ControlMessage> {
+        if !self.contains(Self::RX_SOFTWARE | Self::SOFTWARE) {
+            return None;
+        }
+
+        Some(ControlMessage::Timestamping(
+            RealTimeClock::get().read_time(),
+        ))
+    }
+}
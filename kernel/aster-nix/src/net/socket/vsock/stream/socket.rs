@@ -147,7 +147,7 @@ impl VsockStreamSocket {
     }
 
     fn recv(&self, buf: &mut [u8], flags: SendRecvFlags) -> Result<(usize, SocketAddr)> {
-        if self.is_nonblocking() {
+        if self.is_nonblocking() || flags.contains(SendRecvFlags::MSG_DONTWAIT) {
             self.try_recv(buf, flags)
         } else {
             self.wait_events(IoEvents::IN, || self.try_recv(buf, flags))
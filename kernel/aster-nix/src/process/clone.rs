@@ -179,7 +179,7 @@ fn clone_child_thread(parent_context: &UserContext, clone_args: CloneArgs) -> Re
         *sigmask
     };
 
-    let child_tid = allocate_tid();
+    let child_tid = allocate_tid()?;
     let child_thread = {
         let credentials = {
             let credentials = credentials();
@@ -237,7 +237,7 @@ fn clone_child_process(
     };
 
     // clone file table
-    let child_file_table = clone_files(current.file_table(), clone_flags);
+    let child_file_table = clone_files(&current.file_table(), clone_flags);
 
     // clone fs
     let child_fs = clone_fs(current.fs(), clone_flags);
@@ -265,7 +265,7 @@ fn clone_child_process(
     // inherit parent's nice value
     let child_nice = current.nice().load(Ordering::Relaxed);
 
-    let child_tid = allocate_tid();
+    let child_tid = allocate_tid()?;
 
     let child = {
         let child_elf_path = current.executable_path();
@@ -405,17 +405,14 @@ fn clone_fs(
     }
 }
 
-fn clone_files(
-    parent_file_table: &Arc<Mutex<FileTable>>,
-    clone_flags: CloneFlags,
-) -> Arc<Mutex<FileTable>> {
+fn clone_files(parent_file_table: &Arc<FileTable>, clone_flags: CloneFlags) -> Arc<FileTable> {
     // if CLONE_FILES is set, the child and parent shares the same file table
     // Otherwise, the child will deep copy a new file table.
     // FIXME: the clone may not be deep copy.
     if clone_flags.contains(CloneFlags::CLONE_FILES) {
-        parent_file_table.clone()
+        parent_file_table.share()
     } else {
-        Arc::new(Mutex::new(parent_file_table.lock().clone()))
+        Arc::new(parent_file_table.as_ref().clone())
     }
 }
 
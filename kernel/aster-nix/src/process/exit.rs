@@ -38,8 +38,8 @@ pub fn do_exit_group(term_status: TermStatus) {
         child.enqueue_signal(signal);
     }
 
-    // Close all files then exit the process
-    let files = current.file_table().lock().close_all();
+    // Close all files, unless the file table is still shared, then exit the process
+    let files = current.file_table().release();
     for file in files {
         let _ = file.clean_for_close();
     }
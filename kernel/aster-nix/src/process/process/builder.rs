@@ -28,7 +28,7 @@ pub struct ProcessBuilder<'a> {
     argv: Option<Vec<CString>>,
     envp: Option<Vec<CString>>,
     process_vm: Option<ProcessVm>,
-    file_table: Option<Arc<Mutex<FileTable>>>,
+    file_table: Option<Arc<FileTable>>,
     fs: Option<Arc<RwMutex<FsResolver>>>,
     umask: Option<Arc<RwLock<FileCreationMask>>>,
     resource_limits: Option<ResourceLimits>,
@@ -67,7 +67,7 @@ impl<'a> ProcessBuilder<'a> {
         self
     }
 
-    pub fn file_table(&mut self, file_table: Arc<Mutex<FileTable>>) -> &mut Self {
+    pub fn file_table(&mut self, file_table: Arc<FileTable>) -> &mut Self {
         self.file_table = Some(file_table);
         self
     }
@@ -152,7 +152,7 @@ impl<'a> ProcessBuilder<'a> {
         let process_vm = process_vm.or_else(|| Some(ProcessVm::alloc())).unwrap();
 
         let file_table = file_table
-            .or_else(|| Some(Arc::new(Mutex::new(FileTable::new_with_stdio()))))
+            .or_else(|| Some(Arc::new(FileTable::new_with_stdio())))
             .unwrap();
 
         let fs = fs
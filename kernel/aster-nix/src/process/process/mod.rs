@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use ostd::sync::Rcu;
+
 use self::timer_manager::PosixTimerManager;
 use super::{
     posix_thread::PosixThreadExt,
@@ -19,7 +21,12 @@ use super::{
 };
 use crate::{
     device::tty::open_ntty_as_controlling_terminal,
-    fs::{file_table::FileTable, fs_resolver::FsResolver, utils::FileCreationMask},
+    fs::{
+        aio::{AioContext, AioContextId},
+        file_table::FileTable,
+        fs_resolver::FsResolver,
+        utils::FileCreationMask,
+    },
     prelude::*,
     sched::nice::Nice,
     thread::{allocate_tid, Thread},
@@ -77,8 +84,10 @@ pub struct Process {
     children: Mutex<BTreeMap<Pid, Arc<Process>>>,
     /// Process group
     pub(super) process_group: Mutex<Weak<ProcessGroup>>,
-    /// File table
-    file_table: Arc<Mutex<FileTable>>,
+    /// File table, which may be replaced when it is unshared
+    file_table: Rcu<Box<Arc<FileTable>>>,
+    /// Serializes the replacements of `file_table`.
+    file_table_lock: Mutex<()>,
     /// FsResolver
     fs: Arc<RwMutex<FsResolver>>,
     /// umask
@@ -101,6 +110,9 @@ pub struct Process {
 
     /// A manager that manages timer resources and utilities of the process.
     timer_manager: PosixTimerManager,
+
+    /// The AIO contexts created by `io_setup`.
+    aio_contexts: Mutex<BTreeMap<AioContextId, Arc<AioContext>>>,
 }
 
 impl Process {
@@ -113,7 +125,7 @@ impl Process {
         process_vm: ProcessVm,
 
         fs: Arc<RwMutex<FsResolver>>,
-        file_table: Arc<Mutex<FileTable>>,
+        file_table: Arc<FileTable>,
 
         umask: Arc<RwLock<FileCreationMask>>,
         resource_limits: ResourceLimits,
@@ -139,7 +151,8 @@ impl Process {
             parent: Mutex::new(parent),
             children: Mutex::new(BTreeMap::new()),
             process_group: Mutex::new(Weak::new()),
-            file_table,
+            file_table: Rcu::new(Box::new(file_table)),
+            file_table_lock: Mutex::new(()),
             fs,
             umask,
             sig_dispositions,
@@ -148,6 +161,7 @@ impl Process {
             nice: Atomic::new(nice),
             timer_manager: PosixTimerManager::new(&prof_clock, process_ref),
             prof_clock,
+            aio_contexts: Mutex::new(BTreeMap::new()),
         })
     }
 
@@ -173,7 +187,7 @@ impl Process {
         envp: Vec<CString>,
     ) -> Result<Arc<Self>> {
         let process_builder = {
-            let pid = allocate_tid();
+            let pid = allocate_tid()?;
             let parent = Weak::new();
 
             let credentials = Credentials::new_root();
@@ -234,6 +248,11 @@ impl Process {
         &self.timer_manager
     }
 
+    /// Gets the AIO contexts of the process.
+    pub fn aio_contexts(&self) -> &Mutex<BTreeMap<AioContextId, Arc<AioContext>>> {
+        &self.aio_contexts
+    }
+
     pub fn threads(&self) -> &Mutex<Vec<Arc<Thread>>> {
         &self.threads
     }
@@ -547,8 +566,22 @@ impl Process {
 
     // ************** File system ****************
 
-    pub fn file_table(&self) -> &Arc<Mutex<FileTable>> {
-        &self.file_table
+    pub fn file_table(&self) -> Arc<FileTable> {
+        self.file_table.get().clone()
+    }
+
+    /// Replaces the file table with a copy of it, so that the file table
+    /// is no longer shared with other processes.
+    ///
+    /// The file table is kept as is if no other process shares it.
+    pub fn unshare_file_table(&self) {
+        let _guard = self.file_table_lock.lock();
+        let Some(new_file_table) = self.file_table().unshare() else {
+            return;
+        };
+        self.file_table
+            .replace(Box::new(Arc::new(new_file_table)))
+            .delay();
     }
 
     pub fn fs(&self) -> &Arc<RwMutex<FsResolver>> {
@@ -659,7 +692,7 @@ mod test {
     fn new_process(parent: Option<Arc<Process>>) -> Arc<Process> {
         crate::util::random::init();
         crate::fs::rootfs::init_root_mount();
-        let pid = allocate_tid();
+        let pid = allocate_tid().unwrap();
         let parent = if let Some(parent) = parent {
             Arc::downgrade(&parent)
         } else {
@@ -672,7 +705,7 @@ mod test {
             String::new(),
             ProcessVm::alloc(),
             Arc::new(RwMutex::new(FsResolver::new())),
-            Arc::new(Mutex::new(FileTable::new())),
+            Arc::new(FileTable::new()),
             Arc::new(RwLock::new(FileCreationMask::default())),
             ResourceLimits::default(),
             Nice::default(),
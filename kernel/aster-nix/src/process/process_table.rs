@@ -6,7 +6,9 @@
 //! This table can be used to get process with pid.
 //! TODO: progress group, thread all need similar mapping
 
-use alloc::collections::btree_map::Values;
+use core::slice::Iter;
+
+use ostd::collections::rcu_hashmap::RcuHashMap;
 
 use super::{Pgid, Pid, Process, ProcessGroup, Session, Sid};
 use crate::{
@@ -14,48 +16,78 @@ use crate::{
     prelude::*,
 };
 
-static PROCESS_TABLE: Mutex<BTreeMap<Pid, Arc<Process>>> = Mutex::new(BTreeMap::new());
+/// The lock that serializes the updates of `PROCESS_TABLE`.
+///
+/// The lookups and the iterations do not take the lock.
+static PROCESS_TABLE_LOCK: Mutex<()> = Mutex::new(());
 static PROCESS_GROUP_TABLE: Mutex<BTreeMap<Pgid, Arc<ProcessGroup>>> = Mutex::new(BTreeMap::new());
 static PROCESS_TABLE_SUBJECT: Subject<PidEvent> = Subject::new();
 static SESSION_TABLE: Mutex<BTreeMap<Sid, Arc<Session>>> = Mutex::new(BTreeMap::new());
 
+lazy_static! {
+    static ref PROCESS_TABLE: RcuHashMap<Pid, Arc<Process>> = RcuHashMap::new();
+}
+
 // ************ Process *************
 
 /// Gets a process with pid
 pub fn get_process(pid: Pid) -> Option<Arc<Process>> {
-    PROCESS_TABLE.lock().get(&pid).cloned()
+    PROCESS_TABLE.get(&pid)
+}
+
+/// Acquires a lock on the process table for adding or removing processes.
+pub(super) fn process_table_mut() -> ProcessTableMut<'static> {
+    ProcessTableMut {
+        _guard: PROCESS_TABLE_LOCK.lock(),
+    }
 }
 
-pub(super) fn process_table_mut() -> MutexGuard<'static, BTreeMap<Pid, Arc<Process>>> {
-    PROCESS_TABLE.lock()
+/// A guard of the process table that allows modifications.
+pub(super) struct ProcessTableMut<'a> {
+    _guard: MutexGuard<'a, ()>,
 }
 
-/// Acquires a lock on the process table and returns a `ProcessTable`.
-pub fn process_table() -> ProcessTable<'static> {
-    ProcessTable {
-        inner: PROCESS_TABLE.lock(),
+impl<'a> ProcessTableMut<'a> {
+    /// Inserts a process into the table.
+    pub(super) fn insert(&mut self, pid: Pid, process: Arc<Process>) {
+        PROCESS_TABLE.insert(pid, process);
+    }
+
+    /// Removes the process with `pid` from the table.
+    pub(super) fn remove(&mut self, pid: &Pid) -> Option<Arc<Process>> {
+        PROCESS_TABLE.remove(pid)
     }
 }
 
-/// A wrapper for the mutex-protected process table.
+/// Takes a snapshot of the processes in the process table.
+///
+/// The processes that are added or removed concurrently may or may not be in
+/// the snapshot.
+pub fn process_table() -> ProcessTable {
+    let mut processes = PROCESS_TABLE.values();
+    processes.sort_unstable_by_key(|process| process.pid());
+    ProcessTable { processes }
+}
+
+/// A snapshot of the process table.
 ///
-/// It provides the `iter` method to iterator over the processes in the table.
-pub struct ProcessTable<'a> {
-    inner: MutexGuard<'a, BTreeMap<Pid, Arc<Process>>>,
+/// It provides the `iter` method to iterator over the processes in the order of pids.
+pub struct ProcessTable {
+    processes: Vec<Arc<Process>>,
 }
 
-impl<'a> ProcessTable<'a> {
+impl ProcessTable {
     /// Returns an iterator over the processes in the table.
     pub fn iter(&self) -> ProcessTableIter {
         ProcessTableIter {
-            inner: self.inner.values(),
+            inner: self.processes.iter(),
         }
     }
 }
 
 /// An iterator over the processes of the process table.
 pub struct ProcessTableIter<'a> {
-    inner: Values<'a, Pid, Arc<Process>>,
+    inner: Iter<'a, Arc<Process>>,
 }
 
 impl<'a> Iterator for ProcessTableIter<'a> {
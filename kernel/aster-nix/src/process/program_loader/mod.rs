@@ -11,7 +11,7 @@ use super::process_vm::ProcessVm;
 use crate::{
     fs::{
         fs_resolver::{FsPath, FsResolver, AT_FDCWD},
-        path::Dentry,
+        path::{Dentry, PerMountFlags},
     },
     prelude::*,
 };
@@ -81,5 +81,9 @@ pub fn check_executable_file(dentry: &Arc<Dentry>) -> Result<()> {
         return_errno_with_message!(Errno::EACCES, "the dentry is not executable");
     }
 
+    if dentry.mount_node().flags().contains(PerMountFlags::NOEXEC) {
+        return_errno_with_message!(Errno::EACCES, "the mount does not allow execution");
+    }
+
     Ok(())
 }
@@ -45,6 +45,18 @@ impl FaultSignal {
         };
         FaultSignal { num, code, addr }
     }
+
+    /// Creates a signal for the uncorrected memory error consumed by the process.
+    ///
+    /// The memory error has been handled by poisoning the page at `addr`, but the
+    /// data consumed by the process has been lost.
+    pub fn new_memory_error(addr: Option<Vaddr>) -> FaultSignal {
+        FaultSignal {
+            num: SIGBUS,
+            code: BUS_MCEERR_AR,
+            addr: addr.map(|addr| addr as u64),
+        }
+    }
 }
 
 impl Signal for FaultSignal {
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
 pub mod nice;
+pub mod pressure;
 mod priority_scheduler;
 
 // There may be multiple scheduling policies in the system,
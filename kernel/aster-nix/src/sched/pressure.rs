@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Pressure stall information (PSI).
+//!
+//! PSI tells how much time tasks spend waiting for a resource, i.e., the CPU,
+//! memory, or I/O. It is exposed to the user space at `/proc/pressure/*`.
+//!
+//! The _some_ line is the share of the time during which at least one task
+//! stalls on the resource. The stalls are collected from the following places:
+//!  - CPU: tasks that are runnable but waiting in the run queue;
+//!  - I/O: tasks that are waiting for block I/O in the page cache;
+//!  - Memory: nothing, since there is no memory reclaim or swapping, so the
+//!    tasks never stall on memory and the pressure is always zero.
+//!
+//! The _full_ line is the share of the time during which all the non-idle
+//! tasks stall at the same time. It is always zero for the CPU, which is also
+//! the case for the system-wide CPU pressure in Linux. For the other resources,
+//! it requires knowing how many tasks are running on the CPUs, which the
+//! scheduler does not report, so it is not supported and reported as zeros.
+//!
+//! Only the system-wide pressure is supported. There is no per-cgroup
+//! pressure since there are no cgroups, and the pressure triggers, which are
+//! set up by writing to the files, are not supported either.
+
+use core::{fmt::Write, time::Duration};
+
+use aster_time::read_monotonic_time;
+
+use crate::prelude::*;
+
+/// The resources that tasks may stall on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PressureResource {
+    Cpu,
+    Memory,
+    Io,
+}
+
+/// Returns the system-wide pressure of the resource.
+pub fn pressure_of(resource: PressureResource) -> &'static Pressure {
+    static CPU_PRESSURE: Pressure = Pressure::new();
+    static MEMORY_PRESSURE: Pressure = Pressure::new();
+    static IO_PRESSURE: Pressure = Pressure::new();
+
+    match resource {
+        PressureResource::Cpu => &CPU_PRESSURE,
+        PressureResource::Memory => &MEMORY_PRESSURE,
+        PressureResource::Io => &IO_PRESSURE,
+    }
+}
+
+/// Runs `f`, which waits for I/O, and accounts the time as an I/O stall.
+pub fn account_io_wait<T>(f: impl FnOnce() -> T) -> T {
+    let pressure = pressure_of(PressureResource::Io);
+    pressure.stall_begin();
+    let res = f();
+    pressure.stall_end();
+    res
+}
+
+/// The pressure of a resource.
+pub struct Pressure {
+    some: SpinLock<StallTracker>,
+}
+
+impl Pressure {
+    const fn new() -> Self {
+        Self {
+            some: SpinLock::new(StallTracker::new()),
+        }
+    }
+
+    /// Records that a task starts stalling on the resource.
+    pub fn stall_begin(&self) {
+        let now = read_monotonic_time();
+        let mut some = self.some.lock_irq_disabled();
+        some.update_avgs(now);
+        if some.num_stalled == 0 {
+            some.stall_start = now;
+        }
+        some.num_stalled += 1;
+    }
+
+    /// Records that a task stops stalling on the resource.
+    pub fn stall_end(&self) {
+        let now = read_monotonic_time();
+        let mut some = self.some.lock_irq_disabled();
+        some.update_avgs(now);
+        debug_assert!(some.num_stalled > 0);
+        some.num_stalled -= 1;
+        if some.num_stalled == 0 {
+            some.total += now.saturating_sub(some.stall_start);
+        }
+    }
+
+    /// Renders the pressure in the format of `/proc/pressure/*`.
+    pub fn render(&self) -> String {
+        let now = read_monotonic_time();
+        let (avgs, total) = {
+            let mut some = self.some.lock_irq_disabled();
+            some.update_avgs(now);
+            (some.avgs, some.total_at(now))
+        };
+
+        let mut output = String::new();
+        writeln!(
+            output,
+            "some avg10={} avg60={} avg300={} total={}",
+            FixedPercent(avgs[0]),
+            FixedPercent(avgs[1]),
+            FixedPercent(avgs[2]),
+            total.as_micros()
+        )
+        .unwrap();
+        writeln!(output, "full avg10=0.00 avg60=0.00 avg300=0.00 total=0").unwrap();
+        output
+    }
+}
+
+/// The period to update the running averages.
+const AVGS_PERIOD: Duration = Duration::from_secs(2);
+
+/// The number of bits of the fractional part of the running averages.
+const FSHIFT: u32 = 11;
+/// 1.0 in the fixed-point representation.
+const FIXED_1: u64 = 1 << FSHIFT;
+/// The decay factors of the 10s, 60s, and 300s averages for a 2s period,
+/// i.e., `FIXED_1 / exp(2s / window)`.
+const EXP_FACTORS: [u64; 3] = [1677, 1981, 2034];
+
+/// Tracks the stalls of tasks on a resource.
+struct StallTracker {
+    /// The number of tasks that are stalling.
+    num_stalled: usize,
+    /// The time when `num_stalled` becomes non-zero.
+    stall_start: Duration,
+    /// The total time of the stalls that have ended.
+    total: Duration,
+    /// The running averages of the stall percentage, in the fixed-point representation.
+    avgs: [u64; 3],
+    /// The total stall time when the averages are last updated.
+    avgs_total: Duration,
+    /// The time when the averages are last updated.
+    avgs_last_update: Duration,
+}
+
+impl StallTracker {
+    const fn new() -> Self {
+        Self {
+            num_stalled: 0,
+            stall_start: Duration::ZERO,
+            total: Duration::ZERO,
+            avgs: [0; 3],
+            avgs_total: Duration::ZERO,
+            avgs_last_update: Duration::ZERO,
+        }
+    }
+
+    /// Returns the total stall time, including the ongoing one.
+    fn total_at(&self, now: Duration) -> Duration {
+        if self.num_stalled > 0 {
+            self.total + now.saturating_sub(self.stall_start)
+        } else {
+            self.total
+        }
+    }
+
+    /// Updates the running averages if a period has elapsed.
+    fn update_avgs(&mut self, now: Duration) {
+        let period = now.saturating_sub(self.avgs_last_update);
+        if period < AVGS_PERIOD {
+            return;
+        }
+
+        let total = self.total_at(now);
+        let stall = (total - self.avgs_total).min(period);
+        self.avgs_total = total;
+        self.avgs_last_update = now;
+
+        // The averages are updated lazily, so several periods may have been
+        // missed. Like Linux, the stall time is attributed to the last period
+        // and the missed periods are treated as having no stalls.
+        let missed_periods = (period.as_nanos() / AVGS_PERIOD.as_nanos()) as u64 - 1;
+        // Keep the fractional part of the percentage, which is shown with two decimals.
+        let stall_percent = (stall.as_nanos() * 100 * FIXED_1 as u128 / period.as_nanos()) as u64;
+        for (avg, exp) in self.avgs.iter_mut().zip(EXP_FACTORS) {
+            // The averages decay to zero after this many periods anyway.
+            for _ in 0..missed_periods.min(1024) {
+                *avg = calc_load(*avg, exp, 0);
+            }
+            *avg = calc_load(*avg, exp, stall_percent);
+        }
+    }
+}
+
+/// Computes the next value of an exponentially decaying average.
+fn calc_load(load: u64, exp: u64, active: u64) -> u64 {
+    let mut new_load = load * exp + active * (FIXED_1 - exp);
+    if active >= load {
+        new_load += FIXED_1 - 1;
+    }
+    new_load / FIXED_1
+}
+
+/// A percentage in the fixed-point representation.
+struct FixedPercent(u64);
+
+impl core::fmt::Display for FixedPercent {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let int = self.0 >> FSHIFT;
+        let frac = ((self.0 & (FIXED_1 - 1)) * 100) >> FSHIFT;
+        write!(f, "{}.{:02}", int, frac)
+    }
+}
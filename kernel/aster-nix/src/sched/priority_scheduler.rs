@@ -3,6 +3,7 @@
 use intrusive_collections::LinkedList;
 use ostd::task::{set_scheduler, Scheduler, Task, TaskAdapter};
 
+use super::pressure::{pressure_of, PressureResource};
 use crate::prelude::*;
 
 pub fn init() {
@@ -35,6 +36,8 @@ impl PreemptScheduler {
 
 impl Scheduler for PreemptScheduler {
     fn enqueue(&self, task: Arc<Task>) {
+        // A task in the run queue is stalling on the CPU.
+        pressure_of(PressureResource::Cpu).stall_begin();
         if task.is_real_time() {
             self.real_time_tasks.lock_irq_disabled().push_back(task);
         } else {
@@ -43,11 +46,15 @@ impl Scheduler for PreemptScheduler {
     }
 
     fn dequeue(&self) -> Option<Arc<Task>> {
-        if !self.real_time_tasks.lock_irq_disabled().is_empty() {
+        let task = if !self.real_time_tasks.lock_irq_disabled().is_empty() {
             self.real_time_tasks.lock_irq_disabled().pop_front()
         } else {
             self.normal_tasks.lock_irq_disabled().pop_front()
+        };
+        if task.is_some() {
+            pressure_of(PressureResource::Cpu).stall_end();
         }
+        task
     }
 
     fn should_preempt(&self, task: &Arc<Task>) -> bool {
@@ -65,8 +65,8 @@ fn do_accept(
 
     let fd = {
         let current = current!();
-        let mut file_table = current.file_table().lock();
-        file_table.insert(connected_socket, fd_flags)
+        let file_table = current.file_table();
+        file_table.insert(connected_socket, fd_flags)?
     };
 
     Ok(fd)
@@ -0,0 +1,416 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The legacy Linux AIO syscalls.
+//!
+//! As in Linux, only the reads and writes of the files opened with `O_DIRECT`
+//! are asynchronous, whose bios are submitted without waiting for them. The
+//! other requests are executed synchronously by `io_submit` with the
+//! corresponding synchronous syscall. The completion events of the requests are
+//! queued in the AIO context.
+
+use core::{mem::size_of, time::Duration};
+
+use aster_block::bio::BioStatus;
+use aster_rights::Rights;
+use ostd::mm::Frame;
+
+use super::{
+    fsync::{sys_fdatasync, sys_fsync},
+    pread64::sys_pread64,
+    preadv::sys_preadv,
+    pwrite64::sys_pwrite64,
+    pwritev::sys_pwritev,
+    SyscallReturn,
+};
+use crate::{
+    fs::{
+        aio::{AioContext, AioContextId, IoEvent},
+        file_handle::FileLike,
+        file_table::FileDesc,
+        inode_handle::InodeHandle,
+        utils::StatusFlags,
+    },
+    prelude::*,
+    thread::work_queue::{submit_work_func, WorkPriority},
+    time::timespec_t,
+    util::{read_val_from_user, write_val_to_user},
+    vm::{
+        perms::VmPerms,
+        vmo::{VmoOptions, VmoRightsOp},
+    },
+};
+
+/// The maximum number of events of all the AIO contexts of a process.
+const AIO_MAX_NR: usize = 65536;
+
+pub fn sys_io_setup(nr_events: u32, ctx_id_addr: Vaddr) -> Result<SyscallReturn> {
+    debug!(
+        "nr_events = {}, ctx_id_addr = 0x{:x}",
+        nr_events, ctx_id_addr
+    );
+
+    if read_val_from_user::<AioContextId>(ctx_id_addr)? != 0 {
+        return_errno_with_message!(Errno::EINVAL, "the context ID is not initialized to zero");
+    }
+    if nr_events == 0 {
+        return_errno_with_message!(Errno::EINVAL, "the number of events is zero");
+    }
+
+    // As in Linux, the ID of a context is the address of a mapping in the user
+    // space. The mapping is left zeroed, so user-space libraries (e.g., libaio)
+    // do not recognize it as a ring buffer and always reap events by syscalls.
+    let current = current!();
+    let root_vmar = current.root_vmar();
+    let ctx_id = {
+        let vmo = VmoOptions::<Rights>::new(PAGE_SIZE).alloc()?;
+        root_vmar.new_map(vmo.to_dyn(), VmPerms::READ)?.build()?
+    };
+
+    {
+        let mut contexts = current.aio_contexts().lock();
+        let nr_total_events = contexts
+            .values()
+            .map(|context| context.max_events())
+            .sum::<usize>()
+            + nr_events as usize;
+        if nr_total_events > AIO_MAX_NR {
+            drop(contexts);
+            root_vmar.destroy(ctx_id..ctx_id + PAGE_SIZE)?;
+            return_errno_with_message!(Errno::EAGAIN, "too many AIO events");
+        }
+        contexts.insert(ctx_id as AioContextId, AioContext::new(nr_events as usize));
+    }
+
+    if let Err(err) = write_val_to_user(ctx_id_addr, &(ctx_id as AioContextId)) {
+        let context = current
+            .aio_contexts()
+            .lock()
+            .remove(&(ctx_id as AioContextId));
+        if let Some(context) = context {
+            context.destroy();
+        }
+        root_vmar.destroy(ctx_id..ctx_id + PAGE_SIZE)?;
+        return Err(err);
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_io_destroy(ctx_id: AioContextId) -> Result<SyscallReturn> {
+    debug!("ctx_id = 0x{:x}", ctx_id);
+
+    let current = current!();
+    let context = current
+        .aio_contexts()
+        .lock()
+        .remove(&ctx_id)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the AIO context does not exist"))?;
+    context.destroy();
+
+    let ctx_addr = ctx_id as Vaddr;
+    current
+        .root_vmar()
+        .destroy(ctx_addr..ctx_addr + PAGE_SIZE)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_io_submit(ctx_id: AioContextId, nr: i64, iocbpp: Vaddr) -> Result<SyscallReturn> {
+    debug!(
+        "ctx_id = 0x{:x}, nr = {}, iocbpp = 0x{:x}",
+        ctx_id, nr, iocbpp
+    );
+
+    if nr < 0 {
+        return_errno_with_message!(Errno::EINVAL, "the number of requests is negative");
+    }
+    let context = get_context(ctx_id)?;
+
+    let nr = (nr as usize).min(context.max_events());
+    for i in 0..nr {
+        let res = read_val_from_user::<Vaddr>(iocbpp + i * size_of::<Vaddr>())
+            .and_then(|iocb_addr| submit_one(&context, iocb_addr));
+        if let Err(err) = res {
+            // The error is reported only if no request is submitted.
+            if i == 0 {
+                return Err(err);
+            }
+            return Ok(SyscallReturn::Return(i as _));
+        }
+    }
+
+    Ok(SyscallReturn::Return(nr as _))
+}
+
+pub fn sys_io_getevents(
+    ctx_id: AioContextId,
+    min_nr: i64,
+    nr: i64,
+    events_addr: Vaddr,
+    timeout_addr: Vaddr,
+) -> Result<SyscallReturn> {
+    debug!(
+        "ctx_id = 0x{:x}, min_nr = {}, nr = {}, events_addr = 0x{:x}, timeout_addr = 0x{:x}",
+        ctx_id, min_nr, nr, events_addr, timeout_addr
+    );
+
+    if min_nr < 0 || nr < min_nr {
+        return_errno_with_message!(Errno::EINVAL, "invalid number of events");
+    }
+    let context = get_context(ctx_id)?;
+
+    let timeout = if timeout_addr != 0 {
+        let timespec = read_val_from_user::<timespec_t>(timeout_addr)?;
+        if timespec.sec < 0 || !(0..1_000_000_000).contains(&timespec.nsec) {
+            return_errno_with_message!(Errno::EINVAL, "invalid timeout");
+        }
+        Some(Duration::from(timespec))
+    } else {
+        None
+    };
+
+    let events = context.get_events(min_nr as usize, nr as usize, timeout.as_ref())?;
+    for (i, event) in events.iter().enumerate() {
+        write_val_to_user(events_addr + i * size_of::<IoEvent>(), event)?;
+    }
+
+    Ok(SyscallReturn::Return(events.len() as _))
+}
+
+pub fn sys_io_cancel(
+    ctx_id: AioContextId,
+    iocb_addr: Vaddr,
+    result_addr: Vaddr,
+) -> Result<SyscallReturn> {
+    debug!(
+        "ctx_id = 0x{:x}, iocb_addr = 0x{:x}, result_addr = 0x{:x}",
+        ctx_id, iocb_addr, result_addr
+    );
+
+    let context = get_context(ctx_id)?;
+    let iocb = read_val_from_user::<Iocb>(iocb_addr)?;
+    if iocb.key != 0 {
+        return_errno_with_message!(Errno::EINVAL, "invalid iocb key");
+    }
+
+    if !context.is_in_flight(iocb_addr as u64) {
+        return_errno_with_message!(Errno::EINVAL, "the request is not in progress");
+    }
+
+    // Canceling requests is not supported, see `docs/src/kernel/linux-compatibility.md`.
+    // A request in progress has submitted its bios to the block device, which cannot
+    // be withdrawn, so the request will complete and deliver its event as usual.
+    return_errno_with_message!(Errno::EAGAIN, "the request cannot be canceled");
+}
+
+fn get_context(ctx_id: AioContextId) -> Result<Arc<AioContext>> {
+    current!()
+        .aio_contexts()
+        .lock()
+        .get(&ctx_id)
+        .cloned()
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the AIO context does not exist"))
+}
+
+/// Submits the request at `iocb_addr`.
+///
+/// The completion event of the request is queued in the context when the request completes.
+fn submit_one(context: &Arc<AioContext>, iocb_addr: Vaddr) -> Result<()> {
+    let iocb = read_val_from_user::<Iocb>(iocb_addr)?;
+    debug!("iocb = {:?}", iocb);
+
+    if iocb.reserved2 != 0 {
+        return_errno_with_message!(Errno::EINVAL, "the reserved field is not zero");
+    }
+    if iocb.rw_flags != 0 {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the RWF flags are not supported");
+    }
+    let flags = IocbFlags::from_bits(iocb.flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid iocb flags"))?;
+    let opcode = IocbCmd::try_from(iocb.lio_opcode)?;
+
+    let fd = iocb.fildes as FileDesc;
+    let (file, resfd_file) = {
+        let current = current!();
+        let file_table = current.file_table();
+        // As in Linux, a bad file descriptor fails the submission instead of the request.
+        let file = file_table.get_file(fd)?;
+        if flags.contains(IocbFlags::RESFD) {
+            (file, Some(file_table.get_file(iocb.resfd as FileDesc)?))
+        } else {
+            (file, None)
+        }
+    };
+
+    context.reserve(iocb_addr as u64)?;
+    let complete = {
+        let context = context.clone();
+        let data = iocb.data;
+        move |res: i64| {
+            context.complete(IoEvent {
+                data,
+                obj: iocb_addr as u64,
+                res,
+                res2: 0,
+            });
+
+            if let Some(file) = resfd_file {
+                // Notify the eventfd of the completion. Like Linux, errors are ignored. The
+                // request may complete in the interrupt context, where the eventfd cannot be
+                // written to, so the notification is deferred to a work queue.
+                submit_work_func(
+                    move || {
+                        let _ = file.write(&1u64.to_ne_bytes());
+                    },
+                    WorkPriority::High,
+                );
+            }
+        }
+    };
+
+    let (buf, len, offset) = (iocb.buf as Vaddr, iocb.nbytes as usize, iocb.offset);
+    if let Some(res) = submit_direct_io(&file, opcode, buf, len, offset, complete.clone()) {
+        // The request is either being executed asynchronously or failed to be submitted.
+        if let Err(err) = res {
+            complete(-(err.error() as i64));
+        }
+        return Ok(());
+    }
+
+    let res = match opcode {
+        IocbCmd::Pread => sys_pread64(fd, buf, len, offset),
+        IocbCmd::Pwrite => sys_pwrite64(fd, buf, len, offset),
+        IocbCmd::Fsync => sys_fsync(fd),
+        IocbCmd::Fdsync => sys_fdatasync(fd),
+        IocbCmd::Noop => Ok(SyscallReturn::Return(0)),
+        IocbCmd::Preadv => sys_preadv(fd, buf, len, offset),
+        IocbCmd::Pwritev => sys_pwritev(fd, buf, len, offset),
+    };
+    let res = match res {
+        Ok(SyscallReturn::Return(res)) => res as i64,
+        Ok(SyscallReturn::NoReturn) => unreachable!(),
+        Err(err) => -(err.error() as i64),
+    };
+    complete(res);
+
+    Ok(())
+}
+
+/// Submits the bios of a read or write request on a file opened with `O_DIRECT`.
+///
+/// The bios transfer the data to or from the user buffer directly, and `complete` is called
+/// with the result of the request when they complete. This method returns `None` if the
+/// request cannot be executed asynchronously, in which case it should be executed
+/// synchronously instead.
+fn submit_direct_io<F>(
+    file: &Arc<dyn FileLike>,
+    opcode: IocbCmd,
+    buf: Vaddr,
+    len: usize,
+    offset: i64,
+    complete: F,
+) -> Option<Result<()>>
+where
+    F: FnOnce(i64) + Send + 'static,
+{
+    let is_read = match opcode {
+        IocbCmd::Pread => true,
+        IocbCmd::Pwrite => false,
+        _ => return None,
+    };
+    let inode_handle = file.downcast_ref::<InodeHandle>()?;
+    if !inode_handle.status_flags().contains(StatusFlags::O_DIRECT) {
+        return None;
+    }
+    // The user buffer is transferred in pages. The other requests are left to the
+    // synchronous path, which validates them as usual.
+    if offset < 0
+        || buf % PAGE_SIZE != 0
+        || len % PAGE_SIZE != 0
+        || (offset as usize) % PAGE_SIZE != 0
+    {
+        return None;
+    }
+
+    let res = if is_read {
+        // A read writes to the user buffer.
+        get_user_frames(buf, len, true)
+            .and_then(|frames| inode_handle.read_direct_async(offset as usize, &frames))
+    } else {
+        // A write reads from the user buffer.
+        get_user_frames(buf, len, false)
+            .and_then(|frames| inode_handle.write_direct_async(offset as usize, &frames))
+            .map(|bio_waiter| (len, bio_waiter))
+    };
+
+    match res {
+        Ok((len, bio_waiter)) => {
+            bio_waiter.on_complete(move |status| match status {
+                Some(BioStatus::Complete) => complete(len as i64),
+                _ => complete(-(Errno::EIO as i64)),
+            });
+            Some(Ok(()))
+        }
+        // The file system does not support asynchronous I/O.
+        Err(err) if err.error() == Errno::EOPNOTSUPP => None,
+        Err(err) => Some(Err(err)),
+    }
+}
+
+/// Returns the frames of the page-aligned user buffer.
+///
+/// The frames stay valid even if the buffer is unmapped before the I/O completes.
+fn get_user_frames(buf: Vaddr, len: usize, write: bool) -> Result<Vec<Frame>> {
+    let current = current!();
+    let root_vmar = current.root_vmar();
+    (buf..buf + len)
+        .step_by(PAGE_SIZE)
+        .map(|addr| {
+            let vm_mapping = root_vmar
+                .get_vm_mapping(addr)
+                .map_err(|_| Error::with_message(Errno::EFAULT, "the buffer is not mapped"))?;
+            vm_mapping.get_frame(addr, write)
+        })
+        .collect()
+}
+
+/// The AIO request, i.e., `struct iocb` in Linux.
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+#[allow(dead_code)]
+struct Iocb {
+    data: u64,
+    key: u32,
+    rw_flags: u32,
+    lio_opcode: u16,
+    reqprio: i16,
+    fildes: u32,
+    buf: u64,
+    nbytes: u64,
+    offset: i64,
+    reserved2: u64,
+    flags: u32,
+    resfd: u32,
+}
+
+#[derive(Debug, Clone, Copy, TryFromInt)]
+#[repr(u16)]
+enum IocbCmd {
+    Pread = 0,
+    Pwrite = 1,
+    Fsync = 2,
+    Fdsync = 3,
+    // TODO: Support `IOCB_CMD_POLL`.
+    Noop = 6,
+    Preadv = 7,
+    Pwritev = 8,
+}
+
+bitflags! {
+    struct IocbFlags: u32 {
+        /// Notify the eventfd in `resfd` of the completion.
+        const RESFD = 1 << 0;
+        /// The `reqprio` field is valid.
+        const IOPRIO = 1 << 1;
+    }
+}
@@ -3,6 +3,7 @@
 use crate::syscall::{
     accept::{sys_accept, sys_accept4},
     access::{sys_access, sys_faccessat},
+    aio::{sys_io_cancel, sys_io_destroy, sys_io_getevents, sys_io_setup, sys_io_submit},
     alarm::sys_alarm,
     arch_prctl::sys_arch_prctl,
     bind::sys_bind,
@@ -16,6 +17,7 @@ use crate::syscall::{
     clock_gettime::sys_clock_gettime,
     clone::{sys_clone, sys_clone3},
     close::sys_close,
+    close_range::sys_close_range,
     connect::sys_connect,
     dup::{sys_dup, sys_dup2, sys_dup3},
     epoll::{sys_epoll_create, sys_epoll_create1, sys_epoll_ctl, sys_epoll_pwait, sys_epoll_wait},
@@ -60,7 +62,7 @@ use crate::syscall::{
     mprotect::sys_mprotect,
     munmap::sys_munmap,
     nanosleep::{sys_clock_nanosleep, sys_nanosleep},
-    open::{sys_creat, sys_open, sys_openat},
+    open::{sys_creat, sys_open, sys_openat, sys_openat2},
     pause::sys_pause,
     pipe::{sys_pipe, sys_pipe2},
     poll::sys_poll,
@@ -68,13 +70,14 @@ use crate::syscall::{
     pread64::sys_pread64,
     preadv::{sys_preadv, sys_preadv2, sys_readv},
     prlimit64::sys_prlimit64,
+    process_vm::{sys_process_vm_readv, sys_process_vm_writev},
     pwrite64::sys_pwrite64,
     pwritev::{sys_pwritev, sys_pwritev2, sys_writev},
     read::sys_read,
     readlink::{sys_readlink, sys_readlinkat},
     recvfrom::sys_recvfrom,
-    recvmsg::sys_recvmsg,
-    rename::{sys_rename, sys_renameat},
+    recvmsg::{sys_recvmmsg, sys_recvmsg},
+    rename::{sys_rename, sys_renameat, sys_renameat2},
     rmdir::sys_rmdir,
     rt_sigaction::sys_rt_sigaction,
     rt_sigpending::sys_rt_sigpending,
@@ -82,10 +85,11 @@ use crate::syscall::{
     rt_sigreturn::sys_rt_sigreturn,
     rt_sigsuspend::sys_rt_sigsuspend,
     sched_getaffinity::sys_sched_getaffinity,
+    sched_getattr::sys_sched_getattr,
     sched_yield::sys_sched_yield,
     select::sys_select,
     sendfile::sys_sendfile,
-    sendmsg::sys_sendmsg,
+    sendmsg::{sys_sendmmsg, sys_sendmsg},
     sendto::sys_sendto,
     set_get_priority::{sys_get_priority, sys_set_priority},
     set_robust_list::sys_set_robust_list,
@@ -107,6 +111,7 @@ use crate::syscall::{
     sigaltstack::sys_sigaltstack,
     socket::sys_socket,
     socketpair::sys_socketpair,
+    splice::{sys_splice, sys_tee},
     stat::{sys_fstat, sys_fstatat, sys_lstat, sys_stat},
     statfs::{sys_fstatfs, sys_statfs},
     symlink::{sys_symlink, sys_symlinkat},
@@ -250,6 +255,11 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_TIME = 201             => sys_time(args[..1]);
     SYS_FUTEX = 202            => sys_futex(args[..6]);
     SYS_SCHED_GETAFFINITY = 204 => sys_sched_getaffinity(args[..3]);
+    SYS_IO_SETUP = 206         => sys_io_setup(args[..2]);
+    SYS_IO_DESTROY = 207       => sys_io_destroy(args[..1]);
+    SYS_IO_GETEVENTS = 208     => sys_io_getevents(args[..5]);
+    SYS_IO_SUBMIT = 209        => sys_io_submit(args[..3]);
+    SYS_IO_CANCEL = 210        => sys_io_cancel(args[..3]);
     SYS_EPOLL_CREATE = 213     => sys_epoll_create(args[..1]);
     SYS_GETDENTS64 = 217       => sys_getdents64(args[..3]);
     SYS_SET_TID_ADDRESS = 218  => sys_set_tid_address(args[..1]);
@@ -278,6 +288,8 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_FCHMODAT = 268         => sys_fchmodat(args[..3]);
     SYS_FACCESSAT = 269        => sys_faccessat(args[..3]);
     SYS_SET_ROBUST_LIST = 273  => sys_set_robust_list(args[..2]);
+    SYS_SPLICE = 275           => sys_splice(args[..6]);
+    SYS_TEE = 276              => sys_tee(args[..4]);
     SYS_UTIMENSAT = 280        => sys_utimensat(args[..4]);
     SYS_EPOLL_PWAIT = 281      => sys_epoll_pwait(args[..6]);
     SYS_EVENTFD = 284          => sys_eventfd(args[..1]);
@@ -288,10 +300,18 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_PIPE2 = 293            => sys_pipe2(args[..2]);
     SYS_PREADV = 295           => sys_preadv(args[..4]);
     SYS_PWRITEV = 296          => sys_pwritev(args[..4]);
+    SYS_RECVMMSG = 299         => sys_recvmmsg(args[..5]);
     SYS_PRLIMIT64 = 302        => sys_prlimit64(args[..4]);
+    SYS_SENDMMSG = 307         => sys_sendmmsg(args[..4]);
+    SYS_PROCESS_VM_READV = 310 => sys_process_vm_readv(args[..6]);
+    SYS_PROCESS_VM_WRITEV = 311 => sys_process_vm_writev(args[..6]);
+    SYS_SCHED_GETATTR = 315    => sys_sched_getattr(args[..4]);
+    SYS_RENAMEAT2 = 316        => sys_renameat2(args[..5]);
     SYS_GETRANDOM = 318        => sys_getrandom(args[..3]);
     SYS_EXECVEAT = 322         => sys_execveat(args[..5], &mut context);
     SYS_PREADV2 = 327          => sys_preadv2(args[..5]);
     SYS_PWRITEV2 = 328         => sys_pwritev2(args[..5]);
     SYS_CLONE3 = 435           => sys_clone3(args[..2], &context);
+    SYS_CLOSE_RANGE = 436      => sys_close_range(args[..3]);
+    SYS_OPENAT2 = 437          => sys_openat2(args[..4]);
 }
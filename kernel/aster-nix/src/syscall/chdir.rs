@@ -34,7 +34,7 @@ pub fn sys_fchdir(fd: FileDesc) -> Result<SyscallReturn> {
 
     let current = current!();
     let dentry = {
-        let file_table = current.file_table().lock();
+        let file_table = current.file_table();
         let file = file_table.get_file(fd)?;
         let inode_handle = file
             .downcast_ref::<InodeHandle>()
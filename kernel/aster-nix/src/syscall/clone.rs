@@ -6,7 +6,7 @@ use super::SyscallReturn;
 use crate::{
     prelude::*,
     process::{clone_child, signal::constants::SIGCHLD, CloneArgs, CloneFlags},
-    util::read_val_from_user,
+    util::{read_extensible_struct_from_user, ExtensibleStruct},
 };
 
 // The order of arguments for clone differs in different architecture.
@@ -36,12 +36,8 @@ pub fn sys_clone3(
         clong_args_addr,
         size
     );
-    if size != core::mem::size_of::<Clone3Args>() {
-        return_errno_with_message!(Errno::EINVAL, "invalid size");
-    }
-
     let clone_args = {
-        let args: Clone3Args = read_val_from_user(clong_args_addr)?;
+        let args: Clone3Args = read_extensible_struct_from_user(clong_args_addr, size)?;
         trace!("clone3 args = {:x?}", args);
         CloneArgs::from(args)
     };
@@ -79,6 +75,11 @@ struct Clone3Args {
     cgroup: u64,
 }
 
+impl ExtensibleStruct for Clone3Args {
+    /// The size of `struct clone_args` in Linux 5.3, which has no `set_tid` and `cgroup`.
+    const MIN_SIZE: usize = 64;
+}
+
 impl From<Clone3Args> for CloneArgs {
     fn from(value: Clone3Args) -> Self {
         const FLAGS_MASK: u64 = 0xff;
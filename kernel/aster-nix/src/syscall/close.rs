@@ -6,9 +6,10 @@ use crate::{fs::file_table::FileDesc, prelude::*};
 pub fn sys_close(fd: FileDesc) -> Result<SyscallReturn> {
     debug!("fd = {}", fd);
     let current = current!();
-    let mut file_table = current.file_table().lock();
-    let _ = file_table.get_file(fd)?;
-    let file = file_table.close_file(fd).unwrap();
+    let file_table = current.file_table();
+    let file = file_table
+        .close_file(fd)
+        .ok_or_else(|| Error::with_message(Errno::EBADF, "the fd does not exist"))?;
     file.clean_for_close()?;
     Ok(SyscallReturn::Return(0))
 }
@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::prelude::*;
+
+pub fn sys_close_range(first: u32, last: u32, flags: u32) -> Result<SyscallReturn> {
+    let flags = CloseRangeFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
+    debug!("first = {}, last = {}, flags = {:?}", first, last, flags);
+
+    if first > last {
+        return_errno_with_message!(Errno::EINVAL, "the range is empty");
+    }
+    let range = first as usize..=last as usize;
+
+    let current = current!();
+    if flags.contains(CloseRangeFlags::UNSHARE) {
+        current.unshare_file_table();
+    }
+
+    let file_table = current.file_table();
+    if flags.contains(CloseRangeFlags::CLOEXEC) {
+        file_table.set_cloexec_range(range);
+        return Ok(SyscallReturn::Return(0));
+    }
+
+    // Like Linux, errors on closing the files are ignored.
+    for file in file_table.close_range(range) {
+        let _ = file.clean_for_close();
+    }
+    Ok(SyscallReturn::Return(0))
+}
+
+bitflags! {
+    struct CloseRangeFlags: u32 {
+        /// Unshares the file table before closing the file descriptors.
+        const UNSHARE = 1 << 1;
+        /// Sets the close-on-exec flag instead of closing the file descriptors.
+        const CLOEXEC = 1 << 2;
+    }
+}
@@ -4,15 +4,15 @@ use super::SyscallReturn;
 use crate::{
     fs::file_table::{FdFlags, FileDesc},
     prelude::*,
-    process::ResourceType,
+    process::{Process, ResourceType},
 };
 
 pub fn sys_dup(old_fd: FileDesc) -> Result<SyscallReturn> {
     debug!("old_fd = {}", old_fd);
 
     let current = current!();
-    let mut file_table = current.file_table().lock();
-    let new_fd = file_table.dup(old_fd, 0, FdFlags::empty())?;
+    let file_table = current.file_table();
+    let new_fd = file_table.dup(old_fd, 0, FdFlags::empty(), nofile_limit(&current))?;
 
     Ok(SyscallReturn::Return(new_fd as _))
 }
@@ -22,7 +22,7 @@ pub fn sys_dup2(old_fd: FileDesc, new_fd: FileDesc) -> Result<SyscallReturn> {
 
     if old_fd == new_fd {
         let current = current!();
-        let file_table = current.file_table().lock();
+        let file_table = current.file_table();
         let _ = file_table.get_file(old_fd)?;
         return Ok(SyscallReturn::Return(new_fd as _));
     }
@@ -48,19 +48,23 @@ fn do_dup3(old_fd: FileDesc, new_fd: FileDesc, flags: FdFlags) -> Result<Syscall
     }
 
     let current = current!();
-    if new_fd
-        >= current
-            .resource_limits()
-            .lock()
-            .get_rlimit(ResourceType::RLIMIT_NOFILE)
-            .get_cur() as FileDesc
-    {
-        return_errno!(Errno::EBADF);
+    if new_fd < 0 || new_fd as u64 >= nofile_limit(&current) {
+        return_errno_with_message!(Errno::EBADF, "the new fd is out of range");
     }
 
-    let mut file_table = current.file_table().lock();
-    let _ = file_table.close_file(new_fd);
-    let new_fd = file_table.dup(old_fd, new_fd, flags)?;
+    // Replace `new_fd` atomically, so that it never becomes free in the meantime.
+    let file_table = current.file_table();
+    let file = file_table.get_file(old_fd)?;
+    let _ = file_table.insert_at(new_fd, file, flags)?;
 
     Ok(SyscallReturn::Return(new_fd as _))
 }
+
+/// Returns the maximum number of file descriptors that the process can open.
+pub(super) fn nofile_limit(process: &Process) -> u64 {
+    process
+        .resource_limits()
+        .lock()
+        .get_rlimit(ResourceType::RLIMIT_NOFILE)
+        .get_cur()
+}
@@ -40,8 +40,8 @@ pub fn sys_epoll_create1(flags: u32) -> Result<SyscallReturn> {
 
     let current = current!();
     let epoll_file: Arc<EpollFile> = EpollFile::new();
-    let mut file_table = current.file_table().lock();
-    let fd = file_table.insert(epoll_file, fd_flags);
+    let file_table = current.file_table();
+    let fd = file_table.insert(epoll_file, fd_flags)?;
     Ok(SyscallReturn::Return(fd as _))
 }
 
@@ -78,10 +78,7 @@ pub fn sys_epoll_ctl(
     };
 
     let current = current!();
-    let file = {
-        let file_table = current.file_table().lock();
-        file_table.get_file(epfd)?.clone()
-    };
+    let file = current.file_table().get_file(epfd)?;
     let epoll_file = file
         .downcast_ref::<EpollFile>()
         .ok_or(Error::with_message(Errno::EINVAL, "not epoll file"))?;
@@ -104,9 +101,8 @@ fn do_epoll_wait(epfd: FileDesc, max_events: i32, timeout: i32) -> Result<Vec<Ep
     };
 
     let current = current!();
-    let file_table = current.file_table().lock();
-    let epoll_file = file_table
-        .get_file(epfd)?
+    let file = current.file_table().get_file(epfd)?;
+    let epoll_file = file
         .downcast_ref::<EpollFile>()
         .ok_or(Error::with_message(Errno::EINVAL, "not epoll file"))?;
     let epoll_events = epoll_file.wait(max_events, timeout.as_ref())?;
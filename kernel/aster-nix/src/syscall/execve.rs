@@ -9,7 +9,7 @@ use crate::{
     fs::{
         file_table::FileDesc,
         fs_resolver::{FsPath, AT_FDCWD},
-        path::Dentry,
+        path::{Dentry, PerMountFlags},
         utils::InodeType,
     },
     prelude::*,
@@ -104,7 +104,7 @@ fn do_execve(
     let current = current!();
 
     // Ensure that the file descriptors with the close-on-exec flag are closed.
-    let closed_files = current.file_table().lock().close_files_on_exec();
+    let closed_files = current.file_table().close_files_on_exec();
     for file in closed_files {
         file.clean_for_close()?;
     }
@@ -119,6 +119,10 @@ fn do_execve(
     // After the program has been successfully loaded, the virtual memory of the current process
     // is initialized. Hence, it is necessary to clear the previously recorded robust list.
     *posix_thread.robust_list().lock() = None;
+    // The AIO contexts are identified by the mappings in the old virtual memory.
+    for context in core::mem::take(&mut *current.aio_contexts().lock()).into_values() {
+        context.destroy();
+    }
     debug!("load elf in execve succeeds");
 
     let credentials = credentials_mut();
@@ -186,7 +190,7 @@ fn set_uid_from_elf(
     credentials: &Credentials<WriteOp>,
     elf_file: &Arc<Dentry>,
 ) -> Result<()> {
-    if elf_file.mode()?.has_set_uid() {
+    if elf_file.mode()?.has_set_uid() && !is_nosuid(elf_file) {
         let uid = elf_file.owner()?;
         credentials.set_euid(uid);
 
@@ -204,7 +208,7 @@ fn set_gid_from_elf(
     credentials: &Credentials<WriteOp>,
     elf_file: &Arc<Dentry>,
 ) -> Result<()> {
-    if elf_file.mode()?.has_set_gid() {
+    if elf_file.mode()?.has_set_gid() && !is_nosuid(elf_file) {
         let gid = elf_file.group()?;
         credentials.set_egid(gid);
 
@@ -215,3 +219,11 @@ fn set_gid_from_elf(
     credentials.reset_sgid();
     Ok(())
 }
+
+/// Returns whether the set-user-ID and set-group-ID bits of the file are ignored.
+fn is_nosuid(elf_file: &Dentry) -> bool {
+    elf_file
+        .mount_node()
+        .flags()
+        .contains(PerMountFlags::NOSUID)
+}
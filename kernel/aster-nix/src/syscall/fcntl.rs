@@ -1,9 +1,11 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use super::SyscallReturn;
+use super::{dup::nofile_limit, SyscallReturn};
 use crate::{
     fs::{
+        file_handle::FileLike,
         file_table::{FdFlags, FileDesc},
+        pipe::Pipe,
         utils::StatusFlags,
     },
     prelude::*,
@@ -13,23 +15,12 @@ pub fn sys_fcntl(fd: FileDesc, cmd: i32, arg: u64) -> Result<SyscallReturn> {
     let fcntl_cmd = FcntlCmd::try_from(cmd)?;
     debug!("fd = {}, cmd = {:?}, arg = {}", fd, fcntl_cmd, arg);
     match fcntl_cmd {
-        FcntlCmd::F_DUPFD => {
-            let current = current!();
-            let mut file_table = current.file_table().lock();
-            let new_fd = file_table.dup(fd, arg as FileDesc, FdFlags::empty())?;
-            Ok(SyscallReturn::Return(new_fd as _))
-        }
-        FcntlCmd::F_DUPFD_CLOEXEC => {
-            let current = current!();
-            let mut file_table = current.file_table().lock();
-            let new_fd = file_table.dup(fd, arg as FileDesc, FdFlags::CLOEXEC)?;
-            Ok(SyscallReturn::Return(new_fd as _))
-        }
+        FcntlCmd::F_DUPFD => do_dup_from(fd, arg, FdFlags::empty()),
+        FcntlCmd::F_DUPFD_CLOEXEC => do_dup_from(fd, arg, FdFlags::CLOEXEC),
         FcntlCmd::F_GETFD => {
             let current = current!();
-            let file_table = current.file_table().lock();
-            let entry = file_table.get_entry(fd)?;
-            let fd_flags = entry.flags();
+            let file_table = current.file_table();
+            let fd_flags = file_table.fd_flags(fd)?;
             Ok(SyscallReturn::Return(fd_flags.bits() as _))
         }
         FcntlCmd::F_SETFD => {
@@ -41,17 +32,13 @@ pub fn sys_fcntl(fd: FileDesc, cmd: i32, arg: u64) -> Result<SyscallReturn> {
                     .ok_or(Error::with_message(Errno::EINVAL, "invalid flags"))?
             };
             let current = current!();
-            let file_table = current.file_table().lock();
-            let entry = file_table.get_entry(fd)?;
-            entry.set_flags(flags);
+            let file_table = current.file_table();
+            file_table.set_fd_flags(fd, flags)?;
             Ok(SyscallReturn::Return(0))
         }
         FcntlCmd::F_GETFL => {
             let current = current!();
-            let file = {
-                let file_table = current.file_table().lock();
-                file_table.get_file(fd)?.clone()
-            };
+            let file = current.file_table().get_file(fd)?;
             let status_flags = file.status_flags();
             let access_mode = file.access_mode();
             Ok(SyscallReturn::Return(
@@ -60,10 +47,7 @@ pub fn sys_fcntl(fd: FileDesc, cmd: i32, arg: u64) -> Result<SyscallReturn> {
         }
         FcntlCmd::F_SETFL => {
             let current = current!();
-            let file = {
-                let file_table = current.file_table().lock();
-                file_table.get_file(fd)?.clone()
-            };
+            let file = current.file_table().get_file(fd)?;
             let new_status_flags = {
                 // This cmd can change(set or unset) only the O_APPEND, O_ASYNC, O_DIRECT,
                 // O_NOATIME and O_NONBLOCK flags.
@@ -80,7 +64,38 @@ pub fn sys_fcntl(fd: FileDesc, cmd: i32, arg: u64) -> Result<SyscallReturn> {
             file.set_status_flags(new_status_flags)?;
             Ok(SyscallReturn::Return(0))
         }
+        FcntlCmd::F_SETPIPE_SZ => {
+            let current = current!();
+            let file = current.file_table().get_file(fd)?;
+            let new_size = pipe_of(file.as_ref())?.set_capacity(arg as usize)?;
+            Ok(SyscallReturn::Return(new_size as _))
+        }
+        FcntlCmd::F_GETPIPE_SZ => {
+            let current = current!();
+            let file = current.file_table().get_file(fd)?;
+            let size = pipe_of(file.as_ref())?.capacity();
+            Ok(SyscallReturn::Return(size as _))
+        }
+    }
+}
+
+/// Duplicates `fd` to the lowest available fd equal to or greater than `min_fd`.
+fn do_dup_from(fd: FileDesc, min_fd: u64, flags: FdFlags) -> Result<SyscallReturn> {
+    let current = current!();
+    // Unlike `dup2`, an out-of-range fd is an invalid argument here.
+    if min_fd >= nofile_limit(&current) || min_fd > FileDesc::MAX as u64 {
+        return_errno_with_message!(Errno::EINVAL, "the new fd is out of range");
     }
+
+    let new_fd = current
+        .file_table()
+        .dup(fd, min_fd as FileDesc, flags, nofile_limit(&current))?;
+    Ok(SyscallReturn::Return(new_fd as _))
+}
+
+fn pipe_of(file: &dyn FileLike) -> Result<&Pipe> {
+    file.as_pipe()
+        .ok_or_else(|| Error::with_message(Errno::EBADF, "the file is not a pipe"))
 }
 
 #[repr(i32)]
@@ -93,4 +108,6 @@ enum FcntlCmd {
     F_GETFL = 3,
     F_SETFL = 4,
     F_DUPFD_CLOEXEC = 1030,
+    F_SETPIPE_SZ = 1031,
+    F_GETPIPE_SZ = 1032,
 }
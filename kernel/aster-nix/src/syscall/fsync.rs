@@ -11,7 +11,7 @@ pub fn sys_fsync(fd: FileDesc) -> Result<SyscallReturn> {
 
     let dentry = {
         let current = current!();
-        let file_table = current.file_table().lock();
+        let file_table = current.file_table();
         let file = file_table.get_file(fd)?;
         let inode_handle = file
             .downcast_ref::<InodeHandle>()
@@ -27,7 +27,7 @@ pub fn sys_fdatasync(fd: FileDesc) -> Result<SyscallReturn> {
 
     let dentry = {
         let current = current!();
-        let file_table = current.file_table().lock();
+        let file_table = current.file_table();
         let file = file_table.get_file(fd)?;
         let inode_handle = file
             .downcast_ref::<InodeHandle>()
@@ -9,6 +9,7 @@ use crate::{cpu::LinuxAbi, prelude::*};
 
 mod accept;
 mod access;
+mod aio;
 mod alarm;
 mod arch;
 mod arch_prctl;
@@ -23,6 +24,7 @@ mod chroot;
 mod clock_gettime;
 mod clone;
 mod close;
+mod close_range;
 mod connect;
 mod constants;
 mod dup;
@@ -75,6 +77,7 @@ mod prctl;
 mod pread64;
 mod preadv;
 mod prlimit64;
+mod process_vm;
 mod pwrite64;
 mod pwritev;
 mod read;
@@ -89,6 +92,7 @@ mod rt_sigprocmask;
 mod rt_sigreturn;
 mod rt_sigsuspend;
 mod sched_getaffinity;
+mod sched_getattr;
 mod sched_yield;
 mod select;
 mod sendfile;
@@ -114,6 +118,7 @@ mod shutdown;
 mod sigaltstack;
 mod socket;
 mod socketpair;
+mod splice;
 mod stat;
 mod statfs;
 mod symlink;
@@ -6,7 +6,7 @@ use crate::{
         exfat::{ExfatFS, ExfatMountOptions},
         ext2::Ext2,
         fs_resolver::{FsPath, AT_FDCWD},
-        path::Dentry,
+        path::{Dentry, PerMountFlags},
         utils::{FileSystem, InodeType},
     },
     prelude::*,
@@ -25,7 +25,13 @@ pub fn sys_mount(
     flags: u64,
     data: Vaddr,
 ) -> Result<SyscallReturn> {
-    let devname = read_cstring_from_user(devname_addr, MAX_FILENAME_LEN)?;
+    // The device name is not used by some operations, such as remounting, in which
+    // case it can be NULL.
+    let devname = if devname_addr == 0 {
+        CString::default()
+    } else {
+        read_cstring_from_user(devname_addr, MAX_FILENAME_LEN)?
+    };
     let dirname = read_cstring_from_user(dirname_addr, MAX_FILENAME_LEN)?;
     let mount_flags = MountFlags::from_bits_truncate(flags as u32);
     debug!(
@@ -44,7 +50,7 @@ pub fn sys_mount(
     };
 
     if mount_flags.contains(MountFlags::MS_REMOUNT) && mount_flags.contains(MountFlags::MS_BIND) {
-        do_reconfigure_mnt()?;
+        do_reconfigure_mnt(dst_dentry, mount_flags.into())?;
     } else if mount_flags.contains(MountFlags::MS_REMOUNT) {
         do_remount()?;
     } else if mount_flags.contains(MountFlags::MS_BIND) {
@@ -62,14 +68,22 @@ pub fn sys_mount(
     } else if mount_flags.contains(MountFlags::MS_MOVE) {
         do_move_mount_old(devname, dst_dentry)?;
     } else {
-        do_new_mount(devname, fstype_addr, dst_dentry)?;
+        do_new_mount(devname, fstype_addr, dst_dentry, mount_flags.into())?;
     }
 
     Ok(SyscallReturn::Return(0))
 }
 
-fn do_reconfigure_mnt() -> Result<()> {
-    return_errno_with_message!(Errno::EINVAL, "do_reconfigure_mnt is not supported");
+/// Changes the flags of the mount whose root is `target_dentry`.
+///
+/// Such as use user command `mount -o remount,bind,ro dst`.
+fn do_reconfigure_mnt(target_dentry: Arc<Dentry>, flags: PerMountFlags) -> Result<()> {
+    if !target_dentry.is_root_of_mount() {
+        return_errno_with_message!(Errno::EINVAL, "the target is not the root of a mount");
+    }
+
+    target_dentry.mount_node().set_flags(flags);
+    Ok(())
 }
 
 fn do_remount() -> Result<()> {
@@ -128,7 +142,12 @@ fn do_move_mount_old(src_name: CString, dst_dentry: Arc<Dentry>) -> Result<()> {
 }
 
 /// Mount a new filesystem.
-fn do_new_mount(devname: CString, fs_type: Vaddr, target_dentry: Arc<Dentry>) -> Result<()> {
+fn do_new_mount(
+    devname: CString,
+    fs_type: Vaddr,
+    target_dentry: Arc<Dentry>,
+    flags: PerMountFlags,
+) -> Result<()> {
     if target_dentry.type_() != InodeType::Dir {
         return_errno_with_message!(Errno::ENOTDIR, "mountpoint must be directory");
     };
@@ -138,7 +157,7 @@ fn do_new_mount(devname: CString, fs_type: Vaddr, target_dentry: Arc<Dentry>) ->
         return_errno_with_message!(Errno::EINVAL, "fs_type is empty");
     }
     let fs = get_fs(fs_type, devname)?;
-    target_dentry.mount(fs)?;
+    target_dentry.mount(fs)?.set_flags(flags);
     Ok(())
 }
 
@@ -189,3 +208,14 @@ bitflags! {
         const MS_KERNMOUNT     =   1 << 22;      // This is a kern_mount call.
     }
 }
+
+impl From<MountFlags> for PerMountFlags {
+    fn from(flags: MountFlags) -> Self {
+        let mut per_mount_flags = PerMountFlags::empty();
+        per_mount_flags.set(Self::RDONLY, flags.contains(MountFlags::MS_RDONLY));
+        per_mount_flags.set(Self::NOSUID, flags.contains(MountFlags::MS_NOSUID));
+        per_mount_flags.set(Self::NODEV, flags.contains(MountFlags::MS_NODEV));
+        per_mount_flags.set(Self::NOEXEC, flags.contains(MountFlags::MS_NOEXEC));
+        per_mount_flags
+    }
+}
@@ -6,11 +6,11 @@ use crate::{
         file_handle::FileLike,
         file_table::{FdFlags, FileDesc},
         fs_resolver::{FsPath, AT_FDCWD},
-        utils::{AccessMode, CreationFlags},
+        utils::{AccessMode, CreationFlags, StatusFlags},
     },
     prelude::*,
     syscall::constants::MAX_FILENAME_LEN,
-    util::read_cstring_from_user,
+    util::{read_cstring_from_user, read_extensible_struct_from_user, ExtensibleStruct},
 };
 
 pub fn sys_openat(
@@ -25,6 +25,27 @@ pub fn sys_openat(
         dirfd, path, flags, mode
     );
 
+    do_openat(dirfd, path, flags, mode)
+}
+
+pub fn sys_openat2(
+    dirfd: FileDesc,
+    path_addr: Vaddr,
+    open_how_addr: Vaddr,
+    size: usize,
+) -> Result<SyscallReturn> {
+    let path = read_cstring_from_user(path_addr, MAX_FILENAME_LEN)?;
+    let open_how: OpenHow = read_extensible_struct_from_user(open_how_addr, size)?;
+    debug!(
+        "dirfd = {}, path = {:?}, open_how = {:?}, size = {}",
+        dirfd, path, open_how, size
+    );
+
+    let (flags, mode) = open_how.check()?;
+    do_openat(dirfd, path, flags, mode)
+}
+
+fn do_openat(dirfd: FileDesc, path: CString, flags: u32, mode: u16) -> Result<SyscallReturn> {
     let current = current!();
     let file_handle = {
         let path = path.to_string_lossy();
@@ -33,7 +54,7 @@ pub fn sys_openat(
         let inode_handle = current.fs().read().open(&fs_path, flags, mask_mode)?;
         Arc::new(inode_handle)
     };
-    let mut file_table = current.file_table().lock();
+    let file_table = current.file_table();
     let fd = {
         let fd_flags =
             if CreationFlags::from_bits_truncate(flags).contains(CreationFlags::O_CLOEXEC) {
@@ -41,7 +62,7 @@ pub fn sys_openat(
             } else {
                 FdFlags::empty()
             };
-        file_table.insert(file_handle, fd_flags)
+        file_table.insert(file_handle, fd_flags)?
     };
     Ok(SyscallReturn::Return(fd as _))
 }
@@ -56,6 +77,59 @@ pub fn sys_creat(path_addr: Vaddr, mode: u16) -> Result<SyscallReturn> {
     self::sys_openat(AT_FDCWD, path_addr, flags, mode)
 }
 
+/// The arguments of `openat2`, i.e., `struct open_how` in Linux.
+#[derive(Debug, Clone, Copy, Pod)]
+#[repr(C)]
+struct OpenHow {
+    flags: u64,
+    mode: u64,
+    resolve: u64,
+}
+
+impl ExtensibleStruct for OpenHow {
+    /// The size of `struct open_how` in Linux 5.6.
+    const MIN_SIZE: usize = 24;
+}
+
+impl OpenHow {
+    /// Checks the arguments and returns the flags and the mode for `openat`.
+    ///
+    /// Unlike `openat`, which ignores the unknown flags and the unused mode,
+    /// `openat2` rejects them.
+    fn check(&self) -> Result<(u32, u16)> {
+        /// The flag that is implied on 64-bit platforms.
+        const O_LARGEFILE: u64 = 1 << 15;
+        /// The mask of the access mode.
+        const O_ACCMODE: u64 = 0b11;
+        /// The mask of the permission bits and the set-ID and sticky bits.
+        const S_IALLUGO: u64 = 0o7777;
+
+        let valid_flags = CreationFlags::all().bits() as u64
+            | StatusFlags::all().bits() as u64
+            | O_LARGEFILE
+            | O_ACCMODE;
+        if self.flags & !valid_flags != 0 {
+            return_errno_with_message!(Errno::EINVAL, "unknown flags");
+        }
+
+        let creation_flags = CreationFlags::from_bits_truncate(self.flags as u32);
+        if creation_flags.intersects(CreationFlags::O_CREAT | CreationFlags::_O_TMPFILE) {
+            if self.mode & !S_IALLUGO != 0 {
+                return_errno_with_message!(Errno::EINVAL, "invalid mode");
+            }
+        } else if self.mode != 0 {
+            return_errno_with_message!(Errno::EINVAL, "the mode is not used");
+        }
+
+        // FIXME: Support the `RESOLVE_*` flags, which restrict the path resolution.
+        if self.resolve != 0 {
+            return_errno_with_message!(Errno::EINVAL, "the resolve flags are not supported");
+        }
+
+        Ok((self.flags as u32, self.mode as u16))
+    }
+}
+
 /// File for output busybox ash log.
 #[allow(dead_code)]
 struct BusyBoxTraceFile;
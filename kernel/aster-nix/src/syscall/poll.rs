@@ -65,10 +65,7 @@ pub fn do_poll(poll_fds: &[PollFd], timeout: Option<Duration>) -> Result<usize>
 
             // Poll the file
             let current = current!();
-            let file = {
-                let file_table = current.file_table().lock();
-                file_table.get_file(fd)?.clone()
-            };
+            let file = current.file_table().get_file(fd)?;
             let need_poller = if num_revents == 0 {
                 Some(&poller)
             } else {
@@ -59,8 +59,8 @@ fn do_sys_preadv(
 
     let file = {
         let current = current!();
-        let filetable = current.file_table().lock();
-        filetable.get_file(fd)?.clone()
+        let filetable = current.file_table();
+        filetable.get_file(fd)?
     };
 
     if io_vec_count == 0 {
@@ -126,8 +126,8 @@ fn do_sys_readv(fd: FileDesc, io_vec_ptr: Vaddr, io_vec_count: usize) -> Result<
 
     let file = {
         let current = current!();
-        let filetable = current.file_table().lock();
-        filetable.get_file(fd)?.clone()
+        let filetable = current.file_table();
+        filetable.get_file(fd)?
     };
 
     if io_vec_count == 0 {
@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::mm::VmSpace;
+
+use super::SyscallReturn;
+use crate::{
+    prelude::*,
+    process::{
+        credentials, credentials::capabilities::CapSet, posix_thread::PosixThreadExt,
+        process_table, Pid, Process,
+    },
+    util::{copy_iovs_from_user, read_bytes_from_user, write_bytes_to_user, IoVec},
+};
+
+pub fn sys_process_vm_readv(
+    pid: Pid,
+    local_iov_ptr: Vaddr,
+    local_iov_count: usize,
+    remote_iov_ptr: Vaddr,
+    remote_iov_count: usize,
+    flags: u64,
+) -> Result<SyscallReturn> {
+    let res = do_process_vm_rw(
+        pid,
+        local_iov_ptr,
+        local_iov_count,
+        remote_iov_ptr,
+        remote_iov_count,
+        flags,
+        Direction::Read,
+    )?;
+    Ok(SyscallReturn::Return(res as _))
+}
+
+pub fn sys_process_vm_writev(
+    pid: Pid,
+    local_iov_ptr: Vaddr,
+    local_iov_count: usize,
+    remote_iov_ptr: Vaddr,
+    remote_iov_count: usize,
+    flags: u64,
+) -> Result<SyscallReturn> {
+    let res = do_process_vm_rw(
+        pid,
+        local_iov_ptr,
+        local_iov_count,
+        remote_iov_ptr,
+        remote_iov_count,
+        flags,
+        Direction::Write,
+    )?;
+    Ok(SyscallReturn::Return(res as _))
+}
+
+/// The maximum number of IO vectors.
+const IOV_MAX: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    /// Reads from the remote process.
+    Read,
+    /// Writes to the remote process.
+    Write,
+}
+
+fn do_process_vm_rw(
+    pid: Pid,
+    local_iov_ptr: Vaddr,
+    local_iov_count: usize,
+    remote_iov_ptr: Vaddr,
+    remote_iov_count: usize,
+    flags: u64,
+    direction: Direction,
+) -> Result<usize> {
+    debug!(
+        "pid = {}, local_iov_ptr = 0x{:x}, local_iov_count = {}, remote_iov_ptr = 0x{:x}, \
+        remote_iov_count = {}, flags = {}, direction = {:?}",
+        pid, local_iov_ptr, local_iov_count, remote_iov_ptr, remote_iov_count, flags, direction
+    );
+
+    if flags != 0 {
+        return_errno_with_message!(Errno::EINVAL, "flags must be zero");
+    }
+    if local_iov_count > IOV_MAX || remote_iov_count > IOV_MAX {
+        return_errno_with_message!(Errno::EINVAL, "too many IO vectors");
+    }
+
+    let local_iovs = copy_iovs_from_user(local_iov_ptr, local_iov_count)?;
+    let remote_iovs = copy_iovs_from_user(remote_iov_ptr, remote_iov_count)?;
+
+    let target = process_table::get_process(pid)
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the target process does not exist"))?;
+    check_access_permission(&target)?;
+    let vm_space = target.root_vmar().vm_space();
+
+    let mut total_len = 0;
+    let mut buffer = vec![0u8; PAGE_SIZE];
+    let mut local_iter = IoVecIter::new(&local_iovs);
+    let mut remote_iter = IoVecIter::new(&remote_iovs);
+
+    while let (Some(local), Some(remote)) = (local_iter.peek(), remote_iter.peek()) {
+        let len = local.len().min(remote.len()).min(buffer.len());
+        let res = copy_chunk(
+            vm_space,
+            local.base(),
+            remote.base(),
+            &mut buffer[..len],
+            direction,
+        );
+
+        // Like Linux, a partial transfer is reported as a success.
+        let copied_len = match res {
+            Ok(copied_len) => copied_len,
+            Err(_) if total_len > 0 => break,
+            Err(err) => return Err(err),
+        };
+        total_len += copied_len;
+        if copied_len < len {
+            break;
+        }
+        local_iter.advance(copied_len);
+        remote_iter.advance(copied_len);
+    }
+
+    Ok(total_len)
+}
+
+/// Copies a chunk between the local and the remote process via `buffer`.
+///
+/// Returns the number of bytes copied, which may be less than the length of
+/// `buffer` if the remote memory is only partially accessible.
+fn copy_chunk(
+    vm_space: &VmSpace,
+    local_addr: Vaddr,
+    remote_addr: Vaddr,
+    buffer: &mut [u8],
+    direction: Direction,
+) -> Result<usize> {
+    match direction {
+        Direction::Read => {
+            let read_len = vm_space
+                .read_remote(remote_addr, &mut VmWriter::from(&mut *buffer))
+                .map_err(|_| Error::with_message(Errno::EFAULT, "cannot read the remote memory"))?;
+            write_bytes_to_user(local_addr, &mut VmReader::from(&buffer[..read_len]))?;
+            Ok(read_len)
+        }
+        Direction::Write => {
+            read_bytes_from_user(local_addr, &mut VmWriter::from(&mut *buffer))?;
+            vm_space
+                .write_remote(remote_addr, &mut VmReader::from(&*buffer))
+                .map_err(|_| Error::with_message(Errno::EFAULT, "cannot write the remote memory"))
+        }
+    }
+}
+
+/// Checks whether the current process may access the memory of `target`.
+///
+/// This follows the `PTRACE_MODE_ATTACH_REALCREDS` check of Linux.
+fn check_access_permission(target: &Arc<Process>) -> Result<()> {
+    if Arc::ptr_eq(target, &current!()) {
+        return Ok(());
+    }
+
+    let credentials = credentials();
+    if credentials.effective_capset().contains(CapSet::SYS_PTRACE) {
+        return Ok(());
+    }
+
+    let Some(main_thread) = target.main_thread() else {
+        return_errno_with_message!(Errno::ESRCH, "the target process has exited");
+    };
+    let target_credentials = main_thread.as_posix_thread().unwrap().credentials();
+
+    let (uid, gid) = (credentials.ruid(), credentials.rgid());
+    let is_same_user = uid == target_credentials.ruid()
+        && uid == target_credentials.euid()
+        && uid == target_credentials.suid();
+    let is_same_group = gid == target_credentials.rgid()
+        && gid == target_credentials.egid()
+        && gid == target_credentials.sgid();
+    if !is_same_user || !is_same_group {
+        return_errno_with_message!(Errno::EPERM, "the target process cannot be accessed");
+    }
+
+    Ok(())
+}
+
+/// An iterator over the bytes of a list of IO vectors, chunk by chunk.
+struct IoVecIter<'a> {
+    iovs: &'a [IoVec],
+    /// The offset in the first IO vector of `iovs`.
+    offset: usize,
+}
+
+impl<'a> IoVecIter<'a> {
+    fn new(iovs: &'a [IoVec]) -> Self {
+        let mut iter = Self { iovs, offset: 0 };
+        iter.skip_exhausted();
+        iter
+    }
+
+    /// Returns the remaining part of the current IO vector.
+    fn peek(&self) -> Option<IoVec> {
+        let iov = self.iovs.first()?;
+        Some(IoVec::new(
+            iov.base() + self.offset,
+            iov.len() - self.offset,
+        ))
+    }
+
+    /// Advances by `len` bytes, which must be within the current IO vector.
+    fn advance(&mut self, len: usize) {
+        self.offset += len;
+        self.skip_exhausted();
+    }
+
+    fn skip_exhausted(&mut self) {
+        while let Some(iov) = self.iovs.first() {
+            if self.offset < iov.len() {
+                break;
+            }
+            self.iovs = &self.iovs[1..];
+            self.offset = 0;
+        }
+    }
+}
@@ -18,8 +18,8 @@ pub fn sys_pwrite64(
     }
     let file = {
         let current = current!();
-        let filetable = current.file_table().lock();
-        filetable.get_file(fd)?.clone()
+        let filetable = current.file_table();
+        filetable.get_file(fd)?
     };
     // TODO: Check (f.file->f_mode & FMODE_PWRITE); We don't have f_mode in our FileLike trait
     if user_buf_len == 0 {
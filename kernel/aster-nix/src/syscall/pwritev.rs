@@ -54,8 +54,8 @@ fn do_sys_pwritev(
     }
     let file = {
         let current = current!();
-        let filetable = current.file_table().lock();
-        filetable.get_file(fd)?.clone()
+        let filetable = current.file_table();
+        filetable.get_file(fd)?
     };
     // TODO: Check (f.file->f_mode & FMODE_PREAD); We don't have f_mode in our FileLike trait
     if io_vec_count == 0 {
@@ -110,8 +110,8 @@ fn do_sys_writev(fd: FileDesc, io_vec_ptr: Vaddr, io_vec_count: usize) -> Result
     );
     let file = {
         let current = current!();
-        let filetable = current.file_table().lock();
-        filetable.get_file(fd)?.clone()
+        let filetable = current.file_table();
+        filetable.get_file(fd)?
     };
     let mut total_len = 0;
 
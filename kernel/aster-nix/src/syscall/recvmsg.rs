@@ -1,13 +1,16 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use core::time::Duration;
+
 use super::SyscallReturn;
 use crate::{
     fs::file_table::FileDesc,
-    net::socket::SendRecvFlags,
+    net::socket::{SendRecvFlags, Socket},
     prelude::*,
+    time::{clocks::MonotonicClock, timespec_t},
     util::{
-        net::{get_socket_from_fd, CUserMsgHdr},
-        read_val_from_user,
+        net::{get_socket_from_fd, CUserMmsgHdr, CUserMsgHdr},
+        read_val_from_user, write_val_to_user,
     },
 };
 
@@ -20,8 +23,86 @@ pub fn sys_recvmsg(sockfd: FileDesc, user_msghdr_ptr: Vaddr, flags: i32) -> Resu
         sockfd, c_user_msghdr, flags
     );
 
+    let socket = get_socket_from_fd(sockfd)?;
+    let total_bytes = recv_one(&socket, user_msghdr_ptr, &c_user_msghdr, flags)?;
+
+    Ok(SyscallReturn::Return(total_bytes as _))
+}
+
+pub fn sys_recvmmsg(
+    sockfd: FileDesc,
+    user_mmsghdr_ptr: Vaddr,
+    vlen: u32,
+    flags: i32,
+    timeout_ptr: Vaddr,
+) -> Result<SyscallReturn> {
+    let mut flags = SendRecvFlags::from_bits_truncate(flags);
+
+    debug!(
+        "sockfd = {}, user_mmsghdr_ptr = 0x{:x}, vlen = {}, flags = {:?}, timeout_ptr = 0x{:x}",
+        sockfd, user_mmsghdr_ptr, vlen, flags, timeout_ptr
+    );
+
+    let deadline = if timeout_ptr != 0 {
+        let timeout = read_val_from_user::<timespec_t>(timeout_ptr)?;
+        if timeout.sec < 0 || !(0..1_000_000_000).contains(&timeout.nsec) {
+            return_errno_with_message!(Errno::EINVAL, "invalid timeout");
+        }
+        Some(MonotonicClock::get().read_time() + Duration::from(timeout))
+    } else {
+        None
+    };
+
+    let socket = get_socket_from_fd(sockfd)?;
+
+    // `MSG_WAITFORONE` is handled here and is not understood by the sockets.
+    let wait_for_one = flags.contains(SendRecvFlags::MSG_WAITFORONE);
+    flags.remove(SendRecvFlags::MSG_WAITFORONE);
+
+    let vlen = (vlen as usize).min(CUserMmsgHdr::MAX_VLEN);
+    let mut nr_received = 0;
+    while nr_received < vlen {
+        let user_msghdr_ptr = CUserMmsgHdr::addr_of(user_mmsghdr_ptr, nr_received);
+        let res = read_val_from_user::<CUserMsgHdr>(user_msghdr_ptr)
+            .and_then(|c_user_msghdr| recv_one(&socket, user_msghdr_ptr, &c_user_msghdr, flags))
+            .and_then(|total_bytes| {
+                CUserMmsgHdr::write_msg_len_to_user(user_msghdr_ptr, total_bytes)
+            });
+        if let Err(err) = res {
+            // The error is reported only if no message is received.
+            if nr_received == 0 {
+                return Err(err);
+            }
+            break;
+        }
+        nr_received += 1;
+
+        // After the first message is received, the remaining messages are received
+        // without blocking, and the first `EAGAIN` stops the loop.
+        if wait_for_one {
+            flags.insert(SendRecvFlags::MSG_DONTWAIT);
+        }
+        // As in Linux, the timeout is only checked after a message is received.
+        if deadline.is_some_and(|deadline| MonotonicClock::get().read_time() >= deadline) {
+            break;
+        }
+    }
+
+    if let Some(deadline) = deadline {
+        let remaining = deadline.saturating_sub(MonotonicClock::get().read_time());
+        write_val_to_user(timeout_ptr, &timespec_t::from(remaining))?;
+    }
+
+    Ok(SyscallReturn::Return(nr_received as _))
+}
+
+fn recv_one(
+    socket: &Arc<dyn Socket>,
+    user_msghdr_ptr: Vaddr,
+    c_user_msghdr: &CUserMsgHdr,
+    flags: SendRecvFlags,
+) -> Result<usize> {
     let (total_bytes, message_header) = {
-        let socket = get_socket_from_fd(sockfd)?;
         let io_vecs = c_user_msghdr.copy_iovs_from_user()?;
         socket.recvmsg(&io_vecs, flags)?
     };
@@ -30,9 +111,11 @@ pub fn sys_recvmsg(sockfd: FileDesc, user_msghdr_ptr: Vaddr, flags: i32) -> Resu
         c_user_msghdr.write_socket_addr_to_user(addr)?;
     }
 
-    if c_user_msghdr.msg_control != 0 {
-        warn!("receiving control message is not supported");
-    }
+    let (msg_controllen, msg_flags) = match message_header.control_message() {
+        Some(control_message) => c_user_msghdr.write_control_message_to_user(control_message)?,
+        None => (0, SendRecvFlags::empty()),
+    };
+    CUserMsgHdr::write_recv_result_to_user(user_msghdr_ptr, msg_controllen, msg_flags)?;
 
-    Ok(SyscallReturn::Return(total_bytes as _))
+    Ok(total_bytes)
 }
@@ -5,26 +5,35 @@ use crate::{
     fs::{
         file_table::FileDesc,
         fs_resolver::{FsPath, AT_FDCWD},
-        utils::InodeType,
+        utils::{InodeType, RenameFlags},
     },
     prelude::*,
     syscall::constants::MAX_FILENAME_LEN,
     util::read_cstring_from_user,
 };
 
-pub fn sys_renameat(
+pub fn sys_renameat2(
     old_dirfd: FileDesc,
     old_path_addr: Vaddr,
     new_dirfd: FileDesc,
     new_path_addr: Vaddr,
+    flags: u32,
 ) -> Result<SyscallReturn> {
     let old_path = read_cstring_from_user(old_path_addr, MAX_FILENAME_LEN)?;
     let new_path = read_cstring_from_user(new_path_addr, MAX_FILENAME_LEN)?;
+    let flags = RenameFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid flags"))?;
     debug!(
-        "old_dirfd = {}, old_path = {:?}, new_dirfd = {}, new_path = {:?}",
-        old_dirfd, old_path, new_dirfd, new_path
+        "old_dirfd = {}, old_path = {:?}, new_dirfd = {}, new_path = {:?}, flags = {:?}",
+        old_dirfd, old_path, new_dirfd, new_path, flags
     );
 
+    if flags.contains(RenameFlags::EXCHANGE)
+        && flags.intersects(RenameFlags::NOREPLACE | RenameFlags::WHITEOUT)
+    {
+        return_errno_with_message!(Errno::EINVAL, "RENAME_EXCHANGE conflicts with other flags");
+    }
+
     let current = current!();
     let fs = current.fs().read();
 
@@ -50,25 +59,57 @@ pub fn sys_renameat(
         fs.lookup_dir_and_base_name(&new_fs_path)?
     };
 
+    if flags.contains(RenameFlags::EXCHANGE) {
+        let new_dentry = new_dir_dentry.lookup(&new_name)?;
+        if new_dentry.type_() != InodeType::Dir && new_path.to_bytes().ends_with(b"/") {
+            return_errno_with_message!(Errno::ENOTDIR, "newpath is not dir");
+        }
+
+        // Neither of the two can be an ancestor of the other
+        let old_abs_path = old_dentry.abs_path() + "/";
+        let new_abs_path = new_dentry.abs_path() + "/";
+        if old_abs_path == new_abs_path {
+            return Ok(SyscallReturn::Return(0));
+        }
+        if new_abs_path.starts_with(&old_abs_path) || old_abs_path.starts_with(&new_abs_path) {
+            return_errno_with_message!(Errno::EINVAL, "one path contains the other");
+        }
+
+        old_dir_dentry.rename(&old_name, &new_dir_dentry, &new_name, flags)?;
+        return Ok(SyscallReturn::Return(0));
+    }
+
     // Check abs_path
     let old_abs_path = old_dentry.abs_path();
     let new_abs_path = new_dir_dentry.abs_path() + "/" + &new_name;
     if new_abs_path.starts_with(&old_abs_path) {
-        if new_abs_path.len() == old_abs_path.len() {
-            return Ok(SyscallReturn::Return(0));
-        } else {
+        if new_abs_path.len() != old_abs_path.len() {
             return_errno_with_message!(
                 Errno::EINVAL,
                 "newpath contains a path prefix of the oldpath"
             );
         }
+        // Renaming a file to itself does nothing, unless the file system must report that
+        // the new path exists.
+        if !flags.contains(RenameFlags::NOREPLACE) {
+            return Ok(SyscallReturn::Return(0));
+        }
     }
 
-    old_dir_dentry.rename(&old_name, &new_dir_dentry, &new_name)?;
+    old_dir_dentry.rename(&old_name, &new_dir_dentry, &new_name, flags)?;
 
     Ok(SyscallReturn::Return(0))
 }
 
+pub fn sys_renameat(
+    old_dirfd: FileDesc,
+    old_path_addr: Vaddr,
+    new_dirfd: FileDesc,
+    new_path_addr: Vaddr,
+) -> Result<SyscallReturn> {
+    self::sys_renameat2(old_dirfd, old_path_addr, new_dirfd, new_path_addr, 0)
+}
+
 pub fn sys_rename(old_path_addr: Vaddr, new_path_addr: Vaddr) -> Result<SyscallReturn> {
     self::sys_renameat(AT_FDCWD, old_path_addr, AT_FDCWD, new_path_addr)
 }
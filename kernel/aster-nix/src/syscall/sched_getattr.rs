@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::Ordering;
+
+use super::SyscallReturn;
+use crate::{
+    prelude::*,
+    process::{process_table, Pid},
+    util::{write_extensible_struct_to_user, ExtensibleStruct},
+};
+
+pub fn sys_sched_getattr(
+    pid: i32,
+    sched_attr_addr: Vaddr,
+    size: u32,
+    flags: u32,
+) -> Result<SyscallReturn> {
+    debug!(
+        "pid = {}, sched_attr_addr = 0x{:x}, size = {}, flags = {}",
+        pid, sched_attr_addr, size, flags
+    );
+
+    if sched_attr_addr == 0 || pid < 0 || flags != 0 {
+        return_errno_with_message!(Errno::EINVAL, "invalid arguments");
+    }
+    // Unlike other syscalls with extensible structs, a too large size is also `EINVAL`.
+    let size = size as usize;
+    if size > PAGE_SIZE {
+        return_errno_with_message!(Errno::EINVAL, "the struct size is too large");
+    }
+
+    let process = if pid == 0 {
+        current!()
+    } else {
+        process_table::get_process(pid as Pid)
+            .ok_or_else(|| Error::with_message(Errno::ESRCH, "the process does not exist"))?
+    };
+
+    // Only the normal scheduling policy is supported, whose only attribute is the nice value.
+    let sched_attr = SchedAttr {
+        size: size.min(core::mem::size_of::<SchedAttr>()) as u32,
+        sched_policy: SCHED_NORMAL,
+        sched_nice: process.nice().load(Ordering::Relaxed).to_raw() as i32,
+        ..Default::default()
+    };
+    write_extensible_struct_to_user(sched_attr_addr, &sched_attr, size)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+const SCHED_NORMAL: u32 = 0;
+
+/// The scheduling attributes, i.e., `struct sched_attr` in Linux.
+#[derive(Debug, Clone, Copy, Pod, Default)]
+#[repr(C)]
+struct SchedAttr {
+    size: u32,
+    sched_policy: u32,
+    sched_flags: u64,
+    sched_nice: i32,
+    sched_priority: u32,
+    sched_runtime: u64,
+    sched_deadline: u64,
+    sched_period: u64,
+    sched_util_min: u32,
+    sched_util_max: u32,
+}
+
+impl ExtensibleStruct for SchedAttr {
+    /// The size of `struct sched_attr` in Linux 3.14, which has no `sched_util_min` and
+    /// `sched_util_max`.
+    const MIN_SIZE: usize = 48;
+}
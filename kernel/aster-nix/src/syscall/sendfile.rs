@@ -38,10 +38,10 @@ pub fn sys_sendfile(
 
     let (out_file, in_file) = {
         let current = current!();
-        let file_table = current.file_table().lock();
-        let out_file = file_table.get_file(out_fd)?.clone();
+        let file_table = current.file_table();
+        let out_file = file_table.get_file(out_fd)?;
         // FIXME: the in_file must support mmap-like operations (i.e., it cannot be a socket).
-        let in_file = file_table.get_file(in_fd)?.clone();
+        let in_file = file_table.get_file(in_fd)?;
         (out_file, in_file)
     };
 
@@ -3,10 +3,10 @@
 use super::SyscallReturn;
 use crate::{
     fs::file_table::FileDesc,
-    net::socket::{MessageHeader, SendRecvFlags},
+    net::socket::{MessageHeader, SendRecvFlags, Socket},
     prelude::*,
     util::{
-        net::{get_socket_from_fd, CUserMsgHdr},
+        net::{get_socket_from_fd, CUserMmsgHdr, CUserMsgHdr},
         read_val_from_user,
     },
 };
@@ -21,7 +21,53 @@ pub fn sys_sendmsg(sockfd: FileDesc, user_msghdr_ptr: Vaddr, flags: i32) -> Resu
     );
 
     let socket = get_socket_from_fd(sockfd)?;
+    let total_bytes = send_one(&socket, &c_user_msghdr, flags)?;
 
+    Ok(SyscallReturn::Return(total_bytes as _))
+}
+
+pub fn sys_sendmmsg(
+    sockfd: FileDesc,
+    user_mmsghdr_ptr: Vaddr,
+    vlen: u32,
+    flags: i32,
+) -> Result<SyscallReturn> {
+    let flags = SendRecvFlags::from_bits_truncate(flags);
+
+    debug!(
+        "sockfd = {}, user_mmsghdr_ptr = 0x{:x}, vlen = {}, flags = {:?}",
+        sockfd, user_mmsghdr_ptr, vlen, flags
+    );
+
+    let socket = get_socket_from_fd(sockfd)?;
+
+    let vlen = (vlen as usize).min(CUserMmsgHdr::MAX_VLEN);
+    let mut nr_sent = 0;
+    while nr_sent < vlen {
+        let user_msghdr_ptr = CUserMmsgHdr::addr_of(user_mmsghdr_ptr, nr_sent);
+        let res = read_val_from_user::<CUserMsgHdr>(user_msghdr_ptr)
+            .and_then(|c_user_msghdr| send_one(&socket, &c_user_msghdr, flags))
+            .and_then(|total_bytes| {
+                CUserMmsgHdr::write_msg_len_to_user(user_msghdr_ptr, total_bytes)
+            });
+        if let Err(err) = res {
+            // The error is reported only if no message is sent.
+            if nr_sent == 0 {
+                return Err(err);
+            }
+            break;
+        }
+        nr_sent += 1;
+    }
+
+    Ok(SyscallReturn::Return(nr_sent as _))
+}
+
+fn send_one(
+    socket: &Arc<dyn Socket>,
+    c_user_msghdr: &CUserMsgHdr,
+    flags: SendRecvFlags,
+) -> Result<usize> {
     let (io_vecs, message_header) = {
         let addr = c_user_msghdr.read_socket_addr_from_user()?;
         let io_vecs = c_user_msghdr.copy_iovs_from_user()?;
@@ -37,7 +83,5 @@ pub fn sys_sendmsg(sockfd: FileDesc, user_msghdr_ptr: Vaddr, flags: i32) -> Resu
         (io_vecs, MessageHeader::new(addr, control_message))
     };
 
-    let total_bytes = socket.sendmsg(&io_vecs, message_header, flags)?;
-
-    Ok(SyscallReturn::Return(total_bytes as _))
+    socket.sendmsg(&io_vecs, message_header, flags)
 }
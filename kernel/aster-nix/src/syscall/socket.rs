@@ -43,13 +43,13 @@ pub fn sys_socket(domain: i32, type_: i32, protocol: i32) -> Result<SyscallRetur
     };
     let fd = {
         let current = current!();
-        let mut file_table = current.file_table().lock();
+        let file_table = current.file_table();
         let fd_flags = if sock_flags.contains(SockFlags::SOCK_CLOEXEC) {
             FdFlags::CLOEXEC
         } else {
             FdFlags::empty()
         };
-        file_table.insert(file_like, fd_flags)
+        file_table.insert(file_like, fd_flags)?
     };
     Ok(SyscallReturn::Return(fd as _))
 }
@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::SyscallReturn;
+use crate::{
+    fs::{file_handle::FileLike, file_table::FileDesc, pipe::Pipe, utils::StatusFlags},
+    prelude::*,
+};
+
+pub fn sys_splice(
+    fd_in: FileDesc,
+    off_in: Vaddr,
+    fd_out: FileDesc,
+    off_out: Vaddr,
+    len: usize,
+    flags: u32,
+) -> Result<SyscallReturn> {
+    debug!(
+        "fd_in = {}, off_in = 0x{:x}, fd_out = {}, off_out = 0x{:x}, len = 0x{:x}, flags = 0x{:x}",
+        fd_in, off_in, fd_out, off_out, len, flags
+    );
+
+    if len == 0 {
+        return Ok(SyscallReturn::Return(0));
+    }
+    let flags = SpliceFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid splice flags"))?;
+
+    let (in_file, out_file) = get_files(fd_in, fd_out)?;
+    // FIXME: Support splicing between a pipe and a file that is not a pipe.
+    let (in_pipe, out_pipe) = pipes_of(in_file.as_ref(), out_file.as_ref())?;
+    if off_in != 0 || off_out != 0 {
+        return_errno_with_message!(Errno::ESPIPE, "pipes cannot be spliced at offsets");
+    }
+
+    let is_nonblocking = is_nonblocking(flags, in_file.as_ref(), out_file.as_ref());
+    let moved_len = in_pipe.splice_to(out_pipe, len, is_nonblocking)?;
+    Ok(SyscallReturn::Return(moved_len as _))
+}
+
+pub fn sys_tee(fd_in: FileDesc, fd_out: FileDesc, len: usize, flags: u32) -> Result<SyscallReturn> {
+    debug!(
+        "fd_in = {}, fd_out = {}, len = 0x{:x}, flags = 0x{:x}",
+        fd_in, fd_out, len, flags
+    );
+
+    let flags = SpliceFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid splice flags"))?;
+    if len == 0 {
+        return Ok(SyscallReturn::Return(0));
+    }
+
+    let (in_file, out_file) = get_files(fd_in, fd_out)?;
+    let (in_pipe, out_pipe) = pipes_of(in_file.as_ref(), out_file.as_ref())?;
+
+    let is_nonblocking = is_nonblocking(flags, in_file.as_ref(), out_file.as_ref());
+    let dup_len = in_pipe.tee_to(out_pipe, len, is_nonblocking)?;
+    Ok(SyscallReturn::Return(dup_len as _))
+}
+
+/// Gets the files to read from and to write to.
+fn get_files(fd_in: FileDesc, fd_out: FileDesc) -> Result<(Arc<dyn FileLike>, Arc<dyn FileLike>)> {
+    let current = current!();
+    let file_table = current.file_table();
+
+    let in_file = file_table.get_file(fd_in)?;
+    if !in_file.access_mode().is_readable() {
+        return_errno_with_message!(Errno::EBADF, "the file is not opened for reading");
+    }
+    let out_file = file_table.get_file(fd_out)?;
+    if !out_file.access_mode().is_writable() {
+        return_errno_with_message!(Errno::EBADF, "the file is not opened for writing");
+    }
+
+    Ok((in_file, out_file))
+}
+
+fn pipes_of<'a>(
+    in_file: &'a dyn FileLike,
+    out_file: &'a dyn FileLike,
+) -> Result<(&'a Pipe, &'a Pipe)> {
+    match (in_file.as_pipe(), out_file.as_pipe()) {
+        (Some(in_pipe), Some(out_pipe)) => Ok((in_pipe, out_pipe)),
+        _ => return_errno_with_message!(Errno::EINVAL, "only pipes are supported"),
+    }
+}
+
+fn is_nonblocking(flags: SpliceFlags, in_file: &dyn FileLike, out_file: &dyn FileLike) -> bool {
+    // As in Linux, the operation does not block if either file is non-blocking.
+    flags.contains(SpliceFlags::SPLICE_F_NONBLOCK)
+        || in_file.status_flags().contains(StatusFlags::O_NONBLOCK)
+        || out_file.status_flags().contains(StatusFlags::O_NONBLOCK)
+}
+
+bitflags! {
+    struct SpliceFlags: u32 {
+        const SPLICE_F_MOVE = 1;
+        const SPLICE_F_NONBLOCK = 2;
+        const SPLICE_F_MORE = 4;
+        const SPLICE_F_GIFT = 8;
+    }
+}
@@ -19,7 +19,7 @@ pub fn sys_fstat(fd: FileDesc, stat_buf_ptr: Vaddr) -> Result<SyscallReturn> {
     debug!("fd = {}, stat_buf_addr = 0x{:x}", fd, stat_buf_ptr);
 
     let current = current!();
-    let file_table = current.file_table().lock();
+    let file_table = current.file_table();
     let file = file_table.get_file(fd)?;
     let stat = Stat::from(file.metadata());
     write_val_to_user(stat_buf_ptr, &stat)?;
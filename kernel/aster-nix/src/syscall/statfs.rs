@@ -6,6 +6,7 @@ use crate::{
         file_table::FileDesc,
         fs_resolver::FsPath,
         inode_handle::InodeHandle,
+        path::Dentry,
         utils::{SuperBlock, PATH_MAX},
     },
     prelude::*,
@@ -22,7 +23,7 @@ pub fn sys_statfs(path_ptr: Vaddr, statfs_buf_ptr: Vaddr) -> Result<SyscallRetur
         let fs_path = FsPath::try_from(path.as_ref())?;
         current.fs().read().lookup(&fs_path)?
     };
-    let statfs = Statfs::from(dentry.fs().sb());
+    let statfs = Statfs::new(&dentry);
     write_val_to_user(statfs_buf_ptr, &statfs)?;
     Ok(SyscallReturn::Return(0))
 }
@@ -31,13 +32,13 @@ pub fn sys_fstatfs(fd: FileDesc, statfs_buf_ptr: Vaddr) -> Result<SyscallReturn>
     debug!("fd = {}, statfs_buf_addr = 0x{:x}", fd, statfs_buf_ptr);
 
     let current = current!();
-    let file_table = current.file_table().lock();
+    let file_table = current.file_table();
     let file = file_table.get_file(fd)?;
     let inode_handle = file
         .downcast_ref::<InodeHandle>()
         .ok_or(Error::with_message(Errno::EBADF, "not inode"))?;
     let dentry = inode_handle.dentry();
-    let statfs = Statfs::from(dentry.fs().sb());
+    let statfs = Statfs::new(dentry);
     write_val_to_user(statfs_buf_ptr, &statfs)?;
     Ok(SyscallReturn::Return(0))
 }
@@ -72,6 +73,18 @@ struct Statfs {
     f_spare: [u64; 4],
 }
 
+/// The flag in `f_flags` that tells `f_flags` is valid.
+const ST_VALID: u64 = 0x20;
+
+impl Statfs {
+    /// Returns the statistics of the filesystem and the flags of the mount where `dentry` is.
+    fn new(dentry: &Dentry) -> Self {
+        let mut statfs = Self::from(dentry.fs().sb());
+        statfs.f_flags |= ST_VALID | dentry.mount_node().flags().bits() as u64;
+        statfs
+    }
+}
+
 impl From<SuperBlock> for Statfs {
     fn from(sb: SuperBlock) -> Self {
         Self {
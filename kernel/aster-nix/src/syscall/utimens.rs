@@ -8,8 +8,10 @@ use crate::{
         file_table::FileDesc,
         fs_resolver::{FsPath, AT_FDCWD},
         path::Dentry,
+        utils::InodeMode,
     },
     prelude::*,
+    process::{credentials, credentials::capabilities::CapSet},
     time::{clocks::RealTimeCoarseClock, timespec_t, timeval_t},
     util::{read_cstring_from_user, read_val_from_user},
 };
@@ -19,6 +21,11 @@ use crate::{
 /// and times[1] represents the modification time.
 /// The `flags` argument is a bit mask that can include the following values:
 /// - `AT_SYMLINK_NOFOLLOW`: If set, the file is not dereferenced if it is a symbolic link.
+/// - `AT_EMPTY_PATH`: If set, the file referred to by `dirfd` is operated on if the pathname
+///   is an empty string.
+///
+/// A time whose `nsec` is `UTIME_NOW` is set to the current time, and a time whose
+/// `nsec` is `UTIME_OMIT` is left unchanged.
 pub fn sys_utimensat(
     dirfd: FileDesc,
     pathname_ptr: Vaddr,
@@ -31,9 +38,6 @@ pub fn sys_utimensat(
     );
     let times = if timespecs_ptr != 0 {
         let (autime, mutime) = read_time_from_user::<timespec_t>(timespecs_ptr)?;
-        if autime.is_utime_omit() && mutime.is_utime_omit() {
-            return Ok(SyscallReturn::Return(0));
-        }
         Some(TimeSpecPair {
             atime: autime,
             mtime: mutime,
@@ -109,12 +113,20 @@ struct Utimbuf {
 }
 
 fn vfs_utimes(dentry: &Arc<Dentry>, times: Option<TimeSpecPair>) -> Result<SyscallReturn> {
+    if let Some(times) = times.as_ref() {
+        if !times.atime.is_valid() || !times.mtime.is_valid() {
+            return_errno_with_message!(Errno::EINVAL, "invalid time")
+        }
+    }
+    // Unless both times are set to the current time, the ownership of the file is required.
+    let is_set_explicitly = times
+        .as_ref()
+        .is_some_and(|times| !(times.atime.is_utime_now() && times.mtime.is_utime_now()));
+    check_utimes_permission(dentry, is_set_explicitly)?;
+
+    let now = RealTimeCoarseClock::get().read_time();
     let (atime, mtime) = match times {
         Some(times) => {
-            if !times.atime.is_valid() || !times.mtime.is_valid() {
-                return_errno_with_message!(Errno::EINVAL, "invalid time")
-            }
-            let now = RealTimeCoarseClock::get().read_time();
             let atime = if times.atime.is_utime_omit() {
                 dentry.atime()
             } else if times.atime.is_utime_now() {
@@ -131,19 +143,44 @@ fn vfs_utimes(dentry: &Arc<Dentry>, times: Option<TimeSpecPair>) -> Result<Sysca
             };
             (atime, mtime)
         }
-        None => {
-            let now = RealTimeCoarseClock::get().read_time();
-            (now, now)
-        }
+        None => (now, now),
     };
 
     // Update times
     dentry.set_atime(atime);
     dentry.set_mtime(mtime);
+    dentry.set_ctime(now);
 
     Ok(SyscallReturn::Return(0))
 }
 
+/// Checks whether the current process can change the times of the file.
+///
+/// The owner of the file, or a process with `CAP_FOWNER`, can set any times. Other
+/// processes can only set both times to the current time, if they have write access.
+fn check_utimes_permission(dentry: &Dentry, is_set_explicitly: bool) -> Result<()> {
+    let credentials = credentials();
+    let capset = credentials.effective_capset();
+    if dentry.owner()? == credentials.fsuid() || capset.contains(CapSet::FOWNER) {
+        return Ok(());
+    }
+    if is_set_explicitly {
+        return_errno_with_message!(Errno::EPERM, "only the owner can set the times explicitly");
+    }
+
+    let mode = dentry.mode()?;
+    let group = dentry.group()?;
+    let is_writable = if credentials.fsgid() == group || credentials.groups().contains(&group) {
+        mode.contains(InodeMode::S_IWGRP)
+    } else {
+        mode.contains(InodeMode::S_IWOTH)
+    };
+    if !is_writable && !capset.contains(CapSet::DAC_OVERRIDE) {
+        return_errno_with_message!(Errno::EACCES, "the file is not writable");
+    }
+    Ok(())
+}
+
 // Common function to handle updating file times, supporting both fd and path based operations
 fn do_utimes(
     dirfd: FileDesc,
@@ -151,6 +188,15 @@ fn do_utimes(
     times: Option<TimeSpecPair>,
     flags: u32,
 ) -> Result<SyscallReturn> {
+    // If both times are omitted, there is nothing to do. As in Linux, neither the path nor
+    // the permissions are checked.
+    if times
+        .as_ref()
+        .is_some_and(|times| times.atime.is_utime_omit() && times.mtime.is_utime_omit())
+    {
+        return Ok(SyscallReturn::Return(0));
+    }
+
     let flags = UtimensFlags::from_bits(flags)
         .ok_or(Error::with_message(Errno::EINVAL, "invalid flags"))?;
 
@@ -158,6 +204,9 @@ fn do_utimes(
         String::new()
     } else {
         let cstring = read_cstring_from_user(pathname_ptr, MAX_FILENAME_LEN)?;
+        if cstring.is_empty() && !flags.contains(UtimensFlags::AT_EMPTY_PATH) {
+            return_errno_with_message!(Errno::ENOENT, "the pathname is empty");
+        }
         cstring.to_string_lossy().into_owned()
     };
     let current = current!();
@@ -230,5 +279,6 @@ const UTIME_OMIT: i64 = (1i64 << 30) - 2i64;
 bitflags::bitflags! {
     struct UtimensFlags: u32 {
         const AT_SYMLINK_NOFOLLOW = 0x100;
+        const AT_EMPTY_PATH = 0x1000;
     }
 }
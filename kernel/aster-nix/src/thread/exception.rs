@@ -2,11 +2,15 @@
 
 #![allow(unused_variables)]
 
+use core::ptr;
+
+use aster_rights::Full;
 use ostd::{cpu::*, mm::VmSpace};
 
 use crate::{
-    prelude::*, process::signal::signals::fault::FaultSignal,
-    vm::page_fault_handler::PageFaultHandler,
+    prelude::*,
+    process::{process_table, signal::signals::fault::FaultSignal, Process},
+    vm::{page_fault_handler::PageFaultHandler, vmar::Vmar},
 };
 
 /// We can't handle most exceptions, just send self a fault signal before return to user space.
@@ -23,6 +27,7 @@ pub fn handle_exception(context: &UserContext) {
                 generate_fault_signal(trap_info);
             }
         }
+        MACHINE_CHECK => handle_memory_error(root_vmar, trap_info),
         _ => {
             // We current do nothing about other exceptions
             generate_fault_signal(trap_info);
@@ -47,13 +52,8 @@ pub(crate) fn handle_page_fault(
     let write = trap_info.error_code & WRITE_ACCESS_MASK != 0;
     if not_present || write {
         // If page is not present or due to write access, we should ask the vmar try to commit this page
-        let current = current!();
-        let root_vmar = current.root_vmar();
-
-        debug_assert_eq!(
-            Arc::as_ptr(root_vmar.vm_space()),
-            vm_space as *const VmSpace
-        );
+        let process = process_of_vm_space(vm_space).ok_or(())?;
+        let root_vmar = process.root_vmar();
 
         if let Err(e) = root_vmar.handle_page_fault(page_fault_addr, not_present, write) {
             error!(
@@ -69,6 +69,43 @@ pub(crate) fn handle_page_fault(
     }
 }
 
+/// Handles the uncorrected memory error whose corrupted data is consumed by the current process.
+///
+/// The poisoned page is unmapped before the process is notified with `SIGBUS`, so
+/// the corrupted data will not be consumed again if the process handles the signal.
+fn handle_memory_error(root_vmar: &Vmar<Full>, trap_info: &CpuExceptionInfo) {
+    let addr = trap_info.memory_error_addr.and_then(|paddr| {
+        root_vmar.unmap_poisoned_page(paddr).unwrap_or_else(|err| {
+            warn!(
+                "failed to unmap the poisoned page at {:#x}: {:?}",
+                paddr, err
+            );
+            None
+        })
+    });
+
+    let signal = FaultSignal::new_memory_error(addr);
+    current!().enqueue_signal(signal);
+}
+
+/// Finds the process that owns the `VmSpace`.
+///
+/// This is usually the current process. But the page fault may also occur when
+/// the `VmSpace` of another process is accessed remotely, e.g., by `ptrace`.
+fn process_of_vm_space(vm_space: &VmSpace) -> Option<Arc<Process>> {
+    let owns_vm_space =
+        |process: &Arc<Process>| ptr::eq(Arc::as_ptr(process.root_vmar().vm_space()), vm_space);
+
+    let current = current!();
+    if owns_vm_space(&current) {
+        return Some(current);
+    }
+    process_table::process_table()
+        .iter()
+        .find(|process| owns_vm_space(process))
+        .cloned()
+}
+
 /// generate a fault signal for current process.
 fn generate_fault_signal(trap_info: &CpuExceptionInfo) {
     let current = current!();
@@ -39,7 +39,7 @@ impl KernelThreadExt for Thread {
             // ensure the thread is exit
             current_thread.exit();
         };
-        let tid = allocate_tid();
+        let tid = allocate_tid().unwrap();
         let thread = Arc::new_cyclic(|thread_ref| {
             let weal_thread = thread_ref.clone();
             let task = TaskOptions::new(thread_fn)
@@ -4,6 +4,7 @@
 
 use core::sync::atomic::{AtomicU32, Ordering};
 
+use id_alloc::AtomicBitmap;
 use ostd::task::Task;
 
 use self::status::{AtomicThreadStatus, ThreadStatus};
@@ -18,7 +19,22 @@ pub mod work_queue;
 
 pub type Tid = u32;
 
-static TID_ALLOCATOR: AtomicU32 = AtomicU32::new(0);
+/// The maximum number of TIDs, which is the default `pid_max` in Linux.
+const MAX_TID: usize = 32768;
+
+/// The TIDs that are skipped when the allocation wraps around.
+///
+/// As in Linux, the small TIDs are likely to be held by the daemons that
+/// run for a long time, so it is a waste of time to search them.
+const RESERVED_TIDS: usize = 300;
+
+lazy_static! {
+    /// The TIDs in use, each of which is released when its thread is dropped.
+    static ref TID_BITMAP: AtomicBitmap = AtomicBitmap::new(MAX_TID);
+}
+
+/// The TID where the search for the next free TID starts.
+static NEXT_TID: AtomicU32 = AtomicU32::new(0);
 
 /// A thread is a wrapper on top of task.
 pub struct Thread {
@@ -107,7 +123,22 @@ impl Thread {
     }
 }
 
-/// Allocates a new tid for the new thread
-pub fn allocate_tid() -> Tid {
-    TID_ALLOCATOR.fetch_add(1, Ordering::SeqCst)
+impl Drop for Thread {
+    fn drop(&mut self) {
+        TID_BITMAP.test_and_clear(self.tid as usize);
+    }
+}
+
+/// Allocates a new tid for the new thread.
+///
+/// As in Linux, the TIDs are allocated cyclically, so that a released TID
+/// is not reused until the larger TIDs run out.
+pub fn allocate_tid() -> Result<Tid> {
+    let next_tid = NEXT_TID.load(Ordering::Relaxed) as usize;
+    let tid = TID_BITMAP
+        .set_next_zero(next_tid)
+        .or_else(|| TID_BITMAP.set_next_zero(RESERVED_TIDS))
+        .ok_or_else(|| Error::with_message(Errno::EAGAIN, "no TIDs are available"))?;
+    NEXT_TID.store(tid as u32 + 1, Ordering::Relaxed);
+    Ok(tid as Tid)
 }
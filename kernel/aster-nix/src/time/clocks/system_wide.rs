@@ -4,7 +4,7 @@ use alloc::sync::Arc;
 use core::time::Duration;
 
 use aster_time::read_monotonic_time;
-use ostd::{arch::timer::Jiffies, cpu_local, sync::SpinLock, CpuLocal};
+use ostd::{arch::timer::Jiffies, cpu_local, sync::SeqLock, CpuLocal};
 use paste::paste;
 use spin::Once;
 
@@ -66,8 +66,11 @@ pub struct RealTimeCoarseClock {
 
 impl RealTimeCoarseClock {
     /// A reference to the current value of this clock.
-    fn current_ref() -> &'static Once<SpinLock<Duration>> {
-        static CURRENT: Once<SpinLock<Duration>> = Once::new();
+    ///
+    /// The value is read far more often than it is updated by the timer
+    /// interrupts, so it is protected by a sequence lock.
+    fn current_ref() -> &'static Once<SeqLock<Duration>> {
+        static CURRENT: Once<SeqLock<Duration>> = Once::new();
 
         &CURRENT
     }
@@ -153,7 +156,7 @@ impl Clock for MonotonicClock {
 
 impl Clock for RealTimeCoarseClock {
     fn read_time(&self) -> Duration {
-        *Self::current_ref().get().unwrap().lock_irq_disabled()
+        Self::current_ref().get().unwrap().read()
     }
 }
 
@@ -266,12 +269,12 @@ fn init_jiffies_clock_manager() {
 fn update_coarse_clock() {
     let real_time = RealTimeClock::get().read_time();
     let current = RealTimeCoarseClock::current_ref().get().unwrap();
-    *current.lock_irq_disabled() = real_time;
+    current.set(real_time);
 }
 
 fn init_coarse_clock() {
     let real_time = RealTimeClock::get().read_time();
-    RealTimeCoarseClock::current_ref().call_once(|| SpinLock::new(real_time));
+    RealTimeCoarseClock::current_ref().call_once(|| SeqLock::new(real_time));
     time::softirq::register_callback(update_coarse_clock);
 }
 
@@ -294,7 +297,7 @@ pub fn init_for_ktest() {
         TimerManager::new(Arc::new(clock))
     });
     CLOCK_REALTIME_COARSE_INSTANCE.call_once(|| Arc::new(RealTimeCoarseClock { _private: () }));
-    RealTimeCoarseClock::current_ref().call_once(|| SpinLock::new(Duration::from_secs(0)));
+    RealTimeCoarseClock::current_ref().call_once(|| SeqLock::new(Duration::from_secs(0)));
     JIFFIES_TIMER_MANAGER.call_once(|| {
         let clock = JiffiesClock { _private: () };
         TimerManager::new(Arc::new(clock))
@@ -98,6 +98,82 @@ pub fn write_val_to_user<T: Pod>(dest: Vaddr, val: &T) -> Result<()> {
     Ok(user_writer.write_val(val)?)
 }
 
+/// A `Pod` struct of the user ABI whose layout can be extended over time,
+/// e.g., `struct clone_args`.
+///
+/// New fields are only appended to the end of such a struct, and the user
+/// space passes the size of the struct it knows along with the struct.
+pub trait ExtensibleStruct: Pod {
+    /// The size of the first published version of the struct.
+    const MIN_SIZE: usize;
+}
+
+/// Reads an extensible struct of `size` bytes
+/// from the user space of the current process.
+///
+/// If the user struct is smaller, the missing fields are filled with zeros.
+/// If the user struct is larger, the unknown fields must be zeros.
+/// This works the same as `copy_struct_from_user` in Linux.
+pub fn read_extensible_struct_from_user<T: ExtensibleStruct>(src: Vaddr, size: usize) -> Result<T> {
+    if size < T::MIN_SIZE {
+        return_errno_with_message!(Errno::EINVAL, "the struct size is too small");
+    }
+    if size > PAGE_SIZE {
+        return_errno_with_message!(Errno::E2BIG, "the struct size is too large");
+    }
+
+    let mut val = T::new_zeroed();
+    let known_size = mem::size_of::<T>();
+    let copy_len = size.min(known_size);
+    read_bytes_from_user(
+        src,
+        &mut VmWriter::from(&mut val.as_bytes_mut()[..copy_len]),
+    )?;
+
+    if size > known_size {
+        let mut unknown_fields = vec![0u8; size - known_size];
+        read_bytes_from_user(
+            src + known_size,
+            &mut VmWriter::from(unknown_fields.as_mut_slice()),
+        )?;
+        if unknown_fields.iter().any(|byte| *byte != 0) {
+            return_errno_with_message!(Errno::E2BIG, "the unknown fields are not zeros");
+        }
+    }
+
+    Ok(val)
+}
+
+/// Writes an extensible struct to the user space of the current process,
+/// where the user struct is `size` bytes.
+///
+/// If the user struct is smaller, the fields unknown to the user are dropped.
+/// If the user struct is larger, the fields unknown to the kernel are filled with zeros.
+/// This works the same as `copy_struct_to_user` in Linux.
+pub fn write_extensible_struct_to_user<T: ExtensibleStruct>(
+    dest: Vaddr,
+    val: &T,
+    size: usize,
+) -> Result<()> {
+    if size < T::MIN_SIZE {
+        return_errno_with_message!(Errno::EINVAL, "the struct size is too small");
+    }
+    if size > PAGE_SIZE {
+        return_errno_with_message!(Errno::E2BIG, "the struct size is too large");
+    }
+
+    let known_size = mem::size_of::<T>();
+    let copy_len = size.min(known_size);
+    write_bytes_to_user(dest, &mut VmReader::from(&val.as_bytes()[..copy_len]))?;
+
+    if size > known_size {
+        let zeros = vec![0u8; size - known_size];
+        write_bytes_to_user(dest + known_size, &mut VmReader::from(zeros.as_slice()))?;
+    }
+
+    Ok(())
+}
+
 /// Read a C string from the user space of the current process.
 /// The length of the string should not exceed `max_len`,
 /// including the final `\0` byte.
@@ -9,12 +9,12 @@ pub use addr::{
     CSocketAddrFamily,
 };
 pub use options::{new_raw_socket_option, CSocketOptionLevel};
-pub use socket::{CUserMsgHdr, Protocol, SockFlags, SockType, SOCK_TYPE_MASK};
+pub use socket::{CUserMmsgHdr, CUserMsgHdr, Protocol, SockFlags, SockType, SOCK_TYPE_MASK};
 
 use crate::{fs::file_table::FileDesc, net::socket::Socket, prelude::*};
 
 pub fn get_socket_from_fd(sockfd: FileDesc) -> Result<Arc<dyn Socket>> {
     let current = current!();
-    let file_table = current.file_table().lock();
+    let file_table = current.file_table();
     file_table.get_socket(sockfd)
 }
@@ -6,7 +6,8 @@ use super::RawSocketOption;
 use crate::{
     impl_raw_sock_option_get_only, impl_raw_socket_option,
     net::socket::options::{
-        Error, KeepAlive, Linger, RecvBuf, ReuseAddr, ReusePort, SendBuf, SocketOption,
+        BusyPoll, Error, KeepAlive, Linger, RecvBuf, ReuseAddr, ReusePort, SendBuf, SocketOption,
+        Timestamping,
     },
     prelude::*,
     vm::vmar::Vmar,
@@ -37,6 +38,8 @@ enum CSocketOptionName {
     LINGER = 13,
     BSDCOMPAT = 14,
     REUSEPORT = 15,
+    TIMESTAMPING_OLD = 37,
+    BUSY_POLL = 46,
     RCVTIMEO_NEW = 66,
     SNDTIMEO_NEW = 67,
 }
@@ -51,6 +54,8 @@ pub fn new_socket_option(name: i32) -> Result<Box<dyn RawSocketOption>> {
         CSocketOptionName::REUSEPORT => Ok(Box::new(ReusePort::new())),
         CSocketOptionName::LINGER => Ok(Box::new(Linger::new())),
         CSocketOptionName::KEEPALIVE => Ok(Box::new(KeepAlive::new())),
+        CSocketOptionName::BUSY_POLL => Ok(Box::new(BusyPoll::new())),
+        CSocketOptionName::TIMESTAMPING_OLD => Ok(Box::new(Timestamping::new())),
         _ => todo!(),
     }
 }
@@ -62,3 +67,5 @@ impl_raw_sock_option_get_only!(Error);
 impl_raw_socket_option!(ReusePort);
 impl_raw_socket_option!(Linger);
 impl_raw_socket_option!(KeepAlive);
+impl_raw_socket_option!(BusyPoll);
+impl_raw_socket_option!(Timestamping);
@@ -1,10 +1,18 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use core::mem::{offset_of, size_of};
+
+use align_ext::AlignExt;
+
 use super::read_socket_addr_from_user;
 use crate::{
-    net::socket::SocketAddr,
+    net::socket::{ControlMessage, SendRecvFlags, SocketAddr},
     prelude::*,
-    util::{copy_iovs_from_user, net::write_socket_addr_with_max_len, IoVec},
+    time::timespec_t,
+    util::{
+        copy_iovs_from_user, net::write_socket_addr_with_max_len, write_bytes_to_user,
+        write_val_to_user, IoVec,
+    },
 };
 
 /// Standard well-defined IP protocols.
@@ -84,13 +92,13 @@ pub struct CUserMsgHdr {
     /// Scatter/Gather iov array
     pub msg_iov: Vaddr,
     /// The # of elements in msg_iov
-    pub msg_iovlen: u32,
+    pub msg_iovlen: usize,
     /// Ancillary data
     pub msg_control: Vaddr,
     /// Ancillary data buffer length
-    pub msg_controllen: u32,
+    pub msg_controllen: usize,
     /// Flags on received message
-    pub msg_flags: u32,
+    pub msg_flags: i32,
 }
 
 impl CUserMsgHdr {
@@ -113,6 +121,109 @@ impl CUserMsgHdr {
     }
 
     pub fn copy_iovs_from_user(&self) -> Result<Box<[IoVec]>> {
-        copy_iovs_from_user(self.msg_iov, self.msg_iovlen as usize)
+        copy_iovs_from_user(self.msg_iov, self.msg_iovlen)
+    }
+
+    /// Writes the control message to the ancillary data buffer.
+    ///
+    /// This method returns the number of bytes written to the buffer and the flags of the
+    /// received message, which contain `MSG_CTRUNC` if the buffer is too small to hold the
+    /// control message. A control message that does not fit is discarded as a whole.
+    pub fn write_control_message_to_user(
+        &self,
+        control_message: &ControlMessage,
+    ) -> Result<(usize, SendRecvFlags)> {
+        let (cmsg_level, cmsg_type, data) = match control_message {
+            ControlMessage::Timestamping(timestamp) => {
+                // The software timestamp is followed by two hardware timestamps, which are
+                // always zero since there are no hardware clocks.
+                let timestamps = [
+                    timespec_t::from(*timestamp),
+                    timespec_t::default(),
+                    timespec_t::default(),
+                ];
+                let data: Vec<u8> = timestamps
+                    .iter()
+                    .flat_map(|timestamp| timestamp.as_bytes())
+                    .copied()
+                    .collect();
+                (SOL_SOCKET, SCM_TIMESTAMPING, data)
+            }
+        };
+
+        let cmsg_len = size_of::<CControlMessageHeader>() + data.len();
+        if self.msg_control == 0 || self.msg_controllen < cmsg_len {
+            return Ok((0, SendRecvFlags::MSG_CTRUNC));
+        }
+
+        let cmsg_header = CControlMessageHeader {
+            cmsg_len,
+            cmsg_level,
+            cmsg_type,
+        };
+        write_val_to_user(self.msg_control, &cmsg_header)?;
+        write_bytes_to_user(
+            self.msg_control + size_of::<CControlMessageHeader>(),
+            &mut VmReader::from(data.as_slice()),
+        )?;
+
+        // The length is aligned as `CMSG_SPACE` does, but it must not exceed the buffer size.
+        let written_len = cmsg_len
+            .align_up(size_of::<usize>())
+            .min(self.msg_controllen);
+        Ok((written_len, SendRecvFlags::empty()))
+    }
+
+    /// Writes the length of the ancillary data and the flags of the received message to the
+    /// header at `addr`.
+    pub fn write_recv_result_to_user(
+        addr: Vaddr,
+        msg_controllen: usize,
+        msg_flags: SendRecvFlags,
+    ) -> Result<()> {
+        write_val_to_user(addr + offset_of!(Self, msg_controllen), &msg_controllen)?;
+        write_val_to_user(addr + offset_of!(Self, msg_flags), &msg_flags.bits())
+    }
+}
+
+/// The header of a control message, i.e., `struct cmsghdr`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+struct CControlMessageHeader {
+    /// The length of the control message, including the header
+    cmsg_len: usize,
+    /// The originating protocol
+    cmsg_level: i32,
+    /// The protocol-specific type
+    cmsg_type: i32,
+}
+
+/// The level of the socket-level control messages.
+const SOL_SOCKET: i32 = 1;
+/// The type of the control message that carries the timestamps of a packet.
+const SCM_TIMESTAMPING: i32 = 37;
+
+/// The message header used by `sendmmsg` and `recvmmsg`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod)]
+pub struct CUserMmsgHdr {
+    /// The message header
+    pub msg_hdr: CUserMsgHdr,
+    /// The # of bytes transmitted for the message
+    pub msg_len: u32,
+}
+
+impl CUserMmsgHdr {
+    /// The maximum number of messages in a `sendmmsg` or `recvmmsg` call.
+    pub const MAX_VLEN: usize = 1024;
+
+    /// Returns the user-space address of the `i`-th header in the array at `base`.
+    pub fn addr_of(base: Vaddr, i: usize) -> Vaddr {
+        base + i * size_of::<Self>()
+    }
+
+    /// Writes the number of transmitted bytes to the header at `addr`.
+    pub fn write_msg_len_to_user(addr: Vaddr, msg_len: usize) -> Result<()> {
+        write_val_to_user(addr + size_of::<CUserMsgHdr>(), &(msg_len as u32))
     }
 }
@@ -14,7 +14,10 @@ use core::{cmp::min, ops::Range};
 
 use align_ext::AlignExt;
 use aster_rights::Rights;
-use ostd::mm::{VmSpace, MAX_USERSPACE_VADDR};
+use ostd::{
+    mm::{Paddr, VmSpace, MAX_USERSPACE_VADDR},
+    sync::RwSemaphore,
+};
 
 use self::{
     interval::{Interval, IntervalSet},
@@ -90,7 +93,10 @@ impl<R> Vmar<R> {
 
 pub(super) struct Vmar_ {
     /// vmar inner
-    inner: Mutex<VmarInner>,
+    ///
+    /// It is a sleeping lock that allows concurrent readers, since page faults
+    /// look up the mappings while holding it and may sleep on I/O.
+    inner: RwSemaphore<VmarInner>,
     /// The offset relative to the root VMAR
     base: Vaddr,
     /// The total size of the VMAR in bytes
@@ -147,7 +153,7 @@ impl Vmar_ {
         };
 
         Arc::new(Vmar_ {
-            inner: Mutex::new(inner),
+            inner: RwSemaphore::new(inner),
             base,
             size,
             vm_space,
@@ -185,7 +191,7 @@ impl Vmar_ {
     // Do real protect. The protected range is ensured to be mapped.
     fn do_protect_inner(&self, perms: VmPerms, range: Range<usize>) -> Result<()> {
         let protect_mappings: Vec<Arc<VmMapping>> = {
-            let inner = self.inner.lock();
+            let inner = self.inner.read();
             inner
                 .vm_mappings
                 .find(&range)
@@ -201,7 +207,7 @@ impl Vmar_ {
             vm_mapping.protect(perms, intersected_range)?;
         }
 
-        for child_vmar_ in self.inner.lock().child_vmar_s.find(&range) {
+        for child_vmar_ in self.inner.read().child_vmar_s.find(&range) {
             let child_vmar_range = child_vmar_.range();
             debug_assert!(is_intersected(&child_vmar_range, &range));
             let intersected_range = get_intersected_range(&range, &child_vmar_range);
@@ -220,7 +226,7 @@ impl Vmar_ {
         assert!(protected_range.end <= self.base + self.size);
 
         // The protected range should not interstect with any free region
-        let inner = self.inner.lock();
+        let inner = self.inner.read();
         if inner
             .free_regions
             .find(protected_range)
@@ -253,7 +259,7 @@ impl Vmar_ {
             return_errno_with_message!(Errno::EACCES, "page fault addr is not in current vmar");
         }
 
-        let inner = self.inner.lock();
+        let inner = self.inner.read();
         if let Some(child_vmar) = inner.child_vmar_s.find_one(&page_fault_addr) {
             debug_assert!(is_intersected(
                 &child_vmar.range(),
@@ -274,6 +280,22 @@ impl Vmar_ {
         return_errno_with_message!(Errno::EACCES, "page fault addr is not in current vmar");
     }
 
+    /// Unmaps the page backed by the frame at `paddr`, and returns the address of the page.
+    fn unmap_frame(&self, paddr: Paddr) -> Result<Option<Vaddr>> {
+        let inner = self.inner.read();
+        for child_vmar in inner.child_vmar_s.values() {
+            if let Some(addr) = child_vmar.unmap_frame(paddr)? {
+                return Ok(Some(addr));
+            }
+        }
+        for vm_mapping in inner.vm_mappings.values() {
+            if let Some(addr) = vm_mapping.unmap_frame(paddr)? {
+                return Ok(Some(addr));
+            }
+        }
+        Ok(None)
+    }
+
     /// Clear all content of the root vmar
     pub fn clear_root_vmar(&self) -> Result<()> {
         debug_assert!(self.is_root_vmar());
@@ -281,7 +303,7 @@ impl Vmar_ {
             return_errno_with_message!(Errno::EACCES, "The vmar is not root vmar");
         }
         self.vm_space.clear();
-        let mut inner = self.inner.lock();
+        let mut inner = self.inner.write();
         inner.child_vmar_s.clear();
         inner.vm_mappings.clear();
         inner.free_regions.clear();
@@ -291,7 +313,7 @@ impl Vmar_ {
     }
 
     pub fn destroy_all(&self) -> Result<()> {
-        let mut inner = self.inner.lock();
+        let mut inner = self.inner.write();
         inner.is_destroyed = true;
         let mut free_regions = BTreeMap::new();
         for (child_vmar_base, child_vmar) in &inner.child_vmar_s {
@@ -318,7 +340,7 @@ impl Vmar_ {
 
     pub fn destroy(&self, range: Range<usize>) -> Result<()> {
         self.check_destroy_range(&range)?;
-        let mut inner = self.inner.lock();
+        let mut inner = self.inner.write();
         let mut free_regions = BTreeMap::new();
 
         for child_vmar_ in inner.child_vmar_s.find(&range) {
@@ -369,7 +391,7 @@ impl Vmar_ {
         debug_assert!(range.start % PAGE_SIZE == 0);
         debug_assert!(range.end % PAGE_SIZE == 0);
 
-        let inner = self.inner.lock();
+        let inner = self.inner.read();
 
         for child_vmar_ in inner.child_vmar_s.find(range) {
             let child_vmar_range = child_vmar_.range();
@@ -388,12 +410,12 @@ impl Vmar_ {
     }
 
     fn is_destroyed(&self) -> bool {
-        self.inner.lock().is_destroyed
+        self.inner.read().is_destroyed
     }
 
     fn merge_continuous_regions(&self) {
         let mut new_free_regions = BTreeMap::new();
-        let mut inner = self.inner.lock();
+        let mut inner = self.inner.write();
         let keys = inner.free_regions.keys().cloned().collect::<Vec<_>>();
         for key in keys {
             if let Some(mut free_region) = inner.free_regions.remove(&key) {
@@ -414,7 +436,7 @@ impl Vmar_ {
         let read_end = buf.len() + read_start;
         let read_range = read_start..read_end;
         // If the read range is in child vmar.
-        let inner = self.inner.lock();
+        let inner = self.inner.read();
         for child_vmar_ in inner.child_vmar_s.find(&read_range) {
             let child_vmar_range = child_vmar_.range();
             if child_vmar_range.start <= read_start && read_end <= child_vmar_range.end {
@@ -464,7 +486,7 @@ impl Vmar_ {
         let write_range = write_start..write_end;
 
         // If the write range is in child vmar.
-        let inner = self.inner.lock();
+        let inner = self.inner.read();
         for child_vmar_ in inner.child_vmar_s.find(&write_range) {
             let child_vmar_range = child_vmar_.range();
             if child_vmar_range.start <= write_start && write_end <= child_vmar_range.end {
@@ -509,7 +531,12 @@ impl Vmar_ {
         let (region_base, child_vmar_offset) =
             self.find_free_region_for_child(child_vmar_offset, child_vmar_size, align)?;
         // This unwrap should never fails
-        let free_region = self.inner.lock().free_regions.remove(&region_base).unwrap();
+        let free_region = self
+            .inner
+            .write()
+            .free_regions
+            .remove(&region_base)
+            .unwrap();
         let child_range = child_vmar_offset..(child_vmar_offset + child_vmar_size);
         let regions_after_allocation = free_region.allocate_range(child_range.clone());
         regions_after_allocation.into_iter().for_each(|region| {
@@ -549,7 +576,7 @@ impl Vmar_ {
         child_size: usize,
         align: usize,
     ) -> Result<(Vaddr, Vaddr)> {
-        let inner = self.inner.lock();
+        let inner = self.inner.read();
 
         if let Some(child_vmar_offset) = child_offset {
             // if the offset is set, we should find a free region can satisfy both the offset and size
@@ -580,7 +607,7 @@ impl Vmar_ {
     }
 
     fn check_vmo_overwrite(&self, vmo_range: Range<usize>, can_overwrite: bool) -> Result<()> {
-        let inner = self.inner.lock();
+        let inner = self.inner.read();
         if inner
             .child_vmar_s
             .find(&vmo_range)
@@ -630,7 +657,7 @@ impl Vmar_ {
         let map_size = size.max(vmo_size);
 
         if can_overwrite {
-            let mut inner = self.inner.lock();
+            let mut inner = self.inner.write();
             // If can_overwrite, the offset is ensured not to be None.
             let offset = offset.ok_or(Error::with_message(
                 Errno::EINVAL,
@@ -662,7 +689,7 @@ impl Vmar_ {
             // Otherwise, the vmo in a single region.
             let (free_region_base, offset) =
                 self.find_free_region_for_child(offset, map_size, align)?;
-            let mut inner = self.inner.lock();
+            let mut inner = self.inner.write();
             let free_region = inner.free_regions.remove(&free_region_base).unwrap();
             let vmo_range = offset..(offset + map_size);
             let intersected_range = get_intersected_range(&free_region.range(), &vmo_range);
@@ -675,7 +702,7 @@ impl Vmar_ {
     }
 
     fn trim_existing_mappings(&self, trim_range: Range<usize>) -> Result<()> {
-        let mut inner = self.inner.lock();
+        let mut inner = self.inner.write();
         let mut mappings_to_remove = BTreeSet::new();
         let mut mappings_to_append = BTreeMap::new();
         for vm_mapping in inner.vm_mappings.values() {
@@ -719,7 +746,7 @@ impl Vmar_ {
             Vmar_::new(vmar_inner, vm_space, self.base, self.size, parent)
         };
 
-        let inner = self.inner.lock();
+        let inner = self.inner.read();
         // Clone free regions.
         for (free_region_base, free_region) in &inner.free_regions {
             new_vmar_
@@ -753,7 +780,7 @@ impl Vmar_ {
 
     /// get mapped vmo at given offset
     fn get_vm_mapping(&self, offset: Vaddr) -> Result<Arc<VmMapping>> {
-        let inner = self.inner.lock();
+        let inner = self.inner.read();
         let range = offset..offset + 1;
 
         if let Some(vm_mapping) = inner.vm_mappings.find_one(&offset) {
@@ -785,6 +812,15 @@ impl<R> Vmar<R> {
         self.check_rights(rights)?;
         self.0.get_vm_mapping(offset)
     }
+
+    /// Unmaps the page backed by the poisoned frame at `paddr`, so that the corrupted data
+    /// is not accessed again. The address of the page is returned if it is mapped.
+    ///
+    /// The poisoned frame will not be mapped again. Accessing the page afterwards is a
+    /// page fault that cannot be handled.
+    pub fn unmap_poisoned_page(&self, paddr: Paddr) -> Result<Option<Vaddr>> {
+        self.0.unmap_frame(paddr.align_down(PAGE_SIZE))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -5,7 +5,7 @@
 
 use core::ops::Range;
 
-use ostd::mm::{Frame, FrameVec, PageFlags, VmIo, VmMapOptions, VmSpace};
+use ostd::mm::{Frame, FrameVec, Paddr, PageFlags, VmIo, VmMapOptions, VmQueryResult, VmSpace};
 
 use super::{interval::Interval, is_intersected, Vmar, Vmar_};
 use crate::{
@@ -233,7 +233,20 @@ impl VmMapping {
         not_present: bool,
         write: bool,
     ) -> Result<()> {
-        let vmo_offset = self.vmo_offset() + page_fault_addr - self.map_to_addr();
+        self.commit_and_map_page(page_fault_addr, write)?;
+        Ok(())
+    }
+
+    /// Returns the frame that backs the page at `addr`, and maps the page if it is not mapped.
+    ///
+    /// The kernel can access the frame (e.g., by DMA) on behalf of the user. If `write` is
+    /// true, the frame is private to the mapping, i.e., it is not shared copy-on-write.
+    pub fn get_frame(&self, addr: Vaddr, write: bool) -> Result<Frame> {
+        self.commit_and_map_page(addr, write)
+    }
+
+    fn commit_and_map_page(&self, addr: Vaddr, write: bool) -> Result<Frame> {
+        let vmo_offset = self.vmo_offset() + addr - self.map_to_addr();
         if vmo_offset >= self.vmo.size() {
             return_errno_with_message!(Errno::EACCES, "page fault addr is not backed up by a vmo");
         }
@@ -248,11 +261,36 @@ impl VmMapping {
         self.check_perms(&required_perm)?;
 
         let frame = self.vmo.get_committed_frame(page_idx, write)?;
+        if frame.is_poisoned() {
+            return_errno_with_message!(Errno::EHWPOISON, "the page has a memory error");
+        }
 
         // If read access to cow vmo triggers page fault, the map should be readonly.
         // If user next tries to write to the frame, another page fault will be triggered.
         let is_readonly = self.vmo.is_cow_vmo() && !write;
-        self.map_one_page(page_idx, frame, is_readonly)
+        self.map_one_page(page_idx, frame.clone(), is_readonly)?;
+        Ok(frame)
+    }
+
+    /// Unmaps the page backed by the frame at `paddr`, and returns the address of the page.
+    ///
+    /// This method returns `None` if the frame is not mapped in the mapping.
+    pub(super) fn unmap_frame(&self, paddr: Paddr) -> Result<Option<Vaddr>> {
+        let parent = self.parent.upgrade().unwrap();
+        let vm_space = parent.vm_space();
+        let mut inner = self.inner.lock();
+
+        let addr = vm_space
+            .query_range(&inner.range())?
+            .find_map(|result| match result {
+                VmQueryResult::Mapped { va, frame, .. } if frame.start_paddr() == paddr => Some(va),
+                _ => None,
+            });
+        if let Some(addr) = addr {
+            let page_idx = (addr - inner.map_to_addr + inner.vmo_offset) / PAGE_SIZE;
+            inner.unmap_one_page(vm_space, page_idx)?;
+        }
+        Ok(addr)
     }
 
     /// Protect a specified range of pages in the mapping to the target perms.
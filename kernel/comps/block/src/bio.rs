@@ -1,10 +1,13 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use alloc::boxed::Box;
+use core::sync::atomic::AtomicBool;
+
 use align_ext::AlignExt;
 use int_to_c_enum::TryFromInt;
 use ostd::{
     mm::{Frame, Segment, VmReader, VmWriter},
-    sync::WaitQueue,
+    sync::{SpinLock, WaitQueue},
 };
 
 use super::{id::Sid, BlockDevice};
@@ -45,6 +48,7 @@ impl Bio {
             complete_fn,
             status: AtomicU32::new(BioStatus::Init as u32),
             wait_queue: WaitQueue::new(),
+            complete_hooks: SpinLock::new(Vec::new()),
         });
         Self(inner)
     }
@@ -225,6 +229,51 @@ impl BioWaiter {
     pub fn clear(&mut self) {
         self.bios.clear();
     }
+
+    /// Invokes `callback` once all `Bio` requests have completed, without waiting for them.
+    ///
+    /// The callback receives the same value as [`Self::wait`] returns. It is invoked
+    /// immediately if all the requests have already completed. Otherwise, it is invoked
+    /// when the last request completes, which may be in the interrupt context.
+    pub fn on_complete<F>(self, callback: F)
+    where
+        F: FnOnce(Option<BioStatus>) + Send + 'static,
+    {
+        if self.bios.is_empty() {
+            callback(Some(BioStatus::Complete));
+            return;
+        }
+
+        struct PendingCallback<F> {
+            nr_remaining: AtomicUsize,
+            has_failed: AtomicBool,
+            callback: SpinLock<Option<F>>,
+        }
+
+        let pending = Arc::new(PendingCallback {
+            nr_remaining: AtomicUsize::new(self.bios.len()),
+            has_failed: AtomicBool::new(false),
+            callback: SpinLock::new(Some(callback)),
+        });
+        for bio in self.bios.iter() {
+            let pending = pending.clone();
+            bio.add_complete_hook(Box::new(move |status| {
+                if status != BioStatus::Complete {
+                    pending.has_failed.store(true, Ordering::Relaxed);
+                }
+                if pending.nr_remaining.fetch_sub(1, Ordering::AcqRel) != 1 {
+                    return;
+                }
+
+                let callback = pending.callback.lock_irq_disabled().take().unwrap();
+                if pending.has_failed.load(Ordering::Relaxed) {
+                    callback(None);
+                } else {
+                    callback(Some(BioStatus::Complete));
+                }
+            }));
+        }
+    }
 }
 
 impl Default for BioWaiter {
@@ -279,9 +328,17 @@ impl SubmittedBio {
         if let Some(complete_fn) = self.0.complete_fn {
             complete_fn(self);
         }
+
+        let complete_hooks = core::mem::take(&mut *self.0.complete_hooks.lock_irq_disabled());
+        for hook in complete_hooks {
+            hook(status);
+        }
     }
 }
 
+/// A hook that is invoked with the status of a `Bio` when the `Bio` completes.
+type CompleteHook = Box<dyn FnOnce(BioStatus) + Send>;
+
 /// The common inner part of `Bio`.
 struct BioInner {
     /// The type of the I/O
@@ -296,6 +353,8 @@ struct BioInner {
     status: AtomicU32,
     /// The wait queue for I/O completion
     wait_queue: WaitQueue,
+    /// The hooks to invoke on I/O completion
+    complete_hooks: SpinLock<Vec<CompleteHook>>,
 }
 
 impl BioInner {
@@ -314,6 +373,23 @@ impl BioInner {
     pub fn status(&self) -> BioStatus {
         BioStatus::try_from(self.status.load(Ordering::Relaxed)).unwrap()
     }
+
+    /// Adds a hook to invoke when the `Bio` completes.
+    ///
+    /// The hook is invoked immediately if the `Bio` has already completed.
+    fn add_complete_hook(&self, hook: CompleteHook) {
+        let mut complete_hooks = self.complete_hooks.lock_irq_disabled();
+        // The status is updated before the hooks are taken in `SubmittedBio::complete`,
+        // so the hook is either taken there or invoked here.
+        let status = self.status();
+        if status == BioStatus::Submit {
+            complete_hooks.push(hook);
+            return;
+        }
+        drop(complete_hooks);
+
+        hook(status);
+    }
 }
 
 impl Debug for BioInner {
@@ -8,7 +8,7 @@ use alloc::{
 };
 use core::ops::Range;
 
-use bitvec::{array::BitArray, prelude::Lsb0};
+use id_alloc::Bitmap;
 use ostd::{
     mm::{
         Daddr, DmaDirection, DmaStream, FrameAllocOptions, HasDaddr, VmReader, VmWriter, PAGE_SIZE,
@@ -137,9 +137,8 @@ impl DmaPool {
 struct DmaPage {
     storage: DmaStream,
     segment_size: usize,
-    // `BitArray` is 64 bits, since each `DmaSegment` is bigger than 64 bytes,
-    // there's no more than `PAGE_SIZE` / 64 = 64 `DmaSegment`s in a `DmaPage`.
-    allocated_segments: SpinLock<BitArray>,
+    // There is one bit for each `DmaSegment` in the `DmaPage`.
+    allocated_segments: SpinLock<Bitmap>,
     pool: Weak<DmaPool>,
 }
 
@@ -160,14 +159,14 @@ impl DmaPage {
         Ok(Self {
             storage: dma_stream,
             segment_size,
-            allocated_segments: SpinLock::new(BitArray::ZERO),
+            allocated_segments: SpinLock::new(Bitmap::new(PAGE_SIZE / segment_size)),
             pool,
         })
     }
 
     fn alloc_segment(self: &Arc<Self>) -> Option<DmaSegment> {
         let mut segments = self.allocated_segments.lock_irq_disabled();
-        let free_segment_index = get_next_free_index(&segments, self.nr_blocks_per_page())?;
+        let free_segment_index = segments.find_first_zero()?;
         segments.set(free_segment_index, true);
 
         let segment = DmaSegment {
@@ -181,26 +180,11 @@ impl DmaPage {
     }
 
     fn is_free(&self) -> bool {
-        *self.allocated_segments.lock() == BitArray::<[usize; 1], Lsb0>::ZERO
-    }
-
-    const fn nr_blocks_per_page(&self) -> usize {
-        PAGE_SIZE / self.segment_size
+        !self.allocated_segments.lock().any()
     }
 
     fn is_full(&self) -> bool {
-        let segments = self.allocated_segments.lock_irq_disabled();
-        get_next_free_index(&segments, self.nr_blocks_per_page()).is_none()
-    }
-}
-
-fn get_next_free_index(segments: &BitArray, nr_blocks_per_page: usize) -> Option<usize> {
-    let free_segment_index = segments.iter_zeros().next()?;
-
-    if free_segment_index >= nr_blocks_per_page {
-        None
-    } else {
-        Some(free_segment_index)
+        self.allocated_segments.lock_irq_disabled().all()
     }
 }
 
@@ -262,14 +246,13 @@ impl Drop for DmaSegment {
 
         let mut allocated_segments = page.allocated_segments.lock_irq_disabled();
 
-        let nr_blocks_per_page = PAGE_SIZE / self.size;
-        let became_avail = get_next_free_index(&allocated_segments, nr_blocks_per_page).is_none();
+        let became_avail = allocated_segments.all();
 
         debug_assert!((page.daddr()..page.daddr() + PAGE_SIZE).contains(&self.daddr()));
         let segment_idx = (self.daddr() - page.daddr()) / self.size;
         allocated_segments.set(segment_idx, false);
 
-        let became_free = allocated_segments.not_any();
+        let became_free = !allocated_segments.any();
 
         if became_free && all_pages.len() > pool.high_watermark {
             avail_pages.retain(|page_| !Arc::ptr_eq(page_, &page));
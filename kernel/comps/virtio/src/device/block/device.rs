@@ -8,7 +8,7 @@ use aster_block::{
     request_queue::{BioRequest, BioRequestSingleQueue},
 };
 use aster_util::safe_ptr::SafePtr;
-use id_alloc::IdAlloc;
+use id_alloc::AtomicBitmap;
 use log::info;
 use ostd::{
     io_mem::IoMem,
@@ -91,7 +91,7 @@ struct DeviceInner {
     transport: SpinLock<Box<dyn VirtioTransport>>,
     block_requests: DmaStream,
     block_responses: DmaStream,
-    id_allocator: SpinLock<IdAlloc>,
+    id_allocator: AtomicBitmap,
     submitted_requests: SpinLock<BTreeMap<u16, SubmittedRequest>>,
 }
 
@@ -124,7 +124,7 @@ impl DeviceInner {
             transport: SpinLock::new(transport),
             block_requests,
             block_responses,
-            id_allocator: SpinLock::new(IdAlloc::with_capacity(Self::QUEUE_SIZE as usize)),
+            id_allocator: AtomicBitmap::new(Self::QUEUE_SIZE as usize),
             submitted_requests: SpinLock::new(BTreeMap::new()),
         });
 
@@ -173,7 +173,7 @@ impl DeviceInner {
             let resp_slice = DmaStreamSlice::new(&self.block_responses, id * RESP_SIZE, RESP_SIZE);
             resp_slice.sync().unwrap();
             let resp: BlockResp = resp_slice.read_val(0).unwrap();
-            self.id_allocator.lock().free(id);
+            self.id_allocator.test_and_clear(id);
             match RespStatus::try_from(resp.status).unwrap() {
                 RespStatus::Ok => {}
                 // FIXME: Return an error instead of triggering a kernel panic
@@ -204,7 +204,7 @@ impl DeviceInner {
     // TODO: Most logic is the same as read and write, there should be a refactor.
     // TODO: Should return an Err instead of panic if the device fails.
     fn request_device_id(&self) -> String {
-        let id = self.id_allocator.lock_irq_disabled().alloc().unwrap();
+        let id = self.id_allocator.set_next_zero(0).unwrap();
         let req_slice = {
             let req_slice = DmaStreamSlice::new(&self.block_requests, id * REQ_SIZE, REQ_SIZE);
             let req = BlockReq {
@@ -246,7 +246,7 @@ impl DeviceInner {
         queue.pop_used_with_token(token).expect("pop used failed");
 
         resp_slice.sync().unwrap();
-        self.id_allocator.lock_irq_disabled().free(id);
+        self.id_allocator.test_and_clear(id);
         let resp: BlockResp = resp_slice.read_val(0).unwrap();
         match RespStatus::try_from(resp.status).unwrap() {
             RespStatus::Ok => {}
@@ -271,7 +271,7 @@ impl DeviceInner {
     fn read(&self, bio_request: BioRequest) {
         let dma_streams = Self::dma_stream_map(&bio_request);
 
-        let id = self.id_allocator.lock_irq_disabled().alloc().unwrap();
+        let id = self.id_allocator.set_next_zero(0).unwrap();
         let req_slice = {
             let req_slice = DmaStreamSlice::new(&self.block_requests, id * REQ_SIZE, REQ_SIZE);
             let req = BlockReq {
@@ -332,7 +332,7 @@ impl DeviceInner {
     fn write(&self, bio_request: BioRequest) {
         let dma_streams = Self::dma_stream_map(&bio_request);
 
-        let id = self.id_allocator.lock_irq_disabled().alloc().unwrap();
+        let id = self.id_allocator.set_next_zero(0).unwrap();
         let req_slice = {
             let req_slice = DmaStreamSlice::new(&self.block_requests, id * REQ_SIZE, REQ_SIZE);
             let req = BlockReq {
@@ -7,12 +7,12 @@ use clap::{crate_version, Args, Parser};
 use crate::{
     arch::Arch,
     commands::{
-        execute_build_command, execute_debug_command, execute_forwarded_command,
-        execute_new_command, execute_run_command, execute_test_command,
+        execute_add_component_command, execute_build_command, execute_debug_command,
+        execute_forwarded_command, execute_new_command, execute_run_command, execute_test_command,
     },
     config::{
         manifest::{ProjectType, TomlManifest},
-        scheme::{BootMethod, BootProtocol},
+        scheme::{BootMethod, BootProtocol, GrubMenuEntryScheme},
         Config,
     },
 };
@@ -29,6 +29,7 @@ pub fn main() {
 
     match osdk_subcommand {
         OsdkSubcommand::New(args) => execute_new_command(args),
+        OsdkSubcommand::AddComponent(args) => execute_add_component_command(args),
         OsdkSubcommand::Build(build_args) => {
             execute_build_command(&load_config(&build_args.common_args), build_args);
         }
@@ -71,6 +72,8 @@ enum CargoSubcommand {
 pub enum OsdkSubcommand {
     #[command(about = "Create a new kernel package or library package which depends on OSTD")]
     New(NewArgs),
+    #[command(about = "Add a component crate outside the workspace to the kernel package")]
+    AddComponent(AddComponentArgs),
     #[command(about = "Compile the project and its dependencies")]
     Build(BuildArgs),
     #[command(about = "Run the kernel with a VMM")]
@@ -137,6 +140,54 @@ impl NewArgs {
     }
 }
 
+#[derive(Debug, Parser)]
+pub struct AddComponentArgs {
+    #[arg(
+        name = "source",
+        required = true,
+        help = "The path or the Git URL of the component"
+    )]
+    pub source: String,
+    #[arg(
+        long,
+        short = 'p',
+        help = "The package name of the component, required if the Git repository has multiple packages",
+        value_name = "SPEC"
+    )]
+    pub package: Option<String>,
+    #[arg(
+        long,
+        help = "The branch to use when adding from Git",
+        value_name = "BRANCH",
+        conflicts_with_all = ["tag", "rev"]
+    )]
+    pub branch: Option<String>,
+    #[arg(
+        long,
+        help = "The tag to use when adding from Git",
+        value_name = "TAG",
+        conflicts_with_all = ["branch", "rev"]
+    )]
+    pub tag: Option<String>,
+    #[arg(
+        long,
+        help = "The specific commit to use when adding from Git",
+        value_name = "REV",
+        conflicts_with_all = ["branch", "tag"]
+    )]
+    pub rev: Option<String>,
+}
+
+impl AddComponentArgs {
+    /// Returns whether the component is fetched from a Git repository.
+    pub fn is_git(&self) -> bool {
+        ["http://", "https://", "ssh://", "git://", "git@"]
+            .iter()
+            .any(|prefix| self.source.starts_with(prefix))
+            || self.source.ends_with(".git")
+    }
+}
+
 #[derive(Debug, Parser)]
 pub struct BuildArgs {
     #[arg(
@@ -210,6 +261,29 @@ pub struct TestArgs {
         help = "Only run tests containing this string in their names"
     )]
     pub test_name: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Record the execution of QEMU into the file, so that it can be replayed by '--replay'",
+        conflicts_with = "replay"
+    )]
+    pub record: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Replay the execution recorded in the file deterministically, \
+                with the QEMU GDB server waiting for a connection"
+    )]
+    pub replay: Option<PathBuf>,
+    #[arg(
+        long = "gdb-server-addr",
+        help = "The network address on which the GDB server listens during the replay, \
+        it can be either a path for the UNIX domain socket or a TCP port on an IP address.",
+        value_name = "ADDR",
+        default_value = ".aster-gdb-socket",
+        requires = "replay"
+    )]
+    pub gdb_server_addr: String,
     #[command(flatten)]
     pub common_args: CommonArgs,
 }
@@ -343,6 +417,22 @@ pub struct CommonArgs {
         global = true
     )]
     pub grub_boot_protocol: Option<BootProtocol>,
+    #[arg(
+        long = "grub-default-entry",
+        help = "The title of the GRUB menu entry to boot by default",
+        value_name = "TITLE",
+        global = true
+    )]
+    pub grub_default_entry: Option<String>,
+    #[arg(
+        long = "grub-menu-entry",
+        help = "An extra GRUB menu entry, written as the fields of an entry in `grub.menu_entries`, \
+        e.g., 'title = \"debug\", kcmd_args = [\"LOG_LEVEL=debug\"]'",
+        value_name = "ENTRY",
+        value_parser = parse_grub_menu_entry,
+        global = true
+    )]
+    pub grub_menu_entries: Vec<GrubMenuEntryScheme>,
     #[arg(
         long = "qemu-exe",
         help = "The QEMU executable file",
@@ -359,3 +449,17 @@ pub struct CommonArgs {
     )]
     pub qemu_args: Vec<String>,
 }
+
+/// Parses a GRUB menu entry from the fields of an inline TOML table, which are
+/// the same as those of an entry in `grub.menu_entries` of the manifest.
+fn parse_grub_menu_entry(fields: &str) -> Result<GrubMenuEntryScheme, String> {
+    #[derive(Deserialize)]
+    struct Wrapper {
+        entry: GrubMenuEntryScheme,
+    }
+
+    let toml = format!("entry = {{ {} }}", fields);
+    toml::from_str::<Wrapper>(&toml)
+        .map(|wrapper| wrapper.entry)
+        .map_err(|err| err.to_string())
+}
@@ -0,0 +1,344 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The `add-component` subcommand, which adds a component crate developed
+//! outside the workspace to the kernel package.
+//!
+//! The component is added as a dependency of the kernel package, so that it is
+//! built with the same toolchain and target as the kernel. To make sure that
+//! the component is linked into the kernel image even if the kernel never refers
+//! to it, an `extern crate` item is added to the kernel crate.
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use super::util::cargo;
+use crate::{
+    cli::AddComponentArgs,
+    error::Errno,
+    error_msg,
+    util::{get_cargo_metadata, get_current_crate_info, CrateInfo},
+    warn_msg,
+};
+
+pub fn execute_add_component_command(args: &AddComponentArgs) {
+    let kernel = get_current_crate_info();
+    let kernel_manifest_path = PathBuf::from(&kernel.path).join("Cargo.toml");
+
+    let local_path = if args.is_git() {
+        None
+    } else {
+        let path = canonicalize_component_path(&args.source);
+        check_local_component(&path, &kernel);
+        Some(path)
+    };
+
+    let old_deps = get_dependency_names(&kernel_manifest_path);
+    cargo_add(args, local_path.as_deref(), &kernel);
+
+    let component_name = if let Some(package) = &args.package {
+        package.clone()
+    } else if let Some(path) = &local_path {
+        get_package_name(&path.join("Cargo.toml"))
+    } else {
+        let new_deps = get_dependency_names(&kernel_manifest_path);
+        let Some(name) = new_deps.difference(&old_deps).next() else {
+            error_msg!("Cannot determine the package name of the component, please specify it with `--package`");
+            process::exit(Errno::AddComponent as _);
+        };
+        name.clone()
+    };
+    check_ostd_version(&kernel, &component_name);
+
+    add_extern_crate(&kernel, &component_name);
+    if let Some(path) = &local_path {
+        register_component(path, &component_name, &kernel);
+    }
+
+    println!(
+        "[OSDK] Added component `{}` to the kernel package `{}`",
+        component_name, kernel.name
+    );
+}
+
+fn canonicalize_component_path(source: &str) -> PathBuf {
+    let Ok(path) = Path::new(source).canonicalize() else {
+        error_msg!("The component path {} does not exist", source);
+        process::exit(Errno::AddComponent as _);
+    };
+    if !path.join("Cargo.toml").is_file() {
+        error_msg!(
+            "The component path {} is not a Cargo package",
+            path.display()
+        );
+        process::exit(Errno::AddComponent as _);
+    }
+    path
+}
+
+/// Checks whether a local component can be built together with the kernel.
+fn check_local_component(path: &Path, kernel: &CrateInfo) {
+    let manifest = read_toml(&path.join("Cargo.toml"));
+    let depends_on_ostd = manifest
+        .get("dependencies")
+        .and_then(|deps| deps.as_table())
+        .is_some_and(|deps| deps.contains_key("ostd"));
+    if !depends_on_ostd {
+        warn_msg!(
+            "The component at {} does not depend on OSTD",
+            path.display()
+        );
+    }
+
+    // The component is built with the toolchain of the kernel, so a component
+    // that pins another toolchain may fail to build or behave differently.
+    let Some(channel) = find_toolchain_channel(path) else {
+        return;
+    };
+    let kernel_channel = find_toolchain_channel(Path::new(&kernel.path));
+    if kernel_channel.as_ref() != Some(&channel) {
+        error_msg!(
+            "The component requires the toolchain {}, which does not match that of the kernel",
+            channel
+        );
+        process::exit(Errno::AddComponent as _);
+    }
+}
+
+/// Finds the toolchain channel specified in `rust-toolchain.toml` of `path` or its ancestors.
+fn find_toolchain_channel(path: &Path) -> Option<String> {
+    let toolchain_path = path
+        .ancestors()
+        .map(|dir| dir.join("rust-toolchain.toml"))
+        .find(|toolchain_path| toolchain_path.is_file())?;
+    let toolchain = read_toml(&toolchain_path);
+    let channel = toolchain.get("toolchain")?.get("channel")?.as_str()?;
+    Some(channel.to_string())
+}
+
+/// Checks that the component is built with the same OSTD as the kernel.
+///
+/// If the OSTD versions are not semver-compatible, or the OSTDs come from different sources,
+/// Cargo builds two copies of OSTD, and the component fails to link or to work with the kernel.
+/// In that case, the component is removed from the kernel package again.
+fn check_ostd_version(kernel: &CrateInfo, component_name: &str) {
+    let output = cargo()
+        .current_dir(&kernel.path)
+        .args(["metadata", "--format-version", "1"])
+        .output()
+        .unwrap();
+    if !output.status.success() {
+        error_msg!("Failed to resolve the dependencies of the kernel package");
+        process::exit(Errno::AddComponent as _);
+    }
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let packages = metadata.get("packages").unwrap().as_array().unwrap();
+    let nodes = metadata["resolve"]["nodes"].as_array().unwrap();
+
+    // Finds the OSTD package that the package named `name` depends on.
+    let ostd_of = |name: &str| {
+        let package = packages.iter().find(|package| package["name"] == name)?;
+        let node = nodes.iter().find(|node| node["id"] == package["id"])?;
+        node["deps"].as_array()?.iter().find_map(|dep| {
+            packages
+                .iter()
+                .find(|package| package["id"] == dep["pkg"] && package["name"] == "ostd")
+        })
+    };
+    let (Some(kernel_ostd), Some(component_ostd)) =
+        (ostd_of(&kernel.name), ostd_of(component_name))
+    else {
+        return;
+    };
+    if kernel_ostd["id"] == component_ostd["id"] {
+        return;
+    }
+
+    let status = cargo()
+        .current_dir(&kernel.path)
+        .args(["remove", "--package", kernel.name.as_str(), component_name])
+        .status()
+        .unwrap();
+    if !status.success() {
+        error_msg!("Failed to remove the component from the kernel package");
+    }
+    error_msg!(
+        "The component depends on OSTD {}, which does not match OSTD {} of the kernel",
+        describe_package(component_ostd),
+        describe_package(kernel_ostd)
+    );
+    process::exit(Errno::AddComponent as _);
+}
+
+/// Describes the version and the source of a package in the Cargo metadata.
+fn describe_package(package: &serde_json::Value) -> String {
+    let version = package["version"].as_str().unwrap();
+    match package["source"].as_str() {
+        Some(source) => format!("{} from {}", version, source),
+        None => {
+            let manifest_path = Path::new(package["manifest_path"].as_str().unwrap());
+            format!(
+                "{} at {}",
+                version,
+                manifest_path.parent().unwrap().display()
+            )
+        }
+    }
+}
+
+fn cargo_add(args: &AddComponentArgs, local_path: Option<&Path>, kernel: &CrateInfo) {
+    let mut command = cargo();
+    command
+        .current_dir(&kernel.path)
+        .args(["add", "--package", kernel.name.as_str()]);
+
+    if let Some(path) = local_path {
+        command.arg("--path").arg(path);
+    } else {
+        command.args(["--git", args.source.as_str()]);
+        for (option, value) in [
+            ("--branch", &args.branch),
+            ("--tag", &args.tag),
+            ("--rev", &args.rev),
+        ] {
+            if let Some(value) = value {
+                command.args([option, value.as_str()]);
+            }
+        }
+    }
+    if let Some(package) = &args.package {
+        command.arg(package);
+    }
+
+    let status = command.status().unwrap();
+    if !status.success() {
+        error_msg!("Failed to add the component to the kernel package");
+        process::exit(Errno::AddComponent as _);
+    }
+}
+
+/// Adds `extern crate <component>;` to the kernel crate if it is absent.
+fn add_extern_crate(kernel: &CrateInfo, component_name: &str) {
+    let crate_ident = component_name.replace('-', "_");
+    let src_path = get_src_path(kernel);
+    let content = fs::read_to_string(&src_path).unwrap();
+
+    let file = syn::parse_file(&content).unwrap();
+    let is_present = file.items.iter().any(|item| {
+        matches!(item, syn::Item::ExternCrate(extern_crate) if extern_crate.ident == crate_ident)
+    });
+    if is_present {
+        return;
+    }
+
+    // Insert the item after the existing `extern crate` items, or after the
+    // crate-level attributes if there are none.
+    let mut lines: Vec<&str> = content.lines().collect();
+    let pos = lines
+        .iter()
+        .rposition(|line| line.starts_with("extern crate "))
+        .or_else(|| lines.iter().rposition(|line| line.starts_with("#![")))
+        .map_or(0, |index| index + 1);
+    let extern_crate = format!("extern crate {};", crate_ident);
+    lines.insert(pos, &extern_crate);
+
+    let mut new_content = lines.join("\n");
+    new_content.push('\n');
+    fs::write(&src_path, new_content).unwrap();
+}
+
+/// Declares the component in `Components.toml` of the workspace, if any.
+///
+/// The component system requires every component inside the workspace to be declared.
+fn register_component(path: &Path, component_name: &str, kernel: &CrateInfo) {
+    let metadata = get_cargo_metadata(Some(&kernel.path), None::<&[&str]>).unwrap();
+    let workspace_root = PathBuf::from(metadata.get("workspace_root").unwrap().as_str().unwrap());
+    let components_path = workspace_root.join("Components.toml");
+    if !path.starts_with(&workspace_root) || !components_path.is_file() {
+        return;
+    }
+
+    let components_toml = read_toml(&components_path);
+    let Some(components) = components_toml
+        .get("components")
+        .and_then(|components| components.as_table())
+    else {
+        return;
+    };
+    let is_declared = components
+        .values()
+        .any(|value| value.get("name").and_then(|name| name.as_str()) == Some(component_name));
+    if is_declared {
+        return;
+    }
+
+    // Append the declaration to the `[components]` table textually to keep
+    // the comments and the layout of the file.
+    let content = fs::read_to_string(&components_path).unwrap();
+    let mut lines: Vec<&str> = content.lines().collect();
+    let header = lines
+        .iter()
+        .position(|line| line.trim() == "[components]")
+        .unwrap();
+    let mut pos = lines[header + 1..]
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .map_or(lines.len(), |index| header + 1 + index);
+    while pos > header + 1 && lines[pos - 1].trim().is_empty() {
+        pos -= 1;
+    }
+    let declaration = format!(
+        "{} = {{ name = \"{}\" }}",
+        component_name.replace('-', "_"),
+        component_name
+    );
+    lines.insert(pos, &declaration);
+
+    let mut new_content = lines.join("\n");
+    new_content.push('\n');
+    fs::write(components_path, new_content).unwrap();
+}
+
+fn get_src_path(kernel: &CrateInfo) -> PathBuf {
+    let metadata = get_cargo_metadata(Some(&kernel.path), None::<&[&str]>).unwrap();
+    let packages = metadata.get("packages").unwrap().as_array().unwrap();
+    let package = packages
+        .iter()
+        .find(|package| package.get("name").unwrap().as_str().unwrap() == kernel.name)
+        .unwrap();
+    let targets = package.get("targets").unwrap().as_array().unwrap();
+    targets[0].get("src_path").unwrap().as_str().unwrap().into()
+}
+
+fn get_dependency_names(manifest_path: &Path) -> BTreeSet<String> {
+    let manifest = read_toml(manifest_path);
+    manifest
+        .get("dependencies")
+        .and_then(|deps| deps.as_table())
+        .map(|deps| deps.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+fn get_package_name(manifest_path: &Path) -> String {
+    let manifest = read_toml(manifest_path);
+    let name = manifest
+        .get("package")
+        .and_then(|package| package.get("name"))
+        .and_then(|name| name.as_str());
+    let Some(name) = name else {
+        error_msg!(
+            "{} is not a valid package manifest",
+            manifest_path.display()
+        );
+        process::exit(Errno::AddComponent as _);
+    };
+    name.to_string()
+}
+
+fn read_toml(path: &Path) -> toml::Table {
+    let content = fs::read_to_string(path).unwrap();
+    toml::from_str(&content).unwrap()
+}
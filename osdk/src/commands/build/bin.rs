@@ -1,13 +1,14 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use std::{
-    fs::OpenOptions,
+    fs::{self, OpenOptions},
     io::{Seek, SeekFrom, Write},
     path::{Path, PathBuf},
     process::Command,
 };
 
 use linux_bzimage_builder::{legacy32_rust_target_json, make_bzimage, BzImageType};
+use sha2::{Digest, Sha256};
 
 use crate::{
     bundle::{
@@ -17,11 +18,16 @@ use crate::{
     util::get_current_crate_info,
 };
 
+/// Makes the bzImage and installs it in `install_dir`.
+///
+/// If `initramfs_path` is given, the digest of the initramfs is embedded in the kernel, which
+/// refuses to unpack any other initramfs.
 pub fn make_install_bzimage(
     install_dir: impl AsRef<Path>,
     target_dir: impl AsRef<Path>,
     aster_elf: &AsterBin,
     linux_x86_legacy_boot: bool,
+    initramfs_path: Option<&Path>,
 ) -> AsterBin {
     let target_name = get_current_crate_info().name;
     let image_type = if linux_x86_legacy_boot {
@@ -54,7 +60,15 @@ pub fn make_install_bzimage(
     let install_path = install_dir.as_ref().join(target_name);
     info!("Building bzImage");
     println!("install_path: {:?}", install_path);
-    make_bzimage(&install_path, image_type, aster_elf.path(), &setup_bin);
+    let initramfs_digest: Option<[u8; 32]> =
+        initramfs_path.map(|path| Sha256::digest(fs::read(path).unwrap()).into());
+    make_bzimage(
+        &install_path,
+        image_type,
+        aster_elf.path(),
+        &setup_bin,
+        initramfs_digest.as_ref(),
+    );
 
     AsterBin::new(
         &install_path,
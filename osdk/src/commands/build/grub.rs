@@ -13,7 +13,7 @@ use crate::{
         vm_image::{AsterGrubIsoImageMeta, AsterVmImage, AsterVmImageType},
     },
     config::{
-        scheme::{ActionChoice, BootProtocol},
+        scheme::{ActionChoice, BootProtocol, DEFAULT_GRUB_ENTRY_TITLE},
         Config,
     },
     util::get_current_crate_info,
@@ -57,6 +57,7 @@ pub fn create_bootdev_image(
                 &target_dir,
                 aster_bin,
                 action.build.linux_x86_legacy_boot,
+                initramfs_path.as_ref().map(|path| path.as_ref()),
             );
         }
         _ => {
@@ -72,10 +73,61 @@ pub fn create_bootdev_image(
     } else {
         None
     };
+    let mut menu_entries = vec![MenuEntryOnDevice {
+        title: DEFAULT_GRUB_ENTRY_TITLE.to_owned(),
+        kernel: format!("/boot/{}", target_name),
+        kcmdline: action.boot.kcmdline.join(" "),
+        initramfs: initramfs_in_image.clone(),
+    }];
+    for (i, entry) in action.grub.menu_entries.iter().enumerate() {
+        let entry_dir = format!("boot/entry{}", i + 1);
+        fs::create_dir_all(iso_root.join(&entry_dir)).unwrap();
+
+        // Copy the files that differ from those of the default entry.
+        let initramfs = match &entry.initramfs {
+            Some(init_path) if entry.initramfs != action.boot.initramfs => {
+                let initramfs = format!("{}/initramfs.cpio.gz", entry_dir);
+                fs::copy(init_path, iso_root.join(&initramfs)).unwrap();
+                Some(format!("/{}", initramfs))
+            }
+            Some(_) => initramfs_in_image.clone(),
+            None => None,
+        };
+        let kernel = match &entry.kernel {
+            Some(kernel_path) => {
+                let kernel = format!("{}/kernel", entry_dir);
+                fs::copy(kernel_path, iso_root.join(&kernel)).unwrap();
+                format!("/{}", kernel)
+            }
+            // The bzImage only accepts the initramfs that it is built with, so booting the
+            // kernel being built with another initramfs needs a bzImage of its own.
+            None if matches!(protocol, BootProtocol::Linux)
+                && initramfs.is_some()
+                && initramfs != initramfs_in_image =>
+            {
+                make_install_bzimage(
+                    iso_root.join(&entry_dir),
+                    &target_dir,
+                    aster_bin,
+                    action.build.linux_x86_legacy_boot,
+                    entry.initramfs.as_deref(),
+                );
+                format!("/{}/{}", entry_dir, target_name)
+            }
+            None => menu_entries[0].kernel.clone(),
+        };
+
+        menu_entries.push(MenuEntryOnDevice {
+            title: entry.title.clone(),
+            kernel,
+            kcmdline: entry.kcmdline.join(" "),
+            initramfs,
+        });
+    }
     let grub_cfg = generate_grub_cfg(
-        &action.boot.kcmdline.join(" "),
+        &menu_entries,
+        &action.grub.default_entry,
         !action.grub.display_grub_menu,
-        initramfs_in_image,
         protocol,
     );
     let grub_cfg_path = iso_root.join("boot").join("grub").join("grub.cfg");
@@ -101,13 +153,20 @@ pub fn create_bootdev_image(
     )
 }
 
+/// A GRUB menu entry with the paths of its files on the boot device.
+struct MenuEntryOnDevice {
+    title: String,
+    kernel: String,
+    kcmdline: String,
+    initramfs: Option<String>,
+}
+
 fn generate_grub_cfg(
-    kcmdline: &str,
+    menu_entries: &[MenuEntryOnDevice],
+    default_entry: &str,
     skip_grub_menu: bool,
-    initramfs_path: Option<String>,
     protocol: &BootProtocol,
 ) -> String {
-    let target_name = get_current_crate_info().name;
     let grub_cfg = include_str!("grub.cfg.template").to_string();
 
     // Delete the first two lines that notes the file a template file.
@@ -119,49 +178,34 @@ fn generate_grub_cfg(
             if skip_grub_menu { "hidden" } else { "menu" },
         )
         .replace("#GRUB_TIMEOUT#", if skip_grub_menu { "0" } else { "5" });
-    // Replace all occurrences of "#KERNEL_COMMAND_LINE#" with the desired value.
-    let grub_cfg = grub_cfg.replace("#KERNEL_COMMAND_LINE#", kcmdline);
-    // Replace the grub commands according to the protocol selected.
-    let aster_bin_path_on_device = PathBuf::from("/boot")
-        .join(target_name)
-        .into_os_string()
-        .into_string()
-        .unwrap();
-    match protocol {
-        BootProtocol::Multiboot => grub_cfg
-            .replace("#GRUB_CMD_KERNEL#", "multiboot")
-            .replace("#KERNEL#", &aster_bin_path_on_device)
-            .replace(
-                "#GRUB_CMD_INITRAMFS#",
-                &if let Some(p) = &initramfs_path {
-                    "module --nounzip ".to_owned() + p
-                } else {
-                    "".to_owned()
-                },
-            ),
-        BootProtocol::Multiboot2 => grub_cfg
-            .replace("#GRUB_CMD_KERNEL#", "multiboot2")
-            .replace("#KERNEL#", &aster_bin_path_on_device)
-            .replace(
-                "#GRUB_CMD_INITRAMFS#",
-                &if let Some(p) = &initramfs_path {
-                    "module2 --nounzip ".to_owned() + p
-                } else {
-                    "".to_owned()
-                },
-            ),
-        BootProtocol::Linux => grub_cfg
-            .replace("#GRUB_CMD_KERNEL#", "linux")
-            .replace("#KERNEL#", &aster_bin_path_on_device)
-            .replace(
-                "#GRUB_CMD_INITRAMFS#",
-                &if let Some(p) = &initramfs_path {
-                    "initrd ".to_owned() + p
-                } else {
-                    "".to_owned()
-                },
-            ),
+    let grub_cfg = grub_cfg.replace("#GRUB_DEFAULT#", default_entry);
+
+    let menu_entries = menu_entries
+        .iter()
+        .map(|entry| generate_menu_entry(entry, protocol))
+        .collect::<Vec<String>>()
+        .join("\n");
+    grub_cfg.replace("#GRUB_MENU_ENTRIES#", &menu_entries)
+}
+
+fn generate_menu_entry(entry: &MenuEntryOnDevice, protocol: &BootProtocol) -> String {
+    // Select the grub commands according to the protocol selected.
+    let (grub_cmd_kernel, grub_cmd_initramfs) = match protocol {
+        BootProtocol::Multiboot => ("multiboot", "module --nounzip"),
+        BootProtocol::Multiboot2 => ("multiboot2", "module2 --nounzip"),
+        BootProtocol::Linux => ("linux", "initrd"),
+    };
+
+    let mut menu_entry = format!("menuentry '{}' {{\n", entry.title);
+    menu_entry += &format!(
+        "    {} {} {}\n",
+        grub_cmd_kernel, entry.kernel, entry.kcmdline
+    );
+    if let Some(initramfs) = &entry.initramfs {
+        menu_entry += &format!("    {} {}\n", grub_cmd_initramfs, initramfs);
     }
+    menu_entry += "    boot\n}\n";
+    menu_entry
 }
 
 fn get_grub_mkrescue_version(grub_mkrescue: &PathBuf) -> String {
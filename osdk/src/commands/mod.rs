@@ -2,6 +2,7 @@
 
 //! This module contains subcommands of cargo-osdk.
 
+mod add_component;
 mod build;
 mod debug;
 mod new;
@@ -10,8 +11,9 @@ mod test;
 mod util;
 
 pub use self::{
-    build::execute_build_command, debug::execute_debug_command, new::execute_new_command,
-    run::execute_run_command, test::execute_test_command,
+    add_component::execute_add_component_command, build::execute_build_command,
+    debug::execute_debug_command, new::execute_new_command, run::execute_run_command,
+    test::execute_test_command,
 };
 
 use crate::arch::get_default_arch;
@@ -3,66 +3,24 @@
 use super::{build::create_base_and_cached_build, util::DEFAULT_TARGET_RELPATH};
 use crate::{
     cli::GdbServerArgs,
-    config::{scheme::ActionChoice, unix_args::split_to_kv_array, Config},
+    config::{
+        scheme::{Action, ActionChoice},
+        unix_args::split_to_kv_array,
+        Config,
+    },
     util::{get_current_crate_info, get_target_directory},
+    warn_msg,
 };
 
 pub fn execute_run_command(config: &Config, gdb_server_args: &GdbServerArgs) {
-    if gdb_server_args.is_gdb_enabled {
-        use std::env;
-        env::set_var(
-            "RUSTFLAGS",
-            env::var("RUSTFLAGS").unwrap_or_default() + " -g",
-        );
-    }
-
     let cargo_target_directory = get_target_directory();
     let osdk_output_directory = cargo_target_directory.join(DEFAULT_TARGET_RELPATH);
     let target_name = get_current_crate_info().name;
 
     let mut config = config.clone();
     if gdb_server_args.is_gdb_enabled {
-        let qemu_gdb_args = {
-            let gdb_stub_addr = gdb_server_args.gdb_server_addr.as_str();
-            match gdb::stub_type_of(gdb_stub_addr) {
-                gdb::StubAddrType::Unix => {
-                    format!(
-                        " -chardev socket,path={},server=on,wait=off,id=gdb0 -gdb chardev:gdb0 -S",
-                        gdb_stub_addr
-                    )
-                }
-                gdb::StubAddrType::Tcp => {
-                    format!(
-                        " -gdb tcp:{} -S",
-                        gdb::tcp_addr_util::format_tcp_addr(gdb_stub_addr)
-                    )
-                }
-            }
-        };
-        config.run.qemu.args += &qemu_gdb_args;
-
-        // FIXME: Disable KVM from QEMU args in debug mode.
-        // Currently, the QEMU GDB server does not work properly with KVM enabled.
-        let mut splitted = split_to_kv_array(&config.run.qemu.args);
-        let args_num = splitted.len();
-        splitted.retain(|x| !x.contains("kvm"));
-        if splitted.len() != args_num {
-            println!(
-                "[WARNING] KVM is forced to be disabled in GDB server currently. \
-                    Options related with KVM are ignored."
-            );
-        }
-
-        config.run.qemu.args = splitted.join(" ");
-
-        // Ensure debug info added when debugging in the release profile.
-        if config.run.build.profile.contains("release") {
-            config
-                .run
-                .build
-                .override_configs
-                .push(format!("profile.{}.debug=true", config.run.build.profile));
-        }
+        enable_debug_info(&mut config.run);
+        enable_gdb_server(&mut config.run, &gdb_server_args.gdb_server_addr);
     }
     let _vsc_launch_file = gdb_server_args.vsc_launch_file.then(|| {
         vsc::check_gdb_config(gdb_server_args);
@@ -83,6 +41,62 @@ pub fn execute_run_command(config: &Config, gdb_server_args: &GdbServerArgs) {
     bundle.run(&config, ActionChoice::Run);
 }
 
+/// Ensures that the kernel is built with debug info, even in the release profile.
+pub(super) fn enable_debug_info(action: &mut Action) {
+    use std::env;
+    env::set_var(
+        "RUSTFLAGS",
+        env::var("RUSTFLAGS").unwrap_or_default() + " -g",
+    );
+
+    if action.build.profile.contains("release") {
+        action
+            .build
+            .override_configs
+            .push(format!("profile.{}.debug=true", action.build.profile));
+    }
+}
+
+/// Starts QEMU with a GDB server listening on `gdb_stub_addr`, and halts the
+/// CPU until GDB connects.
+pub(super) fn enable_gdb_server(action: &mut Action, gdb_stub_addr: &str) {
+    let qemu_gdb_args = match gdb::stub_type_of(gdb_stub_addr) {
+        gdb::StubAddrType::Unix => {
+            format!(
+                " -chardev socket,path={},server=on,wait=off,id=gdb0 -gdb chardev:gdb0 -S",
+                gdb_stub_addr
+            )
+        }
+        gdb::StubAddrType::Tcp => {
+            format!(
+                " -gdb tcp:{} -S",
+                gdb::tcp_addr_util::format_tcp_addr(gdb_stub_addr)
+            )
+        }
+    };
+    action.qemu.args += &qemu_gdb_args;
+
+    // FIXME: Disable KVM from QEMU args in debug mode.
+    // Currently, the QEMU GDB server does not work properly with KVM enabled.
+    disable_kvm(action, "GDB server");
+}
+
+/// Removes the QEMU args related with KVM, which `feature` does not work with.
+pub(super) fn disable_kvm(action: &mut Action, feature: &str) {
+    let mut splitted = split_to_kv_array(&action.qemu.args);
+    let args_num = splitted.len();
+    splitted.retain(|x| !x.contains("kvm"));
+    if splitted.len() != args_num {
+        warn_msg!(
+            "KVM is forced to be disabled in {} currently. \
+                Options related with KVM are ignored.",
+            feature
+        );
+    }
+
+    action.qemu.args = splitted.join(" ");
+}
+
 mod gdb {
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum StubAddrType {
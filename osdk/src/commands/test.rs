@@ -1,22 +1,42 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use std::fs;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
-use super::{build::do_cached_build, util::DEFAULT_TARGET_RELPATH};
+use super::{
+    build::do_cached_build,
+    run::{disable_kvm, enable_debug_info, enable_gdb_server},
+    util::DEFAULT_TARGET_RELPATH,
+};
 use crate::{
     base_crate::new_base_crate,
     cli::TestArgs,
-    config::{scheme::ActionChoice, Config},
+    config::{
+        scheme::{Action, ActionChoice},
+        unix_args::split_to_kv_array,
+        Config,
+    },
     util::{
         get_cargo_metadata, get_current_crate_info, get_target_directory, parse_package_id_string,
     },
+    warn_msg,
 };
 
 pub fn execute_test_command(config: &Config, args: &TestArgs) {
+    let record_replay = RecordReplay::from_args(args);
     let crates = get_workspace_default_members();
+    let nr_crates = crates.len();
     for crate_path in crates {
         std::env::set_current_dir(crate_path).unwrap();
-        test_current_crate(config, args);
+        let mut config = config.clone();
+        if let Some(record_replay) = &record_replay {
+            // Each crate is tested by a separate QEMU run, which needs its own file.
+            let crate_name = (nr_crates > 1).then(|| get_current_crate_info().name);
+            record_replay.apply(&mut config.test, crate_name.as_deref(), args);
+        }
+        test_current_crate(&config, args);
     }
 }
 
@@ -89,3 +109,91 @@ fn get_workspace_default_members() -> Vec<String> {
         })
         .collect()
 }
+
+/// The QEMU record/replay mode of the tests.
+///
+/// A recorded execution is replayed deterministically, including the timing
+/// of the interrupts, so that a failure caused by a rare race is reproduced in
+/// every replay. Recording and replaying require the same kernel image and the
+/// same QEMU options, so the tests should be built and run with the same
+/// arguments, except for `--record` and `--replay`.
+struct RecordReplay {
+    is_replay: bool,
+    /// The absolute path of the record file.
+    ///
+    /// QEMU runs in another working directory, so the path must be absolute.
+    path: PathBuf,
+}
+
+impl RecordReplay {
+    fn from_args(args: &TestArgs) -> Option<Self> {
+        let (is_replay, path) = match (&args.record, &args.replay) {
+            (Some(path), _) => (false, path),
+            (None, Some(path)) => (true, path),
+            (None, None) => return None,
+        };
+        let path = std::env::current_dir().unwrap().join(path);
+        Some(Self { is_replay, path })
+    }
+
+    fn apply(&self, action: &mut Action, crate_name: Option<&str>, args: &TestArgs) {
+        let rrfile = match crate_name {
+            Some(crate_name) => append_extension(&self.path, crate_name),
+            None => self.path.clone(),
+        };
+        let mode = if self.is_replay { "replay" } else { "record" };
+        println!(
+            "[OSDK] QEMU will {} the execution in {}",
+            mode,
+            rrfile.display()
+        );
+
+        // The same kernel image must be used in both modes, so the debug info
+        // needed by the replay is also added when recording.
+        enable_debug_info(action);
+        // Record/replay is only supported by TCG.
+        disable_kvm(action, "record/replay mode");
+        warn_unreplayable_devices(action);
+
+        action.qemu.args += &format!(
+            " -icount shift=auto,rr={},rrfile={}",
+            mode,
+            rrfile.display()
+        );
+        if self.is_replay {
+            enable_gdb_server(action, &args.gdb_server_addr);
+        }
+    }
+}
+
+fn append_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    path.into()
+}
+
+/// Warns about the devices whose inputs are not recorded unless they are
+/// specially configured.
+///
+/// See <https://www.qemu.org/docs/master/devel/replay.html> for how to
+/// configure them with `--qemu-args`.
+fn warn_unreplayable_devices(action: &Action) {
+    let args = split_to_kv_array(&action.qemu.args);
+    let has_drive = args.iter().any(|arg| arg.starts_with("-drive"));
+    let has_blkreplay = args.iter().any(|arg| arg.contains("blkreplay"));
+    if has_drive && !has_blkreplay {
+        warn_msg!(
+            "Block devices that are not backed by the 'blkreplay' driver \
+                may make the replay diverge from the record."
+        );
+    }
+    let has_netdev = args.iter().any(|arg| arg.starts_with("-netdev"));
+    let has_net_filter = args.iter().any(|arg| arg.contains("filter-replay"));
+    if has_netdev && !has_net_filter {
+        warn_msg!(
+            "Network devices without a 'filter-replay' object \
+                may make the replay diverge from the record."
+        );
+    }
+}
@@ -187,6 +187,14 @@ fn deserialize_toml_manifest(path: impl AsRef<Path>) -> Option<TomlManifest> {
                     if let Some(ref mut grub_mkrescue_path) = grub.grub_mkrescue {
                         canonicalize(grub_mkrescue_path);
                     }
+                    for entry in grub.menu_entries.iter_mut() {
+                        if let Some(ref mut kernel) = entry.kernel {
+                            canonicalize(kernel);
+                        }
+                        if let Some(ref mut initramfs) = entry.initramfs {
+                            canonicalize(initramfs);
+                        }
+                    }
                 }
             };
         }
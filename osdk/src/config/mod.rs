@@ -45,6 +45,11 @@ fn apply_args_before_finalize(action_scheme: &mut ActionScheme, args: &CommonArg
         if let Some(grub_boot_protocol) = args.grub_boot_protocol {
             grub.boot_protocol = Some(grub_boot_protocol);
         }
+        if let Some(default_entry) = &args.grub_default_entry {
+            grub.default_entry = Some(default_entry.clone());
+        }
+        grub.menu_entries
+            .extend(args.grub_menu_entries.iter().cloned());
     }
 
     if action_scheme.boot.is_none() {
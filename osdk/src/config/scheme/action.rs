@@ -130,9 +130,10 @@ impl ActionScheme {
     }
 
     pub fn finalize(self, arch: Arch) -> Action {
+        let boot = self.boot.unwrap_or_default();
         Action {
-            boot: self.boot.unwrap_or_default().finalize(),
-            grub: self.grub.unwrap_or_default().finalize(),
+            grub: self.grub.unwrap_or_default().finalize(&boot),
+            boot: boot.finalize(),
             qemu: self.qemu.unwrap_or_default().finalize(arch),
             build: self.build.unwrap_or_default().finalize(),
         }
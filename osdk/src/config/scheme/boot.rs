@@ -59,13 +59,18 @@ impl BootScheme {
     }
 
     pub fn finalize(self) -> Boot {
-        let mut kcmdline = self.kcmd_args;
-        kcmdline.push("--".to_owned());
-        kcmdline.extend(self.init_args);
         Boot {
-            kcmdline,
+            kcmdline: make_kcmdline(self.kcmd_args, self.init_args),
             initramfs: self.initramfs,
             method: self.method.unwrap_or(BootMethod::QemuDirect),
         }
     }
 }
+
+/// Makes the full kernel command line, where the arguments after `--` go to the init process.
+pub(super) fn make_kcmdline(kcmd_args: Vec<String>, init_args: Vec<String>) -> Vec<String> {
+    let mut kcmdline = kcmd_args;
+    kcmdline.push("--".to_owned());
+    kcmdline.extend(init_args);
+    kcmdline
+}
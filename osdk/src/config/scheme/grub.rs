@@ -2,7 +2,13 @@
 
 use clap::ValueEnum;
 
-use std::path::PathBuf;
+use std::{path::PathBuf, process};
+
+use super::{boot::make_kcmdline, BootScheme};
+use crate::{error::Errno, error_msg};
+
+/// The title of the GRUB menu entry that boots the kernel being built.
+pub const DEFAULT_GRUB_ENTRY_TITLE: &str = "asterinas";
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GrubScheme {
@@ -13,6 +19,27 @@ pub struct GrubScheme {
     /// Whether to display the GRUB menu, defaults to `false`
     #[serde(default)]
     pub display_grub_menu: bool,
+    /// Additional GRUB menu entries, listed after the entry of the kernel being built
+    #[serde(default)]
+    pub menu_entries: Vec<GrubMenuEntryScheme>,
+    /// The title of the GRUB menu entry to boot by default
+    pub default_entry: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GrubMenuEntryScheme {
+    /// The title of the menu entry, which must be unique
+    pub title: String,
+    /// The path of the kernel image, defaults to the kernel being built
+    pub kernel: Option<PathBuf>,
+    /// Command line arguments for the guest kernel, appended to `boot.kcmd_args`
+    #[serde(default)]
+    pub kcmd_args: Vec<String>,
+    /// Command line arguments for the guest init process, appended to `boot.init_args`
+    #[serde(default)]
+    pub init_args: Vec<String>,
+    /// The path of initramfs, defaults to `boot.initramfs`
+    pub initramfs: Option<PathBuf>,
 }
 
 #[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
@@ -29,6 +56,8 @@ pub struct Grub {
     pub grub_mkrescue: PathBuf,
     pub boot_protocol: BootProtocol,
     pub display_grub_menu: bool,
+    pub menu_entries: Vec<GrubMenuEntry>,
+    pub default_entry: String,
 }
 
 impl Default for Grub {
@@ -37,10 +66,20 @@ impl Default for Grub {
             grub_mkrescue: PathBuf::from("grub-mkrescue"),
             boot_protocol: BootProtocol::default(),
             display_grub_menu: false,
+            menu_entries: Vec::new(),
+            default_entry: DEFAULT_GRUB_ENTRY_TITLE.to_owned(),
         }
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GrubMenuEntry {
+    pub title: String,
+    pub kernel: Option<PathBuf>,
+    pub kcmdline: Vec<String>,
+    pub initramfs: Option<PathBuf>,
+}
+
 impl GrubScheme {
     pub fn inherit(&mut self, from: &Self) {
         if self.grub_mkrescue.is_none() {
@@ -50,13 +89,61 @@ impl GrubScheme {
             self.boot_protocol = from.boot_protocol;
         }
         // `display_grub_menu` is not inherited
+        if self.menu_entries.is_empty() {
+            self.menu_entries.clone_from(&from.menu_entries);
+        }
+        if self.default_entry.is_none() {
+            self.default_entry.clone_from(&from.default_entry);
+        }
     }
 
-    pub fn finalize(self) -> Grub {
+    /// Finalizes the GRUB settings, where the menu entries are based on the `boot` settings.
+    pub fn finalize(self, boot: &BootScheme) -> Grub {
+        let mut titles = vec![DEFAULT_GRUB_ENTRY_TITLE];
+        for entry in &self.menu_entries {
+            if entry.title.contains('\'') {
+                error_msg!("GRUB menu entry title `{}` contains quotes", entry.title);
+                process::exit(Errno::ParseMetadata as _);
+            }
+            if titles.contains(&entry.title.as_str()) {
+                error_msg!("Duplicate GRUB menu entry title `{}`", entry.title);
+                process::exit(Errno::ParseMetadata as _);
+            }
+            titles.push(entry.title.as_str());
+        }
+
+        let default_entry = self
+            .default_entry
+            .unwrap_or_else(|| DEFAULT_GRUB_ENTRY_TITLE.to_owned());
+        if !titles.contains(&default_entry.as_str()) {
+            error_msg!(
+                "The default GRUB menu entry `{}` does not exist",
+                default_entry
+            );
+            process::exit(Errno::ParseMetadata as _);
+        }
+
+        let menu_entries = self
+            .menu_entries
+            .into_iter()
+            .map(|entry| {
+                let kcmd_args = [boot.kcmd_args.as_slice(), &entry.kcmd_args].concat();
+                let init_args = [boot.init_args.as_slice(), &entry.init_args].concat();
+                GrubMenuEntry {
+                    title: entry.title,
+                    kernel: entry.kernel,
+                    kcmdline: make_kcmdline(kcmd_args, init_args),
+                    initramfs: entry.initramfs.or_else(|| boot.initramfs.clone()),
+                }
+            })
+            .collect();
+
         Grub {
             grub_mkrescue: self.grub_mkrescue.unwrap_or(PathBuf::from("grub-mkrescue")),
             boot_protocol: self.boot_protocol.unwrap_or(BootProtocol::Multiboot2),
             display_grub_menu: self.display_grub_menu,
+            menu_entries,
+            default_entry,
         }
     }
 }
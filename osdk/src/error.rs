@@ -10,6 +10,7 @@ pub enum Errno {
     ExecuteCommand = 5,
     BuildCrate = 6,
     RunBundle = 7,
+    AddComponent = 8,
 }
 
 /// Print error message to console
@@ -16,6 +16,13 @@ fn cli_new_help_message() {
     assert_stdout_contains_msg(&output, "cargo osdk new [OPTIONS] <name>");
 }
 
+#[test]
+fn cli_add_component_help_message() {
+    let output = cargo_osdk(&["add-component", "-h"]).output().unwrap();
+    assert_success(&output);
+    assert_stdout_contains_msg(&output, "cargo osdk add-component [OPTIONS] <source>");
+}
+
 #[test]
 fn cli_build_help_message() {
     let output = cargo_osdk(&["build", "-h"]).output().unwrap();
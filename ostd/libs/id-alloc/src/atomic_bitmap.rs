@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    fmt::Debug,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+const BITS_PER_WORD: usize = u64::BITS as usize;
+
+/// A bitmap with a fixed number of bits that can be accessed concurrently.
+///
+/// Each bit is updated atomically, which allows, e.g., allocating IDs from
+/// the bitmap without a lock. Operations that touch more than one bit are
+/// not atomic as a whole.
+pub struct AtomicBitmap {
+    words: Box<[AtomicU64]>,
+    len: usize,
+}
+
+impl AtomicBitmap {
+    /// Creates a bitmap of `len` bits, which are all cleared.
+    pub fn new(len: usize) -> Self {
+        let words = (0..len.div_ceil(BITS_PER_WORD))
+            .map(|_| AtomicU64::new(0))
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        Self { words, len }
+    }
+
+    /// Returns the number of bits in the bitmap.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the bitmap has no bits at all.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the value of the bit at `index`.
+    ///
+    /// # Panics
+    ///
+    /// If the `index` is out of bounds, this method will panic.
+    pub fn get(&self, index: usize) -> bool {
+        assert!(index < self.len);
+        self.word_of(index).load(Ordering::Acquire) & bit_mask(index) != 0
+    }
+
+    /// Sets the bit at `index` and returns its old value.
+    ///
+    /// # Panics
+    ///
+    /// If the `index` is out of bounds, this method will panic.
+    pub fn test_and_set(&self, index: usize) -> bool {
+        assert!(index < self.len);
+        self.word_of(index)
+            .fetch_or(bit_mask(index), Ordering::AcqRel)
+            & bit_mask(index)
+            != 0
+    }
+
+    /// Clears the bit at `index` and returns its old value.
+    ///
+    /// # Panics
+    ///
+    /// If the `index` is out of bounds, this method will panic.
+    pub fn test_and_clear(&self, index: usize) -> bool {
+        assert!(index < self.len);
+        self.word_of(index)
+            .fetch_and(!bit_mask(index), Ordering::AcqRel)
+            & bit_mask(index)
+            != 0
+    }
+
+    /// Returns the index of the first cleared bit at or after `from`.
+    ///
+    /// The result may be stale as soon as it is returned if the bitmap is
+    /// modified concurrently.
+    pub fn find_next_zero(&self, from: usize) -> Option<usize> {
+        self.find_next(from, true)
+    }
+
+    /// Returns the index of the first set bit at or after `from`.
+    ///
+    /// The result may be stale as soon as it is returned if the bitmap is
+    /// modified concurrently.
+    pub fn find_next_one(&self, from: usize) -> Option<usize> {
+        self.find_next(from, false)
+    }
+
+    /// Finds a cleared bit at or after `from` and sets it atomically.
+    ///
+    /// Returns the index of the bit, or `None` if all the bits at or after
+    /// `from` are set.
+    pub fn set_next_zero(&self, from: usize) -> Option<usize> {
+        let mut from = from;
+        loop {
+            let index = self.find_next_zero(from)?;
+            if !self.test_and_set(index) {
+                return Some(index);
+            }
+            // Someone else has taken the bit. Try the next one.
+            from = index + 1;
+        }
+    }
+
+    /// Returns the number of set bits.
+    ///
+    /// The result is only a snapshot if the bitmap is modified concurrently.
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.load(Ordering::Relaxed).count_ones() as usize)
+            .sum()
+    }
+
+    fn find_next(&self, from: usize, find_zero: bool) -> Option<usize> {
+        if from >= self.len {
+            return None;
+        }
+
+        let flip = if find_zero { u64::MAX } else { 0 };
+        let first_word = from / BITS_PER_WORD;
+        // Mask out the bits before `from` in the first word.
+        let first_mask = u64::MAX << (from % BITS_PER_WORD);
+
+        let index = self.words[first_word..]
+            .iter()
+            .enumerate()
+            .find_map(|(i, word)| {
+                let mut word = word.load(Ordering::Acquire) ^ flip;
+                if i == 0 {
+                    word &= first_mask;
+                }
+                (word != 0)
+                    .then(|| (first_word + i) * BITS_PER_WORD + word.trailing_zeros() as usize)
+            })?;
+        // The unused bits, which are never set, may be found when searching for zeros.
+        (index < self.len).then_some(index)
+    }
+
+    fn word_of(&self, index: usize) -> &AtomicU64 {
+        &self.words[index / BITS_PER_WORD]
+    }
+}
+
+impl Debug for AtomicBitmap {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("AtomicBitmap")
+            .field("len", &self.len)
+            .field("num_ones", &self.count_ones())
+            .finish()
+    }
+}
+
+fn bit_mask(index: usize) -> u64 {
+    1 << (index % BITS_PER_WORD)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_set_next_zero() {
+        let bitmap = AtomicBitmap::new(70);
+        for i in 0..70 {
+            assert_eq!(bitmap.set_next_zero(0), Some(i));
+        }
+        assert_eq!(bitmap.set_next_zero(0), None);
+        assert_eq!(bitmap.count_ones(), 70);
+
+        assert!(bitmap.test_and_clear(65));
+        assert!(!bitmap.test_and_clear(65));
+        assert_eq!(bitmap.find_next_zero(0), Some(65));
+        assert_eq!(bitmap.set_next_zero(66), None);
+        assert_eq!(bitmap.set_next_zero(3), Some(65));
+    }
+}
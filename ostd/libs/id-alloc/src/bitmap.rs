@@ -0,0 +1,460 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::{vec, vec::Vec};
+use core::{fmt::Debug, ops::Range};
+
+const BITS_PER_BYTE: usize = u8::BITS as usize;
+const BITS_PER_WORD: usize = u64::BITS as usize;
+const BYTES_PER_WORD: usize = BITS_PER_WORD / BITS_PER_BYTE;
+
+/// A bitmap with a fixed number of bits, which can be resized on demand.
+///
+/// The bits are stored in bytes, with bit `i` being the `(i % 8)`-th least
+/// significant bit of the `(i / 8)`-th byte. This is the layout used by the
+/// on-disk bitmaps of file systems like ext2 and exFAT, so a bitmap can be
+/// loaded from and written back to the disk as is.
+///
+/// Besides the operations on individual bits, the bitmap supports searching
+/// for set or cleared bits and set operations between bitmaps, in the style of
+/// Linux's `cpumask`.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Bitmap {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl Bitmap {
+    /// Creates a bitmap of `len` bits, which are all cleared.
+    pub fn new(len: usize) -> Self {
+        Self {
+            bytes: vec![0; len.div_ceil(BITS_PER_BYTE)],
+            len,
+        }
+    }
+
+    /// Creates a bitmap of `len` bits, which are all set.
+    pub fn new_full(len: usize) -> Self {
+        let mut bitmap = Self::new(len);
+        bitmap.fill(true);
+        bitmap
+    }
+
+    /// Creates a bitmap of `len` bits from the raw bytes of a bitmap.
+    ///
+    /// If `bytes` has fewer than `len` bits, the remaining bits are cleared.
+    /// If `bytes` has more, the extra bits are ignored.
+    pub fn from_bytes(bytes: &[u8], len: usize) -> Self {
+        let mut bitmap = Self::new(len);
+        let num_bytes = bitmap.bytes.len().min(bytes.len());
+        bitmap.bytes[..num_bytes].copy_from_slice(&bytes[..num_bytes]);
+        bitmap.clear_unused_bits();
+        bitmap
+    }
+
+    /// Returns the number of bits in the bitmap.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the bitmap has no bits at all.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Resizes the bitmap to `new_len` bits.
+    ///
+    /// The new bits, if any, are set to `value`.
+    pub fn resize(&mut self, new_len: usize, value: bool) {
+        let old_len = self.len;
+        self.bytes.resize(new_len.div_ceil(BITS_PER_BYTE), 0);
+        self.len = new_len;
+        if new_len > old_len {
+            self.set_range(old_len..new_len, value);
+        } else {
+            self.clear_unused_bits();
+        }
+    }
+
+    /// Returns the value of the bit at `index`.
+    ///
+    /// # Panics
+    ///
+    /// If the `index` is out of bounds, this method will panic.
+    pub fn get(&self, index: usize) -> bool {
+        assert!(index < self.len);
+        self.bytes[index / BITS_PER_BYTE] & bit_mask(index) != 0
+    }
+
+    /// Sets the bit at `index` to `value`.
+    ///
+    /// # Panics
+    ///
+    /// If the `index` is out of bounds, this method will panic.
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(index < self.len);
+        let byte = &mut self.bytes[index / BITS_PER_BYTE];
+        if value {
+            *byte |= bit_mask(index);
+        } else {
+            *byte &= !bit_mask(index);
+        }
+    }
+
+    /// Sets the bits within `range` to `value`.
+    ///
+    /// # Panics
+    ///
+    /// If the `range` is out of bounds, this method will panic.
+    pub fn set_range(&mut self, range: Range<usize>, value: bool) {
+        assert!(range.start <= range.end && range.end <= self.len);
+
+        let mut index = range.start;
+        while index < range.end {
+            // Handle whole bytes at once when possible.
+            if index % BITS_PER_BYTE == 0 && range.end - index >= BITS_PER_BYTE {
+                let num_bytes = (range.end - index) / BITS_PER_BYTE;
+                let start = index / BITS_PER_BYTE;
+                self.bytes[start..start + num_bytes].fill(if value { u8::MAX } else { 0 });
+                index += num_bytes * BITS_PER_BYTE;
+            } else {
+                self.set(index, value);
+                index += 1;
+            }
+        }
+    }
+
+    /// Sets all the bits to `value`.
+    pub fn fill(&mut self, value: bool) {
+        self.bytes.fill(if value { u8::MAX } else { 0 });
+        self.clear_unused_bits();
+    }
+
+    /// Returns whether all the bits within `range` are cleared.
+    ///
+    /// # Panics
+    ///
+    /// If the `range` is out of bounds, this method will panic.
+    pub fn is_range_clear(&self, range: Range<usize>) -> bool {
+        assert!(range.start <= range.end && range.end <= self.len);
+        !matches!(self.find_next_one(range.start), Some(index) if index < range.end)
+    }
+
+    /// Returns the index of the first set bit.
+    pub fn find_first_one(&self) -> Option<usize> {
+        self.find_next_one(0)
+    }
+
+    /// Returns the index of the first set bit at or after `from`.
+    pub fn find_next_one(&self, from: usize) -> Option<usize> {
+        self.find_next(from, false)
+    }
+
+    /// Returns the index of the first cleared bit.
+    pub fn find_first_zero(&self) -> Option<usize> {
+        self.find_next_zero(0)
+    }
+
+    /// Returns the index of the first cleared bit at or after `from`.
+    pub fn find_next_zero(&self, from: usize) -> Option<usize> {
+        self.find_next(from, true)
+    }
+
+    /// Returns the start of the first `count` consecutive cleared bits at or
+    /// after `from`.
+    ///
+    /// If `count` is zero, `None` is returned.
+    pub fn find_next_zero_area(&self, from: usize, count: usize) -> Option<usize> {
+        if count == 0 {
+            return None;
+        }
+
+        let mut start = self.find_next_zero(from)?;
+        loop {
+            let end = start.checked_add(count)?;
+            if end > self.len {
+                return None;
+            }
+            match self.find_next_one(start) {
+                Some(one) if one < end => start = self.find_next_zero(one)?,
+                _ => return Some(start),
+            }
+        }
+    }
+
+    /// Returns the number of set bits.
+    pub fn count_ones(&self) -> usize {
+        // The unused bits are always cleared.
+        self.bytes
+            .iter()
+            .map(|byte| byte.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns the number of cleared bits.
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// Returns whether any bit is set.
+    pub fn any(&self) -> bool {
+        self.bytes.iter().any(|&byte| byte != 0)
+    }
+
+    /// Returns whether all the bits are set.
+    pub fn all(&self) -> bool {
+        self.find_first_zero().is_none()
+    }
+
+    /// Returns an iterator over the indexes of the set bits.
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut next = 0;
+        core::iter::from_fn(move || {
+            let index = self.find_next_one(next)?;
+            next = index + 1;
+            Some(index)
+        })
+    }
+
+    /// Returns an iterator over the indexes of the cleared bits.
+    pub fn iter_zeros(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut next = 0;
+        core::iter::from_fn(move || {
+            let index = self.find_next_zero(next)?;
+            next = index + 1;
+            Some(index)
+        })
+    }
+
+    /// Keeps only the bits that are also set in `other`.
+    ///
+    /// # Panics
+    ///
+    /// If the two bitmaps have different lengths, this method will panic.
+    pub fn and(&mut self, other: &Bitmap) {
+        self.combine(other, |a, b| a & b);
+    }
+
+    /// Sets the bits that are set in `other`.
+    ///
+    /// # Panics
+    ///
+    /// If the two bitmaps have different lengths, this method will panic.
+    pub fn or(&mut self, other: &Bitmap) {
+        self.combine(other, |a, b| a | b);
+    }
+
+    /// Flips the bits that are set in `other`.
+    ///
+    /// # Panics
+    ///
+    /// If the two bitmaps have different lengths, this method will panic.
+    pub fn xor(&mut self, other: &Bitmap) {
+        self.combine(other, |a, b| a ^ b);
+    }
+
+    /// Clears the bits that are set in `other`.
+    ///
+    /// # Panics
+    ///
+    /// If the two bitmaps have different lengths, this method will panic.
+    pub fn and_not(&mut self, other: &Bitmap) {
+        self.combine(other, |a, b| a & !b);
+    }
+
+    /// Flips all the bits.
+    pub fn complement(&mut self) {
+        self.bytes.iter_mut().for_each(|byte| *byte = !*byte);
+        self.clear_unused_bits();
+    }
+
+    /// Returns whether the two bitmaps have any set bit in common.
+    ///
+    /// # Panics
+    ///
+    /// If the two bitmaps have different lengths, this method will panic.
+    pub fn intersects(&self, other: &Bitmap) -> bool {
+        assert_eq!(self.len, other.len);
+        self.bytes
+            .iter()
+            .zip(other.bytes.iter())
+            .any(|(a, b)| a & b != 0)
+    }
+
+    /// Returns whether all the set bits of `self` are also set in `other`.
+    ///
+    /// # Panics
+    ///
+    /// If the two bitmaps have different lengths, this method will panic.
+    pub fn is_subset(&self, other: &Bitmap) -> bool {
+        assert_eq!(self.len, other.len);
+        self.bytes
+            .iter()
+            .zip(other.bytes.iter())
+            .all(|(a, b)| a & !b == 0)
+    }
+
+    /// Views the bitmap as a slice of `u8` bytes.
+    ///
+    /// The unused bits in the last byte are always cleared.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn find_next(&self, from: usize, find_zero: bool) -> Option<usize> {
+        if from >= self.len {
+            return None;
+        }
+
+        // Scan a word of bytes at a time instead of a byte at a time.
+        let flip = if find_zero { u64::MAX } else { 0 };
+        let first_word = from / BITS_PER_WORD;
+        // Mask out the bits before `from` in the first word.
+        let first_mask = u64::MAX << (from % BITS_PER_WORD);
+
+        let index = self.bytes[first_word * BYTES_PER_WORD..]
+            .chunks(BYTES_PER_WORD)
+            .enumerate()
+            .find_map(|(i, bytes)| {
+                let mut word = load_word(bytes) ^ flip;
+                if i == 0 {
+                    word &= first_mask;
+                }
+                (word != 0)
+                    .then(|| (first_word + i) * BITS_PER_WORD + word.trailing_zeros() as usize)
+            })?;
+        // The unused bits, which are cleared, may be found when searching for zeros.
+        (index < self.len).then_some(index)
+    }
+
+    fn combine(&mut self, other: &Bitmap, op: impl Fn(u8, u8) -> u8) {
+        assert_eq!(self.len, other.len);
+        for (a, &b) in self.bytes.iter_mut().zip(other.bytes.iter()) {
+            *a = op(*a, b);
+        }
+        self.clear_unused_bits();
+    }
+
+    fn clear_unused_bits(&mut self) {
+        let num_used_bits = self.len % BITS_PER_BYTE;
+        if num_used_bits != 0 {
+            let last = self.bytes.last_mut().unwrap();
+            *last &= (1 << num_used_bits) - 1;
+        }
+    }
+}
+
+impl Debug for Bitmap {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Bitmap")
+            .field("len", &self.len)
+            .field("num_ones", &self.count_ones())
+            .finish()
+    }
+}
+
+fn bit_mask(index: usize) -> u8 {
+    1 << (index % BITS_PER_BYTE)
+}
+
+/// Loads the bytes of a word in the little-endian order, which keeps the
+/// indexes of the bits. The missing bytes at the end are zeros.
+fn load_word(bytes: &[u8]) -> u64 {
+    let mut word = [0u8; BYTES_PER_WORD];
+    word[..bytes.len()].copy_from_slice(bytes);
+    u64::from_le_bytes(word)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        let mut bitmap = Bitmap::new(100);
+        assert_eq!(bitmap.find_first_one(), None);
+        assert_eq!(bitmap.find_first_zero(), Some(0));
+
+        bitmap.set(3, true);
+        bitmap.set(64, true);
+        bitmap.set(99, true);
+        assert_eq!(bitmap.find_first_one(), Some(3));
+        assert_eq!(bitmap.find_next_one(4), Some(64));
+        assert_eq!(bitmap.find_next_one(65), Some(99));
+        assert_eq!(bitmap.find_next_one(100), None);
+        assert_eq!(bitmap.iter_ones().collect::<Vec<_>>(), vec![3, 64, 99]);
+
+        bitmap.set_range(0..99, true);
+        assert_eq!(bitmap.find_first_zero(), None);
+        assert!(bitmap.all());
+        bitmap.set(50, false);
+        assert_eq!(bitmap.find_next_zero(10), Some(50));
+        assert_eq!(bitmap.find_next_zero(51), None);
+        assert_eq!(bitmap.count_zeros(), 1);
+    }
+
+    #[test]
+    fn test_find_across_words() {
+        let mut bitmap = Bitmap::new(200);
+        bitmap.set(130, true);
+        assert_eq!(bitmap.find_next_one(70), Some(130));
+        assert_eq!(bitmap.find_next_one(130), Some(130));
+        assert_eq!(bitmap.find_next_one(131), None);
+
+        bitmap.fill(true);
+        bitmap.set(199, false);
+        assert_eq!(bitmap.find_next_zero(65), Some(199));
+        bitmap.set(199, true);
+        assert_eq!(bitmap.find_next_zero(0), None);
+    }
+
+    #[test]
+    fn test_find_zero_area() {
+        let mut bitmap = Bitmap::new(40);
+        bitmap.set_range(0..40, true);
+        bitmap.set_range(3..5, false);
+        bitmap.set_range(10..20, false);
+        bitmap.set_range(30..40, false);
+
+        assert_eq!(bitmap.find_next_zero_area(0, 2), Some(3));
+        assert_eq!(bitmap.find_next_zero_area(0, 3), Some(10));
+        assert_eq!(bitmap.find_next_zero_area(12, 8), Some(12));
+        assert_eq!(bitmap.find_next_zero_area(12, 9), Some(30));
+        assert_eq!(bitmap.find_next_zero_area(0, 11), None);
+        assert_eq!(bitmap.find_next_zero_area(0, 0), None);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let mut a = Bitmap::new(10);
+        let mut b = Bitmap::new(10);
+        a.set_range(0..6, true);
+        b.set_range(4..10, true);
+        assert!(a.intersects(&b));
+        assert!(!a.is_subset(&b));
+
+        let mut c = a.clone();
+        c.and(&b);
+        assert_eq!(c.iter_ones().collect::<Vec<_>>(), vec![4, 5]);
+        assert!(c.is_subset(&a) && c.is_subset(&b));
+
+        let mut d = a.clone();
+        d.and_not(&b);
+        assert_eq!(d.iter_ones().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+
+        d.complement();
+        assert_eq!(d.iter_ones().collect::<Vec<_>>(), vec![4, 5, 6, 7, 8, 9]);
+        assert_eq!(d.as_bytes(), &[0xf0, 0x03]);
+    }
+
+    #[test]
+    fn test_bytes_and_resize() {
+        let mut bitmap = Bitmap::from_bytes(&[0xff, 0xff], 12);
+        assert_eq!(bitmap.count_ones(), 12);
+        assert_eq!(bitmap.as_bytes(), &[0xff, 0x0f]);
+
+        bitmap.resize(20, false);
+        assert_eq!(bitmap.find_first_zero(), Some(12));
+        bitmap.resize(4, false);
+        assert_eq!(bitmap.as_bytes(), &[0x0f]);
+    }
+}
@@ -3,25 +3,30 @@
 #![cfg_attr(not(test), no_std)]
 #![deny(unsafe_code)]
 
+//! Bitmaps and the ID allocator built on them.
+
+extern crate alloc;
+
+mod atomic_bitmap;
+mod bitmap;
+
 use core::{fmt::Debug, ops::Range};
 
-use bitvec::prelude::BitVec;
+pub use self::{atomic_bitmap::AtomicBitmap, bitmap::Bitmap};
 
 /// An id allocator implemented by the bitmap.
 /// The true bit implies that the id is allocated, and vice versa.
 #[derive(Clone)]
 pub struct IdAlloc {
-    bitset: BitVec<u8>,
+    bitmap: Bitmap,
     first_available_id: usize,
 }
 
 impl IdAlloc {
     /// Constructs a new id allocator with a maximum capacity.
     pub fn with_capacity(capacity: usize) -> Self {
-        let mut bitset = BitVec::with_capacity(capacity);
-        bitset.resize(capacity, false);
         Self {
-            bitset,
+            bitmap: Bitmap::new(capacity),
             first_available_id: 0,
         }
     }
@@ -30,36 +35,28 @@ impl IdAlloc {
     ///
     /// The slice of `u8` bytes is the raw data of a bitmap.
     pub fn from_bytes_with_capacity(slice: &[u8], capacity: usize) -> Self {
-        let bitset = if capacity > slice.len() * 8 {
-            let mut bitset = BitVec::from_slice(slice);
-            bitset.resize(capacity, false);
-            bitset
-        } else {
-            let mut bitset = BitVec::from_slice(&slice[..capacity.div_ceil(8)]);
-            bitset.truncate(capacity);
-            bitset
-        };
-
-        let first_available_id = (0..bitset.len())
-            .find(|&i| !bitset[i])
-            .map_or(bitset.len(), |i| i);
+        let bitmap = Bitmap::from_bytes(slice, capacity);
+        let first_available_id = bitmap.find_first_zero().unwrap_or(capacity);
 
         Self {
-            bitset,
+            bitmap,
             first_available_id,
         }
     }
 
+    /// Returns the maximum number of `id`s that can be allocated.
+    pub fn capacity(&self) -> usize {
+        self.bitmap.len()
+    }
+
     /// Allocates and returns a new `id`.
     ///
     /// If allocation is not possible, it returns `None`.
     pub fn alloc(&mut self) -> Option<usize> {
-        if self.first_available_id < self.bitset.len() {
+        if self.first_available_id < self.bitmap.len() {
             let id = self.first_available_id;
-            self.bitset.set(id, true);
-            self.first_available_id = (id + 1..self.bitset.len())
-                .find(|&i| !self.bitset[i])
-                .map_or(self.bitset.len(), |i| i);
+            self.bitmap.set(id, true);
+            self.first_available_id = self.next_available_id(id + 1);
             Some(id)
         } else {
             None
@@ -71,43 +68,16 @@ impl IdAlloc {
     /// The `count` is the number of consecutive `id`s to allocate. If it is 0, return `None`.
     ///
     /// If allocation is not possible, it returns `None`.
-    ///
-    /// TODO: Choose a more efficient strategy.
     pub fn alloc_consecutive(&mut self, count: usize) -> Option<Range<usize>> {
-        if count == 0 {
-            return None;
-        }
-
-        // Scan the bitmap from the position `first_available_id`
-        // for the first `count` number of consecutive 0's.
-        let allocated_range = {
-            // Invariance: all bits within `curr_range` are 0's
-            let mut curr_range = self.first_available_id..self.first_available_id + 1;
-            while curr_range.len() < count && curr_range.end < self.bitset.len() {
-                if !self.is_allocated(curr_range.end) {
-                    curr_range.end += 1;
-                } else {
-                    curr_range = curr_range.end + 1..curr_range.end + 1;
-                }
-            }
-
-            if curr_range.len() < count {
-                return None;
-            }
-
-            curr_range
-        };
-
-        // Set every bit to 1 within the allocated range
-        for id in allocated_range.clone() {
-            self.bitset.set(id, true);
-        }
+        let start = self
+            .bitmap
+            .find_next_zero_area(self.first_available_id, count)?;
+        let allocated_range = start..start + count;
+        self.bitmap.set_range(allocated_range.clone(), true);
 
         // In case we need to update first_available_id
-        if self.is_allocated(self.first_available_id) {
-            self.first_available_id = (allocated_range.end..self.bitset.len())
-                .find(|&i| !self.bitset[i])
-                .map_or(self.bitset.len(), |i| i);
+        if start == self.first_available_id {
+            self.first_available_id = self.next_available_id(allocated_range.end);
         }
 
         Some(allocated_range)
@@ -124,10 +94,8 @@ impl IdAlloc {
         }
 
         let range_start = range.start;
-        for id in range {
-            debug_assert!(self.is_allocated(id));
-            self.bitset.set(id, false);
-        }
+        debug_assert!(range.clone().all(|id| self.is_allocated(id)));
+        self.bitmap.set_range(range, false);
 
         if range_start < self.first_available_id {
             self.first_available_id = range_start
@@ -142,7 +110,7 @@ impl IdAlloc {
     pub fn free(&mut self, id: usize) {
         debug_assert!(self.is_allocated(id));
 
-        self.bitset.set(id, false);
+        self.bitmap.set(id, false);
         if id < self.first_available_id {
             self.first_available_id = id;
         }
@@ -157,14 +125,12 @@ impl IdAlloc {
     ///
     /// If the `id` is out of bounds, this method will panic.
     pub fn alloc_specific(&mut self, id: usize) -> Option<usize> {
-        if self.bitset[id] {
+        if self.bitmap.get(id) {
             return None;
         }
-        self.bitset.set(id, true);
+        self.bitmap.set(id, true);
         if id == self.first_available_id {
-            self.first_available_id = (id + 1..self.bitset.len())
-                .find(|&i| !self.bitset[i])
-                .map_or(self.bitset.len(), |i| i);
+            self.first_available_id = self.next_available_id(id + 1);
         }
         Some(id)
     }
@@ -175,19 +141,25 @@ impl IdAlloc {
     ///
     /// If the `id` is out of bounds, this method will panic.
     pub fn is_allocated(&self, id: usize) -> bool {
-        self.bitset[id]
+        self.bitmap.get(id)
     }
 
     /// Views the id allocator as a slice of `u8` bytes.
     pub fn as_bytes(&self) -> &[u8] {
-        self.bitset.as_raw_slice()
+        self.bitmap.as_bytes()
+    }
+
+    fn next_available_id(&self, from: usize) -> usize {
+        self.bitmap
+            .find_next_zero(from)
+            .unwrap_or(self.bitmap.len())
     }
 }
 
 impl Debug for IdAlloc {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         f.debug_struct("IdAlloc")
-            .field("len", &self.bitset.len())
+            .field("len", &self.bitmap.len())
             .field("first_available_id", &self.first_available_id)
             .finish()
     }
@@ -12,13 +12,17 @@
 //!
 //! The setup code should be built into the ELF target and we convert it to a flat binary
 //! in the builder.
+//!
+//! The builder can also embed the SHA-256 digest of the initramfs in the kernel ELF, which
+//! the kernel checks before unpacking the initramfs. Since the kernel ELF is the payload of
+//! the bzImage, signing the bzImage covers the initramfs as well.
 
 mod mapping;
 mod pe_header;
 
 use std::{
     fs::File,
-    io::{Read, Seek, SeekFrom, Write},
+    io::{Read, Write},
     path::Path,
 };
 
@@ -39,13 +43,16 @@ pub enum BzImageType {
 ///  - `target_image_path`: The path to the target bzImage;
 ///  - `image_type`: The type of the bzImage that we are building;
 ///  - `kernel_path`: The path to the kernel ELF;
-///  - `setup_elf_path`: The path to the setup ELF.
+///  - `setup_elf_path`: The path to the setup ELF;
+///  - `initramfs_digest`: The SHA-256 digest of the initramfs that the kernel should verify,
+///    or `None` if the initramfs should not be verified.
 ///
 pub fn make_bzimage(
     target_image_path: &Path,
     image_type: BzImageType,
     kernel_path: &Path,
     setup_elf_path: &Path,
+    initramfs_digest: Option<&[u8; 32]>,
 ) {
     let mut setup_elf = Vec::new();
     File::open(setup_elf_path)
@@ -61,6 +68,9 @@ pub fn make_bzimage(
         .unwrap()
         .read_to_end(&mut kernel)
         .unwrap();
+    if let Some(digest) = initramfs_digest {
+        fill_initramfs_digest(&mut kernel, digest);
+    }
     let payload = kernel;
 
     let setup_len = setup.len();
@@ -68,9 +78,8 @@ pub fn make_bzimage(
     let payload_offset = SetupFileOffset::from(setup_len);
     fill_legacy_header_fields(&mut setup, payload_len, setup_len, payload_offset.into());
 
-    let mut kernel_image = File::create(target_image_path).unwrap();
-    kernel_image.write_all(&setup).unwrap();
-    kernel_image.write_all(&payload).unwrap();
+    let mut image = setup;
+    image.extend_from_slice(&payload);
 
     let image_size = setup_len + payload_len;
 
@@ -84,13 +93,19 @@ pub fn make_bzimage(
             "PE/COFF header is too large"
         );
 
-        kernel_image.seek(SeekFrom::Start(0)).unwrap();
-        kernel_image.write_all(&pe_header.header_at_zero).unwrap();
-        kernel_image
-            .seek(SeekFrom::Start(usize::from(pe_header.relocs.0) as u64))
-            .unwrap();
-        kernel_image.write_all(&pe_header.relocs.1).unwrap();
+        image[..pe_header.header_at_zero.len()].copy_from_slice(&pe_header.header_at_zero);
+        let (reloc_offset, relocs) = pe_header.relocs;
+        let reloc_offset = usize::from(reloc_offset);
+        image[reloc_offset..reloc_offset + relocs.len()].copy_from_slice(&relocs);
+
+        // Signing tools such as `sbsign` append the signature to the end of the image,
+        // which must be 8-byte aligned.
+        image.resize(image.len().next_multiple_of(8), 0x00);
+        pe_header::fill_pe_checksum(&mut image);
     }
+
+    let mut kernel_image = File::create(target_image_path).unwrap();
+    kernel_image.write_all(&image).unwrap();
 }
 
 /// To build the legacy32 bzImage setup header, the OSDK should use this target.
@@ -128,6 +143,42 @@ fn to_flat_binary(elf_file: &[u8]) -> Vec<u8> {
     bin
 }
 
+/// The name of the kernel ELF section that holds the digest of the initramfs.
+///
+/// The section is defined in `ostd/src/arch/x86/boot/linux_boot/initramfs_digest.rs`,
+/// which must be kept in sync with the section name, the tags and the layout here.
+const INITRAMFS_DIGEST_SECTION: &str = ".initramfs_digest";
+/// The tag of the digest before it is filled.
+const INITRAMFS_DIGEST_TAG_NONE: &[u8; 8] = b"NODIGEST";
+/// The tag of a SHA-256 digest, which is followed by the 32-byte digest.
+const INITRAMFS_DIGEST_TAG_SHA256: &[u8; 8] = b"SHA-256\0";
+
+/// Fills the SHA-256 digest of the initramfs into the kernel ELF.
+fn fill_initramfs_digest(kernel: &mut [u8], digest: &[u8; 32]) {
+    let (offset, size) = {
+        let elf = xmas_elf::ElfFile::new(kernel).unwrap();
+        let section = elf
+            .find_section_by_name(INITRAMFS_DIGEST_SECTION)
+            .expect("the kernel does not have the initramfs digest section");
+        (section.offset() as usize, section.size() as usize)
+    };
+
+    let tag_len = INITRAMFS_DIGEST_TAG_NONE.len();
+    assert_eq!(
+        size,
+        tag_len + digest.len(),
+        "the initramfs digest section has an unexpected size"
+    );
+    let section = &mut kernel[offset..offset + size];
+    assert_eq!(
+        &section[..tag_len],
+        INITRAMFS_DIGEST_TAG_NONE,
+        "the initramfs digest has already been filled"
+    );
+    section[..tag_len].copy_from_slice(INITRAMFS_DIGEST_TAG_SHA256);
+    section[tag_len..].copy_from_slice(digest);
+}
+
 /// This function should be used when generating the Linux x86 Boot setup header.
 /// Some fields in the Linux x86 Boot setup header should be filled after assembled.
 /// And the filled fields must have the bytes with values of 0xAB. See
@@ -8,7 +8,10 @@
 //! The reference to the Linux PE header definition:
 //! <https://github.com/torvalds/linux/blob/master/include/linux/pe.h>
 
-use std::{mem::size_of, ops::Range};
+use std::{
+    mem::{offset_of, size_of},
+    ops::Range,
+};
 
 use bytemuck::{Pod, Zeroable};
 use serde::Serialize;
@@ -460,3 +463,33 @@ pub(crate) fn make_pe_coff_header(setup_elf: &[u8], image_size: usize) -> ImageP
         relocs: (reloc_offset, relocs),
     }
 }
+
+/// Fills the checksum field of the PE32+ optional header in the image.
+///
+/// The checksum is verified by some firmware and signing tools. It must be
+/// computed after all the other contents of the image are finalized.
+pub(crate) fn fill_pe_checksum(image: &mut [u8]) {
+    let pe_hdr_offset = u32::from_le_bytes(image[0x3c..0x40].try_into().unwrap()) as usize;
+    let csum_offset = pe_hdr_offset + size_of::<PeHdr>() + offset_of!(Pe32PlusOptHdr, csum);
+
+    let csum = pe_checksum(image, csum_offset);
+    image[csum_offset..csum_offset + size_of::<u32>()].copy_from_slice(&csum.to_le_bytes());
+}
+
+/// Computes the PE checksum, which is the folded 16-bit one's complement sum of the
+/// image, excluding the checksum field, plus the length of the image.
+fn pe_checksum(image: &[u8], csum_offset: usize) -> u32 {
+    let mut sum: u32 = 0;
+    for (index, word) in image.chunks(2).enumerate() {
+        let offset = index * 2;
+        if (csum_offset..csum_offset + size_of::<u32>()).contains(&offset) {
+            continue;
+        }
+        let word = u16::from_le_bytes([word[0], word.get(1).copied().unwrap_or(0)]);
+        sum += word as u32;
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    sum = (sum & 0xffff) + (sum >> 16);
+
+    sum + image.len() as u32
+}
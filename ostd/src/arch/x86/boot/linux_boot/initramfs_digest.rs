@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The digest of the initramfs that is embedded in the kernel image.
+//!
+//! The bzImage builder fills in the SHA-256 digest of the initramfs that the image is
+//! installed with. The kernel is the payload of the bzImage, so the digest is covered by
+//! the signature of a signed bzImage, and checking the initramfs against the digest before
+//! it is unpacked extends the verified boot chain to the initramfs.
+
+use super::sha256::sha256;
+
+/// The tag of a digest that is not filled in, in which case the initramfs is not verified.
+///
+/// The tags and the layout of [`InitramfsDigest`] must be kept the same as the ones in the
+/// bzImage builder (`ostd/libs/linux-bzimage/builder`).
+const TAG_NONE: [u8; 8] = *b"NODIGEST";
+/// The tag of a SHA-256 digest.
+const TAG_SHA256: [u8; 8] = *b"SHA-256\0";
+
+#[repr(C)]
+struct InitramfsDigest {
+    tag: [u8; 8],
+    digest: [u8; 32],
+}
+
+#[link_section = ".initramfs_digest"]
+#[used]
+static INITRAMFS_DIGEST: InitramfsDigest = InitramfsDigest {
+    tag: TAG_NONE,
+    digest: [0; 32],
+};
+
+/// Verifies the initramfs against the digest embedded in the kernel image.
+///
+/// # Panics
+///
+/// This function panics if the initramfs does not match the digest.
+pub(super) fn verify(initramfs: &[u8]) {
+    // The digest is patched into the image after the kernel is built, so it must not be
+    // folded into the initial value by the compiler.
+    // SAFETY: The pointer is derived from a reference to a static, so it is valid to read.
+    let expected = unsafe { core::ptr::read_volatile(&INITRAMFS_DIGEST) };
+    match expected.tag {
+        TAG_NONE => (),
+        TAG_SHA256 => assert!(
+            sha256(initramfs) == expected.digest,
+            "the initramfs does not match the digest in the kernel image"
+        ),
+        _ => panic!("the digest of the initramfs is malformed"),
+    }
+}
@@ -3,6 +3,9 @@
 //! The Linux 64-bit Boot Protocol supporting module.
 //!
 
+mod initramfs_digest;
+mod sha256;
+
 use alloc::{borrow::ToOwned, format, string::String, vec::Vec};
 use core::ffi::CStr;
 
@@ -82,7 +85,9 @@ fn init_initramfs(initramfs: &'static Once<&'static [u8]>) {
     if length == 0 {
         return;
     }
-    initramfs.call_once(|| unsafe { core::slice::from_raw_parts(base_va as *const u8, length) });
+    let initramfs_buf = unsafe { core::slice::from_raw_parts(base_va as *const u8, length) };
+    initramfs_digest::verify(initramfs_buf);
+    initramfs.call_once(|| initramfs_buf);
 }
 
 fn init_acpi_arg(acpi: &'static Once<BootloaderAcpiArg>) {
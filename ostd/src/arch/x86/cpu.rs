@@ -9,10 +9,7 @@ use core::{
 };
 
 use bitflags::bitflags;
-use bitvec::{
-    prelude::{BitVec, Lsb0},
-    slice::IterOnes,
-};
+use id_alloc::Bitmap;
 use log::debug;
 #[cfg(feature = "intel_tdx")]
 use tdx_guest::tdcall;
@@ -22,6 +19,8 @@ use x86_64::registers::rflags::RFlags;
 #[cfg(feature = "intel_tdx")]
 use crate::arch::tdx_guest::{handle_virtual_exception, TdxTrapFrame};
 use crate::{
+    arch::mce::{handle_machine_check, process_errors, MceSeverity},
+    mm::Paddr,
     trap::call_irq_callback_functions,
     user::{ReturnReason, UserContextApi, UserContextApiInternal},
 };
@@ -41,24 +40,22 @@ pub fn this_cpu() -> u32 {
 /// A set of CPUs.
 #[derive(Default)]
 pub struct CpuSet {
-    bitset: BitVec,
+    bitset: Bitmap,
 }
 
 impl CpuSet {
     /// Creates a new `CpuSet` with all CPUs included.
     pub fn new_full() -> Self {
-        let num_cpus = num_cpus();
-        let mut bitset = BitVec::with_capacity(num_cpus as usize);
-        bitset.resize(num_cpus as usize, true);
-        Self { bitset }
+        Self {
+            bitset: Bitmap::new_full(num_cpus() as usize),
+        }
     }
 
     /// Creates a new `CpuSet` with no CPUs included.
     pub fn new_empty() -> Self {
-        let num_cpus = num_cpus();
-        let mut bitset = BitVec::with_capacity(num_cpus as usize);
-        bitset.resize(num_cpus as usize, false);
-        Self { bitset }
+        Self {
+            bitset: Bitmap::new(num_cpus() as usize),
+        }
     }
 
     /// Adds a CPU with identifier `cpu_id` to the `CpuSet`.
@@ -97,12 +94,32 @@ impl CpuSet {
 
     /// Checks if the `CpuSet` contains a specific CPU.
     pub fn contains(&self, cpu_id: u32) -> bool {
-        self.bitset.get(cpu_id as usize).as_deref() == Some(&true)
+        (cpu_id as usize) < self.bitset.len() && self.bitset.get(cpu_id as usize)
+    }
+
+    /// Returns the number of CPUs in the `CpuSet`.
+    pub fn count(&self) -> usize {
+        self.bitset.count_ones()
+    }
+
+    /// Checks if the `CpuSet` contains no CPUs.
+    pub fn is_empty(&self) -> bool {
+        !self.bitset.any()
+    }
+
+    /// Checks if the `CpuSet` shares any CPU with `other`.
+    pub fn intersects(&self, other: &CpuSet) -> bool {
+        self.bitset.intersects(&other.bitset)
+    }
+
+    /// Removes the CPUs that are not in `other` from the `CpuSet`.
+    pub fn retain_from(&mut self, other: &CpuSet) {
+        self.bitset.and(&other.bitset);
     }
 
     /// Returns an iterator over the set CPUs.
-    pub fn iter(&self) -> IterOnes<'_, usize, Lsb0> {
-        self.bitset.iter_ones()
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.bitset.iter_ones().map(|cpu_id| cpu_id as u32)
     }
 }
 
@@ -125,6 +142,8 @@ pub struct CpuExceptionInfo {
     pub error_code: usize,
     /// The virtual address where a page fault occurred.
     pub page_fault_addr: usize,
+    /// The physical address of the memory error that caused a machine check, if known.
+    pub memory_error_addr: Option<Paddr>,
 }
 
 #[cfg(feature = "intel_tdx")]
@@ -290,6 +309,7 @@ impl UserContextApiInternal for UserContext {
         self.user_context.general.rflags |= (RFlags::INTERRUPT_FLAG | RFlags::ID).bits() as usize;
 
         let return_reason: ReturnReason;
+        let mut memory_error_addr = None;
         const SYSCALL_TRAPNUM: u16 = 0x100;
 
         let mut user_preemption = UserPreemption::new();
@@ -305,6 +325,25 @@ impl UserContextApiInternal for UserContext {
                         handle_virtual_exception(self.general_regs_mut(), &ve_info);
                         continue;
                     }
+                    if *exception == MACHINE_CHECK {
+                        let mce = handle_machine_check();
+                        // The machine check has returned, so the pages can be poisoned
+                        // before the user space sees the exception.
+                        process_errors();
+                        // The process that consumed the corrupted data is notified
+                        // of the exception, so it can be killed alone.
+                        match mce.severity {
+                            MceSeverity::Corrected => continue,
+                            MceSeverity::Uncorrected => {
+                                memory_error_addr = mce.addr;
+                                return_reason = ReturnReason::UserException;
+                                break;
+                            }
+                            MceSeverity::Fatal => {
+                                panic!("Fatal machine check in the user space");
+                            }
+                        }
+                    }
                     if exception.typ == CpuExceptionType::FaultOrTrap
                         || exception.typ == CpuExceptionType::Fault
                         || exception.typ == CpuExceptionType::Trap
@@ -335,6 +374,7 @@ impl UserContextApiInternal for UserContext {
                 page_fault_addr: unsafe { x86::controlregs::cr2() },
                 id: self.user_context.trap_num,
                 error_code: self.user_context.error_code,
+                memory_error_addr,
             };
         }
 
@@ -10,7 +10,11 @@ use id_alloc::IdAlloc;
 use spin::Once;
 use trapframe::TrapFrame;
 
-use crate::sync::{Mutex, SpinLock, SpinLockGuard};
+use crate::{
+    sync::{Mutex, SpinLock, SpinLockGuard},
+    trap::IpiTarget,
+    Result,
+};
 
 /// The global allocator for software defined IRQ lines.
 pub(crate) static IRQ_ALLOCATOR: Once<SpinLock<IdAlloc>> = Once::new();
@@ -56,6 +60,11 @@ pub(crate) fn is_local_enabled() -> bool {
     x86_64::instructions::interrupts::are_enabled()
 }
 
+/// Sends an inter-processor interrupt with the `vector` to the `target` CPUs.
+pub(crate) fn send_ipi(target: IpiTarget, vector: u8) -> Result<()> {
+    super::kernel::apic::send_ipi(target, vector)
+}
+
 static CALLBACK_ID_ALLOCATOR: Once<Mutex<IdAlloc>> = Once::new();
 
 pub struct CallbackElement {
@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The Interrupt Stack Table (IST).
+//!
+//! The CPU switches to a stack in the IST of the Task State Segment (TSS) before
+//! it delivers an exception whose IDT entry selects the stack. The double fault
+//! handler runs on such a dedicated stack, so it can still report diagnostics
+//! when the double fault is caused by a kernel stack overflow, rather than the
+//! CPU escalating it to a triple fault that resets the machine. NMIs and machine
+//! checks can arrive at any instruction, including the ones that have just
+//! switched to a stack that is not valid yet, so they run on dedicated stacks as
+//! well.
+//!
+//! The GDT, the TSS and the IDT are set up by the `trapframe` crate, so this
+//! module patches them in place.
+
+use alloc::vec;
+use core::{
+    arch::{asm, global_asm},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use align_ext::AlignExt;
+
+use crate::mm::PAGE_SIZE;
+
+/// The size of each stack in the IST.
+const IST_STACK_SIZE: usize = PAGE_SIZE * 16;
+
+/// The vector of the NMI.
+const NMI_VECTOR: usize = 2;
+/// The vector of the double fault exception.
+const DOUBLE_FAULT_VECTOR: usize = 8;
+/// The vector of the machine check exception.
+const MACHINE_CHECK_VECTOR: usize = 18;
+
+/// The indexes of the stacks in the IST, starting from 1.
+const DOUBLE_FAULT_IST_INDEX: u16 = 1;
+const NMI_IST_INDEX: u16 = 2;
+const MACHINE_CHECK_IST_INDEX: u16 = 3;
+
+/// The offset of `RSP0` in the 64-bit TSS.
+const TSS_RSP0_OFFSET: usize = 0x4;
+/// The offset of the IST in the 64-bit TSS.
+const TSS_IST_OFFSET: usize = 0x24;
+/// The size of an entry in the 64-bit IDT.
+const IDT_ENTRY_SIZE: usize = 16;
+
+/// The address of `RSP0` in the TSS, which is read by the IST entries.
+static TSS_RSP0: AtomicUsize = AtomicUsize::new(0);
+/// The original entries of the NMI and the machine check in the `trapframe` crate,
+/// which the IST entries jump to.
+static NMI_TRAPFRAME_ENTRY: AtomicUsize = AtomicUsize::new(0);
+static MACHINE_CHECK_TRAPFRAME_ENTRY: AtomicUsize = AtomicUsize::new(0);
+
+global_asm!(
+    include_str!("ist.S"),
+    tss_rsp0 = sym TSS_RSP0,
+    nmi_trapframe_entry = sym NMI_TRAPFRAME_ENTRY,
+    machine_check_trapframe_entry = sym MACHINE_CHECK_TRAPFRAME_ENTRY,
+);
+
+extern "C" {
+    fn __ist_nmi_entry();
+    fn __ist_machine_check_entry();
+}
+
+/// Sets up the IST stacks of the current CPU.
+///
+/// This function must be called after the `trapframe` crate is initialized.
+///
+/// FIXME: Only the bootstrap processor is brought up, so the entries read the
+/// address of `RSP0` from a global variable. It should be per-CPU once the
+/// application processors are brought up.
+pub(super) fn init() {
+    // SAFETY: The TSS is the one of the current CPU and is never freed.
+    let tss_base = unsafe { current_tss_base() };
+    TSS_RSP0.store(tss_base + TSS_RSP0_OFFSET, Ordering::Relaxed);
+
+    // SAFETY: The stacks are leaked, so they are valid forever, and each of them is
+    // used only by the handler of one vector. The entries of the NMI and the machine
+    // check move the trap frames from the user space to where `trapframe` expects.
+    // They are installed before the IST stacks are selected, and they work on the
+    // regular stacks as well, so the exceptions are handled at any point in between.
+    unsafe {
+        set_tss_ist(tss_base, DOUBLE_FAULT_IST_INDEX, alloc_stack());
+        set_idt_ist(DOUBLE_FAULT_VECTOR, DOUBLE_FAULT_IST_INDEX);
+
+        set_tss_ist(tss_base, NMI_IST_INDEX, alloc_stack());
+        NMI_TRAPFRAME_ENTRY.store(idt_handler(NMI_VECTOR), Ordering::Relaxed);
+        set_idt_handler(NMI_VECTOR, __ist_nmi_entry as usize);
+        set_idt_ist(NMI_VECTOR, NMI_IST_INDEX);
+
+        set_tss_ist(tss_base, MACHINE_CHECK_IST_INDEX, alloc_stack());
+        let trapframe_entry = idt_handler(MACHINE_CHECK_VECTOR);
+        MACHINE_CHECK_TRAPFRAME_ENTRY.store(trapframe_entry, Ordering::Relaxed);
+        set_idt_handler(MACHINE_CHECK_VECTOR, __ist_machine_check_entry as usize);
+        set_idt_ist(MACHINE_CHECK_VECTOR, MACHINE_CHECK_IST_INDEX);
+    }
+}
+
+/// Allocates a stack and returns its top.
+fn alloc_stack() -> u64 {
+    let stack = vec![0u8; IST_STACK_SIZE].leak();
+    (stack.as_ptr() as usize + stack.len()).align_down(16) as u64
+}
+
+/// The operand of `SGDT` and `SIDT`.
+#[repr(C, packed)]
+struct DescriptorTablePointer {
+    limit: u16,
+    base: u64,
+}
+
+/// Returns the base address of the TSS loaded on the current CPU.
+///
+/// # Safety
+///
+/// The GDT and the task register must have been set up.
+unsafe fn current_tss_base() -> usize {
+    let selector: u16;
+    asm!("str {0:x}", out(reg) selector, options(nomem, nostack, preserves_flags));
+    let mut gdt = DescriptorTablePointer { limit: 0, base: 0 };
+    asm!("sgdt [{}]", in(reg) core::ptr::addr_of_mut!(gdt), options(nostack, preserves_flags));
+
+    // A TSS descriptor occupies two GDT entries, which scatter the base address.
+    let descriptor = (gdt.base as usize + (selector & !0x7) as usize) as *const u64;
+    let (low, high) = (descriptor.read(), descriptor.add(1).read());
+    (((low >> 16) & 0xff_ffff) | ((low >> 56) << 24) | ((high & 0xffff_ffff) << 32)) as usize
+}
+
+/// Sets the stack at `index` in the IST of the TSS at `tss_base`.
+///
+/// # Safety
+///
+/// The stack must be valid and must not be used by anything else.
+unsafe fn set_tss_ist(tss_base: usize, index: u16, stack_top: u64) {
+    let ist_entry = (tss_base + TSS_IST_OFFSET + (index as usize - 1) * 8) as *mut u64;
+    ist_entry.write_unaligned(stack_top);
+}
+
+/// Returns the address of the IDT entry of the `vector` on the current CPU.
+fn idt_entry(vector: usize) -> usize {
+    let mut idt = DescriptorTablePointer { limit: 0, base: 0 };
+    // SAFETY: `SIDT` only stores the IDT pointer into `idt`.
+    unsafe {
+        asm!("sidt [{}]", in(reg) core::ptr::addr_of_mut!(idt), options(nostack, preserves_flags));
+    }
+    idt.base as usize + vector * IDT_ENTRY_SIZE
+}
+
+/// Makes the CPU switch to the stack at `index` in the IST when it delivers the `vector`.
+///
+/// # Safety
+///
+/// The stack at `index` in the IST must have been set up.
+unsafe fn set_idt_ist(vector: usize, index: u16) {
+    // The IST index is in the lowest 3 bits of the options at offset 4 of the entry.
+    let options = (idt_entry(vector) + 4) as *mut u16;
+    options.write_volatile((options.read_volatile() & !0x7) | index);
+}
+
+/// Returns the handler address in the IDT entry of the `vector`.
+fn idt_handler(vector: usize) -> usize {
+    // The handler address is scattered in the bytes 0..2, 6..8 and 8..12 of the entry.
+    let entry = idt_entry(vector);
+    // SAFETY: The entry is in the IDT of the current CPU.
+    unsafe {
+        let low = (entry as *const u16).read_volatile() as usize;
+        let middle = ((entry + 6) as *const u16).read_volatile() as usize;
+        let high = ((entry + 8) as *const u32).read_volatile() as usize;
+        low | (middle << 16) | (high << 32)
+    }
+}
+
+/// Sets the handler address in the IDT entry of the `vector`.
+///
+/// # Safety
+///
+/// The handler must handle the `vector` correctly.
+unsafe fn set_idt_handler(vector: usize, handler: usize) {
+    let entry = idt_entry(vector);
+    (entry as *mut u16).write_volatile(handler as u16);
+    ((entry + 6) as *mut u16).write_volatile((handler >> 16) as u16);
+    ((entry + 8) as *mut u32).write_volatile((handler >> 32) as u32);
+}
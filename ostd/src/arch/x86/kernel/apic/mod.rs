@@ -2,12 +2,12 @@
 
 #![allow(dead_code)]
 
-use alloc::sync::Arc;
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
 
 use log::info;
 use spin::Once;
 
-use crate::sync::SpinLock;
+use crate::{cpu::this_cpu, sync::SpinLock, trap::IpiTarget, Error};
 
 pub mod ioapic;
 pub mod x2apic;
@@ -20,8 +20,16 @@ pub trait Apic: ApicTimer + Sync + Send {
 
     fn version(&self) -> u32;
 
+    /// Returns the logical APIC ID, which is fixed by the hardware in x2APIC mode.
+    ///
+    /// This method returns `None` if the logical destination mode is not supported.
+    fn logical_id(&self) -> Option<u32>;
+
     /// End of Interrupt, this function will inform APIC that this interrupt has been processed.
     fn eoi(&mut self);
+
+    /// Sends an inter-processor interrupt (IPI) with the Interrupt Command Register.
+    fn send_ipi(&mut self, icr: Icr);
 }
 
 pub trait ApicTimer: Sync + Send {
@@ -43,6 +51,149 @@ pub trait ApicTimer: Sync + Send {
     fn set_timer_div_config(&mut self, div_config: DivideConfig);
 }
 
+/// An inter-processor interrupt (IPI) to be written to the Interrupt Command Register (ICR).
+///
+/// The IPI is always delivered in the fixed delivery mode and the edge trigger mode.
+#[derive(Debug, Clone, Copy)]
+pub struct Icr {
+    destination: u32,
+    lower: u32,
+}
+
+impl Icr {
+    pub fn new(
+        destination: u32,
+        destination_mode: DestinationMode,
+        shorthand: DestinationShorthand,
+        vector: u8,
+    ) -> Self {
+        /// The level bit, which must be set for all delivery modes except INIT level de-assert.
+        const LEVEL_ASSERT: u32 = 1 << 14;
+
+        let lower = (shorthand as u32) << 18
+            | LEVEL_ASSERT
+            | (destination_mode as u32) << 11
+            | vector as u32;
+        Self { destination, lower }
+    }
+
+    /// Returns the destination field, which is an APIC ID or a logical destination.
+    pub fn destination(&self) -> u32 {
+        self.destination
+    }
+
+    /// Returns the lower 32 bits of the ICR.
+    pub fn lower(&self) -> u32 {
+        self.lower
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum DestinationMode {
+    Physical = 0,
+    Logical = 1,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum DestinationShorthand {
+    NoShorthand = 0b00,
+    MySelf = 0b01,
+    AllIncludingSelf = 0b10,
+    AllExcludingSelf = 0b11,
+}
+
+/// The APIC IDs of a CPU.
+#[derive(Debug, Clone, Copy)]
+struct CpuApicId {
+    physical: u32,
+    logical: Option<u32>,
+}
+
+/// The APIC IDs of the CPUs, indexed by the CPU IDs.
+static CPU_APIC_IDS: SpinLock<Vec<Option<CpuApicId>>> = SpinLock::new(Vec::new());
+
+/// Records the APIC IDs of the current CPU, so that IPIs can be sent to it.
+fn register_this_cpu(apic: &dyn Apic) {
+    let cpu_id = this_cpu() as usize;
+    let mut cpu_apic_ids = CPU_APIC_IDS.lock_irq_disabled();
+    if cpu_apic_ids.len() <= cpu_id {
+        cpu_apic_ids.resize(cpu_id + 1, None);
+    }
+    cpu_apic_ids[cpu_id] = Some(CpuApicId {
+        physical: apic.id(),
+        logical: apic.logical_id(),
+    });
+}
+
+/// Sends an IPI with the `vector` to the `target` CPUs.
+pub(crate) fn send_ipi(target: IpiTarget, vector: u8) -> crate::Result<()> {
+    let cpu_apic_ids = CPU_APIC_IDS.lock_irq_disabled();
+    let apic_id_of = |cpu_id: u32| {
+        cpu_apic_ids
+            .get(cpu_id as usize)
+            .copied()
+            .flatten()
+            .ok_or(Error::InvalidArgs)
+    };
+    let mut apic = APIC_INSTANCE
+        .get()
+        .ok_or(Error::NotEnoughResources)?
+        .lock_irq_disabled();
+
+    match target {
+        IpiTarget::Cpu(cpu_id) => {
+            let apic_id = apic_id_of(cpu_id)?;
+            apic.send_ipi(Icr::new(
+                apic_id.physical,
+                DestinationMode::Physical,
+                DestinationShorthand::NoShorthand,
+                vector,
+            ));
+        }
+        IpiTarget::CpuSet(cpu_set) => {
+            let apic_ids = cpu_set
+                .iter()
+                .map(apic_id_of)
+                .collect::<crate::Result<Vec<_>>>()?;
+            // In x2APIC mode, a logical ID consists of a cluster ID in the upper 16 bits
+            // and a bit in the lower 16 bits. The CPUs in the same cluster can be reached
+            // by a single IPI whose destination combines their bits.
+            let mut clusters = BTreeMap::new();
+            for apic_id in apic_ids {
+                if let Some(logical) = apic_id.logical {
+                    *clusters.entry(logical >> 16).or_insert(0) |= logical & 0xffff;
+                    continue;
+                }
+                apic.send_ipi(Icr::new(
+                    apic_id.physical,
+                    DestinationMode::Physical,
+                    DestinationShorthand::NoShorthand,
+                    vector,
+                ));
+            }
+            for (cluster, bits) in clusters {
+                apic.send_ipi(Icr::new(
+                    cluster << 16 | bits,
+                    DestinationMode::Logical,
+                    DestinationShorthand::NoShorthand,
+                    vector,
+                ));
+            }
+        }
+        IpiTarget::AllExcludingSelf => {
+            apic.send_ipi(Icr::new(
+                0,
+                DestinationMode::Physical,
+                DestinationShorthand::AllExcludingSelf,
+                vector,
+            ));
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 pub enum ApicInitError {
     /// No x2APIC or xAPIC found.
@@ -73,6 +224,7 @@ pub fn init() -> Result<(), ApicInitError> {
             version & 0xff,
             (version >> 16) & 0xff
         );
+        register_this_cpu(&x2apic);
         APIC_INSTANCE.call_once(|| Arc::new(SpinLock::new(x2apic)));
         Ok(())
     } else if let Some(mut xapic) = xapic::XApic::new() {
@@ -84,6 +236,7 @@ pub fn init() -> Result<(), ApicInitError> {
             version & 0xff,
             (version >> 16) & 0xff
         );
+        register_this_cpu(&xapic);
         APIC_INSTANCE.call_once(|| Arc::new(SpinLock::new(xapic)));
         Ok(())
     } else {
@@ -2,11 +2,11 @@
 
 use x86::msr::{
     rdmsr, wrmsr, IA32_APIC_BASE, IA32_X2APIC_APICID, IA32_X2APIC_CUR_COUNT, IA32_X2APIC_DIV_CONF,
-    IA32_X2APIC_EOI, IA32_X2APIC_INIT_COUNT, IA32_X2APIC_LVT_TIMER, IA32_X2APIC_SIVR,
-    IA32_X2APIC_VERSION,
+    IA32_X2APIC_EOI, IA32_X2APIC_ICR, IA32_X2APIC_INIT_COUNT, IA32_X2APIC_LDR,
+    IA32_X2APIC_LVT_TIMER, IA32_X2APIC_SIVR, IA32_X2APIC_VERSION,
 };
 
-use super::ApicTimer;
+use super::{ApicTimer, Icr};
 
 pub struct X2Apic {}
 
@@ -61,11 +61,25 @@ impl super::Apic for X2Apic {
         unsafe { rdmsr(IA32_X2APIC_VERSION) as u32 }
     }
 
+    fn logical_id(&self) -> Option<u32> {
+        // In x2APIC mode, the logical ID is derived from the APIC ID by the hardware.
+        Some(unsafe { rdmsr(IA32_X2APIC_LDR) as u32 })
+    }
+
     fn eoi(&mut self) {
         unsafe {
             wrmsr(IA32_X2APIC_EOI, 0);
         }
     }
+
+    fn send_ipi(&mut self, icr: Icr) {
+        // In x2APIC mode, the whole ICR is written at once and the delivery status
+        // does not need to be polled.
+        let value = (icr.destination() as u64) << 32 | icr.lower() as u64;
+        unsafe {
+            wrmsr(IA32_X2APIC_ICR, value);
+        }
+    }
 }
 
 impl ApicTimer for X2Apic {
@@ -5,7 +5,7 @@
 use spin::Once;
 use x86::apic::xapic;
 
-use super::ApicTimer;
+use super::{ApicTimer, Icr};
 use crate::{mm, sync::Mutex};
 
 const IA32_APIC_BASE_MSR: u32 = 0x1B;
@@ -13,6 +13,10 @@ const IA32_APIC_BASE_MSR_BSP: u32 = 0x100; // Processor is a BSP
 const IA32_APIC_BASE_MSR_ENABLE: u64 = 0x800;
 
 const APIC_LVT_MASK_BITS: u32 = 1 << 16;
+/// The delivery status bit of the ICR, which is set until the IPI is accepted.
+const APIC_ICR_SEND_PENDING: u32 = 1 << 12;
+/// The shift of the APIC ID in both the ID register and the upper half of the ICR.
+const APIC_ID_SHIFT: u32 = 24;
 
 pub static XAPIC_INSTANCE: Once<Mutex<XApic>> = Once::new();
 
@@ -64,16 +68,33 @@ impl XApic {
 
 impl super::Apic for XApic {
     fn id(&self) -> u32 {
-        self.read(xapic::XAPIC_ID)
+        decode_apic_id(self.read(xapic::XAPIC_ID))
     }
 
     fn version(&self) -> u32 {
         self.read(xapic::XAPIC_VERSION)
     }
 
+    fn logical_id(&self) -> Option<u32> {
+        // The logical destination mode is not configured in xAPIC mode.
+        None
+    }
+
     fn eoi(&mut self) {
         self.write(xapic::XAPIC_EOI, 0);
     }
+
+    fn send_ipi(&mut self, icr: Icr) {
+        // Only 8 bits of APIC IDs are supported in xAPIC mode.
+        debug_assert!(icr.destination() <= 0xff);
+
+        // The IPI is sent when the lower half of the ICR is written.
+        self.write(xapic::XAPIC_ICR1, encode_icr_destination(icr.destination()));
+        self.write(xapic::XAPIC_ICR0, icr.lower());
+        while self.read(xapic::XAPIC_ICR0) & APIC_ICR_SEND_PENDING != 0 {
+            core::hint::spin_loop();
+        }
+    }
 }
 
 impl ApicTimer for XApic {
@@ -94,6 +115,16 @@ impl ApicTimer for XApic {
     }
 }
 
+/// Extracts the APIC ID from the value of the ID register.
+fn decode_apic_id(id_reg: u32) -> u32 {
+    id_reg >> APIC_ID_SHIFT
+}
+
+/// Encodes the destination into the upper half of the ICR.
+fn encode_icr_destination(destination: u32) -> u32 {
+    destination << APIC_ID_SHIFT
+}
+
 /// Sets APIC base address and enables it
 fn set_apic_base_address(address: usize) {
     unsafe {
@@ -109,3 +140,19 @@ fn get_apic_base_address() -> usize {
             as usize
     }
 }
+
+#[cfg(ktest)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+
+    #[ktest]
+    fn apic_id_round_trip() {
+        for apic_id in [0, 1, 2, 0x7f, 0xff] {
+            // The ID register and the upper half of the ICR share the same layout.
+            let id_reg = apic_id << APIC_ID_SHIFT;
+            assert_eq!(decode_apic_id(id_reg), apic_id);
+            assert_eq!(encode_icr_destination(decode_apic_id(id_reg)), id_reg);
+        }
+    }
+}
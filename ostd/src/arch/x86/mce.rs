@@ -0,0 +1,307 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Machine check exceptions (MCEs).
+//!
+//! The machine check architecture (MCA) reports hardware errors in banks of
+//! MSRs. Corrected errors are only logged. For an uncorrected memory error whose
+//! physical address is known, the page containing the address is poisoned, so
+//! that it is never allocated again.
+//!
+//! A machine check can interrupt any code, including the holders of locks, so
+//! the handler only saves the errors in a lock-free buffer. They are logged and
+//! the pages are poisoned later by [`process_errors`], which is called when the
+//! machine check returns to the user space and periodically by the timer.
+
+use core::{
+    arch::x86_64::__cpuid,
+    sync::atomic::{AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering},
+};
+
+use log::{error, info, warn};
+use x86::msr::{rdmsr, wrmsr};
+use x86_64::registers::control::{Cr4, Cr4Flags};
+
+use crate::mm::{page::allocator::poison_page, Paddr};
+
+const IA32_MCG_CAP: u32 = 0x179;
+const IA32_MCG_STATUS: u32 = 0x17a;
+const IA32_MCG_CTL: u32 = 0x17b;
+
+/// The MSRs of bank `i` start from `IA32_MC0_CTL + 4 * i`.
+const IA32_MC0_CTL: u32 = 0x400;
+const IA32_MC0_STATUS: u32 = 0x401;
+const IA32_MC0_ADDR: u32 = 0x402;
+
+const MCG_CAP_COUNT_MASK: u64 = 0xff;
+const MCG_CAP_CTL_P: u64 = 1 << 8;
+
+/// Whether the interrupted program can be restarted at the pushed RIP.
+const MCG_STATUS_RIPV: u64 = 1 << 0;
+
+const MCI_STATUS_VAL: u64 = 1 << 63;
+const MCI_STATUS_OVER: u64 = 1 << 62;
+const MCI_STATUS_UC: u64 = 1 << 61;
+const MCI_STATUS_ADDRV: u64 = 1 << 58;
+const MCI_STATUS_PCC: u64 = 1 << 57;
+
+/// The severity of the errors reported by a machine check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum MceSeverity {
+    /// All the errors have been corrected by the hardware.
+    Corrected,
+    /// Some errors are uncorrected, but they are contained in the pages to poison.
+    ///
+    /// The interrupted context has consumed the corrupted data, so it cannot
+    /// continue as if nothing happened.
+    Uncorrected,
+    /// The processor context is corrupted, so no one can continue.
+    Fatal,
+}
+
+/// Enables machine checks on the current CPU.
+pub(super) fn init() {
+    // SAFETY: CPUID is always available in 64-bit mode.
+    let features = unsafe { __cpuid(1) }.edx;
+    let has_mce = features & (1 << 7) != 0;
+    let has_mca = features & (1 << 14) != 0;
+    if !has_mce || !has_mca {
+        info!("Machine check architecture is not supported");
+        return;
+    }
+
+    // SAFETY: The MCA MSRs exist since MCA is supported. Enabling the reporting of
+    // all errors does not affect memory safety.
+    unsafe {
+        let cap = rdmsr(IA32_MCG_CAP);
+        if cap & MCG_CAP_CTL_P != 0 {
+            wrmsr(IA32_MCG_CTL, u64::MAX);
+        }
+        let nr_banks = (cap & MCG_CAP_COUNT_MASK) as u32;
+        for bank in 0..nr_banks {
+            wrmsr(IA32_MC0_CTL + 4 * bank, u64::MAX);
+        }
+    }
+
+    // The errors logged before the boot, e.g., in the last boot before a warm reset,
+    // are reported and cleared.
+    scan_banks();
+    process_errors();
+
+    // SAFETY: Enabling machine checks does not affect memory safety.
+    unsafe { Cr4::update(|flags| flags.insert(Cr4Flags::MACHINE_CHECK_EXCEPTION)) };
+
+    // The corrected errors in the kernel are reported by the timer.
+    crate::arch::timer::register_callback(process_errors);
+}
+
+/// The result of handling a machine check exception.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MachineCheck {
+    /// The severity of the errors.
+    pub(crate) severity: MceSeverity,
+    /// The physical address of the uncorrected memory error, i.e., the value of
+    /// the `MCi_ADDR` register.
+    pub(crate) addr: Option<Paddr>,
+}
+
+/// Handles a machine check exception.
+///
+/// The errors are saved and cleared, but not reported. The caller should call
+/// [`process_errors`] once it is safe to take locks, which poisons the pages
+/// with uncorrected memory errors.
+pub(crate) fn handle_machine_check() -> MachineCheck {
+    // SAFETY: The MSR exists since a machine check exception has occurred.
+    let mcg_status = unsafe { rdmsr(IA32_MCG_STATUS) };
+
+    let mut mce = scan_banks();
+    if mcg_status & MCG_STATUS_RIPV == 0 {
+        mce.severity = MceSeverity::Fatal;
+    }
+
+    // Clear MCIP, otherwise another machine check will shut down the processor.
+    // SAFETY: Clearing the status does not affect memory safety.
+    unsafe { wrmsr(IA32_MCG_STATUS, 0) };
+    mce
+}
+
+/// Saves and clears the errors logged in all the banks.
+fn scan_banks() -> MachineCheck {
+    // SAFETY: The MSRs exist since MCA is supported.
+    let nr_banks = unsafe { rdmsr(IA32_MCG_CAP) } & MCG_CAP_COUNT_MASK;
+
+    let mut mce = MachineCheck {
+        severity: MceSeverity::Corrected,
+        addr: None,
+    };
+    for bank in 0..nr_banks as u32 {
+        // SAFETY: The bank is within the number of banks.
+        let status = unsafe { rdmsr(IA32_MC0_STATUS + 4 * bank) };
+        if status & MCI_STATUS_VAL == 0 {
+            continue;
+        }
+        let addr = if status & MCI_STATUS_ADDRV != 0 {
+            // SAFETY: The address is valid as reported by the status.
+            Some(unsafe { rdmsr(IA32_MC0_ADDR + 4 * bank) } as Paddr)
+        } else {
+            None
+        };
+
+        let severity = severity_of(status, addr);
+        if severity == MceSeverity::Uncorrected && mce.severity < MceSeverity::Uncorrected {
+            mce.addr = addr;
+        }
+        mce.severity = mce.severity.max(severity);
+        ERROR_RECORDS.push(bank, status, addr);
+
+        // SAFETY: Clearing the status does not affect memory safety.
+        unsafe { wrmsr(IA32_MC0_STATUS + 4 * bank, 0) };
+    }
+    mce
+}
+
+fn severity_of(status: u64, addr: Option<Paddr>) -> MceSeverity {
+    if status & MCI_STATUS_UC == 0 {
+        MceSeverity::Corrected
+    } else if addr.is_some() && status & MCI_STATUS_PCC == 0 {
+        MceSeverity::Uncorrected
+    } else {
+        MceSeverity::Fatal
+    }
+}
+
+/// Reports the saved errors, and poisons the pages with uncorrected memory errors.
+///
+/// This function must not be called in the machine check handler.
+pub(crate) fn process_errors() {
+    ERROR_RECORDS.pop_each(report_error);
+
+    let nr_lost = ERROR_RECORDS.nr_lost.swap(0, Ordering::Relaxed);
+    if nr_lost > 0 {
+        warn!("{} machine check errors are lost", nr_lost);
+    }
+}
+
+/// Reports an error logged in a bank, and poisons the page if the error is an
+/// uncorrected error with a valid address.
+fn report_error(bank: u32, status: u64, addr: Option<Paddr>) {
+    let error_code = status & 0xffff;
+    // The compound error code of memory controller errors is `000F 0000 1MMM CCCC`.
+    let kind = if error_code & 0xef80 == 0x0080 {
+        "memory controller"
+    } else {
+        "hardware"
+    };
+    let overflowed = if status & MCI_STATUS_OVER != 0 {
+        " (more errors are lost)"
+    } else {
+        ""
+    };
+
+    let severity = severity_of(status, addr);
+    if severity == MceSeverity::Corrected {
+        warn!(
+            "Corrected {} error in bank {}: status {:#x}, address {:x?}{}",
+            kind, bank, status, addr, overflowed
+        );
+        return;
+    }
+
+    error!(
+        "Uncorrected {} error in bank {}: status {:#x}, address {:x?}{}",
+        kind, bank, status, addr, overflowed
+    );
+    if let (MceSeverity::Uncorrected, Some(addr)) = (severity, addr) {
+        poison_page(addr);
+    }
+}
+
+/// The number of errors that can be saved before they are processed.
+const NR_ERROR_RECORDS: usize = 32;
+
+static ERROR_RECORDS: ErrorRecords = ErrorRecords::new();
+
+/// A lock-free buffer of the errors saved in the machine check handler.
+struct ErrorRecords {
+    records: [ErrorRecord; NR_ERROR_RECORDS],
+    /// The number of errors that are dropped because the buffer is full.
+    nr_lost: AtomicUsize,
+}
+
+struct ErrorRecord {
+    state: AtomicU8,
+    bank: AtomicU32,
+    status: AtomicU64,
+    /// The value of `MCi_ADDR`, which is valid only if `MCI_STATUS_ADDRV` is set.
+    addr: AtomicU64,
+}
+
+impl ErrorRecords {
+    const STATE_FREE: u8 = 0;
+    const STATE_BUSY: u8 = 1;
+    const STATE_READY: u8 = 2;
+
+    const fn new() -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const FREE_RECORD: ErrorRecord = ErrorRecord {
+            state: AtomicU8::new(ErrorRecords::STATE_FREE),
+            bank: AtomicU32::new(0),
+            status: AtomicU64::new(0),
+            addr: AtomicU64::new(0),
+        };
+        Self {
+            records: [FREE_RECORD; NR_ERROR_RECORDS],
+            nr_lost: AtomicUsize::new(0),
+        }
+    }
+
+    /// Saves an error. This method never blocks.
+    fn push(&self, bank: u32, status: u64, addr: Option<Paddr>) {
+        let Some(record) = self.records.iter().find(|record| {
+            record
+                .state
+                .compare_exchange(
+                    Self::STATE_FREE,
+                    Self::STATE_BUSY,
+                    Ordering::Acquire,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+        }) else {
+            self.nr_lost.fetch_add(1, Ordering::Relaxed);
+            return;
+        };
+
+        record.bank.store(bank, Ordering::Relaxed);
+        record.status.store(status, Ordering::Relaxed);
+        record
+            .addr
+            .store(addr.unwrap_or_default() as u64, Ordering::Relaxed);
+        record.state.store(Self::STATE_READY, Ordering::Release);
+    }
+
+    /// Takes the saved errors out one by one and calls `f` with them.
+    fn pop_each(&self, mut f: impl FnMut(u32, u64, Option<Paddr>)) {
+        for record in self.records.iter() {
+            if record
+                .state
+                .compare_exchange(
+                    Self::STATE_READY,
+                    Self::STATE_BUSY,
+                    Ordering::Acquire,
+                    Ordering::Relaxed,
+                )
+                .is_err()
+            {
+                continue;
+            }
+
+            let bank = record.bank.load(Ordering::Relaxed);
+            let status = record.status.load(Ordering::Relaxed);
+            let addr = record.addr.load(Ordering::Relaxed) as Paddr;
+            record.state.store(Self::STATE_FREE, Ordering::Release);
+
+            let addr = (status & MCI_STATUS_ADDRV != 0).then_some(addr);
+            f(bank, status, addr);
+        }
+    }
+}
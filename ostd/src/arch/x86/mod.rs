@@ -9,7 +9,9 @@ pub mod device;
 pub(crate) mod ex_table;
 pub mod iommu;
 pub(crate) mod irq;
+mod ist;
 pub(crate) mod kernel;
+pub(crate) mod mce;
 pub(crate) mod mm;
 pub(crate) mod pci;
 pub mod qemu;
@@ -32,6 +34,8 @@ pub(crate) fn before_all_init() {
 }
 
 pub(crate) fn after_all_init() {
+    ist::init();
+    mce::init();
     irq::init();
     kernel::acpi::init();
     match kernel::apic::init() {
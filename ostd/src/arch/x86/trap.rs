@@ -14,7 +14,10 @@ use super::ex_table::ExTable;
 #[cfg(feature = "intel_tdx")]
 use crate::arch::{cpu::VIRTUALIZATION_EXCEPTION, tdx_guest::handle_virtual_exception};
 use crate::{
-    cpu::{CpuException, CpuExceptionInfo, PageFaultErrorCode, PAGE_FAULT},
+    arch::mce::{handle_machine_check, MceSeverity},
+    cpu::{
+        CpuException, CpuExceptionInfo, PageFaultErrorCode, DOUBLE_FAULT, MACHINE_CHECK, PAGE_FAULT,
+    },
     cpu_local,
     mm::{
         kspace::{KERNEL_PAGE_TABLE, LINEAR_MAPPING_BASE_VADDR, LINEAR_MAPPING_VADDR_RANGE},
@@ -56,6 +59,25 @@ extern "sysv64" fn trap_handler(f: &mut TrapFrame) {
                     handle_kernel_page_fault(f, page_fault_addr);
                 }
             }
+            &DOUBLE_FAULT => {
+                // The handler runs on a dedicated stack, so the diagnostics can be
+                // printed even if the kernel stack has overflowed.
+                panic!(
+                    "Double fault, possibly due to a kernel stack overflow. Trapframe:{:#x?}.",
+                    f
+                );
+            }
+            &MACHINE_CHECK => {
+                // The kernel cannot recover from consuming corrupted data.
+                // The corrected errors are reported later by the timer.
+                let mce = handle_machine_check();
+                if mce.severity != MceSeverity::Corrected {
+                    panic!(
+                        "Machine check in the kernel: {:x?}. Trapframe:{:#x?}.",
+                        mce, f
+                    );
+                }
+            }
             exception => {
                 panic!(
                     "Cannot handle kernel cpu exception:{:?}. Error code:{:x?}; Trapframe:{:#x?}.",
@@ -81,6 +103,7 @@ fn handle_user_page_fault(f: &mut TrapFrame, page_fault_addr: u64) {
         page_fault_addr: page_fault_addr as usize,
         id: f.trap_num,
         error_code: f.error_code,
+        memory_error_addr: None,
     };
 
     let res = user_space.vm_space().handle_page_fault(&info);
@@ -6,7 +6,9 @@
 use alloc::{vec, vec::Vec};
 use core::mem::swap;
 
-use crate::mm::kspace::kernel_loaded_offset;
+use log::warn;
+
+use crate::mm::{kspace::kernel_loaded_offset, PAGE_SIZE};
 
 /// The type of initial memory regions that are needed for the kernel.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
@@ -81,6 +83,30 @@ impl MemoryRegion {
         self.typ
     }
 
+    /// Returns the region aligned to page boundaries,
+    /// or `None` if the region exceeds the address space.
+    ///
+    /// Usable and reclaimable regions are shrunk, while the others are enlarged.
+    /// The returned region may be empty.
+    fn page_aligned(&self) -> Option<MemoryRegion> {
+        let end = self.base.checked_add(self.len)?;
+        let (base, end) = match self.typ {
+            MemoryRegionType::Usable | MemoryRegionType::Reclaimable => {
+                let base = self.base.checked_add(PAGE_SIZE - 1)? & !(PAGE_SIZE - 1);
+                (base, (end & !(PAGE_SIZE - 1)).max(base))
+            }
+            _ => {
+                let end = end.checked_add(PAGE_SIZE - 1)? & !(PAGE_SIZE - 1);
+                (self.base & !(PAGE_SIZE - 1), end)
+            }
+        };
+        Some(MemoryRegion {
+            base,
+            len: end - base,
+            typ: self.typ,
+        })
+    }
+
     /// Removes range `t` from self, resulting in 0, 1 or 2 truncated ranges.
     /// We need to have this method since memory regions can overlap.
     pub fn truncate(&self, t: &MemoryRegion) -> Vec<MemoryRegion> {
@@ -128,42 +154,116 @@ impl MemoryRegion {
 /// Truncates regions, resulting in a set of regions that does not overlap.
 ///
 /// The truncation will be done according to the type of the regions, that
-/// usable and reclaimable regions will be truncated by the unusable regions.
+/// usable and reclaimable regions will be truncated by the unusable regions,
+/// and usable regions will also be truncated by the reclaimable regions.
+///
+/// The regions are sanitized before the truncation. Invalid regions are
+/// dropped, and all the regions are aligned to page boundaries so that no page
+/// is partially usable. Overlapping regions of the same type are merged, so
+/// that the frame allocator never sees a page twice.
 pub fn non_overlapping_regions_from(regions: &[MemoryRegion]) -> Vec<MemoryRegion> {
-    // We should later use regions in `regions_unusable` to truncate all
-    // regions in `regions_usable`.
-    // The difference is that regions in `regions_usable` could be used by
-    // the frame allocator.
     let mut regions_usable = Vec::<MemoryRegion>::new();
+    let mut regions_reclaimable = Vec::<MemoryRegion>::new();
     let mut regions_unusable = Vec::<MemoryRegion>::new();
 
     for r in regions {
+        if r.is_empty() {
+            continue;
+        }
+        let Some(aligned) = r.page_aligned() else {
+            warn!("Ignoring the invalid memory region {:x?}", r);
+            continue;
+        };
+        if r.typ == MemoryRegionType::Usable && aligned != *r {
+            warn!("The usable memory region {:x?} is not page-aligned", r);
+        }
+        if aligned.is_empty() {
+            continue;
+        }
         match r.typ {
-            MemoryRegionType::Usable | MemoryRegionType::Reclaimable => {
-                regions_usable.push(*r);
+            MemoryRegionType::Usable => {
+                regions_usable.push(aligned);
+            }
+            MemoryRegionType::Reclaimable => {
+                regions_reclaimable.push(aligned);
             }
             _ => {
-                regions_unusable.push(*r);
+                regions_unusable.push(aligned);
             }
         }
     }
 
+    let regions_reclaimable = truncate_all(regions_reclaimable, &regions_unusable);
+    let regions_reclaimable = merge_overlapping(regions_reclaimable);
+    let regions_usable = truncate_all(regions_usable, &regions_unusable);
+    let regions_usable = merge_overlapping(truncate_all(regions_usable, &regions_reclaimable));
+
+    // Combine all the regions processed.
+    let mut all_regions = regions_unusable;
+    all_regions.extend(regions_reclaimable);
+    all_regions.extend(regions_usable);
+    all_regions
+}
+
+/// Removes the ranges of all the regions in `truncators` from `regions`.
+fn truncate_all(regions: Vec<MemoryRegion>, truncators: &[MemoryRegion]) -> Vec<MemoryRegion> {
     // `regions_*` are 2 rolling vectors since we are going to truncate
     // the regions in a iterative manner.
-    let mut regions = Vec::<MemoryRegion>::new();
-    let regions_src = &mut regions_usable;
-    let regions_dst = &mut regions;
-    // Truncate the usable regions.
-    for &r_unusable in &regions_unusable {
+    let mut regions_src = regions;
+    let mut regions_dst = Vec::<MemoryRegion>::new();
+    for truncator in truncators {
         regions_dst.clear();
-        for r_usable in &*regions_src {
-            regions_dst.append(&mut r_usable.truncate(&r_unusable));
+        for r in &regions_src {
+            regions_dst.append(&mut r.truncate(truncator));
         }
-        swap(regions_src, regions_dst);
+        swap(&mut regions_src, &mut regions_dst);
     }
+    regions_src
+}
 
-    // Combine all the regions processed.
-    let mut all_regions = regions_unusable;
-    all_regions.append(&mut regions_usable);
-    all_regions
+/// Merges the overlapping regions, which must be of the same type.
+fn merge_overlapping(mut regions: Vec<MemoryRegion>) -> Vec<MemoryRegion> {
+    regions.sort_unstable_by_key(|r| r.base);
+
+    let mut merged = Vec::<MemoryRegion>::with_capacity(regions.len());
+    for r in regions {
+        if let Some(last) = merged.last_mut() {
+            let last_end = last.base + last.len;
+            if r.base < last_end {
+                warn!("The memory regions {:x?} and {:x?} overlap", last, r);
+                last.len = last.len.max(r.base + r.len - last.base);
+                continue;
+            }
+        }
+        merged.push(r);
+    }
+    merged
+}
+
+#[cfg(ktest)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+
+    #[ktest]
+    fn sanitize_regions() {
+        let regions = [
+            MemoryRegion::new(0x1000, 0x4000, MemoryRegionType::Usable),
+            MemoryRegion::new(0x3000, 0x4000, MemoryRegionType::Usable),
+            MemoryRegion::new(0x4000, 0x1000, MemoryRegionType::Reclaimable),
+            MemoryRegion::new(0x5100, 0x100, MemoryRegionType::Reserved),
+            MemoryRegion::new(0x9000, 0x1800, MemoryRegionType::Usable),
+            MemoryRegion::new(usize::MAX, 0x1000, MemoryRegionType::Usable),
+        ];
+        let mut usable: Vec<_> = non_overlapping_regions_from(&regions)
+            .into_iter()
+            .filter(|r| r.typ() == MemoryRegionType::Usable)
+            .map(|r| (r.base(), r.len()))
+            .collect();
+        usable.sort();
+        assert_eq!(
+            usable,
+            [(0x1000, 0x3000), (0x6000, 0x1000), (0x9000, 0x1000)]
+        );
+    }
 }
@@ -6,8 +6,10 @@ use log::info;
 use super::VIRTIO_MMIO_MAGIC;
 use crate::{
     io_mem::IoMem,
+    io_resource::{IoResource, IoResourceKind},
     mm::{paddr_to_vaddr, Paddr, VmIo},
     trap::IrqLine,
+    Result,
 };
 
 /// MMIO Common device.
@@ -17,27 +19,31 @@ use crate::{
 pub struct MmioCommonDevice {
     io_mem: IoMem,
     irq: IrqLine,
+    _io_resource: IoResource,
 }
 
 impl MmioCommonDevice {
-    pub(super) fn new(paddr: Paddr, handle: IrqLine) -> Self {
+    pub(super) fn new(paddr: Paddr, handle: IrqLine) -> Result<Self> {
         // Read magic value
         // SAFETY: It only read the value and judge if the magic value fit 0x74726976
         unsafe {
             debug_assert_eq!(*(paddr_to_vaddr(paddr) as *const u32), VIRTIO_MMIO_MAGIC);
         }
+        let range = paddr..paddr + 0x200;
+        let io_resource = IoResource::claim(IoResourceKind::Memory, range.clone(), "virtio-mmio")?;
         // SAFETY: This range is virtio-mmio device space.
-        let io_mem = unsafe { IoMem::new(paddr..paddr + 0x200) };
+        let io_mem = unsafe { IoMem::new(range) };
         let res = Self {
             io_mem,
             irq: handle,
+            _io_resource: io_resource,
         };
         info!(
             "[Virtio]: Found Virtio mmio device, device id:{:?}, irq number:{:?}",
             res.device_id(),
             res.irq.num()
         );
-        res
+        Ok(res)
     }
 
     pub fn address(&self) -> Paddr {
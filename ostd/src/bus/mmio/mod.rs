@@ -70,7 +70,9 @@ fn iter_range(range: Range<usize>) {
             // If has two IOApic, then start: 24 (0 in IOApic2), end 47 (23 in IOApic2)
             // If one IOApic, then start: 16, end 23
             io_apic.enable(24 - device_count, handle.clone()).unwrap();
-            let device = MmioCommonDevice::new(current, handle);
+            let Ok(device) = MmioCommonDevice::new(current, handle) else {
+                continue;
+            };
             lock.register_mmio_device(device);
         }
     }
@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::sync::Arc;
+use alloc::{format, string::String, sync::Arc};
 use core::mem::size_of;
 
 use bitflags::bitflags;
@@ -9,6 +9,7 @@ use super::PciDeviceLocation;
 use crate::{
     arch::device::io_port::{PortRead, PortWrite},
     io_mem::IoMem,
+    io_resource::{IoResource, IoResourceKind},
     Error, Result,
 };
 
@@ -112,6 +113,7 @@ pub struct MemoryBar {
     prefetchable: bool,
     address_length: AddrLen,
     io_memory: IoMem,
+    _io_resource: Arc<IoResource>,
 }
 
 impl MemoryBar {
@@ -161,13 +163,21 @@ impl MemoryBar {
         // length
         let size = !(len_encoded & !0xF).wrapping_add(1);
         let prefetchable = raw & 0b1000 != 0;
+        let range = (base as usize)..((base + size as u64) as usize);
+        // Claim the range first, so that a BAR overlapping with others is never used.
+        let io_resource = IoResource::claim(
+            IoResourceKind::Memory,
+            range.clone(),
+            &bar_name(location, index),
+        )?;
         // The BAR is located in I/O memory region
         Ok(MemoryBar {
             base,
             size,
             prefetchable,
             address_length,
-            io_memory: unsafe { IoMem::new((base as usize)..((base + size as u64) as usize)) },
+            io_memory: unsafe { IoMem::new(range) },
+            _io_resource: Arc::new(io_resource),
         })
     }
 }
@@ -179,10 +189,11 @@ pub enum AddrLen {
     Bits64,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct IoBar {
     base: u32,
     size: u32,
+    _io_resource: Arc<IoResource>,
 }
 
 impl IoBar {
@@ -230,9 +241,24 @@ impl IoBar {
         let len_encoded = location.read32(offset);
         location.write32(offset, raw);
         let len = !(len_encoded & !0x3) + 1;
+        let base = raw & !0x3;
+        let io_resource = IoResource::claim(
+            IoResourceKind::Port,
+            (base as usize)..(base as usize + len as usize),
+            &bar_name(location, index),
+        )?;
         Ok(Self {
-            base: raw & !0x3,
+            base,
             size: len,
+            _io_resource: Arc::new(io_resource),
         })
     }
 }
+
+/// Returns the name of a BAR, which identifies the owner of its I/O resource.
+fn bar_name(location: &PciDeviceLocation, index: u8) -> String {
+    format!(
+        "PCI {:02x}:{:02x}.{} BAR{}",
+        location.bus, location.device, location.function, index
+    )
+}
@@ -0,0 +1,413 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! An intrusive doubly linked list.
+
+use core::{cell::Cell, marker::PhantomData, ptr::NonNull};
+
+use super::{OwnerId, OwnerToken};
+use crate::sync::OwnerPtr;
+
+/// A link that allows an object to be put into an [`IntrusiveList`].
+pub struct ListLink {
+    prev: Cell<Option<NonNull<ListLink>>>,
+    next: Cell<Option<NonNull<ListLink>>>,
+    owner: OwnerToken,
+}
+
+impl ListLink {
+    /// Creates a new link that is not linked into any list.
+    pub const fn new() -> Self {
+        Self {
+            prev: Cell::new(None),
+            next: Cell::new(None),
+            owner: OwnerToken::new(),
+        }
+    }
+
+    /// Returns whether the link is linked into a list.
+    pub fn is_linked(&self) -> bool {
+        self.owner.is_claimed()
+    }
+}
+
+impl Default for ListLink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// SAFETY: `prev` and `next` are only accessed by the list that owns the link,
+// which requires a mutable reference to the list to modify them. The owner
+// token itself is atomic.
+unsafe impl Send for ListLink {}
+unsafe impl Sync for ListLink {}
+
+/// Describes how objects are linked into an [`IntrusiveList`].
+///
+/// This trait is usually implemented with [`list_adapter!`].
+///
+/// # Safety
+///
+/// [`link_offset`] must return the offset of a [`ListLink`] field within
+/// the target type of [`Pointer`].
+///
+/// [`list_adapter!`]: crate::list_adapter
+/// [`link_offset`]: ListAdapter::link_offset
+/// [`Pointer`]: ListAdapter::Pointer
+pub unsafe trait ListAdapter {
+    /// The owning pointer type through which objects are inserted.
+    type Pointer: OwnerPtr<Target: Sized>;
+
+    /// Returns the offset of the link within the object.
+    fn link_offset() -> usize;
+}
+
+type Value<A> = <<A as ListAdapter>::Pointer as OwnerPtr>::Target;
+
+/// Defines a [`ListAdapter`] for objects with a [`ListLink`] field.
+///
+/// # Example
+///
+/// ```rust
+/// use ostd::{collections::intrusive::{IntrusiveList, ListLink}, list_adapter};
+///
+/// struct Request {
+///     id: u32,
+///     link: ListLink,
+/// }
+///
+/// list_adapter!(RequestAdapter = Arc<Request>: Request { link });
+///
+/// let mut list = IntrusiveList::<RequestAdapter>::new();
+/// ```
+#[macro_export]
+macro_rules! list_adapter {
+    ($(#[$attr:meta])* $vis:vis $name:ident = $ptr:ty: $value:ty { $field:ident }) => {
+        $(#[$attr])*
+        $vis struct $name;
+
+        // SAFETY: The offset is taken from a field that is checked to be a `ListLink`.
+        unsafe impl $crate::collections::intrusive::ListAdapter for $name {
+            type Pointer = $ptr;
+
+            fn link_offset() -> usize {
+                let _: fn(&$value) -> &$crate::collections::intrusive::ListLink =
+                    |value| &value.$field;
+                let _: fn(*const <$ptr as $crate::sync::OwnerPtr>::Target) -> *const $value =
+                    |ptr| ptr;
+                ::core::mem::offset_of!($value, $field)
+            }
+        }
+    };
+}
+
+/// An intrusive doubly linked list.
+///
+/// The list holds the owning pointers of the objects linked into it, and
+/// releases them when the objects are removed or the list is dropped.
+pub struct IntrusiveList<A: ListAdapter> {
+    head: Option<NonNull<ListLink>>,
+    tail: Option<NonNull<ListLink>>,
+    len: usize,
+    id: OwnerId,
+    _marker: PhantomData<A::Pointer>,
+}
+
+impl<A: ListAdapter> IntrusiveList<A> {
+    /// Creates an empty list.
+    pub const fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            id: OwnerId::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of objects in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Appends an object to the back of the list.
+    ///
+    /// If the object is already linked into a list, it is given back as an error.
+    pub fn push_back(&mut self, ptr: A::Pointer) -> Result<(), A::Pointer> {
+        let link = self.claim(ptr)?;
+        // SAFETY: The link has just been claimed, and the links of the list are valid.
+        unsafe {
+            link.as_ref().prev.set(self.tail);
+            link.as_ref().next.set(None);
+            match self.tail {
+                Some(tail) => tail.as_ref().next.set(Some(link)),
+                None => self.head = Some(link),
+            }
+        }
+        self.tail = Some(link);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Prepends an object to the front of the list.
+    ///
+    /// If the object is already linked into a list, it is given back as an error.
+    pub fn push_front(&mut self, ptr: A::Pointer) -> Result<(), A::Pointer> {
+        let link = self.claim(ptr)?;
+        // SAFETY: The link has just been claimed, and the links of the list are valid.
+        unsafe {
+            link.as_ref().prev.set(None);
+            link.as_ref().next.set(self.head);
+            match self.head {
+                Some(head) => head.as_ref().prev.set(Some(link)),
+                None => self.tail = Some(link),
+            }
+        }
+        self.head = Some(link);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes the object at the front of the list.
+    pub fn pop_front(&mut self) -> Option<A::Pointer> {
+        let head = self.head?;
+        // SAFETY: The link is in this list.
+        Some(unsafe { self.unlink(head) })
+    }
+
+    /// Removes the object at the back of the list.
+    pub fn pop_back(&mut self) -> Option<A::Pointer> {
+        let tail = self.tail?;
+        // SAFETY: The link is in this list.
+        Some(unsafe { self.unlink(tail) })
+    }
+
+    /// Returns the object at the front of the list.
+    pub fn front(&self) -> Option<&Value<A>> {
+        // SAFETY: The link is in this list.
+        self.head.map(|link| unsafe { &*Self::value_of(link) })
+    }
+
+    /// Returns the object at the back of the list.
+    pub fn back(&self) -> Option<&Value<A>> {
+        // SAFETY: The link is in this list.
+        self.tail.map(|link| unsafe { &*Self::value_of(link) })
+    }
+
+    /// Returns whether the object is linked into this list.
+    pub fn contains(&self, value: &Value<A>) -> bool {
+        let id = self.id.get();
+        // SAFETY: The reference points to a valid object with a link.
+        id != 0
+            && unsafe { Self::link_of(value).as_ref() }
+                .owner
+                .is_owned_by(id)
+    }
+
+    /// Removes the object from the list.
+    ///
+    /// Returns `None` if the object is not linked into this list.
+    pub fn remove(&mut self, value: &Value<A>) -> Option<A::Pointer> {
+        if !self.contains(value) {
+            return None;
+        }
+        // SAFETY: The link is in this list, as its owner token tells.
+        Some(unsafe { self.unlink(Self::link_of(value)) })
+    }
+
+    /// Removes all the objects from the list.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    /// Returns an iterator over the objects from the front to the back.
+    pub fn iter(&self) -> ListIter<'_, A> {
+        ListIter {
+            next: self.head,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Turns the pointer into a link and claims the link for this list.
+    fn claim(&mut self, ptr: A::Pointer) -> Result<NonNull<ListLink>, A::Pointer> {
+        let id = self.id.get_or_alloc();
+        let value = <A::Pointer as OwnerPtr>::into_raw(ptr);
+        let link = Self::link_of(value);
+        // SAFETY: The pointer points to a valid object with a link.
+        if unsafe { link.as_ref() }.owner.try_claim(id) {
+            Ok(link)
+        } else {
+            // SAFETY: The pointer was obtained from `into_raw` above.
+            Err(unsafe { <A::Pointer as OwnerPtr>::from_raw(value) })
+        }
+    }
+
+    /// Unlinks the link from the list and gives back the owning pointer.
+    ///
+    /// # Safety
+    ///
+    /// The link must be in this list.
+    unsafe fn unlink(&mut self, link: NonNull<ListLink>) -> A::Pointer {
+        let link_ref = link.as_ref();
+        let prev = link_ref.prev.take();
+        let next = link_ref.next.take();
+        match prev {
+            Some(prev) => prev.as_ref().next.set(next),
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => next.as_ref().prev.set(prev),
+            None => self.tail = prev,
+        }
+        self.len -= 1;
+        link_ref.owner.release();
+
+        <A::Pointer as OwnerPtr>::from_raw(Self::value_of(link))
+    }
+
+    fn link_of(value: *const Value<A>) -> NonNull<ListLink> {
+        let link = value.cast::<u8>().wrapping_add(A::link_offset());
+        NonNull::new(link.cast::<ListLink>().cast_mut()).unwrap()
+    }
+
+    fn value_of(link: NonNull<ListLink>) -> *const Value<A> {
+        link.as_ptr()
+            .cast::<u8>()
+            .wrapping_sub(A::link_offset())
+            .cast::<Value<A>>()
+            .cast_const()
+    }
+}
+
+impl<A: ListAdapter> Default for IntrusiveList<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: ListAdapter> Drop for IntrusiveList<A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+// SAFETY: The list owns the pointers of its objects and only hands out shared
+// references to them.
+unsafe impl<A: ListAdapter> Send for IntrusiveList<A> where A::Pointer: Send {}
+unsafe impl<A: ListAdapter> Sync for IntrusiveList<A> where Value<A>: Sync {}
+
+/// An iterator over the objects of an [`IntrusiveList`].
+pub struct ListIter<'a, A: ListAdapter> {
+    next: Option<NonNull<ListLink>>,
+    len: usize,
+    _marker: PhantomData<&'a IntrusiveList<A>>,
+}
+
+impl<'a, A: ListAdapter> Iterator for ListIter<'a, A> {
+    type Item = &'a Value<A>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let link = self.next?;
+        // SAFETY: The link is in the list, which is borrowed by the iterator.
+        unsafe {
+            self.next = link.as_ref().next.get();
+            self.len -= 1;
+            Some(&*IntrusiveList::<A>::value_of(link))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, A: ListAdapter> ExactSizeIterator for ListIter<'a, A> {}
+
+#[cfg(ktest)]
+mod test {
+    use super::*;
+    use crate::{list_adapter, prelude::*};
+
+    struct Item {
+        val: u32,
+        link: ListLink,
+    }
+
+    impl Item {
+        fn new(val: u32) -> Self {
+            Self {
+                val,
+                link: ListLink::new(),
+            }
+        }
+    }
+
+    list_adapter!(BoxAdapter = Box<Item>: Item { link });
+    list_adapter!(ArcAdapter = Arc<Item>: Item { link });
+
+    #[ktest]
+    fn push_pop() {
+        let mut list = IntrusiveList::<BoxAdapter>::new();
+        assert!(list.is_empty());
+
+        list.push_back(Box::new(Item::new(2))).unwrap();
+        list.push_front(Box::new(Item::new(1))).unwrap();
+        list.push_back(Box::new(Item::new(3))).unwrap();
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.front().unwrap().val, 1);
+        assert_eq!(list.back().unwrap().val, 3);
+
+        let vals: Vec<_> = list.iter().map(|item| item.val).collect();
+        assert_eq!(vals, [1, 2, 3]);
+
+        assert_eq!(list.pop_front().unwrap().val, 1);
+        assert_eq!(list.pop_back().unwrap().val, 3);
+        assert_eq!(list.pop_back().unwrap().val, 2);
+        assert!(list.pop_front().is_none());
+        assert!(list.is_empty());
+    }
+
+    #[ktest]
+    fn remove_by_reference() {
+        let items: Vec<_> = (0..4).map(|val| Arc::new(Item::new(val))).collect();
+        let mut list = IntrusiveList::<ArcAdapter>::new();
+        for item in items.iter() {
+            list.push_back(item.clone()).unwrap();
+        }
+
+        let removed = list.remove(&items[1]).unwrap();
+        assert!(Arc::ptr_eq(&removed, &items[1]));
+        assert!(!items[1].link.is_linked());
+        assert!(list.remove(&items[1]).is_none());
+
+        let vals: Vec<_> = list.iter().map(|item| item.val).collect();
+        assert_eq!(vals, [0, 2, 3]);
+
+        list.clear();
+        assert!(items.iter().all(|item| !item.link.is_linked()));
+        assert!(items.iter().all(|item| Arc::strong_count(item) == 1));
+    }
+
+    #[ktest]
+    fn owner_tokens() {
+        let item = Arc::new(Item::new(0));
+        let mut list1 = IntrusiveList::<ArcAdapter>::new();
+        let mut list2 = IntrusiveList::<ArcAdapter>::new();
+
+        list1.push_back(item.clone()).unwrap();
+        assert!(list2.push_back(item.clone()).is_err());
+        assert!(list2.remove(&item).is_none());
+        assert!(list1.contains(&item));
+        assert!(!list2.contains(&item));
+
+        list1.remove(&item).unwrap();
+        list2.push_back(item.clone()).unwrap();
+        assert!(list2.contains(&item));
+    }
+}
@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Intrusive collections.
+//!
+//! An intrusive collection links objects through the links embedded in the
+//! objects themselves, so inserting an object never allocates memory. This
+//! makes them usable in contexts like schedulers, timers, and LRU lists,
+//! where the objects already exist and allocation is undesirable.
+//!
+//! Two collections are provided:
+//!  - [`IntrusiveList`], a doubly linked list;
+//!  - [`IntrusiveRbTree`], a red-black tree ordered by keys.
+//!
+//! # Ownership of links
+//!
+//! Every link records an _owner token_ that identifies the collection it is
+//! linked into. A collection only touches a link after checking the token,
+//! which makes it safe to remove an object through a plain reference, and
+//! makes inserting an object that is already linked elsewhere a recoverable
+//! error rather than a memory corruption.
+//!
+//! An object is put into a collection through an owning pointer (see
+//! [`OwnerPtr`]), which the collection holds until the object is removed.
+//! The way to get from an object to its link is described by an adapter,
+//! which is usually defined with [`list_adapter!`] or [`rbtree_adapter!`].
+//!
+//! [`OwnerPtr`]: crate::sync::OwnerPtr
+//! [`list_adapter!`]: crate::list_adapter
+//! [`rbtree_adapter!`]: crate::rbtree_adapter
+
+mod list;
+mod rbtree;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+pub use self::{
+    list::{IntrusiveList, ListAdapter, ListIter, ListLink},
+    rbtree::{IntrusiveRbTree, RbTreeAdapter, RbTreeIter, RbTreeLink},
+};
+
+/// The owner token stored in a link.
+///
+/// A zero value means that the link is not linked into any collection.
+struct OwnerToken(AtomicUsize);
+
+impl OwnerToken {
+    const fn new() -> Self {
+        Self(AtomicUsize::new(0))
+    }
+
+    fn is_claimed(&self) -> bool {
+        self.0.load(Ordering::Relaxed) != 0
+    }
+
+    fn is_owned_by(&self, owner: usize) -> bool {
+        self.0.load(Ordering::Relaxed) == owner
+    }
+
+    /// Claims the link for the owner if the link is free.
+    fn try_claim(&self, owner: usize) -> bool {
+        self.0
+            .compare_exchange(0, owner, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    fn release(&self) {
+        self.0.store(0, Ordering::Release);
+    }
+}
+
+/// The identity of a collection.
+///
+/// The identity is allocated lazily on the first insertion, so that the
+/// collections can be created in `const` contexts.
+struct OwnerId(usize);
+
+impl OwnerId {
+    const fn new() -> Self {
+        Self(0)
+    }
+
+    fn get(&self) -> usize {
+        self.0
+    }
+
+    fn get_or_alloc(&mut self) -> usize {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+        if self.0 == 0 {
+            self.0 = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        }
+        self.0
+    }
+}
@@ -0,0 +1,747 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! An intrusive red-black tree.
+
+use core::{cell::Cell, cmp::Ordering, marker::PhantomData, ptr::NonNull};
+
+use super::{OwnerId, OwnerToken};
+use crate::sync::OwnerPtr;
+
+type LinkPtr = Option<NonNull<RbTreeLink>>;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Color {
+    Red,
+    Black,
+}
+
+/// A link that allows an object to be put into an [`IntrusiveRbTree`].
+pub struct RbTreeLink {
+    parent: Cell<LinkPtr>,
+    left: Cell<LinkPtr>,
+    right: Cell<LinkPtr>,
+    color: Cell<Color>,
+    owner: OwnerToken,
+}
+
+impl RbTreeLink {
+    /// Creates a new link that is not linked into any tree.
+    pub const fn new() -> Self {
+        Self {
+            parent: Cell::new(None),
+            left: Cell::new(None),
+            right: Cell::new(None),
+            color: Cell::new(Color::Red),
+            owner: OwnerToken::new(),
+        }
+    }
+
+    /// Returns whether the link is linked into a tree.
+    pub fn is_linked(&self) -> bool {
+        self.owner.is_claimed()
+    }
+}
+
+impl Default for RbTreeLink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// SAFETY: The fields other than the owner token are only accessed by the tree
+// that owns the link, which requires a mutable reference to the tree to modify
+// them. The owner token itself is atomic.
+unsafe impl Send for RbTreeLink {}
+unsafe impl Sync for RbTreeLink {}
+
+/// Describes how objects are linked into an [`IntrusiveRbTree`] and how they
+/// are ordered.
+///
+/// This trait is usually implemented with [`rbtree_adapter!`].
+///
+/// The key of an object must not change while the object is in a tree.
+/// Otherwise, the tree may behave incorrectly, although memory safety is
+/// still preserved.
+///
+/// # Safety
+///
+/// [`link_offset`] must return the offset of an [`RbTreeLink`] field within
+/// the target type of [`Pointer`].
+///
+/// [`rbtree_adapter!`]: crate::rbtree_adapter
+/// [`link_offset`]: RbTreeAdapter::link_offset
+/// [`Pointer`]: RbTreeAdapter::Pointer
+pub unsafe trait RbTreeAdapter {
+    /// The owning pointer type through which objects are inserted.
+    type Pointer: OwnerPtr<Target: Sized>;
+
+    /// The type of the keys by which objects are ordered.
+    type Key: Ord;
+
+    /// Returns the offset of the link within the object.
+    fn link_offset() -> usize;
+
+    /// Returns the key of the object.
+    fn key(value: &<Self::Pointer as OwnerPtr>::Target) -> Self::Key;
+}
+
+type Value<A> = <<A as RbTreeAdapter>::Pointer as OwnerPtr>::Target;
+
+/// Defines an [`RbTreeAdapter`] for objects with an [`RbTreeLink`] field.
+///
+/// # Example
+///
+/// ```rust
+/// use ostd::{collections::intrusive::{IntrusiveRbTree, RbTreeLink}, rbtree_adapter};
+///
+/// struct Timer {
+///     deadline: u64,
+///     link: RbTreeLink,
+/// }
+///
+/// rbtree_adapter!(TimerAdapter = Arc<Timer>: Timer { link } key u64 = |timer| timer.deadline);
+///
+/// let mut timers = IntrusiveRbTree::<TimerAdapter>::new();
+/// ```
+#[macro_export]
+macro_rules! rbtree_adapter {
+    (
+        $(#[$attr:meta])* $vis:vis $name:ident = $ptr:ty: $value:ty { $field:ident }
+        key $key:ty = |$arg:ident| $key_expr:expr
+    ) => {
+        $(#[$attr])*
+        $vis struct $name;
+
+        // SAFETY: The offset is taken from a field that is checked to be an `RbTreeLink`.
+        unsafe impl $crate::collections::intrusive::RbTreeAdapter for $name {
+            type Pointer = $ptr;
+            type Key = $key;
+
+            fn link_offset() -> usize {
+                let _: fn(&$value) -> &$crate::collections::intrusive::RbTreeLink =
+                    |value| &value.$field;
+                let _: fn(*const <$ptr as $crate::sync::OwnerPtr>::Target) -> *const $value =
+                    |ptr| ptr;
+                ::core::mem::offset_of!($value, $field)
+            }
+
+            fn key($arg: &$value) -> $key {
+                $key_expr
+            }
+        }
+    };
+}
+
+/// An intrusive red-black tree.
+///
+/// Objects are kept in the ascending order of their keys. Objects with equal
+/// keys are allowed, and are kept in the order of insertion.
+///
+/// The tree holds the owning pointers of the objects linked into it, and
+/// releases them when the objects are removed or the tree is dropped.
+pub struct IntrusiveRbTree<A: RbTreeAdapter> {
+    root: LinkPtr,
+    len: usize,
+    id: OwnerId,
+    _marker: PhantomData<A::Pointer>,
+}
+
+impl<A: RbTreeAdapter> IntrusiveRbTree<A> {
+    /// Creates an empty tree.
+    pub const fn new() -> Self {
+        Self {
+            root: None,
+            len: 0,
+            id: OwnerId::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of objects in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts an object into the tree.
+    ///
+    /// If the object is already linked into a tree, it is given back as an error.
+    pub fn insert(&mut self, ptr: A::Pointer) -> Result<(), A::Pointer> {
+        let id = self.id.get_or_alloc();
+        let value = <A::Pointer as OwnerPtr>::into_raw(ptr);
+        let node = Self::link_of(value);
+        // SAFETY: The pointer points to a valid object with a link.
+        if !unsafe { node.as_ref() }.owner.try_claim(id) {
+            // SAFETY: The pointer was obtained from `into_raw` above.
+            return Err(unsafe { <A::Pointer as OwnerPtr>::from_raw(value) });
+        }
+
+        // SAFETY: The link has just been claimed, and the links of the tree are valid.
+        unsafe {
+            let key = Self::key_of(node);
+
+            let mut parent = None;
+            let mut is_left = false;
+            let mut cur = self.root;
+            while let Some(cur_node) = cur {
+                parent = cur;
+                is_left = key < Self::key_of(cur_node);
+                cur = if is_left {
+                    left(cur_node)
+                } else {
+                    right(cur_node)
+                };
+            }
+
+            let link = node.as_ref();
+            link.parent.set(parent);
+            link.left.set(None);
+            link.right.set(None);
+            link.color.set(Color::Red);
+            match parent {
+                None => self.root = Some(node),
+                Some(parent) if is_left => parent.as_ref().left.set(Some(node)),
+                Some(parent) => parent.as_ref().right.set(Some(node)),
+            }
+
+            self.insert_fixup(node);
+        }
+
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes the object from the tree.
+    ///
+    /// Returns `None` if the object is not linked into this tree.
+    pub fn remove(&mut self, value: &Value<A>) -> Option<A::Pointer> {
+        if !self.contains(value) {
+            return None;
+        }
+        // SAFETY: The link is in this tree, as its owner token tells.
+        Some(unsafe { self.unlink(Self::link_of(value)) })
+    }
+
+    /// Returns whether the object is linked into this tree.
+    pub fn contains(&self, value: &Value<A>) -> bool {
+        let id = self.id.get();
+        // SAFETY: The reference points to a valid object with a link.
+        id != 0
+            && unsafe { Self::link_of(value).as_ref() }
+                .owner
+                .is_owned_by(id)
+    }
+
+    /// Returns the object with the smallest key.
+    pub fn first(&self) -> Option<&Value<A>> {
+        // SAFETY: The links of the tree are valid.
+        self.root
+            .map(|root| unsafe { &*Self::value_of(minimum(root)) })
+    }
+
+    /// Returns the object with the largest key.
+    pub fn last(&self) -> Option<&Value<A>> {
+        // SAFETY: The links of the tree are valid.
+        self.root
+            .map(|root| unsafe { &*Self::value_of(maximum(root)) })
+    }
+
+    /// Removes the object with the smallest key.
+    pub fn pop_first(&mut self) -> Option<A::Pointer> {
+        // SAFETY: The links of the tree are valid and the minimum is in the tree.
+        self.root.map(|root| unsafe { self.unlink(minimum(root)) })
+    }
+
+    /// Removes the object with the largest key.
+    pub fn pop_last(&mut self) -> Option<A::Pointer> {
+        // SAFETY: The links of the tree are valid and the maximum is in the tree.
+        self.root.map(|root| unsafe { self.unlink(maximum(root)) })
+    }
+
+    /// Returns an object whose key equals to the given one.
+    ///
+    /// If there are multiple such objects, the first inserted one is returned.
+    pub fn find(&self, key: &A::Key) -> Option<&Value<A>> {
+        self.lower_bound(key).filter(|value| A::key(value) == *key)
+    }
+
+    /// Returns the first object whose key is not less than the given one.
+    pub fn lower_bound(&self, key: &A::Key) -> Option<&Value<A>> {
+        let mut found = None;
+        let mut cur = self.root;
+        // SAFETY: The links of the tree are valid.
+        unsafe {
+            while let Some(node) = cur {
+                if Self::key_of(node).cmp(key) == Ordering::Less {
+                    cur = right(node);
+                } else {
+                    found = Some(node);
+                    cur = left(node);
+                }
+            }
+            found.map(|node| &*Self::value_of(node))
+        }
+    }
+
+    /// Removes all the objects from the tree.
+    pub fn clear(&mut self) {
+        while self.pop_first().is_some() {}
+    }
+
+    /// Returns an iterator over the objects in the ascending order of keys.
+    pub fn iter(&self) -> RbTreeIter<'_, A> {
+        RbTreeIter {
+            // SAFETY: The links of the tree are valid.
+            next: self.root.map(|root| unsafe { minimum(root) }),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Unlinks the node from the tree and gives back the owning pointer.
+    ///
+    /// # Safety
+    ///
+    /// The node must be in this tree.
+    unsafe fn unlink(&mut self, node: NonNull<RbTreeLink>) -> A::Pointer {
+        let link = node.as_ref();
+
+        let mut removed_color = color(Some(node));
+        let child;
+        let child_parent;
+        match (left(node), right(node)) {
+            (None, _) => {
+                child = right(node);
+                child_parent = parent(node);
+                self.transplant(node, child);
+            }
+            (_, None) => {
+                child = left(node);
+                child_parent = parent(node);
+                self.transplant(node, child);
+            }
+            (Some(node_left), Some(node_right)) => {
+                // Replace the node with its successor.
+                let successor = minimum(node_right);
+                removed_color = color(Some(successor));
+                child = right(successor);
+                if parent(successor) == Some(node) {
+                    child_parent = Some(successor);
+                } else {
+                    child_parent = parent(successor);
+                    self.transplant(successor, child);
+                    successor.as_ref().right.set(Some(node_right));
+                    node_right.as_ref().parent.set(Some(successor));
+                }
+                self.transplant(node, Some(successor));
+                successor.as_ref().left.set(Some(node_left));
+                node_left.as_ref().parent.set(Some(successor));
+                successor.as_ref().color.set(link.color.get());
+            }
+        }
+
+        if removed_color == Color::Black {
+            self.remove_fixup(child, child_parent);
+        }
+
+        link.parent.set(None);
+        link.left.set(None);
+        link.right.set(None);
+        self.len -= 1;
+        link.owner.release();
+
+        <A::Pointer as OwnerPtr>::from_raw(Self::value_of(node))
+    }
+
+    /// Restores the red-black properties after inserting a red node.
+    unsafe fn insert_fixup(&mut self, mut node: NonNull<RbTreeLink>) {
+        while let Some(mut node_parent) = parent(node) {
+            if color(Some(node_parent)) == Color::Black {
+                break;
+            }
+            // The parent is red, so it cannot be the root.
+            let grandparent = parent(node_parent).unwrap();
+
+            if left(grandparent) == Some(node_parent) {
+                let uncle = right(grandparent);
+                if color(uncle) == Color::Red {
+                    set_color(Some(node_parent), Color::Black);
+                    set_color(uncle, Color::Black);
+                    set_color(Some(grandparent), Color::Red);
+                    node = grandparent;
+                    continue;
+                }
+                if right(node_parent) == Some(node) {
+                    node = node_parent;
+                    self.rotate_left(node);
+                    node_parent = parent(node).unwrap();
+                }
+                set_color(Some(node_parent), Color::Black);
+                set_color(Some(grandparent), Color::Red);
+                self.rotate_right(grandparent);
+            } else {
+                let uncle = left(grandparent);
+                if color(uncle) == Color::Red {
+                    set_color(Some(node_parent), Color::Black);
+                    set_color(uncle, Color::Black);
+                    set_color(Some(grandparent), Color::Red);
+                    node = grandparent;
+                    continue;
+                }
+                if left(node_parent) == Some(node) {
+                    node = node_parent;
+                    self.rotate_right(node);
+                    node_parent = parent(node).unwrap();
+                }
+                set_color(Some(node_parent), Color::Black);
+                set_color(Some(grandparent), Color::Red);
+                self.rotate_left(grandparent);
+            }
+        }
+
+        set_color(self.root, Color::Black);
+    }
+
+    /// Restores the red-black properties after removing a black node.
+    ///
+    /// `node` is the node that takes the place of the removed node, which may
+    /// be `None`, so its parent is passed explicitly.
+    unsafe fn remove_fixup(&mut self, mut node: LinkPtr, mut node_parent: LinkPtr) {
+        while node != self.root && color(node) == Color::Black {
+            // The node is not the root, so it has a parent.
+            let parent_node = node_parent.unwrap();
+
+            if left(parent_node) == node {
+                // The removed node is black, so the sibling cannot be `None`.
+                let mut sibling = right(parent_node).unwrap();
+                if color(Some(sibling)) == Color::Red {
+                    set_color(Some(sibling), Color::Black);
+                    set_color(Some(parent_node), Color::Red);
+                    self.rotate_left(parent_node);
+                    sibling = right(parent_node).unwrap();
+                }
+                if color(left(sibling)) == Color::Black && color(right(sibling)) == Color::Black {
+                    set_color(Some(sibling), Color::Red);
+                    node = Some(parent_node);
+                    node_parent = parent(parent_node);
+                    continue;
+                }
+                if color(right(sibling)) == Color::Black {
+                    set_color(left(sibling), Color::Black);
+                    set_color(Some(sibling), Color::Red);
+                    self.rotate_right(sibling);
+                    sibling = right(parent_node).unwrap();
+                }
+                set_color(Some(sibling), color(Some(parent_node)));
+                set_color(Some(parent_node), Color::Black);
+                set_color(right(sibling), Color::Black);
+                self.rotate_left(parent_node);
+            } else {
+                // The removed node is black, so the sibling cannot be `None`.
+                let mut sibling = left(parent_node).unwrap();
+                if color(Some(sibling)) == Color::Red {
+                    set_color(Some(sibling), Color::Black);
+                    set_color(Some(parent_node), Color::Red);
+                    self.rotate_right(parent_node);
+                    sibling = left(parent_node).unwrap();
+                }
+                if color(left(sibling)) == Color::Black && color(right(sibling)) == Color::Black {
+                    set_color(Some(sibling), Color::Red);
+                    node = Some(parent_node);
+                    node_parent = parent(parent_node);
+                    continue;
+                }
+                if color(left(sibling)) == Color::Black {
+                    set_color(right(sibling), Color::Black);
+                    set_color(Some(sibling), Color::Red);
+                    self.rotate_left(sibling);
+                    sibling = left(parent_node).unwrap();
+                }
+                set_color(Some(sibling), color(Some(parent_node)));
+                set_color(Some(parent_node), Color::Black);
+                set_color(left(sibling), Color::Black);
+                self.rotate_right(parent_node);
+            }
+            node = self.root;
+            break;
+        }
+
+        set_color(node, Color::Black);
+    }
+
+    unsafe fn rotate_left(&mut self, node: NonNull<RbTreeLink>) {
+        let pivot = right(node).unwrap();
+        let pivot_left = left(pivot);
+
+        node.as_ref().right.set(pivot_left);
+        if let Some(pivot_left) = pivot_left {
+            pivot_left.as_ref().parent.set(Some(node));
+        }
+        self.transplant(node, Some(pivot));
+        pivot.as_ref().left.set(Some(node));
+        node.as_ref().parent.set(Some(pivot));
+    }
+
+    unsafe fn rotate_right(&mut self, node: NonNull<RbTreeLink>) {
+        let pivot = left(node).unwrap();
+        let pivot_right = right(pivot);
+
+        node.as_ref().left.set(pivot_right);
+        if let Some(pivot_right) = pivot_right {
+            pivot_right.as_ref().parent.set(Some(node));
+        }
+        self.transplant(node, Some(pivot));
+        pivot.as_ref().right.set(Some(node));
+        node.as_ref().parent.set(Some(pivot));
+    }
+
+    /// Replaces the subtree rooted at `old` with the subtree rooted at `new`
+    /// in the eyes of the parent of `old`.
+    unsafe fn transplant(&mut self, old: NonNull<RbTreeLink>, new: LinkPtr) {
+        let old_parent = parent(old);
+        match old_parent {
+            None => self.root = new,
+            Some(old_parent) if left(old_parent) == Some(old) => old_parent.as_ref().left.set(new),
+            Some(old_parent) => old_parent.as_ref().right.set(new),
+        }
+        if let Some(new) = new {
+            new.as_ref().parent.set(old_parent);
+        }
+    }
+
+    unsafe fn key_of(node: NonNull<RbTreeLink>) -> A::Key {
+        A::key(&*Self::value_of(node))
+    }
+
+    fn link_of(value: *const Value<A>) -> NonNull<RbTreeLink> {
+        let link = value.cast::<u8>().wrapping_add(A::link_offset());
+        NonNull::new(link.cast::<RbTreeLink>().cast_mut()).unwrap()
+    }
+
+    fn value_of(link: NonNull<RbTreeLink>) -> *const Value<A> {
+        link.as_ptr()
+            .cast::<u8>()
+            .wrapping_sub(A::link_offset())
+            .cast::<Value<A>>()
+            .cast_const()
+    }
+}
+
+impl<A: RbTreeAdapter> Default for IntrusiveRbTree<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: RbTreeAdapter> Drop for IntrusiveRbTree<A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+// SAFETY: The tree owns the pointers of its objects and only hands out shared
+// references to them.
+unsafe impl<A: RbTreeAdapter> Send for IntrusiveRbTree<A> where A::Pointer: Send {}
+unsafe impl<A: RbTreeAdapter> Sync for IntrusiveRbTree<A> where Value<A>: Sync {}
+
+/// An iterator over the objects of an [`IntrusiveRbTree`] in the ascending
+/// order of keys.
+pub struct RbTreeIter<'a, A: RbTreeAdapter> {
+    next: LinkPtr,
+    len: usize,
+    _marker: PhantomData<&'a IntrusiveRbTree<A>>,
+}
+
+impl<'a, A: RbTreeAdapter> Iterator for RbTreeIter<'a, A> {
+    type Item = &'a Value<A>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next?;
+        // SAFETY: The node is in the tree, which is borrowed by the iterator.
+        unsafe {
+            self.next = successor(node);
+            self.len -= 1;
+            Some(&*IntrusiveRbTree::<A>::value_of(node))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, A: RbTreeAdapter> ExactSizeIterator for RbTreeIter<'a, A> {}
+
+// The helpers below require that the links passed to them are valid links in a tree.
+
+unsafe fn parent(node: NonNull<RbTreeLink>) -> LinkPtr {
+    node.as_ref().parent.get()
+}
+
+unsafe fn left(node: NonNull<RbTreeLink>) -> LinkPtr {
+    node.as_ref().left.get()
+}
+
+unsafe fn right(node: NonNull<RbTreeLink>) -> LinkPtr {
+    node.as_ref().right.get()
+}
+
+/// Returns the color of the node, treating `None` as a black leaf.
+unsafe fn color(node: LinkPtr) -> Color {
+    node.map_or(Color::Black, |node| node.as_ref().color.get())
+}
+
+unsafe fn set_color(node: LinkPtr, color: Color) {
+    if let Some(node) = node {
+        node.as_ref().color.set(color);
+    }
+}
+
+unsafe fn minimum(mut node: NonNull<RbTreeLink>) -> NonNull<RbTreeLink> {
+    while let Some(node_left) = left(node) {
+        node = node_left;
+    }
+    node
+}
+
+unsafe fn maximum(mut node: NonNull<RbTreeLink>) -> NonNull<RbTreeLink> {
+    while let Some(node_right) = right(node) {
+        node = node_right;
+    }
+    node
+}
+
+unsafe fn successor(mut node: NonNull<RbTreeLink>) -> LinkPtr {
+    if let Some(node_right) = right(node) {
+        return Some(minimum(node_right));
+    }
+    while let Some(node_parent) = parent(node) {
+        if left(node_parent) == Some(node) {
+            return Some(node_parent);
+        }
+        node = node_parent;
+    }
+    None
+}
+
+#[cfg(ktest)]
+mod test {
+    use super::*;
+    use crate::{prelude::*, rbtree_adapter};
+
+    struct Item {
+        key: u32,
+        link: RbTreeLink,
+    }
+
+    impl Item {
+        fn new(key: u32) -> Self {
+            Self {
+                key,
+                link: RbTreeLink::new(),
+            }
+        }
+    }
+
+    rbtree_adapter!(BoxAdapter = Box<Item>: Item { link } key u32 = |item| item.key);
+    rbtree_adapter!(ArcAdapter = Arc<Item>: Item { link } key u32 = |item| item.key);
+
+    /// Checks the red-black properties and returns the black height.
+    fn check_subtree(node: LinkPtr, expected_parent: LinkPtr) -> usize {
+        let Some(node) = node else {
+            return 1;
+        };
+        unsafe {
+            assert!(parent(node) == expected_parent);
+            if color(Some(node)) == Color::Red {
+                assert_eq!(color(left(node)), Color::Black);
+                assert_eq!(color(right(node)), Color::Black);
+            }
+            let left_height = check_subtree(left(node), Some(node));
+            let right_height = check_subtree(right(node), Some(node));
+            assert_eq!(left_height, right_height);
+            left_height + (color(Some(node)) == Color::Black) as usize
+        }
+    }
+
+    fn check_tree<A: RbTreeAdapter>(tree: &IntrusiveRbTree<A>) {
+        assert_eq!(unsafe { color(tree.root) }, Color::Black);
+        check_subtree(tree.root, None);
+        assert_eq!(tree.iter().count(), tree.len());
+    }
+
+    /// Generates a permutation of `0..len` without an RNG.
+    fn permutation(len: u32) -> impl Iterator<Item = u32> {
+        // 7919 is a prime, so it is coprime to `len` unless `len` is its multiple.
+        (0..len).map(move |i| (i * 7919) % len)
+    }
+
+    #[ktest]
+    fn insert_in_order() {
+        const LEN: u32 = 500;
+
+        let mut tree = IntrusiveRbTree::<BoxAdapter>::new();
+        for key in permutation(LEN) {
+            tree.insert(Box::new(Item::new(key))).unwrap();
+        }
+        check_tree(&tree);
+
+        let keys: Vec<_> = tree.iter().map(|item| item.key).collect();
+        assert_eq!(keys, (0..LEN).collect::<Vec<_>>());
+        assert_eq!(tree.first().unwrap().key, 0);
+        assert_eq!(tree.last().unwrap().key, LEN - 1);
+        assert_eq!(tree.find(&42).unwrap().key, 42);
+        assert!(tree.find(&LEN).is_none());
+        assert_eq!(tree.lower_bound(&10).unwrap().key, 10);
+    }
+
+    #[ktest]
+    fn remove_keeps_balance() {
+        const LEN: u32 = 500;
+
+        let items: Vec<_> = (0..LEN).map(|key| Arc::new(Item::new(key))).collect();
+        let mut tree = IntrusiveRbTree::<ArcAdapter>::new();
+        for key in permutation(LEN) {
+            tree.insert(items[key as usize].clone()).unwrap();
+        }
+
+        for key in permutation(LEN).filter(|key| key % 3 != 0) {
+            let removed = tree.remove(&items[key as usize]).unwrap();
+            assert!(Arc::ptr_eq(&removed, &items[key as usize]));
+            assert!(!items[key as usize].link.is_linked());
+        }
+        check_tree(&tree);
+
+        let keys: Vec<_> = tree.iter().map(|item| item.key).collect();
+        assert_eq!(
+            keys,
+            (0..LEN).filter(|key| key % 3 == 0).collect::<Vec<_>>()
+        );
+
+        while let Some(first) = tree.pop_first() {
+            assert_eq!(first.key % 3, 0);
+            check_tree(&tree);
+        }
+        assert!(items.iter().all(|item| Arc::strong_count(item) == 1));
+    }
+
+    #[ktest]
+    fn duplicate_keys_and_owners() {
+        let items: Vec<_> = (0..3).map(|_| Arc::new(Item::new(7))).collect();
+        let mut tree = IntrusiveRbTree::<ArcAdapter>::new();
+        for item in items.iter() {
+            tree.insert(item.clone()).unwrap();
+        }
+        assert!(tree.insert(items[0].clone()).is_err());
+        assert!(Arc::ptr_eq(&tree.pop_first().unwrap(), &items[0]));
+
+        let mut other = IntrusiveRbTree::<ArcAdapter>::new();
+        assert!(other.remove(&items[1]).is_none());
+        assert!(tree.contains(&items[1]));
+        assert!(!other.contains(&items[1]));
+    }
+}
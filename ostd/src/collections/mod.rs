@@ -1,4 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
 //! This module provides some advanced collections.
+pub mod intrusive;
+pub mod rcu_hashmap;
 pub mod xarray;
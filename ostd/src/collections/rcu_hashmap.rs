@@ -0,0 +1,697 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! A resizable hash map that is optimized for read-mostly workloads.
+//!
+//! [`RcuHashMap`] lets readers look up entries without taking any lock, while
+//! writers serialize only on the bucket that they modify. It fits the lookup
+//! tables that are queried on hot paths but rarely updated, e.g., the process
+//! table or the socket port tables.
+//!
+//! # Design
+//!
+//! Each bucket is a singly linked list of immutable nodes. A writer holds the
+//! lock of a bucket while it links or unlinks nodes, and an unlinked node is
+//! only freed after an RCU grace period, so a concurrent reader never sees a
+//! dangling node.
+//!
+//! The map grows and shrinks by rehashing its entries into a new table of the
+//! appropriate size. The rehash is incremental: the new table is published as
+//! the _future_ table next to the _current_ one, and each subsequent write
+//! migrates a few buckets. Readers that miss in the current table retry in the
+//! future table. Once all buckets are migrated, the future table becomes the
+//! current one.
+
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{
+    borrow::Borrow,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ptr,
+    sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering},
+};
+
+use crate::sync::{after_grace_period, Rcu, SpinLock};
+
+/// The number of buckets that a new map starts with, which is also the minimum.
+const MIN_NUM_BUCKETS: usize = 16;
+
+/// The number of buckets that a write operation migrates during a resize.
+const NUM_BUCKETS_TO_MIGRATE: usize = 4;
+
+/// A concurrent hash map with lock-free lookups.
+///
+/// Lookups return clones of the values, so the value type is expected to be
+/// cheap to clone (e.g., an [`Arc`]).
+pub struct RcuHashMap<K, V> {
+    tables: Rcu<Box<Tables<K, V>>>,
+    len: AtomicUsize,
+    /// Whether a resize is in progress.
+    ///
+    /// The flag is set when a resize starts and cleared once the old table is
+    /// no longer visible to anyone, so that at most two tables are alive.
+    is_resizing: Arc<AtomicBool>,
+}
+
+/// The tables of a map, which are replaced as a whole by RCU.
+struct Tables<K, V> {
+    current: Arc<Table<K, V>>,
+    future: Option<Arc<Table<K, V>>>,
+}
+
+struct Table<K, V> {
+    buckets: Box<[Bucket<K, V>]>,
+    /// Whether the buckets of the current table can be migrated to this table.
+    ///
+    /// This is only meaningful for a future table. Migration must not start
+    /// until every reader knows about the future table.
+    is_migration_ready: AtomicBool,
+    /// The index of the next bucket of the current table to migrate.
+    next_to_migrate: AtomicUsize,
+    /// The number of buckets of the current table that have been migrated.
+    num_migrated: AtomicUsize,
+}
+
+struct Bucket<K, V> {
+    head: AtomicPtr<Node<K, V>>,
+    /// The lock that serializes the writers of the bucket.
+    ///
+    /// It protects a flag telling whether the bucket has been migrated to the
+    /// future table, after which all updates must go to the future table.
+    lock: SpinLock<bool>,
+    _marker: PhantomData<Box<Node<K, V>>>,
+}
+
+struct Node<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+    next: AtomicPtr<Node<K, V>>,
+}
+
+impl<K, V> RcuHashMap<K, V>
+where
+    K: Hash + Eq + Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        let tables = Tables {
+            current: Arc::new(Table::new(MIN_NUM_BUCKETS)),
+            future: None,
+        };
+        Self {
+            tables: Rcu::new(Box::new(tables)),
+            len: AtomicUsize::new(0),
+            is_resizing: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    ///
+    /// The result may be stale in the presence of concurrent writers.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Returns whether the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a clone of the value corresponding to the key.
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let tables = self.tables.get();
+        let hash = hash_of(key);
+
+        // Search the current table before the future table. A migrating entry
+        // is inserted into the future table before it is removed from the
+        // current table, so it cannot be missed in this order.
+        tables
+            .current
+            .bucket(hash)
+            .find(hash, key)
+            .or_else(|| tables.future.as_ref()?.bucket(hash).find(hash, key))
+            .map(|node| node.value.clone())
+    }
+
+    /// Returns whether the map contains the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Returns the clones of all the values in the map, in no particular order.
+    ///
+    /// An entry that is inserted or removed concurrently may or may not be
+    /// included, but any other entry is included exactly once, even if the
+    /// map is being resized.
+    pub fn values(&self) -> Vec<V> {
+        let tables = self.tables.get();
+        let mut values = Vec::with_capacity(self.len());
+
+        let Some(future) = tables.future.as_ref() else {
+            // A resize that starts now cannot migrate any entry until a grace
+            // period elapses, so the entries stay in the current table.
+            for bucket in tables.current.buckets.iter() {
+                bucket.for_each(|node| values.push(node.value.clone()));
+            }
+            return values;
+        };
+
+        // An entry is taken from the current table if its bucket has not been
+        // migrated, or from the future table otherwise. Each bucket is checked
+        // under its lock, so the entries that are migrated afterward are not
+        // taken again from the future table.
+        let is_migrated = tables
+            .current
+            .buckets
+            .iter()
+            .map(|bucket| {
+                let is_migrated = bucket.lock.lock();
+                if !*is_migrated {
+                    bucket.for_each(|node| values.push(node.value.clone()));
+                }
+                *is_migrated
+            })
+            .collect::<Vec<_>>();
+        let num_buckets = is_migrated.len();
+        for bucket in future.buckets.iter() {
+            bucket.for_each(|node| {
+                if is_migrated[node.hash as usize & (num_buckets - 1)] {
+                    values.push(node.value.clone());
+                }
+            });
+        }
+        values
+    }
+
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map already contains the key, the value is replaced and the old
+    /// value is returned.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        let tables = self.tables.get();
+        let hash = hash_of(&key);
+
+        let old_value = tables.with_locked_bucket(hash, |bucket| {
+            let new_node = Box::into_raw(Box::new(Node {
+                hash,
+                key,
+                value,
+                next: AtomicPtr::new(ptr::null_mut()),
+            }));
+            // SAFETY: The node is newly allocated and the bucket is locked.
+            unsafe { bucket.insert(new_node) }
+        });
+        if old_value.is_none() {
+            self.len.fetch_add(1, Ordering::Relaxed);
+        }
+
+        self.after_write(&tables);
+        old_value
+    }
+
+    /// Removes a key from the map, returning the value if the key was in the map.
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let tables = self.tables.get();
+        let hash = hash_of(key);
+
+        // SAFETY: The bucket is locked.
+        let old_value =
+            tables.with_locked_bucket(hash, |bucket| unsafe { bucket.remove(hash, key) });
+        if old_value.is_some() {
+            self.len.fetch_sub(1, Ordering::Relaxed);
+        }
+
+        self.after_write(&tables);
+        old_value
+    }
+
+    /// Advances the ongoing resize, or starts a new one if the load factor is
+    /// out of the desired range.
+    fn after_write(&self, tables: &Tables<K, V>) {
+        if let Some(future) = tables.future.as_ref() {
+            self.migrate_some(&tables.current, future);
+            return;
+        }
+
+        let num_buckets = tables.current.buckets.len();
+        let len = self.len();
+        let new_num_buckets = if len > num_buckets {
+            num_buckets * 2
+        } else if len < num_buckets / 8 && num_buckets > MIN_NUM_BUCKETS {
+            num_buckets / 2
+        } else {
+            return;
+        };
+
+        if self
+            .is_resizing
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            return;
+        }
+
+        let future = Arc::new(Table::new(new_num_buckets));
+        let new_tables = Tables {
+            current: tables.current.clone(),
+            future: Some(future.clone()),
+        };
+        self.tables.replace(Box::new(new_tables)).delay();
+
+        // Readers that started before the future table was published only
+        // search the current table. Wait for them before moving any entry.
+        after_grace_period(move || {
+            future.is_migration_ready.store(true, Ordering::Release);
+        });
+    }
+
+    fn migrate_some(&self, current: &Arc<Table<K, V>>, future: &Arc<Table<K, V>>) {
+        if !future.is_migration_ready.load(Ordering::Acquire) {
+            return;
+        }
+
+        let num_buckets = current.buckets.len();
+        for _ in 0..NUM_BUCKETS_TO_MIGRATE {
+            let index = future.next_to_migrate.fetch_add(1, Ordering::Relaxed);
+            if index >= num_buckets {
+                return;
+            }
+
+            current.buckets[index].migrate_to(future);
+
+            if future.num_migrated.fetch_add(1, Ordering::AcqRel) + 1 == num_buckets {
+                self.finish_resize(future);
+                return;
+            }
+        }
+    }
+
+    fn finish_resize(&self, future: &Arc<Table<K, V>>) {
+        let new_tables = Tables {
+            current: future.clone(),
+            future: None,
+        };
+        self.tables.replace(Box::new(new_tables)).delay();
+
+        // Writers that still see the old table follow its migrated buckets to
+        // the new table. Another resize may only start after they are gone.
+        let is_resizing = self.is_resizing.clone();
+        after_grace_period(move || {
+            is_resizing.store(false, Ordering::Release);
+        });
+    }
+}
+
+impl<K, V> Default for RcuHashMap<K, V>
+where
+    K: Hash + Eq + Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// SAFETY: The keys and values are shared between the threads that access the
+// map, and they are dropped by whichever thread ends their grace period.
+unsafe impl<K: Send + Sync, V: Send + Sync> Send for RcuHashMap<K, V> {}
+unsafe impl<K: Send + Sync, V: Send + Sync> Sync for RcuHashMap<K, V> {}
+
+impl<K, V> Tables<K, V>
+where
+    K: Clone + Send + 'static,
+    V: Clone + Send + 'static,
+{
+    /// Locks the bucket where the entries with the hash are to be updated,
+    /// and calls the closure with it.
+    fn with_locked_bucket<R>(&self, hash: u64, f: impl FnOnce(&Bucket<K, V>) -> R) -> R {
+        let bucket = self.current.bucket(hash);
+        let is_migrated = bucket.lock.lock();
+        if !*is_migrated {
+            return f(bucket);
+        }
+
+        // The bucket in the current table is kept locked, so that the locks
+        // are always acquired in the order of current-then-future.
+        let future_bucket = self.future.as_ref().unwrap().bucket(hash);
+        let is_future_migrated = future_bucket.lock.lock();
+        debug_assert!(!*is_future_migrated);
+        f(future_bucket)
+    }
+}
+
+impl<K, V> Table<K, V> {
+    fn new(num_buckets: usize) -> Self {
+        debug_assert!(num_buckets.is_power_of_two());
+
+        let buckets = (0..num_buckets)
+            .map(|_| Bucket {
+                head: AtomicPtr::new(ptr::null_mut()),
+                lock: SpinLock::new(false),
+                _marker: PhantomData,
+            })
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        Self {
+            buckets,
+            is_migration_ready: AtomicBool::new(false),
+            next_to_migrate: AtomicUsize::new(0),
+            num_migrated: AtomicUsize::new(0),
+        }
+    }
+
+    fn bucket(&self, hash: u64) -> &Bucket<K, V> {
+        &self.buckets[hash as usize & (self.buckets.len() - 1)]
+    }
+}
+
+impl<K, V> Drop for Table<K, V> {
+    fn drop(&mut self) {
+        for bucket in self.buckets.iter_mut() {
+            let mut node = *bucket.head.get_mut();
+            while !node.is_null() {
+                // SAFETY: The node is owned by the bucket. Since the table is being
+                // dropped, no one else can access it.
+                let node_box = unsafe { Box::from_raw(node) };
+                node = node_box.next.load(Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl<K, V> Bucket<K, V> {
+    /// Finds the node with the key.
+    ///
+    /// The caller must be in an RCU read-side critical section or hold the
+    /// lock of the bucket.
+    fn find<Q>(&self, hash: u64, key: &Q) -> Option<&Node<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        let mut node = self.head.load(Ordering::Acquire);
+        while !node.is_null() {
+            // SAFETY: A reachable node is only freed after a grace period.
+            let node_ref = unsafe { &*node };
+            if node_ref.hash == hash && node_ref.key.borrow() == key {
+                return Some(node_ref);
+            }
+            node = node_ref.next.load(Ordering::Acquire);
+        }
+        None
+    }
+
+    /// Calls the closure with each node of the bucket.
+    ///
+    /// The caller must be in an RCU read-side critical section or hold the
+    /// lock of the bucket.
+    fn for_each(&self, mut f: impl FnMut(&Node<K, V>)) {
+        let mut node = self.head.load(Ordering::Acquire);
+        while !node.is_null() {
+            // SAFETY: A reachable node is only freed after a grace period.
+            let node_ref = unsafe { &*node };
+            f(node_ref);
+            node = node_ref.next.load(Ordering::Acquire);
+        }
+    }
+}
+
+impl<K, V> Bucket<K, V>
+where
+    K: Clone + Send + 'static,
+    V: Clone + Send + 'static,
+{
+    /// Links a new node, replacing the existing node with the same key.
+    ///
+    /// # Safety
+    ///
+    /// The caller must hold the lock of the bucket and pass a node allocated
+    /// by `Box` that is not linked anywhere.
+    unsafe fn insert(&self, new_node: *mut Node<K, V>) -> Option<V>
+    where
+        K: Eq,
+    {
+        let new_node_ref = &*new_node;
+
+        let mut link = &self.head;
+        loop {
+            let node = link.load(Ordering::Relaxed);
+            if node.is_null() {
+                break;
+            }
+            let node_ref = &*node;
+            if node_ref.hash == new_node_ref.hash && node_ref.key == new_node_ref.key {
+                new_node_ref
+                    .next
+                    .store(node_ref.next.load(Ordering::Relaxed), Ordering::Relaxed);
+                link.store(new_node, Ordering::Release);
+
+                let old_value = node_ref.value.clone();
+                retire(node);
+                return Some(old_value);
+            }
+            link = &node_ref.next;
+        }
+
+        new_node_ref
+            .next
+            .store(self.head.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.head.store(new_node, Ordering::Release);
+        None
+    }
+
+    /// Unlinks the node with the key and returns its value.
+    ///
+    /// # Safety
+    ///
+    /// The caller must hold the lock of the bucket.
+    unsafe fn remove<Q>(&self, hash: u64, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        let mut link = &self.head;
+        loop {
+            let node = link.load(Ordering::Relaxed);
+            if node.is_null() {
+                return None;
+            }
+            let node_ref = &*node;
+            if node_ref.hash == hash && node_ref.key.borrow() == key {
+                link.store(node_ref.next.load(Ordering::Relaxed), Ordering::Release);
+
+                let old_value = node_ref.value.clone();
+                retire(node);
+                return Some(old_value);
+            }
+            link = &node_ref.next;
+        }
+    }
+
+    /// Moves all the nodes of the bucket to the future table.
+    fn migrate_to(&self, future: &Table<K, V>) {
+        let mut is_migrated = self.lock.lock();
+        debug_assert!(!*is_migrated);
+
+        // Copy each node into the future table before unlinking the nodes from
+        // this bucket, so that readers can find the entries in either table.
+        let mut node = self.head.load(Ordering::Relaxed);
+        while !node.is_null() {
+            // SAFETY: The bucket is locked, so the node is still linked.
+            let node_ref = unsafe { &*node };
+            let new_node = Box::into_raw(Box::new(Node {
+                hash: node_ref.hash,
+                key: node_ref.key.clone(),
+                value: node_ref.value.clone(),
+                next: AtomicPtr::new(ptr::null_mut()),
+            }));
+
+            let future_bucket = future.bucket(node_ref.hash);
+            let _future_guard = future_bucket.lock.lock();
+            // SAFETY: The future bucket is locked, and the new node cannot be
+            // a duplicate since updates to its key are still directed here.
+            unsafe { future_bucket.push_front(new_node) };
+
+            node = node_ref.next.load(Ordering::Relaxed);
+        }
+
+        let mut node = self.head.swap(ptr::null_mut(), Ordering::Release);
+        *is_migrated = true;
+        drop(is_migrated);
+
+        while !node.is_null() {
+            // SAFETY: The node has been unlinked but not freed yet.
+            let next = unsafe { &*node }.next.load(Ordering::Relaxed);
+            retire(node);
+            node = next;
+        }
+    }
+
+    /// Links a new node at the front of the bucket.
+    ///
+    /// # Safety
+    ///
+    /// The caller must hold the lock of the bucket and pass a node allocated
+    /// by `Box` that is not linked anywhere.
+    unsafe fn push_front(&self, new_node: *mut Node<K, V>) {
+        (*new_node)
+            .next
+            .store(self.head.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.head.store(new_node, Ordering::Release);
+    }
+}
+
+/// Frees an unlinked node after a grace period.
+fn retire<K: Send + 'static, V: Send + 'static>(node: *mut Node<K, V>) {
+    struct RetiredNode<K, V>(*mut Node<K, V>);
+
+    // SAFETY: The node is no longer reachable, so it is only accessed by the
+    // thread that frees it.
+    unsafe impl<K: Send, V: Send> Send for RetiredNode<K, V> {}
+
+    let retired = RetiredNode(node);
+    after_grace_period(move || {
+        let retired = retired;
+        // SAFETY: The node was allocated by `Box` and readers that could see it
+        // have left their read-side critical sections.
+        drop(unsafe { Box::from_raw(retired.0) });
+    });
+}
+
+fn hash_of<Q: Hash + ?Sized>(key: &Q) -> u64 {
+    let mut hasher = FxHasher::default();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The hash function used by the Rust compiler.
+///
+/// It is fast for small keys such as integers, but it does not resist
+/// hash-flooding attacks.
+#[derive(Default)]
+struct FxHasher {
+    hash: u64,
+}
+
+impl FxHasher {
+    const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+    fn add_to_hash(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(Self::SEED);
+    }
+}
+
+impl Hasher for FxHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            self.add_to_hash(u64::from_ne_bytes(chunk.try_into().unwrap()));
+        }
+        for &byte in chunks.remainder() {
+            self.add_to_hash(byte as u64);
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.add_to_hash(i);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+#[cfg(ktest)]
+mod test {
+    use alloc::string::String;
+
+    use super::*;
+    use crate::{prelude::*, task::Task};
+
+    #[ktest]
+    fn insert_get_remove() {
+        let map = RcuHashMap::new();
+        assert!(map.is_empty());
+
+        assert_eq!(map.insert(1, 10), None);
+        assert_eq!(map.insert(2, 20), None);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&1), Some(10));
+        assert_eq!(map.get(&3), None);
+
+        assert_eq!(map.insert(1, 11), Some(10));
+        assert_eq!(map.get(&1), Some(11));
+        assert_eq!(map.len(), 2);
+
+        assert_eq!(map.remove(&1), Some(11));
+        assert_eq!(map.remove(&1), None);
+        assert!(!map.contains_key(&1));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[ktest]
+    fn borrowed_keys() {
+        let map = RcuHashMap::new();
+        map.insert(String::from("foo"), 1);
+        assert_eq!(map.get("foo"), Some(1));
+        assert_eq!(map.remove("foo"), Some(1));
+    }
+
+    #[ktest]
+    fn grow_and_shrink() {
+        const NUM_ENTRIES: usize = 1000;
+
+        let map = RcuHashMap::new();
+        for i in 0..NUM_ENTRIES {
+            map.insert(i, i * 2);
+            // Let grace periods elapse so that resizes can make progress.
+            Task::yield_now();
+            // The entries are neither missed nor repeated during a resize.
+            assert_eq!(map.values().len(), i + 1);
+        }
+        for i in 0..NUM_ENTRIES {
+            assert_eq!(map.get(&i), Some(i * 2));
+        }
+        let mut values = map.values();
+        values.sort();
+        assert_eq!(values, (0..NUM_ENTRIES).map(|i| i * 2).collect::<Vec<_>>());
+
+        for i in 0..NUM_ENTRIES {
+            assert_eq!(map.remove(&i), Some(i * 2));
+            Task::yield_now();
+        }
+        assert!(map.is_empty());
+        for i in 0..NUM_ENTRIES {
+            assert!(!map.contains_key(&i));
+        }
+    }
+}
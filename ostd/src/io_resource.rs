@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! I/O resources.
+//!
+//! An I/O resource is a range of I/O memory or a range of I/O ports. Before a
+//! range is accessed, it should be claimed with [`IoResource::claim`], which works
+//! like `request_mem_region` and `request_region` in Linux. A range can only be
+//! claimed by one owner at a time, so that conflicts between drivers, e.g., two
+//! devices whose BARs overlap, are detected instead of silently corrupting each
+//! other. The claimed ranges are listed by [`claimed_resources`], which provides
+//! the contents of `/proc/iomem` and `/proc/ioports`.
+
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::ops::Range;
+
+use log::warn;
+
+use crate::{sync::SpinLock, Error, Result};
+
+/// The kind of an I/O resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoResourceKind {
+    /// I/O memory, whose addresses are physical addresses.
+    Memory,
+    /// I/O ports.
+    Port,
+}
+
+/// A claimed range of an I/O resource.
+///
+/// The range is released when the `IoResource` is dropped.
+#[derive(Debug)]
+pub struct IoResource {
+    kind: IoResourceKind,
+    range: Range<usize>,
+}
+
+impl IoResource {
+    /// Claims the `range` of the `kind` resource for the owner named `name`.
+    ///
+    /// # Errors
+    ///
+    /// This method returns [`Error::InvalidArgs`] if the range is empty, and
+    /// [`Error::NotEnoughResources`] if the range overlaps with a claimed range.
+    pub fn claim(kind: IoResourceKind, range: Range<usize>, name: &str) -> Result<Self> {
+        if range.is_empty() {
+            return Err(Error::InvalidArgs);
+        }
+
+        let mut registry = registry_of(kind).lock();
+        // Claimed ranges never overlap, so only the last range that starts before
+        // the end of `range` can overlap with it.
+        if let Some((start, (end, owner))) = registry.range(..range.end).next_back() {
+            if *end > range.start {
+                warn!(
+                    "{:?} resource {:#x}..{:#x} of {} conflicts with {:#x}..{:#x} of {}",
+                    kind, range.start, range.end, name, start, end, owner
+                );
+                return Err(Error::NotEnoughResources);
+            }
+        }
+        registry.insert(range.start, (range.end, name.to_string()));
+
+        Ok(Self { kind, range })
+    }
+
+    /// Returns the kind of the resource.
+    pub fn kind(&self) -> IoResourceKind {
+        self.kind
+    }
+
+    /// Returns the claimed range.
+    pub fn range(&self) -> &Range<usize> {
+        &self.range
+    }
+}
+
+impl Drop for IoResource {
+    fn drop(&mut self) {
+        registry_of(self.kind).lock().remove(&self.range.start);
+    }
+}
+
+/// Information about a claimed range of an I/O resource.
+#[derive(Debug, Clone)]
+pub struct IoResourceInfo {
+    /// The claimed range.
+    pub range: Range<usize>,
+    /// The name of the owner.
+    pub name: String,
+}
+
+/// Returns the claimed ranges of the `kind` resource in ascending order.
+pub fn claimed_resources(kind: IoResourceKind) -> Vec<IoResourceInfo> {
+    registry_of(kind)
+        .lock()
+        .iter()
+        .map(|(start, (end, name))| IoResourceInfo {
+            range: *start..*end,
+            name: name.clone(),
+        })
+        .collect()
+}
+
+/// The claimed ranges, which map the start of each range to its end and its owner.
+type Registry = SpinLock<BTreeMap<usize, (usize, String)>>;
+
+static IO_MEM_REGISTRY: Registry = SpinLock::new(BTreeMap::new());
+static IO_PORT_REGISTRY: Registry = SpinLock::new(BTreeMap::new());
+
+fn registry_of(kind: IoResourceKind) -> &'static Registry {
+    match kind {
+        IoResourceKind::Memory => &IO_MEM_REGISTRY,
+        IoResourceKind::Port => &IO_PORT_REGISTRY,
+    }
+}
+
+#[cfg(ktest)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+
+    #[ktest]
+    fn claim_and_release() {
+        const BASE: usize = usize::MAX - 0x10000;
+        let kind = IoResourceKind::Memory;
+
+        let first = IoResource::claim(kind, BASE..BASE + 0x1000, "first").unwrap();
+        assert_eq!(
+            IoResource::claim(kind, BASE + 0xfff..BASE + 0x2000, "second").unwrap_err(),
+            Error::NotEnoughResources
+        );
+        assert_eq!(
+            IoResource::claim(kind, BASE + 0x1000..BASE + 0x1000, "empty").unwrap_err(),
+            Error::InvalidArgs
+        );
+        let second = IoResource::claim(kind, BASE + 0x1000..BASE + 0x2000, "second").unwrap();
+        assert!(claimed_resources(kind)
+            .iter()
+            .any(|info| info.range == (BASE..BASE + 0x1000) && info.name == "first"));
+
+        drop(first);
+        drop(second);
+        let _whole = IoResource::claim(kind, BASE..BASE + 0x2000, "whole").unwrap();
+    }
+}
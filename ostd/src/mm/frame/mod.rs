@@ -69,6 +69,13 @@ impl Frame {
         paddr_to_vaddr(self.start_paddr()) as *mut u8
     }
 
+    /// Returns whether the frame is poisoned by an uncorrected memory error.
+    ///
+    /// The content of a poisoned frame has been lost and must not be accessed.
+    pub fn is_poisoned(&self) -> bool {
+        super::page::allocator::is_page_poisoned(self.start_paddr())
+    }
+
     /// Copies the content of `src` to the frame.
     pub fn copy_from(&self, src: &Frame) {
         if self.paddr() == src.paddr() {
@@ -29,7 +29,7 @@ pub use self::{
     frame::{options::FrameAllocOptions, Frame, FrameVec, FrameVecIter, Segment},
     io::{KernelSpace, UserSpace, VmIo, VmReader, VmWriter},
     page_prop::{CachePolicy, PageFlags, PageProperty},
-    space::{VmMapOptions, VmSpace},
+    space::{VmMapOptions, VmQueryIter, VmQueryResult, VmSpace},
 };
 pub(crate) use self::{
     kspace::paddr_to_vaddr, page::meta::init as init_page_meta, page_prop::PrivilegedPageFlags,
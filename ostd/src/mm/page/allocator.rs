@@ -5,21 +5,77 @@
 //! TODO: Decouple it with the frame allocator in [`crate::mm::frame::options`] by
 //! allocating pages rather untyped memory from this module.
 
-use alloc::vec::Vec;
+use alloc::{collections::BTreeSet, vec, vec::Vec};
+use core::sync::atomic::{AtomicBool, Ordering};
 
 use align_ext::AlignExt;
 use buddy_system_allocator::FrameAllocator;
 use log::info;
 use spin::Once;
 
-use super::{cont_pages::ContPages, meta::PageMeta, Page};
-use crate::{boot::memory_region::MemoryRegionType, mm::PAGE_SIZE, sync::SpinLock};
+use super::{cont_pages::ContPages, memtest, meta::PageMeta, Page};
+use crate::{
+    boot::memory_region::MemoryRegionType,
+    mm::{Paddr, PAGE_SIZE},
+    sync::SpinLock,
+};
 
 pub(in crate::mm) static PAGE_ALLOCATOR: Once<SpinLock<FrameAllocator>> = Once::new();
 
+/// The pages that suffer from uncorrectable memory errors.
+///
+/// A poisoned page is never allocated again. If it is in use when it is
+/// poisoned, it is not returned to the allocator when it is freed.
+static POISONED_PAGES: SpinLock<BTreeSet<Paddr>> = SpinLock::new(BTreeSet::new());
+/// Whether `POISONED_PAGES` is not empty, which avoids locking it on the fast paths.
+static HAS_POISONED_PAGES: AtomicBool = AtomicBool::new(false);
+
+/// Poisons the page that contains `paddr`.
+///
+/// This function must not be called in the machine check handler, which can
+/// interrupt the holder of the lock.
+pub(crate) fn poison_page(paddr: Paddr) {
+    POISONED_PAGES
+        .lock_irq_disabled()
+        .insert(paddr.align_down(PAGE_SIZE));
+    HAS_POISONED_PAGES.store(true, Ordering::Release);
+}
+
+/// Returns whether the page at `paddr` is poisoned.
+pub(in crate::mm) fn is_page_poisoned(paddr: Paddr) -> bool {
+    HAS_POISONED_PAGES.load(Ordering::Acquire)
+        && POISONED_PAGES.lock_irq_disabled().contains(&paddr)
+}
+
+/// Allocates `count` contiguous pages that are not poisoned, and returns the
+/// index of the first one.
+fn alloc_unpoisoned(allocator: &mut FrameAllocator, count: usize) -> Option<usize> {
+    loop {
+        let start = allocator.alloc(count)?;
+        if !HAS_POISONED_PAGES.load(Ordering::Acquire) {
+            return Some(start);
+        }
+
+        // The allocator hands out power-of-two blocks, so check the whole block.
+        let block = start..start + count.next_power_of_two();
+        let poisoned_pages = POISONED_PAGES.lock_irq_disabled();
+        if !block
+            .clone()
+            .any(|idx| poisoned_pages.contains(&(idx * PAGE_SIZE)))
+        {
+            return Some(start);
+        }
+        // Keep the poisoned pages allocated forever and return the others.
+        for idx in block.filter(|idx| !poisoned_pages.contains(&(idx * PAGE_SIZE))) {
+            allocator.dealloc(idx, 1);
+        }
+    }
+}
+
 /// Allocate a single page.
 pub(crate) fn alloc_single<M: PageMeta>() -> Option<Page<M>> {
-    PAGE_ALLOCATOR.get().unwrap().lock().alloc(1).map(|idx| {
+    let mut allocator = PAGE_ALLOCATOR.get().unwrap().lock();
+    alloc_unpoisoned(&mut allocator, 1).map(|idx| {
         let paddr = idx * PAGE_SIZE;
         Page::<M>::from_unused(paddr)
     })
@@ -32,11 +88,8 @@ pub(crate) fn alloc_single<M: PageMeta>() -> Option<Page<M>> {
 /// The function panics if the length is not base-page-aligned.
 pub(crate) fn alloc_contiguous<M: PageMeta>(len: usize) -> Option<ContPages<M>> {
     assert!(len % PAGE_SIZE == 0);
-    PAGE_ALLOCATOR
-        .get()
-        .unwrap()
-        .lock()
-        .alloc(len / PAGE_SIZE)
+    let mut allocator = PAGE_ALLOCATOR.get().unwrap().lock();
+    alloc_unpoisoned(&mut allocator, len / PAGE_SIZE)
         .map(|start| ContPages::from_unused(start * PAGE_SIZE..start * PAGE_SIZE + len))
 }
 
@@ -54,7 +107,7 @@ pub(crate) fn alloc<M: PageMeta>(len: usize) -> Option<Vec<Page<M>>> {
     let mut allocator = PAGE_ALLOCATOR.get().unwrap().lock();
     let mut vector = Vec::new();
     for _ in 0..nframes {
-        let paddr = allocator.alloc(1)? * PAGE_SIZE;
+        let paddr = alloc_unpoisoned(&mut allocator, 1)? * PAGE_SIZE;
         let page = Page::<M>::from_unused(paddr);
         vector.push(page);
     }
@@ -63,18 +116,26 @@ pub(crate) fn alloc<M: PageMeta>(len: usize) -> Option<Vec<Page<M>>> {
 
 pub(crate) fn init() {
     let regions = crate::boot::memory_regions();
+    let nr_memtest_passes = memtest::nr_passes();
     let mut allocator = FrameAllocator::<32>::new();
     for region in regions.iter() {
         if region.typ() == MemoryRegionType::Usable {
             // Make the memory region page-aligned, and skip if it is too small.
-            let start = region.base().align_up(PAGE_SIZE) / PAGE_SIZE;
+            let start = region.base().align_up(PAGE_SIZE);
             let region_end = region.base().checked_add(region.len()).unwrap();
-            let end = region_end.align_down(PAGE_SIZE) / PAGE_SIZE;
+            let end = region_end.align_down(PAGE_SIZE);
             if end <= start {
                 continue;
             }
-            // Add global free pages to the frame allocator.
-            allocator.add_frame(start, end);
+            // Add global free pages to the frame allocator, except for the bad ones.
+            let good_ranges = if nr_memtest_passes > 0 {
+                memtest::test_range(start..end, nr_memtest_passes)
+            } else {
+                vec![start..end]
+            };
+            for range in good_ranges {
+                allocator.add_frame(range.start / PAGE_SIZE, range.end / PAGE_SIZE);
+            }
             info!(
                 "Found usable region, start:{:x}, end:{:x}",
                 region.base(),
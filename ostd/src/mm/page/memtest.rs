@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! An early test of the usable physical memory.
+//!
+//! The test is enabled with the `ostd.memtest=<N>` kernel command-line argument,
+//! which performs `N` passes over the memory before it is handed to the page
+//! allocator. Each pass fills the memory with a pattern and reads it back, which
+//! works the same as `memtest` in Linux. Pages that fail the test are never
+//! handed out.
+
+use alloc::{vec, vec::Vec};
+use core::{mem::size_of, ops::Range};
+
+use align_ext::AlignExt;
+use log::{info, warn};
+
+use crate::{
+    boot::{kcmdline::ModuleArg, kernel_cmdline},
+    mm::{paddr_to_vaddr, Paddr, PAGE_SIZE},
+};
+
+/// The patterns used in the passes, which are the same as those of Linux.
+const PATTERNS: [u64; 17] = [
+    0,
+    0xffffffffffffffff,
+    0x5555555555555555,
+    0xaaaaaaaaaaaaaaaa,
+    0x1111111111111111,
+    0x2222222222222222,
+    0x4444444444444444,
+    0x8888888888888888,
+    0x3333333333333333,
+    0x6666666666666666,
+    0x9999999999999999,
+    0xcccccccccccccccc,
+    0x7777777777777777,
+    0xbbbbbbbbbbbbbbbb,
+    0xdddddddddddddddd,
+    0xeeeeeeeeeeeeeeee,
+    0x7a6c7258554e494c,
+];
+
+/// The physical memory that is linearly mapped by the boot page table.
+///
+/// Memory beyond it is not accessible before the kernel page table is activated,
+/// so it is left untested.
+const TESTABLE_PADDR_END: Paddr = 4 << 30;
+
+/// Returns the number of passes specified in the kernel command line.
+///
+/// If `ostd.memtest` is given without a value, all the patterns are used once.
+pub(super) fn nr_passes() -> usize {
+    let Some(args) = kernel_cmdline().get_module_args("ostd") else {
+        return 0;
+    };
+
+    let mut nr_passes = 0;
+    for arg in args {
+        match arg {
+            ModuleArg::Arg(option) if option.as_bytes() == b"memtest" => {
+                nr_passes = PATTERNS.len();
+            }
+            ModuleArg::KeyVal(option, value) if option.as_bytes() == b"memtest" => {
+                match value
+                    .to_str()
+                    .ok()
+                    .and_then(|value_str| value_str.parse().ok())
+                {
+                    Some(value) => nr_passes = value,
+                    None => warn!("Invalid value of `ostd.memtest`: {:?}", value),
+                }
+            }
+            _ => (),
+        }
+    }
+    nr_passes
+}
+
+/// Tests the page-aligned physical memory `range` and returns its sub-ranges
+/// that pass the test.
+///
+/// The memory must be unused, since its contents are overwritten.
+pub(super) fn test_range(range: Range<Paddr>, nr_passes: usize) -> Vec<Range<Paddr>> {
+    debug_assert!(range.start % PAGE_SIZE == 0 && range.end % PAGE_SIZE == 0);
+
+    let tested = range.start..range.end.min(TESTABLE_PADDR_END);
+    if tested.is_empty() {
+        return vec![range];
+    }
+    info!(
+        "Testing memory {:#x}..{:#x} with {} passes",
+        tested.start, tested.end, nr_passes
+    );
+
+    let mut bad_pages = Vec::new();
+    for pass in 0..nr_passes {
+        let pattern = PATTERNS[(nr_passes - 1 - pass) % PATTERNS.len()];
+        fill_pattern(&tested, pattern);
+        check_pattern(&tested, pattern, &mut bad_pages);
+    }
+    bad_pages.sort_unstable();
+    bad_pages.dedup();
+
+    let mut good_ranges = Vec::new();
+    let mut start = range.start;
+    for bad_page in bad_pages {
+        if start < bad_page {
+            good_ranges.push(start..bad_page);
+        }
+        start = bad_page + PAGE_SIZE;
+    }
+    if start < range.end {
+        good_ranges.push(start..range.end);
+    }
+    good_ranges
+}
+
+fn fill_pattern(range: &Range<Paddr>, pattern: u64) {
+    for paddr in range.clone().step_by(size_of::<u64>()) {
+        let ptr = paddr_to_vaddr(paddr) as *mut u64;
+        // SAFETY: The memory is usable but not used by anyone, and it is mapped by
+        // the boot page table since it is below `TESTABLE_PADDR_END`.
+        unsafe { ptr.write_volatile(pattern) };
+    }
+}
+
+fn check_pattern(range: &Range<Paddr>, pattern: u64, bad_pages: &mut Vec<Paddr>) {
+    for paddr in range.clone().step_by(size_of::<u64>()) {
+        let ptr = paddr_to_vaddr(paddr) as *const u64;
+        // SAFETY: The same as in `fill_pattern`.
+        let value = unsafe { ptr.read_volatile() };
+        if value == pattern {
+            continue;
+        }
+
+        let bad_page = paddr.align_down(PAGE_SIZE);
+        if bad_pages.last() != Some(&bad_page) {
+            warn!(
+                "Bad memory at {:#x}: expected {:#x}, found {:#x}",
+                paddr, pattern, value
+            );
+            bad_pages.push(bad_page);
+        }
+    }
+}
@@ -148,10 +148,16 @@ pub(super) unsafe fn drop_as_last<M: PageMeta>(ptr: *const MetaSlot) {
     // It would return the page to the allocator for further use. This would be done
     // after the release of the metadata to avoid re-allocation before the metadata
     // is reset.
-    allocator::PAGE_ALLOCATOR.get().unwrap().lock().dealloc(
-        mapping::meta_to_page::<PagingConsts>(ptr as Vaddr) / PAGE_SIZE,
-        1,
-    );
+    let paddr = mapping::meta_to_page::<PagingConsts>(ptr as Vaddr);
+    if allocator::is_page_poisoned(paddr) {
+        // A poisoned page is leaked so that it is never reused.
+        return;
+    }
+    allocator::PAGE_ALLOCATOR
+        .get()
+        .unwrap()
+        .lock()
+        .dealloc(paddr / PAGE_SIZE, 1);
 }
 
 mod private {
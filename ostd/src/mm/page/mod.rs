@@ -16,6 +16,7 @@
 
 pub(crate) mod allocator;
 pub(in crate::mm) mod cont_pages;
+mod memtest;
 pub(in crate::mm) mod meta;
 
 use core::{
@@ -5,9 +5,9 @@ use core::mem::ManuallyDrop;
 use super::*;
 use crate::{
     mm::{
-        kspace::LINEAR_MAPPING_BASE_VADDR,
+        kspace::{KERNEL_PAGE_TABLE, LINEAR_MAPPING_BASE_VADDR},
         page::{allocator, meta::FrameMeta},
-        page_prop::{CachePolicy, PageFlags},
+        page_prop::{CachePolicy, PageFlags, PrivilegedPageFlags},
     },
     prelude::*,
 };
@@ -114,6 +114,115 @@ fn test_user_copy_on_write() {
     assert!(child_pt.query(from.start + 10).is_none());
 }
 
+#[ktest]
+fn test_cursor_agrees_with_page_walk() {
+    let pt = PageTable::<UserMode>::empty();
+    let huge = PAGE_SIZE * 512;
+    // The pages lie around the boundaries of the ranges covered by the nodes of each level.
+    let vas = [
+        PAGE_SIZE,
+        huge - PAGE_SIZE,
+        huge,
+        huge * 512 - PAGE_SIZE,
+        huge * 512,
+        huge * 512 * 512 - PAGE_SIZE,
+        huge * 512 * 512,
+    ];
+    let prop = PageProperty::new(PageFlags::RW, CachePolicy::Writeback);
+    for va in vas {
+        let page = allocator::alloc_single::<FrameMeta>().unwrap();
+        unsafe {
+            pt.cursor_mut(&(va..va + PAGE_SIZE))
+                .unwrap()
+                .map(page.into(), prop)
+        };
+    }
+
+    let range = 0..huge * 512 * 512 + PAGE_SIZE;
+    let mut mapped_vas = Vec::new();
+    let mut next_va = range.start;
+    for qr in pt.cursor(&range).unwrap() {
+        let (va, len) = match qr {
+            Qr::NotMapped { va, len } => {
+                assert!(pt.query(va).is_none());
+                assert!(pt.query(va + len - 1).is_none());
+                (va, len)
+            }
+            Qr::Mapped { va, page, prop } => {
+                assert_eq!(pt.query(va).unwrap(), (page.paddr(), prop));
+                assert_eq!(prop.flags, PageFlags::RW);
+                mapped_vas.push(va);
+                (va, page.size())
+            }
+            Qr::MappedUntracked { .. } => panic!("Expected tracked mappings, got {:#x?}", qr),
+        };
+        // The cursor visits the range in order without gaps.
+        assert_eq!(va, next_va);
+        next_va = va + len;
+    }
+    assert_eq!(next_va, range.end);
+    assert_eq!(mapped_vas, vas);
+}
+
+#[ktest]
+fn test_protect_upgrade_and_downgrade() {
+    let pt = PageTable::<UserMode>::empty();
+    let from = PAGE_SIZE..PAGE_SIZE * 3;
+    let prop = PageProperty::new(PageFlags::R, CachePolicy::Writeback);
+    unsafe {
+        let mut cursor = pt.cursor_mut(&from).unwrap();
+        for _ in 0..2 {
+            let page = allocator::alloc_single::<FrameMeta>().unwrap();
+            cursor.map(page.into(), prop);
+        }
+    }
+
+    let first = PAGE_SIZE..PAGE_SIZE * 2;
+    unsafe { pt.protect(&first, |p| p.flags |= PageFlags::W).unwrap() };
+    assert_eq!(pt.query(first.start).unwrap().1.flags, PageFlags::RW);
+    assert_eq!(pt.query(first.end).unwrap().1.flags, PageFlags::R);
+
+    unsafe { pt.protect(&from, |p| p.flags = PageFlags::RX).unwrap() };
+    for va in from.clone().step_by(PAGE_SIZE) {
+        let (_, prop) = pt.query(va).unwrap();
+        assert_eq!(prop.flags, PageFlags::RX);
+        assert_eq!(prop.cache, CachePolicy::Writeback);
+        assert_eq!(prop.priv_flags, PrivilegedPageFlags::USER);
+    }
+
+    // Protecting absent pages in a range is allowed and has no effects on them.
+    let with_absent = 0..PAGE_SIZE * 4;
+    unsafe {
+        pt.protect(&with_absent, |p| p.flags = PageFlags::R)
+            .unwrap()
+    };
+    assert!(pt.query(0).is_none());
+    assert!(pt.query(PAGE_SIZE * 3).is_none());
+    assert_eq!(pt.query(PAGE_SIZE * 2).unwrap().1.flags, PageFlags::R);
+}
+
+#[ktest]
+fn test_kernel_linear_mapping() {
+    let kpt = KERNEL_PAGE_TABLE.get().unwrap();
+    let page = allocator::alloc_single::<FrameMeta>().unwrap();
+    let paddr = page.paddr();
+    let vaddr = paddr_to_vaddr(paddr);
+
+    let (mapped_paddr, prop) = kpt.query(vaddr + 10).unwrap();
+    assert_eq!(mapped_paddr, paddr + 10);
+    assert_eq!(prop.flags, PageFlags::RW);
+    assert_eq!(prop.cache, CachePolicy::Writeback);
+    assert!(prop.priv_flags.contains(PrivilegedPageFlags::GLOBAL));
+    assert!(!prop.priv_flags.contains(PrivilegedPageFlags::USER));
+
+    // The kernel page table maps nothing in the user space.
+    assert!(kpt.query(PAGE_SIZE).is_none());
+
+    // User page tables share the kernel space with the kernel page table.
+    let user_pt = kpt.create_user_page_table();
+    assert_eq!(user_pt.query(vaddr + 10), kpt.query(vaddr + 10));
+}
+
 type Qr = PageTableQueryResult;
 
 #[derive(Clone, Debug, Default)]
@@ -253,3 +362,45 @@ fn test_untracked_large_protect_query() {
     // Since untracked mappings cannot be dropped, we just leak it here.
     let _ = ManuallyDrop::new(pt);
 }
+
+#[ktest]
+fn test_untracked_huge_page_split_on_unmap() {
+    let pt = PageTable::<KernelMode, PageTableEntry, VeryHugePagingConsts>::empty();
+    const UNTRACKED_OFFSET: usize = crate::mm::kspace::LINEAR_MAPPING_BASE_VADDR;
+
+    let huge = PAGE_SIZE * 512;
+    let from = UNTRACKED_OFFSET + huge..UNTRACKED_OFFSET + huge * 3;
+    let to = huge * 5..huge * 7;
+    let prop = PageProperty::new(PageFlags::RW, CachePolicy::Writeback);
+    unsafe { pt.map(&from, &to, prop).unwrap() };
+    assert_eq!(pt.cursor(&from).unwrap().count(), 2);
+
+    let hole = from.start + PAGE_SIZE * 100..from.start + PAGE_SIZE * 101;
+    unsafe { pt.unmap(&hole).unwrap() };
+    assert!(pt.query(hole.start).is_none());
+    assert_eq!(
+        pt.query(hole.start - 1).unwrap().0,
+        to.start + PAGE_SIZE * 100 - 1
+    );
+    assert_eq!(pt.query(hole.end).unwrap().0, to.start + PAGE_SIZE * 101);
+
+    // Only the huge page containing the hole is split into base pages.
+    for qr in pt.cursor(&from).unwrap() {
+        match qr {
+            Qr::NotMapped { va, len } => assert_eq!(va..va + len, hole),
+            Qr::MappedUntracked { va, pa, len, prop } => {
+                assert_eq!(pa, va - from.start + to.start);
+                assert_eq!(prop.flags, PageFlags::RW);
+                if va < from.start + huge {
+                    assert_eq!(len, PAGE_SIZE);
+                } else {
+                    assert_eq!(va..va + len, from.start + huge..from.end);
+                }
+            }
+            Qr::Mapped { .. } => panic!("Expected untracked mappings, got {:#x?}", qr),
+        }
+    }
+
+    // Since untracked mappings cannot be dropped, we just leak it here.
+    let _ = ManuallyDrop::new(pt);
+}
@@ -2,6 +2,7 @@
 
 use core::ops::Range;
 
+use align_ext::AlignExt;
 use spin::Once;
 
 use super::{
@@ -17,7 +18,7 @@ use crate::{
         current_page_table_paddr, tlb_flush_addr_range, tlb_flush_all_excluding_global,
         PageTableEntry, PagingConsts,
     },
-    cpu::CpuExceptionInfo,
+    cpu::{CpuExceptionInfo, PageFaultErrorCode, PAGE_FAULT},
     mm::{
         page_table::{Cursor, PageTableQueryResult as PtQr},
         Frame, MAX_USERSPACE_VADDR,
@@ -292,6 +293,112 @@ impl VmSpace {
         // is activated during the usage period of the `VmWriter`.
         Ok(unsafe { VmWriter::<UserSpace>::from_user_space(vaddr as *mut u8, len) })
     }
+
+    /// Reads data from the user space into `writer`, no matter whether this
+    /// `VmSpace` is activated on the current CPU or not.
+    ///
+    /// Unlike [`VmSpace::reader`], this method can access the memory of other
+    /// tasks, e.g., for `process_vm_readv`, `ptrace`, or `/proc/[pid]/mem`.
+    /// See [`VmSpace::write_remote`] for how the memory is accessed.
+    ///
+    /// Returns the number of bytes read, which is less than requested if a
+    /// page fault in the middle cannot be handled. Returns `Err` if nothing
+    /// can be read.
+    pub fn read_remote(&self, vaddr: Vaddr, writer: &mut VmWriter<'_>) -> Result<usize> {
+        self.access_remote(vaddr, writer.avail(), false, |frame, offset, len| {
+            let mut frame_reader = frame.reader().skip(offset).limit(len);
+            writer.write(&mut frame_reader)
+        })
+    }
+
+    /// Writes data from `reader` into the user space, no matter whether this
+    /// `VmSpace` is activated on the current CPU or not.
+    ///
+    /// The memory is accessed page by page through the kernel mapping of the
+    /// mapped frames. A frame is pinned by a handle while it is accessed, so
+    /// unmapping the page concurrently cannot free the memory under the access.
+    /// Pages that are not mapped, or not writable when writing, are resolved
+    /// with the page fault handler of this `VmSpace`, as if the user accessed
+    /// them. So copy-on-write pages are copied before being written.
+    ///
+    /// Returns the number of bytes written, which is less than requested if a
+    /// page fault in the middle cannot be handled. Returns `Err` if nothing
+    /// can be written.
+    pub fn write_remote(&self, vaddr: Vaddr, reader: &mut VmReader<'_>) -> Result<usize> {
+        self.access_remote(vaddr, reader.remain(), true, |frame, offset, len| {
+            let mut frame_writer = frame.writer().skip(offset).limit(len);
+            reader.read(&mut frame_writer)
+        })
+    }
+
+    fn access_remote(
+        &self,
+        vaddr: Vaddr,
+        len: usize,
+        is_write: bool,
+        mut access: impl FnMut(&Frame, usize, usize) -> usize,
+    ) -> Result<usize> {
+        let end = vaddr.checked_add(len).ok_or(Error::AccessDenied)?;
+        if end > MAX_USERSPACE_VADDR {
+            return Err(Error::AccessDenied);
+        }
+
+        let mut cur_addr = vaddr;
+        while cur_addr < end {
+            let frame = match self.pin_frame(cur_addr, is_write) {
+                Ok(frame) => frame,
+                Err(err) if cur_addr == vaddr => return Err(err),
+                Err(_) => break,
+            };
+            let offset = cur_addr % PAGE_SIZE;
+            let len = (PAGE_SIZE - offset).min(end - cur_addr);
+            let copied = access(&frame, offset, len);
+            cur_addr += copied;
+            if copied < len {
+                // The other side of the copy is exhausted.
+                break;
+            }
+        }
+
+        Ok(cur_addr - vaddr)
+    }
+
+    /// Returns the frame mapped at `vaddr`, handling the page fault if the
+    /// frame is not mapped or not writable when `is_write` is true.
+    fn pin_frame(&self, vaddr: Vaddr, is_write: bool) -> Result<Frame> {
+        let page_range = vaddr.align_down(PAGE_SIZE)..vaddr.align_down(PAGE_SIZE) + PAGE_SIZE;
+
+        for has_faulted in [false, true] {
+            if let Some(VmQueryResult::Mapped { frame, prop, .. }) =
+                self.query_range(&page_range)?.next()
+            {
+                if !is_write || prop.flags.contains(PageFlags::W) {
+                    return Ok(frame);
+                }
+            }
+            if has_faulted {
+                break;
+            }
+
+            let mut error_code = PageFaultErrorCode::USER;
+            if self.query(vaddr)?.is_some() {
+                error_code |= PageFaultErrorCode::PRESENT;
+            }
+            if is_write {
+                error_code |= PageFaultErrorCode::WRITE;
+            }
+            let info = CpuExceptionInfo {
+                id: PAGE_FAULT.number as usize,
+                error_code: error_code.bits(),
+                page_fault_addr: vaddr,
+                memory_error_addr: None,
+            };
+            self.handle_page_fault(&info)
+                .map_err(|_| Error::AccessDenied)?;
+        }
+
+        Err(Error::AccessDenied)
+    }
 }
 
 impl Default for VmSpace {
@@ -378,14 +485,22 @@ pub struct VmQueryIter<'a> {
     cursor: Cursor<'a, UserMode, PageTableEntry, PagingConsts>,
 }
 
+/// The result of a query over the VM space.
 pub enum VmQueryResult {
+    /// The range is not mapped.
     NotMapped {
+        /// The start virtual address of the range.
         va: Vaddr,
+        /// The length of the range.
         len: usize,
     },
+    /// A frame is mapped.
     Mapped {
+        /// The virtual address where the frame is mapped.
         va: Vaddr,
+        /// The mapped frame.
         frame: Frame,
+        /// The property of the mapping.
         prop: PageProperty,
     },
 }
@@ -406,3 +521,75 @@ impl Iterator for VmQueryIter<'_> {
         })
     }
 }
+
+#[cfg(ktest)]
+mod test {
+    use super::*;
+    use crate::{mm::FrameAllocOptions, prelude::*};
+
+    const BASE: Vaddr = 0x4000_0000;
+
+    /// Creates a `VmSpace` that is never activated, with `nframes` zeroed
+    /// frames mapped at `BASE`.
+    fn new_vm_space(nframes: usize, flags: PageFlags) -> VmSpace {
+        let vm_space = VmSpace::new();
+        let frames = FrameAllocOptions::new(nframes).alloc().unwrap();
+        let mut options = VmMapOptions::new();
+        options.addr(Some(BASE)).flags(flags);
+        vm_space.map(frames, &options).unwrap();
+        vm_space
+    }
+
+    #[ktest]
+    fn remote_access_across_pages() {
+        let vm_space = new_vm_space(2, PageFlags::RW);
+        let vaddr = BASE + PAGE_SIZE - 8;
+
+        let data: Vec<u8> = (0..16).collect();
+        let written = vm_space.write_remote(vaddr, &mut VmReader::from(data.as_slice()));
+        assert_eq!(written, Ok(16));
+
+        let mut buf = [0u8; 16];
+        let read = vm_space.read_remote(vaddr, &mut VmWriter::from(&mut buf as &mut [u8]));
+        assert_eq!(read, Ok(16));
+        assert_eq!(buf.as_slice(), data.as_slice());
+    }
+
+    #[ktest]
+    fn remote_access_partial() {
+        let vm_space = new_vm_space(1, PageFlags::RW);
+
+        // The second page is not mapped and there is no page fault handler,
+        // so only the bytes in the first page are read.
+        let mut buf = [0xffu8; 16];
+        let read = vm_space.read_remote(
+            BASE + PAGE_SIZE - 8,
+            &mut VmWriter::from(&mut buf as &mut [u8]),
+        );
+        assert_eq!(read, Ok(8));
+        assert_eq!(&buf[..8], &[0u8; 8]);
+        assert_eq!(&buf[8..], &[0xffu8; 8]);
+
+        // Nothing can be read from an unmapped page.
+        let read =
+            vm_space.read_remote(BASE + PAGE_SIZE, &mut VmWriter::from(&mut buf as &mut [u8]));
+        assert_eq!(read, Err(Error::AccessDenied));
+    }
+
+    #[ktest]
+    fn remote_access_denied() {
+        let vm_space = new_vm_space(1, PageFlags::R);
+        let mut buf = [0u8; 8];
+
+        // A read-only page cannot be written without a page fault handler.
+        let written = vm_space.write_remote(BASE, &mut VmReader::from(buf.as_slice()));
+        assert_eq!(written, Err(Error::AccessDenied));
+
+        // An address range beyond the user space cannot be accessed.
+        let read = vm_space.read_remote(
+            MAX_USERSPACE_VADDR - 4,
+            &mut VmWriter::from(&mut buf as &mut [u8]),
+        );
+        assert_eq!(read, Err(Error::AccessDenied));
+    }
+}
@@ -4,18 +4,21 @@
 
 mod atomic_bits;
 mod mutex;
-// TODO: refactor this rcu implementation
-// Comment out this module since it raises lint error
-// mod rcu;
+mod per_cpu_counter;
+mod rcu;
 mod rwlock;
 mod rwmutex;
+mod rwsem;
+mod seqlock;
 mod spin;
 mod wait;
 
-// pub use self::rcu::{pass_quiescent_state, OwnerPtr, Rcu, RcuReadGuard, RcuReclaimer};
+pub(crate) use self::rcu::after_grace_period;
 pub use self::{
     atomic_bits::AtomicBits,
     mutex::{ArcMutexGuard, Mutex, MutexGuard},
+    per_cpu_counter::PerCpuCounter,
+    rcu::{pass_quiescent_state, OwnerPtr, Rcu, RcuReadGuard, RcuReclaimer},
     rwlock::{
         ArcRwLockReadGuard, ArcRwLockUpgradeableGuard, ArcRwLockWriteGuard, RwLock,
         RwLockReadGuard, RwLockUpgradeableGuard, RwLockWriteGuard,
@@ -24,6 +27,8 @@ pub use self::{
         ArcRwMutexReadGuard, ArcRwMutexUpgradeableGuard, ArcRwMutexWriteGuard, RwMutex,
         RwMutexReadGuard, RwMutexUpgradeableGuard, RwMutexWriteGuard,
     },
+    rwsem::{RwSemaphore, RwSemaphoreReadGuard, RwSemaphoreWriteGuard},
+    seqlock::{SeqLock, SeqLockWriteGuard},
     spin::{ArcSpinLockGuard, SpinLock, SpinLockGuard},
     wait::{WaitQueue, Waiter, Waker},
 };
@@ -2,56 +2,122 @@
 
 //! Read-copy update (RCU).
 
-use core::marker::PhantomData;
-use core::ops::Deref;
-use core::sync::atomic::{
-    AtomicPtr,
-    Ordering::{AcqRel, Acquire},
+use core::{
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ops::Deref,
+    sync::atomic::{
+        AtomicBool, AtomicPtr,
+        Ordering::{AcqRel, Acquire, Release},
+    },
 };
 
-use self::monitor::RcuMonitor;
-use crate::prelude::*;
-use crate::sync::WaitQueue;
+use spin::Once;
+
+use self::{monitor::RcuMonitor, owner_ptr::ThinOwnerPtr};
+use crate::{
+    cpu::num_cpus,
+    prelude::*,
+    sync::WaitQueue,
+    task::{disable_preempt, DisablePreemptGuard},
+};
 
 mod monitor;
 mod owner_ptr;
 
 pub use owner_ptr::OwnerPtr;
 
+/// A Read-Copy Update (RCU) cell for sharing a pointer between threads.
+///
+/// Readers get the current object without any locking through [`Rcu::get`].
+/// Writers publish a new object through [`Rcu::replace`], after which the old
+/// object is reclaimed once all the readers that may still refer to it are gone.
+///
+/// Concurrent writers must be serialized by the user.
+///
+/// The object may be dynamically sized, e.g., `Rcu<Arc<dyn Trait>>`.
 pub struct Rcu<P: OwnerPtr> {
-    ptr: AtomicPtr<<P as OwnerPtr>::Target>,
-    marker: PhantomData<P::Target>,
+    /// The raw pointer of a [`ThinOwnerPtr<P>`].
+    ptr: AtomicPtr<()>,
+    marker: PhantomData<P>,
 }
 
 impl<P: OwnerPtr> Rcu<P> {
+    /// Creates a new RCU cell with the given pointer.
     pub fn new(ptr: P) -> Self {
-        let ptr = AtomicPtr::new(OwnerPtr::into_raw(ptr) as *mut _);
+        let ptr = AtomicPtr::new(ThinOwnerPtr::new(ptr).into_raw());
         Self {
             ptr,
             marker: PhantomData,
         }
     }
 
+    /// Gets the current object for reading.
+    ///
+    /// The returned guard marks an RCU read-side critical section, during which
+    /// preemption is disabled. The object is kept alive as long as the guard is.
     pub fn get(&self) -> RcuReadGuard<'_, P> {
-        let obj = unsafe { &*self.ptr.load(Acquire) };
-        RcuReadGuard { obj, rcu: self }
+        let preempt_guard = disable_preempt();
+        // SAFETY: The pointer is valid because it is only reclaimed after a grace
+        // period, which cannot elapse while this CPU is in a read-side critical section.
+        let obj = unsafe { &*ThinOwnerPtr::<P>::raw_of(self.ptr.load(Acquire)) };
+        RcuReadGuard {
+            obj,
+            _preempt_guard: preempt_guard,
+        }
+    }
+}
+
+impl<P: OwnerPtr> Rcu<Option<P>>
+where
+    P::Target: Sized,
+{
+    /// Gets the current object for reading, or returns `None` if there is no object.
+    ///
+    /// [`Rcu::get`] must not be used on an RCU cell that may hold `None`.
+    pub fn try_get(&self) -> Option<RcuReadGuard<'_, Option<P>>> {
+        let preempt_guard = disable_preempt();
+        // SAFETY: The pointer is valid if it is not null, for the same reason as in `get`.
+        let obj = unsafe { ThinOwnerPtr::<Option<P>>::raw_of(self.ptr.load(Acquire)).as_ref()? };
+        Some(RcuReadGuard {
+            obj,
+            _preempt_guard: preempt_guard,
+        })
     }
 }
 
 impl<P: OwnerPtr + Send> Rcu<P> {
+    /// Replaces the current object with a new one.
+    ///
+    /// The old object is returned in an [`RcuReclaimer`], which releases it
+    /// after a grace period.
     pub fn replace(&self, new_ptr: P) -> RcuReclaimer<P> {
-        let new_ptr = <P as OwnerPtr>::into_raw(new_ptr) as *mut _;
+        let new_ptr = ThinOwnerPtr::new(new_ptr).into_raw();
         let old_ptr = {
             let old_raw_ptr = self.ptr.swap(new_ptr, AcqRel);
-            unsafe { <P as OwnerPtr>::from_raw(old_raw_ptr) }
+            // SAFETY: The pointer was obtained from `into_raw` in `new` or `replace`.
+            unsafe { ThinOwnerPtr::from_raw(old_raw_ptr) }
         };
-        RcuReclaimer { ptr: old_ptr }
+        RcuReclaimer {
+            ptr: ManuallyDrop::new(old_ptr),
+        }
+    }
+}
+
+impl<P: OwnerPtr> Drop for Rcu<P> {
+    fn drop(&mut self) {
+        // SAFETY: The pointer was obtained from `into_raw` in `new` or `replace`. Since we
+        // have exclusive access to `self`, there can be no readers.
+        drop(unsafe { ThinOwnerPtr::<P>::from_raw(*self.ptr.get_mut()) });
     }
 }
 
+/// A guard that provides read access to the object in an [`Rcu`].
+///
+/// Preemption is disabled as long as the guard is alive.
 pub struct RcuReadGuard<'a, P: OwnerPtr> {
     obj: &'a <P as OwnerPtr>::Target,
-    rcu: &'a Rcu<P>,
+    _preempt_guard: DisablePreemptGuard,
 }
 
 impl<'a, P: OwnerPtr> Deref for RcuReadGuard<'a, P> {
@@ -62,43 +128,123 @@ impl<'a, P: OwnerPtr> Deref for RcuReadGuard<'a, P> {
     }
 }
 
-#[repr(transparent)]
-pub struct RcuReclaimer<P> {
-    ptr: P,
+/// An object that has been removed from an [`Rcu`] and waits to be reclaimed.
+///
+/// Dropping the reclaimer blocks until a grace period elapses. Use
+/// [`RcuReclaimer::delay`] to reclaim the object asynchronously instead.
+pub struct RcuReclaimer<P: OwnerPtr> {
+    ptr: ManuallyDrop<ThinOwnerPtr<P>>,
 }
 
-impl<P: Send + 'static> RcuReclaimer<P> {
+impl<P: OwnerPtr + Send + 'static> RcuReclaimer<P> {
+    /// Reclaims the object after a grace period without blocking the caller.
     pub fn delay(mut self) {
-        let ptr: P = unsafe {
-            let ptr = core::mem::replace(&mut self.ptr, core::mem::uninitialized());
-
-            core::mem::forget(self);
+        // SAFETY: `self` is forgotten right after the pointer is taken out.
+        let ptr = unsafe { ManuallyDrop::take(&mut self.ptr) };
+        core::mem::forget(self);
 
-            ptr
-        };
-        get_singleton().after_grace_period(move || {
+        after_grace_period(move || {
             drop(ptr);
         });
     }
 }
 
-impl<P> Drop for RcuReclaimer<P> {
+impl<P: OwnerPtr> Drop for RcuReclaimer<P> {
     fn drop(&mut self) {
+        let is_complete = Arc::new(AtomicBool::new(false));
         let wq = Arc::new(WaitQueue::new());
-        get_singleton().after_grace_period({
+        after_grace_period({
+            let is_complete = is_complete.clone();
             let wq = wq.clone();
             move || {
-                wq.wake_one();
+                is_complete.store(true, Release);
+                wq.wake_all();
             }
         });
-        wq.wait_until(|| Some(0u8));
+        wq.wait_until(|| is_complete.load(Acquire).then_some(()));
+
+        // SAFETY: The pointer is never used again.
+        unsafe { ManuallyDrop::drop(&mut self.ptr) };
     }
 }
 
+/// Invokes the callback after a grace period, i.e., after all the RCU
+/// read-side critical sections that are currently in progress have ended.
+pub(crate) fn after_grace_period<F>(f: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    get_singleton().after_grace_period(f);
+}
+
+/// Informs the RCU mechanism that the current CPU passes a quiescent state.
+///
+/// # Safety
+///
+/// The caller must ensure that the current CPU is not in any RCU read-side
+/// critical section.
 pub unsafe fn pass_quiescent_state() {
     get_singleton().pass_quiescent_state()
 }
 
 fn get_singleton() -> &'static RcuMonitor {
-    todo!()
+    static RCU_MONITOR: Once<RcuMonitor> = Once::new();
+
+    RCU_MONITOR.call_once(|| RcuMonitor::new(num_cpus() as usize))
+}
+
+#[cfg(ktest)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+
+    trait Named: Send + Sync {
+        fn name(&self) -> &'static str;
+    }
+
+    struct Foo;
+
+    impl Named for Foo {
+        fn name(&self) -> &'static str {
+            "foo"
+        }
+    }
+
+    struct Bar;
+
+    impl Named for Bar {
+        fn name(&self) -> &'static str {
+            "bar"
+        }
+    }
+
+    #[ktest]
+    fn trait_object() {
+        let rcu = Rcu::<Arc<dyn Named>>::new(Arc::new(Foo));
+        assert_eq!(rcu.get().name(), "foo");
+
+        rcu.replace(Arc::new(Bar)).delay();
+        assert_eq!(rcu.get().name(), "bar");
+    }
+
+    #[ktest]
+    fn slice() {
+        let rcu = Rcu::<Box<[u32]>>::new(Box::new([1, 2, 3]));
+        assert_eq!(&*rcu.get(), &[1, 2, 3]);
+
+        rcu.replace(Box::new([4, 5])).delay();
+        assert_eq!(&*rcu.get(), &[4, 5]);
+    }
+
+    #[ktest]
+    fn option() {
+        let rcu = Rcu::<Option<Box<u32>>>::new(None);
+        assert!(rcu.try_get().is_none());
+
+        rcu.replace(Some(Box::new(42))).delay();
+        assert_eq!(*rcu.try_get().unwrap(), 42);
+
+        rcu.replace(None).delay();
+        assert!(rcu.try_get().is_none());
+    }
 }
@@ -1,6 +1,9 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::ptr::NonNull;
+use core::{
+    marker::PhantomData,
+    mem::{size_of, ManuallyDrop},
+};
 
 use crate::prelude::*;
 
@@ -10,10 +13,12 @@ use crate::prelude::*;
 /// The most typical examples smart pointer types like `Box<T>` and `Arc<T>`.
 ///
 /// which can be converted to and from the raw pointer type of `*const T`.
+///
+/// The target type may be dynamically sized (e.g., `Arc<dyn Trait>`), in which
+/// case the raw pointer is a fat pointer.
 pub trait OwnerPtr {
     /// The target type that this pointer refers to.
-    // TODO: allow ?Sized
-    type Target;
+    type Target: ?Sized;
 
     /// Converts to a raw pointer.
     ///
@@ -30,7 +35,7 @@ pub trait OwnerPtr {
     unsafe fn from_raw(ptr: *const Self::Target) -> Self;
 }
 
-impl<T> OwnerPtr for Box<T> {
+impl<T: ?Sized> OwnerPtr for Box<T> {
     type Target = T;
 
     fn into_raw(self) -> *const Self::Target {
@@ -42,7 +47,7 @@ impl<T> OwnerPtr for Box<T> {
     }
 }
 
-impl<T> OwnerPtr for Arc<T> {
+impl<T: ?Sized> OwnerPtr for Arc<T> {
     type Target = T;
 
     fn into_raw(self) -> *const Self::Target {
@@ -72,9 +77,88 @@ where
 
     unsafe fn from_raw(ptr: *const Self::Target) -> Self {
         if ptr.is_null() {
+            None
+        } else {
             Some(<P as OwnerPtr>::from_raw(ptr))
+        }
+    }
+}
+
+/// An owner pointer that is represented by a thin raw pointer.
+///
+/// A thin raw pointer fits in an `AtomicPtr`, whereas the raw pointer to a
+/// dynamically sized object is a fat pointer that does not. Such a fat pointer
+/// is moved to the heap, and the thin pointer to the heap allocation is used
+/// instead, at the cost of one more indirection when it is dereferenced.
+pub(super) struct ThinOwnerPtr<P: OwnerPtr> {
+    ptr: *mut (),
+    _marker: PhantomData<P>,
+}
+
+// SAFETY: `ThinOwnerPtr<P>` owns a `P` and the heap allocation of its fat raw
+// pointer, if any, which is only accessed through `ThinOwnerPtr<P>`.
+unsafe impl<P: OwnerPtr + Send> Send for ThinOwnerPtr<P> {}
+
+impl<P: OwnerPtr> ThinOwnerPtr<P> {
+    const IS_THIN: bool = size_of::<*const P::Target>() == size_of::<*mut ()>();
+
+    pub(super) fn new(ptr: P) -> Self {
+        let raw_ptr = ptr.into_raw();
+        let ptr = if Self::IS_THIN {
+            raw_ptr.cast::<()>().cast_mut()
         } else {
-            None
+            Box::into_raw(Box::new(raw_ptr)).cast()
+        };
+        Self {
+            ptr,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Converts to the thin raw pointer.
+    pub(super) fn into_raw(self) -> *mut () {
+        ManuallyDrop::new(self).ptr
+    }
+
+    /// Converts back from the thin raw pointer.
+    ///
+    /// # Safety
+    ///
+    /// The thin raw pointer must have been previously returned by a call to `into_raw`.
+    pub(super) unsafe fn from_raw(ptr: *mut ()) -> Self {
+        Self {
+            ptr,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the raw pointer of `P` that the thin raw pointer represents.
+    ///
+    /// # Safety
+    ///
+    /// The thin raw pointer must have been returned by a call to `into_raw`,
+    /// and the `ThinOwnerPtr` must not have been dropped.
+    pub(super) unsafe fn raw_of(ptr: *mut ()) -> *const P::Target {
+        if Self::IS_THIN {
+            // SAFETY: The raw pointer of `P` is thin, so it has the same size as `ptr`.
+            unsafe { core::mem::transmute_copy(&ptr) }
+        } else {
+            // SAFETY: `ptr` points to the fat raw pointer, which is alive as the
+            // `ThinOwnerPtr` is.
+            unsafe { *ptr.cast::<*const P::Target>() }
+        }
+    }
+}
+
+impl<P: OwnerPtr> Drop for ThinOwnerPtr<P> {
+    fn drop(&mut self) {
+        // SAFETY: `self.ptr` is obtained from `new` and `self` is alive.
+        let raw_ptr = unsafe { Self::raw_of(self.ptr) };
+        if !Self::IS_THIN {
+            // SAFETY: `self.ptr` is allocated in `new` and is never used again.
+            drop(unsafe { Box::from_raw(self.ptr.cast::<*const P::Target>()) });
         }
+        // SAFETY: The raw pointer is obtained from `into_raw` in `new`.
+        drop(unsafe { P::from_raw(raw_ptr) });
     }
 }
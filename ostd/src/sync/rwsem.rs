@@ -0,0 +1,256 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! A sleeping reader-writer semaphore.
+
+use core::{
+    cell::UnsafeCell,
+    fmt,
+    ops::{Deref, DerefMut},
+    sync::atomic::{
+        AtomicUsize,
+        Ordering::{AcqRel, Acquire, Relaxed, Release},
+    },
+};
+
+use super::WaitQueue;
+
+/// A reader-writer semaphore that prefers writers.
+///
+/// Like [`RwMutex`], the semaphore allows either one writer or many readers,
+/// and a task that cannot acquire it sleeps instead of spinning. So it suits
+/// the locks that may be held for a long time, e.g., the lock of a VMAR.
+///
+/// Unlike [`RwMutex`], a waiting writer blocks new readers. So a steady stream
+/// of readers cannot starve the writers. As a consequence, a task must not
+/// acquire the semaphore for reading if it already holds it for reading, since
+/// a writer that arrives in between deadlocks both of them.
+///
+/// The semaphore must not be used in the interrupt context, as it may sleep.
+///
+/// [`RwMutex`]: super::RwMutex
+pub struct RwSemaphore<T: ?Sized> {
+    /// The state of the semaphore:
+    /// - **Bit 63:** Whether a writer holds the semaphore.
+    /// - **Bits 62-0:** The number of readers holding the semaphore.
+    lock: AtomicUsize,
+    /// The number of writers that are waiting for the semaphore.
+    nr_waiting_writers: AtomicUsize,
+    /// The tasks that are waiting for the semaphore.
+    queue: WaitQueue,
+    val: UnsafeCell<T>,
+}
+
+const READER: usize = 1;
+const WRITER: usize = 1 << (usize::BITS - 1);
+const MAX_READER: usize = 1 << (usize::BITS - 2);
+
+impl<T> RwSemaphore<T> {
+    /// Creates a new reader-writer semaphore.
+    pub const fn new(val: T) -> Self {
+        Self {
+            lock: AtomicUsize::new(0),
+            nr_waiting_writers: AtomicUsize::new(0),
+            queue: WaitQueue::new(),
+            val: UnsafeCell::new(val),
+        }
+    }
+}
+
+impl<T: ?Sized> RwSemaphore<T> {
+    /// Acquires the semaphore for reading, and sleeps until it can be acquired.
+    ///
+    /// The calling task sleeps while a writer holds or waits for the semaphore.
+    pub fn read(&self) -> RwSemaphoreReadGuard<T> {
+        self.queue.wait_until(|| self.try_read())
+    }
+
+    /// Acquires the semaphore for writing, and sleeps until it can be acquired.
+    ///
+    /// New readers are blocked from the time when the calling task starts waiting.
+    pub fn write(&self) -> RwSemaphoreWriteGuard<T> {
+        if let Some(guard) = self.try_write() {
+            return guard;
+        }
+
+        self.nr_waiting_writers.fetch_add(1, Relaxed);
+        let guard = self.queue.wait_until(|| self.try_write());
+        self.nr_waiting_writers.fetch_sub(1, Relaxed);
+        guard
+    }
+
+    /// Attempts to acquire the semaphore for reading.
+    ///
+    /// This method fails if a writer holds or waits for the semaphore. It never sleeps.
+    pub fn try_read(&self) -> Option<RwSemaphoreReadGuard<T>> {
+        let lock = self.lock.fetch_add(READER, Acquire);
+        if lock & (WRITER | MAX_READER) == 0 && self.nr_waiting_writers.load(Relaxed) == 0 {
+            return Some(RwSemaphoreReadGuard { inner: self });
+        }
+
+        // The transient reader may have made a waiting writer fail, so the writer
+        // must be woken up if the reader is the last one.
+        if self.lock.fetch_sub(READER, Release) == READER {
+            self.queue.wake_all();
+        }
+        None
+    }
+
+    /// Attempts to acquire the semaphore for writing.
+    ///
+    /// This method never sleeps.
+    pub fn try_write(&self) -> Option<RwSemaphoreWriteGuard<T>> {
+        self.lock
+            .compare_exchange(0, WRITER, Acquire, Relaxed)
+            .ok()
+            .map(|_| RwSemaphoreWriteGuard { inner: self })
+    }
+
+    /// Returns a mutable reference to the protected data.
+    ///
+    /// No locking is needed, since the semaphore is borrowed mutably.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.val.get_mut()
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwSemaphore<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.val, f)
+    }
+}
+
+// SAFETY: The data is accessed by either one writer or many readers, so it
+// must be `Sync` to be shared by the readers.
+unsafe impl<T: ?Sized + Send> Send for RwSemaphore<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for RwSemaphore<T> {}
+
+/// A guard that provides shared read access to the data protected by a [`RwSemaphore`].
+pub struct RwSemaphoreReadGuard<'a, T: ?Sized> {
+    inner: &'a RwSemaphore<T>,
+}
+
+impl<T: ?Sized> Deref for RwSemaphoreReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: The guard ensures that there is no writer.
+        unsafe { &*self.inner.val.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwSemaphoreReadGuard<'_, T> {
+    fn drop(&mut self) {
+        // When there are no readers, wake up the waiters. All of them are woken up,
+        // since a reader that is woken up instead of a writer goes back to sleep.
+        if self.inner.lock.fetch_sub(READER, Release) == READER {
+            self.inner.queue.wake_all();
+        }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwSemaphoreReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized> !Send for RwSemaphoreReadGuard<'_, T> {}
+// SAFETY: The guard only provides shared access to the data.
+unsafe impl<T: ?Sized + Sync> Sync for RwSemaphoreReadGuard<'_, T> {}
+
+/// A guard that provides exclusive write access to the data protected by a [`RwSemaphore`].
+pub struct RwSemaphoreWriteGuard<'a, T: ?Sized> {
+    inner: &'a RwSemaphore<T>,
+}
+
+impl<'a, T: ?Sized> RwSemaphoreWriteGuard<'a, T> {
+    /// Atomically downgrades the write guard to a read guard.
+    ///
+    /// The other readers can acquire the semaphore once this method returns,
+    /// while no writer can acquire it in the meantime.
+    pub fn downgrade(self) -> RwSemaphoreReadGuard<'a, T> {
+        let inner = self.inner;
+        core::mem::forget(self);
+
+        inner.lock.fetch_add(READER, Acquire);
+        inner.lock.fetch_and(!WRITER, AcqRel);
+        inner.queue.wake_all();
+        RwSemaphoreReadGuard { inner }
+    }
+}
+
+impl<T: ?Sized> Deref for RwSemaphoreWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: The guard ensures that there is no other writer or reader.
+        unsafe { &*self.inner.val.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwSemaphoreWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: The guard ensures that there is no other writer or reader.
+        unsafe { &mut *self.inner.val.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwSemaphoreWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.inner.lock.fetch_and(!WRITER, Release);
+        self.inner.queue.wake_all();
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwSemaphoreWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized> !Send for RwSemaphoreWriteGuard<'_, T> {}
+// SAFETY: The guard only provides shared access to the data through `&Self`.
+unsafe impl<T: ?Sized + Sync> Sync for RwSemaphoreWriteGuard<'_, T> {}
+
+#[cfg(ktest)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+
+    #[ktest]
+    fn read_and_write() {
+        let sem = RwSemaphore::new(5);
+
+        let r1 = sem.read();
+        let r2 = sem.try_read().unwrap();
+        assert_eq!(*r1 + *r2, 10);
+        assert!(sem.try_write().is_none());
+        drop(r1);
+        drop(r2);
+
+        let mut w = sem.write();
+        *w += 1;
+        assert!(sem.try_read().is_none());
+        assert!(sem.try_write().is_none());
+
+        let r = w.downgrade();
+        assert_eq!(*r, 6);
+        assert!(sem.try_read().is_some());
+        assert!(sem.try_write().is_none());
+        drop(r);
+
+        assert_eq!(*sem.try_write().unwrap(), 6);
+    }
+
+    #[ktest]
+    fn waiting_writer_blocks_readers() {
+        let sem = RwSemaphore::new(());
+        let _r = sem.read();
+
+        // Pretend that a writer is waiting.
+        sem.nr_waiting_writers.fetch_add(1, Relaxed);
+        assert!(sem.try_read().is_none());
+        sem.nr_waiting_writers.fetch_sub(1, Relaxed);
+        assert!(sem.try_read().is_some());
+    }
+}
@@ -0,0 +1,166 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! A sequence lock.
+//!
+//! A [`SeqLock`] protects data that is read frequently but written rarely,
+//! e.g., the wall-clock time or the statistics of a network interface.
+//! Readers never block writers. Instead, a reader checks a sequence number
+//! before and after it copies the data, and retries if a writer has run
+//! in the meantime.
+
+use core::{
+    cell::UnsafeCell,
+    fmt,
+    ops::{Deref, DerefMut},
+    ptr,
+    sync::atomic::{fence, AtomicUsize, Ordering},
+};
+
+use super::{SpinLock, SpinLockGuard};
+
+/// A sequence lock.
+///
+/// The protected data is read by copying, so it must be [`Copy`]. A reader may
+/// copy the data while a writer is modifying it, but such a torn copy is always
+/// discarded, so the reader only ever returns a consistent snapshot.
+///
+/// The writers are serialized by a spin lock with the local IRQs disabled. So
+/// the lock can be read in the interrupt context, without spinning forever on
+/// a writer that the interrupt has preempted on the same CPU.
+pub struct SeqLock<T> {
+    /// The sequence number, which is odd while a writer is active.
+    seq: AtomicUsize,
+    writer_lock: SpinLock<()>,
+    val: UnsafeCell<T>,
+}
+
+impl<T: Copy> SeqLock<T> {
+    /// Creates a new sequence lock.
+    pub const fn new(val: T) -> Self {
+        Self {
+            seq: AtomicUsize::new(0),
+            writer_lock: SpinLock::new(()),
+            val: UnsafeCell::new(val),
+        }
+    }
+
+    /// Reads a consistent snapshot of the data.
+    ///
+    /// This method retries until no writer is active during the read.
+    pub fn read(&self) -> T {
+        loop {
+            let seq = self.seq.load(Ordering::Acquire);
+            if seq % 2 != 0 {
+                core::hint::spin_loop();
+                continue;
+            }
+
+            // SAFETY: The pointer is valid and aligned. The data may be modified by a
+            // writer concurrently, but `T: Copy` has no drop glue and the copy is
+            // discarded below if a writer has run, so a torn copy is never observed.
+            let val = unsafe { ptr::read_volatile(self.val.get()) };
+
+            // Order the read of the data before the re-check of the sequence number.
+            fence(Ordering::Acquire);
+            if self.seq.load(Ordering::Relaxed) == seq {
+                return val;
+            }
+        }
+    }
+
+    /// Acquires the lock for writing.
+    ///
+    /// The local IRQs are disabled until the returned guard is dropped.
+    pub fn write(&self) -> SeqLockWriteGuard<T> {
+        let writer_guard = self.writer_lock.lock_irq_disabled();
+
+        let seq = self.seq.load(Ordering::Relaxed);
+        self.seq.store(seq + 1, Ordering::Relaxed);
+        // Order the update of the sequence number before the writes to the data.
+        fence(Ordering::Release);
+
+        SeqLockWriteGuard {
+            lock: self,
+            _writer_guard: writer_guard,
+        }
+    }
+
+    /// Replaces the data with `val`.
+    pub fn set(&self, val: T) {
+        *self.write() = val;
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for SeqLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.read(), f)
+    }
+}
+
+// SAFETY: The data is only modified by a single writer at a time, and readers
+// only obtain copies of the data.
+unsafe impl<T: Copy + Send> Send for SeqLock<T> {}
+unsafe impl<T: Copy + Send> Sync for SeqLock<T> {}
+
+/// The guard that provides exclusive write access to the data protected by a [`SeqLock`].
+pub struct SeqLockWriteGuard<'a, T: Copy> {
+    lock: &'a SeqLock<T>,
+    _writer_guard: SpinLockGuard<'a, ()>,
+}
+
+impl<T: Copy> Deref for SeqLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: The guard ensures that there is no other writer.
+        unsafe { &*self.lock.val.get() }
+    }
+}
+
+impl<T: Copy> DerefMut for SeqLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: The guard ensures that there is no other writer.
+        unsafe { &mut *self.lock.val.get() }
+    }
+}
+
+impl<T: Copy> Drop for SeqLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        // Publish the writes to the data, and make the sequence number even again.
+        // This happens before the writer lock is released by dropping the fields.
+        let seq = self.lock.seq.load(Ordering::Relaxed);
+        self.lock.seq.store(seq + 1, Ordering::Release);
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for SeqLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Copy> !Send for SeqLockWriteGuard<'_, T> {}
+
+#[cfg(ktest)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+
+    #[ktest]
+    fn read_and_write() {
+        let lock = SeqLock::new((0u64, 0u64));
+        assert_eq!(lock.read(), (0, 0));
+
+        {
+            let mut guard = lock.write();
+            guard.0 = 1;
+            guard.1 = 2;
+            assert_eq!(*guard, (1, 2));
+        }
+        assert_eq!(lock.read(), (1, 2));
+
+        lock.set((3, 4));
+        assert_eq!(lock.read(), (3, 4));
+        assert_eq!(lock.seq.load(Ordering::Relaxed), 4);
+    }
+}
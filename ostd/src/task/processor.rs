@@ -13,7 +13,7 @@ use super::{
     task::{context_switch, TaskContext},
     Task, TaskStatus,
 };
-use crate::{cpu_local, CpuLocal};
+use crate::{cpu_local, sync::pass_quiescent_state, CpuLocal};
 
 pub struct Processor {
     current: Option<Arc<Task>>,
@@ -68,6 +68,12 @@ pub(crate) fn get_idle_task_ctx_ptr() -> *mut TaskContext {
 
 /// Calls this function to switch to other task by using GLOBAL_SCHEDULER
 pub fn schedule() {
+    if PREEMPT_COUNT.is_preemptive() {
+        // SAFETY: RCU read-side critical sections disable preemption, so the current CPU
+        // cannot be in any of them.
+        unsafe { pass_quiescent_state() };
+    }
+
     if let Some(task) = fetch_task() {
         switch_to_task(task);
     }
@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Inter-processor interrupts (IPIs).
+
+use crate::{cpu::CpuSet, prelude::*, trap::IrqLine};
+
+/// The target CPUs of an inter-processor interrupt (IPI).
+#[derive(Clone, Copy)]
+pub enum IpiTarget<'a> {
+    /// A single CPU.
+    Cpu(u32),
+    /// A set of CPUs, which may or may not contain the current CPU.
+    CpuSet(&'a CpuSet),
+    /// All CPUs except the current one.
+    AllExcludingSelf,
+}
+
+/// Sends an IPI to the `target` CPUs.
+///
+/// The IPI is delivered with the vector of `irq`, so the callbacks registered
+/// on `irq` are invoked on the target CPUs. The same `irq` can be used for IPIs
+/// of the same purpose, e.g., TLB shootdowns.
+///
+/// # Errors
+///
+/// This function returns [`Error::InvalidArgs`] if a target CPU is unknown to the
+/// interrupt controller, and [`Error::NotEnoughResources`] if there is no
+/// interrupt controller that can send IPIs.
+///
+/// [`Error::InvalidArgs`]: crate::Error::InvalidArgs
+/// [`Error::NotEnoughResources`]: crate::Error::NotEnoughResources
+pub fn send_ipi(target: IpiTarget, irq: &IrqLine) -> Result<()> {
+    crate::arch::irq::send_ipi(target, irq.num())
+}
@@ -3,10 +3,12 @@
 //! Handles trap across kernel and user space.
 
 mod handler;
+mod ipi;
 mod irq;
 pub mod softirq;
 
 pub use handler::in_interrupt_context;
+pub use ipi::{send_ipi, IpiTarget};
 pub use softirq::SoftIrqLine;
 pub use trapframe::TrapFrame;
 
@@ -0,0 +1,210 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <linux/aio_abi.h>
+#include <stdint.h>
+#include <sys/eventfd.h>
+#include <sys/syscall.h>
+#include <time.h>
+#include <unistd.h>
+
+#include "../network/test.h"
+
+#define BUF_SIZE 4096
+
+#ifndef FILE_PATH
+#define FILE_PATH "/ext2/test_aio.txt"
+#endif
+
+static aio_context_t ctx;
+static int fd;
+static int efd;
+// The buffers of direct I/O must be aligned to the block size.
+static char wbuf[BUF_SIZE] __attribute__((aligned(BUF_SIZE)));
+static char rbuf[BUF_SIZE] __attribute__((aligned(BUF_SIZE)));
+
+static int io_setup(unsigned int nr_events, aio_context_t *ctxp)
+{
+	return syscall(SYS_io_setup, nr_events, ctxp);
+}
+
+static int io_destroy(aio_context_t ctx)
+{
+	return syscall(SYS_io_destroy, ctx);
+}
+
+static int io_submit(aio_context_t ctx, long nr, struct iocb **iocbpp)
+{
+	return syscall(SYS_io_submit, ctx, nr, iocbpp);
+}
+
+static int io_getevents(aio_context_t ctx, long min_nr, long max_nr,
+			struct io_event *events, struct timespec *timeout)
+{
+	return syscall(SYS_io_getevents, ctx, min_nr, max_nr, events, timeout);
+}
+
+static int io_cancel(aio_context_t ctx, struct iocb *iocb,
+		     struct io_event *result)
+{
+	return syscall(SYS_io_cancel, ctx, iocb, result);
+}
+
+static void init_iocb(struct iocb *iocb, int opcode, void *buf)
+{
+	memset(iocb, 0, sizeof(*iocb));
+	iocb->aio_data = (uintptr_t)iocb;
+	iocb->aio_lio_opcode = opcode;
+	iocb->aio_fildes = fd;
+	iocb->aio_buf = (uintptr_t)buf;
+	iocb->aio_nbytes = BUF_SIZE;
+	iocb->aio_offset = 0;
+}
+
+FN_SETUP(open)
+{
+	memset(wbuf, 'a', BUF_SIZE);
+
+	fd = CHECK(open(FILE_PATH, O_RDWR | O_CREAT | O_TRUNC | O_DIRECT, 0644));
+	efd = CHECK(eventfd(0, 0));
+}
+END_SETUP()
+
+FN_TEST(setup)
+{
+	aio_context_t bad_ctx = 1;
+
+	TEST_ERRNO(io_setup(16, &bad_ctx), EINVAL);
+	TEST_ERRNO(io_setup(0, &ctx), EINVAL);
+	TEST_SUCC(io_setup(16, &ctx));
+}
+END_TEST()
+
+FN_TEST(direct_write_read)
+{
+	struct iocb iocb;
+	struct iocb *iocbp = &iocb;
+	struct io_event event;
+
+	init_iocb(&iocb, IOCB_CMD_PWRITE, wbuf);
+	TEST_RES(io_submit(ctx, 1, &iocbp), _ret == 1);
+	TEST_RES(io_getevents(ctx, 1, 1, &event, NULL),
+		 _ret == 1 && event.data == (uintptr_t)&iocb &&
+			 event.obj == (uintptr_t)&iocb && event.res == BUF_SIZE);
+
+	init_iocb(&iocb, IOCB_CMD_PREAD, rbuf);
+	TEST_RES(io_submit(ctx, 1, &iocbp), _ret == 1);
+	TEST_RES(io_getevents(ctx, 1, 1, &event, NULL),
+		 _ret == 1 && event.res == BUF_SIZE &&
+			 memcmp(rbuf, wbuf, BUF_SIZE) == 0);
+}
+END_TEST()
+
+FN_TEST(read_eof)
+{
+	struct iocb iocb;
+	struct iocb *iocbp = &iocb;
+	struct io_event event;
+
+	init_iocb(&iocb, IOCB_CMD_PREAD, rbuf);
+	iocb.aio_offset = BUF_SIZE;
+	TEST_RES(io_submit(ctx, 1, &iocbp), _ret == 1);
+	TEST_RES(io_getevents(ctx, 1, 1, &event, NULL),
+		 _ret == 1 && event.res == 0);
+}
+END_TEST()
+
+FN_TEST(fsync)
+{
+	struct iocb iocb;
+	struct iocb *iocbp = &iocb;
+	struct io_event event;
+
+	init_iocb(&iocb, IOCB_CMD_FSYNC, NULL);
+	iocb.aio_nbytes = 0;
+	TEST_RES(io_submit(ctx, 1, &iocbp), _ret == 1);
+	TEST_RES(io_getevents(ctx, 1, 1, &event, NULL),
+		 _ret == 1 && event.res == 0);
+}
+END_TEST()
+
+FN_TEST(resfd)
+{
+	struct iocb iocb;
+	struct iocb *iocbp = &iocb;
+	struct io_event event;
+	uint64_t count;
+
+	init_iocb(&iocb, IOCB_CMD_PREAD, rbuf);
+	iocb.aio_flags = IOCB_FLAG_RESFD;
+	iocb.aio_resfd = efd;
+	TEST_RES(io_submit(ctx, 1, &iocbp), _ret == 1);
+	TEST_RES(read(efd, &count, sizeof(count)),
+		 _ret == sizeof(count) && count == 1);
+	TEST_RES(io_getevents(ctx, 1, 1, &event, NULL),
+		 _ret == 1 && event.res == BUF_SIZE);
+}
+END_TEST()
+
+FN_TEST(bad_requests)
+{
+	struct iocb iocb;
+	struct iocb *iocbp = &iocb;
+
+	init_iocb(&iocb, IOCB_CMD_PREAD, rbuf);
+	iocb.aio_fildes = -1;
+	TEST_ERRNO(io_submit(ctx, 1, &iocbp), EBADF);
+
+	init_iocb(&iocb, IOCB_CMD_PREAD, rbuf);
+	iocb.aio_lio_opcode = 100;
+	TEST_ERRNO(io_submit(ctx, 1, &iocbp), EINVAL);
+}
+END_TEST()
+
+FN_TEST(cancel)
+{
+	struct iocb iocb;
+	struct iocb *iocbp = &iocb;
+	struct io_event event;
+
+	init_iocb(&iocb, IOCB_CMD_PREAD, rbuf);
+	TEST_RES(io_submit(ctx, 1, &iocbp), _ret == 1);
+	TEST_RES(io_getevents(ctx, 1, 1, &event, NULL), _ret == 1);
+
+	// The request has completed, so it cannot be found.
+	TEST_ERRNO(io_cancel(ctx, &iocb, &event), EINVAL);
+
+	iocb.aio_key = 1;
+	TEST_ERRNO(io_cancel(ctx, &iocb, &event), EINVAL);
+}
+END_TEST()
+
+FN_TEST(getevents_timeout)
+{
+	struct io_event event;
+	struct timespec timeout = { .tv_sec = 0, .tv_nsec = 10000000 };
+
+	TEST_RES(io_getevents(ctx, 1, 1, &event, &timeout), _ret == 0);
+	TEST_ERRNO(io_getevents(ctx, 2, 1, &event, &timeout), EINVAL);
+}
+END_TEST()
+
+FN_TEST(destroy)
+{
+	struct io_event event;
+
+	TEST_SUCC(io_destroy(ctx));
+	TEST_ERRNO(io_getevents(ctx, 1, 1, &event, NULL), EINVAL);
+	TEST_ERRNO(io_destroy(ctx), EINVAL);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(efd));
+	CHECK(close(fd));
+	CHECK(unlink(FILE_PATH));
+}
+END_SETUP()
@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <sched.h>
+#include <signal.h>
+#include <stdlib.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../network/test.h"
+
+#ifndef SYS_close_range
+#define SYS_close_range 436
+#endif
+
+#ifndef CLOSE_RANGE_UNSHARE
+#define CLOSE_RANGE_UNSHARE (1U << 1)
+#endif
+
+#ifndef CLOSE_RANGE_CLOEXEC
+#define CLOSE_RANGE_CLOEXEC (1U << 2)
+#endif
+
+#define STACK_SIZE (64 * 1024)
+
+static int fds[3];
+
+static int do_close_range(unsigned int first, unsigned int last,
+			  unsigned int flags)
+{
+	return syscall(SYS_close_range, first, last, flags);
+}
+
+static int open_fds(void)
+{
+	int i;
+
+	for (i = 0; i < 3; i++) {
+		fds[i] = open("/dev/null", O_RDONLY);
+		if (fds[i] < 0)
+			return -1;
+	}
+	return 0;
+}
+
+FN_TEST(invalid)
+{
+	TEST_SUCC(open_fds());
+
+	TEST_ERRNO(do_close_range(fds[2], fds[0], 0), EINVAL);
+	TEST_ERRNO(do_close_range(fds[0], fds[2], 1U << 3), EINVAL);
+	TEST_RES(fcntl(fds[0], F_GETFD), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(close)
+{
+	TEST_SUCC(do_close_range(fds[0], fds[1], 0));
+	TEST_ERRNO(fcntl(fds[0], F_GETFD), EBADF);
+	TEST_ERRNO(fcntl(fds[1], F_GETFD), EBADF);
+	TEST_RES(fcntl(fds[2], F_GETFD), _ret == 0);
+
+	TEST_SUCC(do_close_range(fds[2], ~0U, 0));
+	TEST_ERRNO(fcntl(fds[2], F_GETFD), EBADF);
+}
+END_TEST()
+
+FN_TEST(cloexec)
+{
+	TEST_SUCC(open_fds());
+
+	TEST_SUCC(do_close_range(fds[0], fds[1], CLOSE_RANGE_CLOEXEC));
+	TEST_RES(fcntl(fds[0], F_GETFD), _ret == FD_CLOEXEC);
+	TEST_RES(fcntl(fds[1], F_GETFD), _ret == FD_CLOEXEC);
+	TEST_RES(fcntl(fds[2], F_GETFD), _ret == 0);
+
+	TEST_SUCC(do_close_range(fds[0], fds[2], 0));
+}
+END_TEST()
+
+static int close_in_child(void *arg)
+{
+	unsigned int flags = *(unsigned int *)arg;
+
+	if (do_close_range(fds[0], fds[2], flags) < 0)
+		return 1;
+	return fcntl(fds[0], F_GETFD) < 0 ? 0 : 1;
+}
+
+// Closes the file descriptors in a child that shares the file table.
+static int run_child(unsigned int flags)
+{
+	char *stack;
+	int status;
+	pid_t pid;
+
+	stack = malloc(STACK_SIZE);
+	if (stack == NULL)
+		return -1;
+
+	pid = clone(close_in_child, stack + STACK_SIZE, CLONE_FILES | SIGCHLD,
+		    &flags);
+	if (pid < 0 || waitpid(pid, &status, 0) < 0) {
+		free(stack);
+		return -1;
+	}
+	free(stack);
+
+	return WIFEXITED(status) ? WEXITSTATUS(status) : -1;
+}
+
+FN_TEST(unshare)
+{
+	TEST_SUCC(open_fds());
+
+	// The files are closed in the child's own copy of the file table.
+	TEST_RES(run_child(CLOSE_RANGE_UNSHARE), _ret == 0);
+	TEST_RES(fcntl(fds[0], F_GETFD), _ret == 0);
+	TEST_RES(fcntl(fds[2], F_GETFD), _ret == 0);
+
+	// The files are closed in the shared file table.
+	TEST_RES(run_child(0), _ret == 0);
+	TEST_ERRNO(fcntl(fds[0], F_GETFD), EBADF);
+	TEST_ERRNO(fcntl(fds[2], F_GETFD), EBADF);
+}
+END_TEST()
+
+FN_TEST(unshare_cloexec)
+{
+	TEST_SUCC(open_fds());
+
+	// The file table is not shared, so it is updated in place.
+	TEST_SUCC(do_close_range(fds[0], fds[2],
+			      CLOSE_RANGE_UNSHARE | CLOSE_RANGE_CLOEXEC));
+	TEST_RES(fcntl(fds[0], F_GETFD), _ret == FD_CLOEXEC);
+	TEST_RES(fcntl(fds[2], F_GETFD), _ret == FD_CLOEXEC);
+
+	TEST_SUCC(do_close_range(fds[0], fds[2], CLOSE_RANGE_UNSHARE));
+	TEST_ERRNO(fcntl(fds[0], F_GETFD), EBADF);
+}
+END_TEST()
@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <sys/resource.h>
+#include <unistd.h>
+
+#include "../network/test.h"
+
+#define NOFILE 16
+
+static int fd;
+
+FN_SETUP(nofile)
+{
+	struct rlimit rlim = { .rlim_cur = NOFILE, .rlim_max = NOFILE };
+
+	CHECK(prlimit(0, RLIMIT_NOFILE, &rlim, NULL));
+	fd = CHECK(open("/dev/null", O_RDONLY));
+}
+END_SETUP()
+
+FN_TEST(dup_below_limit)
+{
+	TEST_RES(dup2(fd, NOFILE - 1), _ret == NOFILE - 1);
+	TEST_RES(fcntl(fd, F_DUPFD, NOFILE - 2), _ret == NOFILE - 2);
+	TEST_SUCC(close(NOFILE - 1));
+	TEST_SUCC(close(NOFILE - 2));
+}
+END_TEST()
+
+FN_TEST(dup_above_limit)
+{
+	TEST_ERRNO(dup2(fd, NOFILE), EBADF);
+	TEST_ERRNO(fcntl(fd, F_DUPFD, NOFILE), EINVAL);
+
+	// The free fds at or above the limit cannot be used.
+	TEST_RES(dup2(fd, NOFILE - 1), _ret == NOFILE - 1);
+	TEST_ERRNO(fcntl(fd, F_DUPFD, NOFILE - 1), EMFILE);
+	TEST_SUCC(close(NOFILE - 1));
+}
+END_TEST()
+
+FN_TEST(dup_exhausted)
+{
+	int i;
+
+	// Fill all the fds below the limit.
+	for (i = 0; i < NOFILE; i++) {
+		if (fcntl(i, F_GETFD) < 0)
+			CHECK_WITH(dup2(fd, i), _ret == i);
+	}
+
+	TEST_ERRNO(dup(fd), EMFILE);
+	TEST_ERRNO(fcntl(fd, F_DUPFD, 0), EMFILE);
+	TEST_ERRNO(fcntl(fd, F_DUPFD_CLOEXEC, 0), EMFILE);
+}
+END_TEST()
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <sys/mount.h>
+#include <sys/stat.h>
+#include <sys/statvfs.h>
+#include <sys/vfs.h>
+#include <unistd.h>
+
+#include "../network/test.h"
+
+#ifndef ST_VALID
+#define ST_VALID 0x0020
+#endif
+
+#define SRC_DIR "/tmp/mount_flags_src"
+#define DST_DIR "/tmp/mount_flags_dst"
+
+static int fd;
+
+FN_SETUP(bind_mount)
+{
+	CHECK(mkdir(SRC_DIR, 0755));
+	CHECK(mkdir(DST_DIR, 0755));
+	fd = CHECK(open(SRC_DIR "/file", O_WRONLY | O_CREAT, 0644));
+	CHECK(close(fd));
+	CHECK(mount(SRC_DIR, DST_DIR, NULL, MS_BIND, NULL));
+}
+END_SETUP()
+
+FN_TEST(writable)
+{
+	struct statfs buf;
+
+	TEST_RES(statfs(DST_DIR, &buf),
+		 (buf.f_flags & ST_VALID) && !(buf.f_flags & ST_RDONLY));
+	fd = TEST_SUCC(open(DST_DIR "/file", O_WRONLY));
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(remount_not_root)
+{
+	TEST_ERRNO(mount(NULL, DST_DIR "/file", NULL,
+			 MS_REMOUNT | MS_BIND | MS_RDONLY, NULL),
+		   EINVAL);
+}
+END_TEST()
+
+FN_TEST(remount_readonly)
+{
+	struct statfs buf;
+
+	TEST_SUCC(mount(NULL, DST_DIR, NULL,
+			MS_REMOUNT | MS_BIND | MS_RDONLY | MS_NOEXEC, NULL));
+	TEST_RES(statfs(DST_DIR, &buf),
+		 (buf.f_flags & ST_VALID) && (buf.f_flags & ST_RDONLY) &&
+			 (buf.f_flags & ST_NOEXEC) &&
+			 !(buf.f_flags & ST_NOSUID));
+
+	// The flags belong to the mount, not to the filesystem.
+	TEST_RES(statfs(SRC_DIR, &buf), !(buf.f_flags & ST_RDONLY));
+}
+END_TEST()
+
+FN_TEST(readonly)
+{
+	TEST_ERRNO(open(DST_DIR "/file", O_WRONLY), EROFS);
+	TEST_ERRNO(open(DST_DIR "/file", O_RDONLY | O_TRUNC), EROFS);
+	TEST_ERRNO(open(DST_DIR "/new", O_WRONLY | O_CREAT, 0644), EROFS);
+	TEST_ERRNO(mkdir(DST_DIR "/dir", 0755), EROFS);
+	TEST_ERRNO(unlink(DST_DIR "/file"), EROFS);
+	fd = TEST_SUCC(open(DST_DIR "/file", O_RDONLY));
+	TEST_SUCC(close(fd));
+
+	// The source is still writable.
+	fd = TEST_SUCC(open(SRC_DIR "/file", O_WRONLY));
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(remount_writable)
+{
+	struct statfs buf;
+
+	TEST_SUCC(mount(NULL, DST_DIR, NULL, MS_REMOUNT | MS_BIND, NULL));
+	TEST_RES(statfs(DST_DIR, &buf), !(buf.f_flags & ST_RDONLY));
+	fd = TEST_SUCC(open(DST_DIR "/file", O_WRONLY));
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(umount(DST_DIR));
+	CHECK(unlink(SRC_DIR "/file"));
+	CHECK(rmdir(SRC_DIR));
+	CHECK(rmdir(DST_DIR));
+}
+END_SETUP()
@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <sys/socket.h>
+#include <netinet/in.h>
+#include <arpa/inet.h>
+#include <string.h>
+#include <unistd.h>
+
+#include "test.h"
+
+#define NR_MSGS 4
+
+static int sk_recv;
+static int sk_send;
+
+FN_SETUP(sockets)
+{
+	struct sockaddr_in addr;
+
+	memset(&addr, 0, sizeof(addr));
+	addr.sin_family = AF_INET;
+	addr.sin_port = htons(0x1235);
+	CHECK(inet_aton("127.0.0.1", &addr.sin_addr));
+
+	sk_recv = CHECK(socket(PF_INET, SOCK_DGRAM, 0));
+	CHECK(bind(sk_recv, (struct sockaddr *)&addr, sizeof(addr)));
+
+	sk_send = CHECK(socket(PF_INET, SOCK_DGRAM, 0));
+	CHECK(connect(sk_send, (struct sockaddr *)&addr, sizeof(addr)));
+}
+END_SETUP()
+
+static char send_bufs[NR_MSGS][16];
+static struct iovec send_iovs[NR_MSGS];
+static struct mmsghdr send_msgs[NR_MSGS];
+
+static char recv_bufs[NR_MSGS][16];
+static struct iovec recv_iovs[NR_MSGS];
+static struct mmsghdr recv_msgs[NR_MSGS];
+
+static void init_msgs(void)
+{
+	int i;
+
+	memset(send_msgs, 0, sizeof(send_msgs));
+	memset(recv_msgs, 0, sizeof(recv_msgs));
+	memset(recv_bufs, 0, sizeof(recv_bufs));
+
+	for (i = 0; i < NR_MSGS; ++i) {
+		snprintf(send_bufs[i], sizeof(send_bufs[i]), "message %d", i);
+		send_iovs[i].iov_base = send_bufs[i];
+		send_iovs[i].iov_len = strlen(send_bufs[i]) + 1;
+		send_msgs[i].msg_hdr.msg_iov = &send_iovs[i];
+		send_msgs[i].msg_hdr.msg_iovlen = 1;
+
+		recv_iovs[i].iov_base = recv_bufs[i];
+		recv_iovs[i].iov_len = sizeof(recv_bufs[i]);
+		recv_msgs[i].msg_hdr.msg_iov = &recv_iovs[i];
+		recv_msgs[i].msg_hdr.msg_iovlen = 1;
+	}
+}
+
+FN_TEST(sendmmsg_and_recvmmsg)
+{
+	init_msgs();
+
+	TEST_RES(sendmmsg(sk_send, send_msgs, 2, 0),
+		 _ret == 2 && send_msgs[0].msg_len == send_iovs[0].iov_len &&
+			 send_msgs[1].msg_len == send_iovs[1].iov_len);
+
+	TEST_RES(recvmmsg(sk_recv, recv_msgs, NR_MSGS, MSG_DONTWAIT, NULL),
+		 _ret == 2 && recv_msgs[0].msg_len == send_iovs[0].iov_len &&
+			 strcmp(recv_bufs[0], "message 0") == 0 &&
+			 strcmp(recv_bufs[1], "message 1") == 0);
+
+	TEST_ERRNO(recvmmsg(sk_recv, recv_msgs, NR_MSGS, MSG_DONTWAIT, NULL),
+		   EAGAIN);
+}
+END_TEST()
+
+FN_TEST(recvmmsg_waitforone)
+{
+	init_msgs();
+
+	TEST_RES(sendmmsg(sk_send, send_msgs, 3, 0), _ret == 3);
+
+	// The socket is blocking, but only the first message is waited for.
+	TEST_RES(recvmmsg(sk_recv, recv_msgs, NR_MSGS, MSG_WAITFORONE, NULL),
+		 _ret == 3 && strcmp(recv_bufs[0], "message 0") == 0 &&
+			 strcmp(recv_bufs[2], "message 2") == 0);
+
+	TEST_RES(sendmmsg(sk_send, send_msgs, 1, 0), _ret == 1);
+
+	TEST_RES(recvmmsg(sk_recv, recv_msgs, NR_MSGS, MSG_WAITFORONE, NULL),
+		 _ret == 1);
+}
+END_TEST()
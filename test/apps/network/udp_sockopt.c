@@ -0,0 +1,152 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <sys/socket.h>
+#include <netinet/in.h>
+#include <arpa/inet.h>
+#include <linux/net_tstamp.h>
+#include <string.h>
+#include <time.h>
+#include <unistd.h>
+
+#include "test.h"
+
+static int sk_recv;
+static int sk_send;
+static int sk_tcp;
+
+FN_SETUP(sockets)
+{
+	struct sockaddr_in addr;
+
+	memset(&addr, 0, sizeof(addr));
+	addr.sin_family = AF_INET;
+	addr.sin_port = htons(0x1236);
+	CHECK(inet_aton("127.0.0.1", &addr.sin_addr));
+
+	sk_recv = CHECK(socket(PF_INET, SOCK_DGRAM, 0));
+	CHECK(bind(sk_recv, (struct sockaddr *)&addr, sizeof(addr)));
+
+	sk_send = CHECK(socket(PF_INET, SOCK_DGRAM, 0));
+	CHECK(connect(sk_send, (struct sockaddr *)&addr, sizeof(addr)));
+
+	sk_tcp = CHECK(socket(PF_INET, SOCK_STREAM, 0));
+}
+END_SETUP()
+
+FN_TEST(busy_poll)
+{
+	int val;
+	socklen_t len = sizeof(val);
+
+	val = -1;
+	TEST_ERRNO(setsockopt(sk_recv, SOL_SOCKET, SO_BUSY_POLL, &val,
+			      sizeof(val)),
+		   EINVAL);
+	TEST_ERRNO(setsockopt(sk_tcp, SOL_SOCKET, SO_BUSY_POLL, &val,
+			      sizeof(val)),
+		   EINVAL);
+
+	val = 50;
+	TEST_SUCC(setsockopt(sk_recv, SOL_SOCKET, SO_BUSY_POLL, &val,
+			     sizeof(val)));
+	TEST_RES(getsockopt(sk_recv, SOL_SOCKET, SO_BUSY_POLL, &val, &len),
+		 len == sizeof(val) && val == 50);
+}
+END_TEST()
+
+FN_TEST(busy_poll_recv)
+{
+	char buf[16];
+
+	TEST_RES(send(sk_send, "hello", 6, 0), _ret == 6);
+	TEST_RES(recv(sk_recv, buf, sizeof(buf), 0),
+		 _ret == 6 && strcmp(buf, "hello") == 0);
+}
+END_TEST()
+
+FN_TEST(timestamping_flags)
+{
+	int val;
+	socklen_t len = sizeof(val);
+
+	val = 1 << 20;
+	TEST_ERRNO(setsockopt(sk_recv, SOL_SOCKET, SO_TIMESTAMPING, &val,
+			      sizeof(val)),
+		   EINVAL);
+
+	val = SOF_TIMESTAMPING_RX_SOFTWARE | SOF_TIMESTAMPING_SOFTWARE;
+	TEST_SUCC(setsockopt(sk_recv, SOL_SOCKET, SO_TIMESTAMPING, &val,
+			     sizeof(val)));
+	TEST_RES(getsockopt(sk_recv, SOL_SOCKET, SO_TIMESTAMPING, &val, &len),
+		 len == sizeof(val) &&
+			 val == (SOF_TIMESTAMPING_RX_SOFTWARE |
+				 SOF_TIMESTAMPING_SOFTWARE));
+}
+END_TEST()
+
+static char buf[16];
+static struct iovec iov = { .iov_base = buf, .iov_len = sizeof(buf) };
+static char control[CMSG_SPACE(sizeof(struct timespec) * 3)];
+static struct msghdr msg;
+
+static void init_msg(size_t controllen)
+{
+	memset(&msg, 0, sizeof(msg));
+	memset(control, 0, sizeof(control));
+	msg.msg_iov = &iov;
+	msg.msg_iovlen = 1;
+	msg.msg_control = control;
+	msg.msg_controllen = controllen;
+}
+
+FN_TEST(timestamping_recv)
+{
+	struct timespec before;
+	struct cmsghdr *cmsg;
+	struct timespec *ts;
+
+	CHECK(clock_gettime(CLOCK_REALTIME, &before));
+
+	TEST_RES(send(sk_send, "hello", 6, 0), _ret == 6);
+
+	init_msg(sizeof(control));
+	cmsg = (struct cmsghdr *)control;
+	ts = (struct timespec *)CMSG_DATA(cmsg);
+	TEST_RES(recvmsg(sk_recv, &msg, 0),
+		 _ret == 6 && msg.msg_flags == 0 &&
+			 msg.msg_controllen == sizeof(control) &&
+			 cmsg->cmsg_level == SOL_SOCKET &&
+			 cmsg->cmsg_type == SCM_TIMESTAMPING &&
+			 cmsg->cmsg_len ==
+				 CMSG_LEN(sizeof(struct timespec) * 3) &&
+			 ts[0].tv_sec >= before.tv_sec && ts[1].tv_sec == 0 &&
+			 ts[2].tv_sec == 0);
+}
+END_TEST()
+
+FN_TEST(timestamping_ctrunc)
+{
+	TEST_RES(send(sk_send, "hello", 6, 0), _ret == 6);
+
+	init_msg(sizeof(struct cmsghdr));
+	TEST_RES(recvmsg(sk_recv, &msg, 0),
+		 _ret == 6 && (msg.msg_flags & MSG_CTRUNC));
+}
+END_TEST()
+
+FN_TEST(timestamping_disabled)
+{
+	int val = 0;
+
+	TEST_SUCC(setsockopt(sk_recv, SOL_SOCKET, SO_TIMESTAMPING, &val,
+			     sizeof(val)));
+
+	TEST_RES(send(sk_send, "hello", 6, 0), _ret == 6);
+
+	init_msg(sizeof(control));
+	TEST_RES(recvmsg(sk_recv, &msg, 0),
+		 _ret == 6 && msg.msg_flags == 0 && msg.msg_controllen == 0);
+}
+END_TEST()
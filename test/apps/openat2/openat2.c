@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <stdint.h>
+#include <string.h>
+#include <sys/syscall.h>
+#include <unistd.h>
+
+#include "../network/test.h"
+
+#ifndef SYS_openat2
+#define SYS_openat2 437
+#endif
+
+#define FILE_PATH "/tmp/openat2_file"
+
+// The same as `struct open_how` in Linux, followed by a field unknown to the
+// kernel.
+struct open_how_ext {
+	uint64_t flags;
+	uint64_t mode;
+	uint64_t resolve;
+	uint64_t unknown;
+};
+
+static struct open_how_ext how;
+
+static int openat2(size_t size)
+{
+	return syscall(SYS_openat2, AT_FDCWD, FILE_PATH, &how, size);
+}
+
+FN_TEST(create)
+{
+	int fd;
+
+	memset(&how, 0, sizeof(how));
+	how.flags = O_RDWR | O_CREAT | O_EXCL;
+	how.mode = 0644;
+	fd = TEST_SUCC(openat2(24));
+	TEST_RES(write(fd, "hello", 5), _ret == 5);
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(open)
+{
+	char buf[8];
+	int fd;
+
+	memset(&how, 0, sizeof(how));
+	how.flags = O_RDONLY;
+	fd = TEST_SUCC(openat2(24));
+	TEST_RES(read(fd, buf, sizeof(buf)), _ret == 5);
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(size)
+{
+	int fd;
+
+	memset(&how, 0, sizeof(how));
+	how.flags = O_RDONLY;
+	TEST_ERRNO(openat2(16), EINVAL);
+	TEST_ERRNO(openat2(4097), E2BIG);
+
+	fd = TEST_SUCC(openat2(sizeof(how)));
+	TEST_SUCC(close(fd));
+
+	how.unknown = 1;
+	TEST_ERRNO(openat2(sizeof(how)), E2BIG);
+	fd = TEST_SUCC(openat2(24));
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(invalid)
+{
+	memset(&how, 0, sizeof(how));
+	how.flags = O_RDONLY | (1ULL << 40);
+	TEST_ERRNO(openat2(24), EINVAL);
+
+	how.flags = O_RDONLY;
+	how.mode = 0644;
+	TEST_ERRNO(openat2(24), EINVAL);
+
+	how.flags = O_RDONLY | O_CREAT;
+	how.mode = 010000;
+	TEST_ERRNO(openat2(24), EINVAL);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(unlink(FILE_PATH));
+}
+END_SETUP()
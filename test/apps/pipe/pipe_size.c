@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <string.h>
+#include <unistd.h>
+
+#include "../network/test.h"
+
+#define PIPE_MAX_SIZE_PATH "/proc/sys/fs/pipe-max-size"
+
+static int fds[2];
+static char buf[8192];
+
+FN_SETUP(pipe)
+{
+	CHECK(pipe(fds));
+}
+END_SETUP()
+
+FN_TEST(default_size)
+{
+	TEST_RES(fcntl(fds[0], F_GETPIPE_SZ), _ret == 65536);
+	TEST_RES(fcntl(fds[1], F_GETPIPE_SZ), _ret == 65536);
+}
+END_TEST()
+
+FN_TEST(set_size)
+{
+	TEST_RES(fcntl(fds[1], F_SETPIPE_SZ, 5000), _ret == 8192);
+	TEST_RES(fcntl(fds[0], F_GETPIPE_SZ), _ret == 8192);
+
+	TEST_RES(fcntl(fds[1], F_SETPIPE_SZ, 0), _ret == 4096);
+	TEST_RES(fcntl(fds[0], F_GETPIPE_SZ), _ret == 4096);
+}
+END_TEST()
+
+FN_TEST(shrink_busy)
+{
+	TEST_RES(fcntl(fds[1], F_SETPIPE_SZ, 8192), _ret == 8192);
+	TEST_RES(write(fds[1], buf, sizeof(buf)), _ret == sizeof(buf));
+
+	TEST_ERRNO(fcntl(fds[1], F_SETPIPE_SZ, 4096), EBUSY);
+	TEST_RES(fcntl(fds[0], F_GETPIPE_SZ), _ret == 8192);
+
+	TEST_RES(read(fds[0], buf, sizeof(buf)), _ret == sizeof(buf));
+	TEST_RES(fcntl(fds[1], F_SETPIPE_SZ, 4096), _ret == 4096);
+}
+END_TEST()
+
+static int write_sysctl(const char *value)
+{
+	int fd, ret;
+
+	fd = CHECK(open(PIPE_MAX_SIZE_PATH, O_WRONLY));
+	ret = write(fd, value, strlen(value));
+	CHECK(close(fd));
+
+	return ret;
+}
+
+static int read_sysctl(char *value, size_t len)
+{
+	int fd, ret;
+
+	fd = CHECK(open(PIPE_MAX_SIZE_PATH, O_RDONLY));
+	ret = read(fd, value, len - 1);
+	CHECK(close(fd));
+
+	if (ret >= 0)
+		value[ret] = '\0';
+	return ret;
+}
+
+FN_TEST(pipe_max_size)
+{
+	char value[32];
+
+	TEST_RES(read_sysctl(value, sizeof(value)),
+		 strcmp(value, "1048576\n") == 0);
+
+	TEST_RES(write_sysctl("5000\n"), _ret == 5);
+	TEST_RES(read_sysctl(value, sizeof(value)),
+		 strcmp(value, "8192\n") == 0);
+
+	TEST_ERRNO(write_sysctl("2147483649\n"), EINVAL);
+	TEST_RES(read_sysctl(value, sizeof(value)),
+		 strcmp(value, "8192\n") == 0);
+
+	TEST_RES(write_sysctl("1048576\n"), _ret == 8);
+	TEST_RES(read_sysctl(value, sizeof(value)),
+		 strcmp(value, "1048576\n") == 0);
+}
+END_TEST()
+
+FN_TEST(pipe_user_pages)
+{
+	int fd;
+	char value[32];
+
+	fd = TEST_SUCC(open("/proc/sys/fs/pipe-user-pages-soft", O_RDONLY));
+	TEST_RES(read(fd, value, sizeof(value)),
+		 _ret == 6 && memcmp(value, "16384\n", 6) == 0);
+	TEST_SUCC(close(fd));
+
+	fd = TEST_SUCC(open("/proc/sys/fs/pipe-user-pages-hard", O_RDONLY));
+	TEST_RES(read(fd, value, sizeof(value)),
+		 _ret == 2 && memcmp(value, "0\n", 2) == 0);
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(fds[0]));
+	CHECK(close(fds[1]));
+}
+END_SETUP()
+
+FN_SETUP(packet_pipe)
+{
+	CHECK(pipe2(fds, O_DIRECT));
+}
+END_SETUP()
+
+FN_TEST(packet_read)
+{
+	TEST_RES(write(fds[1], "hello", 5), _ret == 5);
+	TEST_RES(write(fds[1], "world!", 6), _ret == 6);
+
+	TEST_RES(read(fds[0], buf, sizeof(buf)),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+	TEST_RES(read(fds[0], buf, sizeof(buf)),
+		 _ret == 6 && memcmp(buf, "world!", 6) == 0);
+}
+END_TEST()
+
+FN_TEST(packet_short_read)
+{
+	TEST_RES(write(fds[1], "hello", 5), _ret == 5);
+	TEST_RES(write(fds[1], "world!", 6), _ret == 6);
+
+	// The rest of a packet is discarded if the buffer is too small.
+	TEST_RES(read(fds[0], buf, 3), _ret == 3 && memcmp(buf, "hel", 3) == 0);
+	TEST_RES(read(fds[0], buf, sizeof(buf)),
+		 _ret == 6 && memcmp(buf, "world!", 6) == 0);
+}
+END_TEST()
+
+FN_TEST(packet_flag)
+{
+	TEST_RES(fcntl(fds[0], F_GETFL), !(_ret & O_DIRECT));
+	TEST_RES(fcntl(fds[1], F_GETFL), _ret & O_DIRECT);
+
+	// Without `O_DIRECT`, the writes are merged.
+	TEST_SUCC(fcntl(fds[1], F_SETFL, 0));
+	TEST_RES(write(fds[1], "hello", 5), _ret == 5);
+	TEST_RES(write(fds[1], "world!", 6), _ret == 6);
+	TEST_RES(read(fds[0], buf, sizeof(buf)),
+		 _ret == 11 && memcmp(buf, "helloworld!", 11) == 0);
+}
+END_TEST()
+
+FN_SETUP(cleanup_packet_pipe)
+{
+	CHECK(close(fds[0]));
+	CHECK(close(fds[1]));
+}
+END_SETUP()
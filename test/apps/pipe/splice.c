@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <signal.h>
+#include <string.h>
+#include <unistd.h>
+
+#include "../network/test.h"
+
+static int fds_a[2];
+static int fds_b[2];
+static char buf[64];
+
+FN_SETUP(pipes)
+{
+	CHECK(pipe(fds_a));
+	CHECK(pipe(fds_b));
+
+	// Writing to a pipe without readers should fail with `EPIPE`.
+	signal(SIGPIPE, SIG_IGN);
+}
+END_SETUP()
+
+FN_TEST(splice_move)
+{
+	TEST_RES(write(fds_a[1], "hello, world", 12), _ret == 12);
+
+	TEST_RES(splice(fds_a[0], NULL, fds_b[1], NULL, 5, 0), _ret == 5);
+	TEST_RES(read(fds_b[0], buf, sizeof(buf)),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+	TEST_RES(read(fds_a[0], buf, sizeof(buf)),
+		 _ret == 7 && memcmp(buf, ", world", 7) == 0);
+}
+END_TEST()
+
+FN_TEST(tee_dup)
+{
+	TEST_RES(write(fds_a[1], "hello", 5), _ret == 5);
+
+	TEST_RES(tee(fds_a[0], fds_b[1], sizeof(buf), 0), _ret == 5);
+	TEST_RES(read(fds_b[0], buf, sizeof(buf)),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+	TEST_RES(read(fds_a[0], buf, sizeof(buf)),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+}
+END_TEST()
+
+FN_TEST(tee_shared_page)
+{
+	TEST_RES(write(fds_a[1], "hello", 5), _ret == 5);
+	TEST_RES(tee(fds_a[0], fds_b[1], sizeof(buf), 0), _ret == 5);
+
+	// The page is shared, so new data must not be merged into it.
+	TEST_RES(write(fds_b[1], "!", 1), _ret == 1);
+	TEST_RES(write(fds_a[1], "?", 1), _ret == 1);
+
+	TEST_RES(read(fds_b[0], buf, sizeof(buf)),
+		 _ret == 6 && memcmp(buf, "hello!", 6) == 0);
+	TEST_RES(read(fds_a[0], buf, sizeof(buf)),
+		 _ret == 6 && memcmp(buf, "hello?", 6) == 0);
+}
+END_TEST()
+
+FN_TEST(nonblocking)
+{
+	TEST_ERRNO(splice(fds_a[0], NULL, fds_b[1], NULL, 5,
+			  SPLICE_F_NONBLOCK),
+		   EAGAIN);
+	TEST_ERRNO(tee(fds_a[0], fds_b[1], 5, SPLICE_F_NONBLOCK), EAGAIN);
+
+	TEST_SUCC(fcntl(fds_a[0], F_SETFL, O_NONBLOCK));
+	TEST_ERRNO(splice(fds_a[0], NULL, fds_b[1], NULL, 5, 0), EAGAIN);
+	TEST_SUCC(fcntl(fds_a[0], F_SETFL, 0));
+}
+END_TEST()
+
+FN_TEST(full_pipe)
+{
+	TEST_RES(fcntl(fds_b[1], F_SETPIPE_SZ, 4096), _ret == 4096);
+	TEST_RES(write(fds_b[1], "x", 1), _ret == 1);
+	TEST_RES(write(fds_a[1], "hello", 5), _ret == 5);
+
+	// The only page of the pipe is in use, so the pipe is full.
+	TEST_ERRNO(splice(fds_a[0], NULL, fds_b[1], NULL, 5,
+			  SPLICE_F_NONBLOCK),
+		   EAGAIN);
+	TEST_ERRNO(tee(fds_a[0], fds_b[1], 5, SPLICE_F_NONBLOCK), EAGAIN);
+
+	// But data can still be appended to the page.
+	TEST_RES(write(fds_b[1], "y", 1), _ret == 1);
+
+	TEST_RES(read(fds_b[0], buf, sizeof(buf)),
+		 _ret == 2 && memcmp(buf, "xy", 2) == 0);
+	TEST_RES(splice(fds_a[0], NULL, fds_b[1], NULL, 5, 0), _ret == 5);
+	TEST_RES(read(fds_b[0], buf, sizeof(buf)),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+}
+END_TEST()
+
+FN_TEST(invalid_args)
+{
+	loff_t off = 0;
+
+	TEST_RES(splice(fds_a[0], NULL, fds_b[1], NULL, 0, 0), _ret == 0);
+	TEST_ERRNO(splice(fds_a[0], NULL, fds_b[1], NULL, 5, 0x100), EINVAL);
+	TEST_ERRNO(tee(fds_a[0], fds_b[1], 5, 0x100), EINVAL);
+
+	TEST_ERRNO(splice(fds_a[1], NULL, fds_b[1], NULL, 5, 0), EBADF);
+	TEST_ERRNO(splice(fds_a[0], NULL, fds_b[0], NULL, 5, 0), EBADF);
+	TEST_ERRNO(tee(fds_a[1], fds_b[1], 5, 0), EBADF);
+
+	TEST_ERRNO(splice(fds_a[0], &off, fds_b[1], NULL, 5, 0), ESPIPE);
+	TEST_ERRNO(splice(fds_a[0], NULL, fds_b[1], &off, 5, 0), ESPIPE);
+
+	TEST_ERRNO(splice(fds_a[0], NULL, fds_a[1], NULL, 5, 0), EINVAL);
+	TEST_ERRNO(tee(fds_a[0], fds_a[1], 5, 0), EINVAL);
+}
+END_TEST()
+
+FN_TEST(closed_ends)
+{
+	int fds_c[2];
+
+	TEST_SUCC(pipe(fds_c));
+	TEST_SUCC(close(fds_c[1]));
+	TEST_RES(splice(fds_c[0], NULL, fds_b[1], NULL, 5, 0), _ret == 0);
+	TEST_RES(tee(fds_c[0], fds_b[1], 5, 0), _ret == 0);
+	TEST_SUCC(close(fds_c[0]));
+
+	TEST_RES(write(fds_a[1], "hello", 5), _ret == 5);
+	TEST_SUCC(close(fds_b[0]));
+	TEST_ERRNO(splice(fds_a[0], NULL, fds_b[1], NULL, 5, 0), EPIPE);
+	TEST_ERRNO(tee(fds_a[0], fds_b[1], 5, 0), EPIPE);
+	TEST_RES(read(fds_a[0], buf, sizeof(buf)),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(fds_a[0]));
+	CHECK(close(fds_a[1]));
+	CHECK(close(fds_b[1]));
+}
+END_SETUP()
@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <linux/capability.h>
+#include <signal.h>
+#include <sys/mman.h>
+#include <sys/syscall.h>
+#include <sys/uio.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../network/test.h"
+
+#define PAGE_SIZE 4096
+#define NOBODY 65534
+
+static pid_t target;
+static char *region;
+static char buf[32];
+
+FN_SETUP(target)
+{
+	int fds[2];
+	char c;
+
+	// The second page is unmapped, so the first page ends at a hole.
+	region = (char *)CHECK_WITH(
+		(long)mmap(NULL, PAGE_SIZE * 2, PROT_READ | PROT_WRITE,
+			   MAP_PRIVATE | MAP_ANONYMOUS, -1, 0),
+		_ret != (long)MAP_FAILED);
+	CHECK(munmap(region + PAGE_SIZE, PAGE_SIZE));
+	CHECK(pipe(fds));
+
+	target = CHECK(fork());
+	if (target == 0) {
+		memset(region, 'a', PAGE_SIZE);
+		write(fds[1], "", 1);
+		for (;;)
+			pause();
+	}
+
+	CHECK_WITH(read(fds[0], &c, 1), _ret == 1);
+	CHECK(close(fds[0]));
+	CHECK(close(fds[1]));
+}
+END_SETUP()
+
+static ssize_t read_target(pid_t pid, void *local, void *remote, size_t len)
+{
+	struct iovec local_iov = { .iov_base = local, .iov_len = len };
+	struct iovec remote_iov = { .iov_base = remote, .iov_len = len };
+
+	return process_vm_readv(pid, &local_iov, 1, &remote_iov, 1, 0);
+}
+
+static ssize_t write_target(pid_t pid, void *local, void *remote, size_t len)
+{
+	struct iovec local_iov = { .iov_base = local, .iov_len = len };
+	struct iovec remote_iov = { .iov_base = remote, .iov_len = len };
+
+	return process_vm_writev(pid, &local_iov, 1, &remote_iov, 1, 0);
+}
+
+FN_TEST(read_write)
+{
+	// The target's copy of the region is not affected by the local one.
+	TEST_RES(read_target(target, buf, region, 16),
+		 _ret == 16 && memcmp(buf, "aaaaaaaaaaaaaaaa", 16) == 0);
+
+	TEST_RES(write_target(target, "hello", region, 5), _ret == 5);
+	TEST_RES(read_target(target, buf, region, 8),
+		 _ret == 8 && memcmp(buf, "helloaaa", 8) == 0);
+}
+END_TEST()
+
+FN_TEST(partial)
+{
+	// Only the bytes before the hole are transferred.
+	TEST_RES(read_target(target, buf, region + PAGE_SIZE - 8, 16),
+		 _ret == 8 && memcmp(buf, "aaaaaaaa", 8) == 0);
+	TEST_RES(write_target(target, "01234567abcdefgh",
+			      region + PAGE_SIZE - 8, 16),
+		 _ret == 8);
+	TEST_RES(read_target(target, buf, region + PAGE_SIZE - 8, 8),
+		 _ret == 8 && memcmp(buf, "01234567", 8) == 0);
+}
+END_TEST()
+
+FN_TEST(efault)
+{
+	// Nothing can be transferred from or to the hole.
+	TEST_ERRNO(read_target(target, buf, region + PAGE_SIZE, 8), EFAULT);
+	TEST_ERRNO(write_target(target, buf, region + PAGE_SIZE, 8), EFAULT);
+
+	// The local buffer is in the hole.
+	TEST_ERRNO(read_target(target, region + PAGE_SIZE, region, 8), EFAULT);
+}
+END_TEST()
+
+FN_TEST(esrch)
+{
+	TEST_ERRNO(read_target(0x7fffffff, buf, region, 8), ESRCH);
+}
+END_TEST()
+
+static int read_as_nobody(void)
+{
+	struct __user_cap_header_struct header = {
+		.version = _LINUX_CAPABILITY_VERSION_3,
+		.pid = 0,
+	};
+	struct __user_cap_data_struct data[2];
+
+	// The target runs as root, but this process does not.
+	memset(data, 0, sizeof(data));
+	if (syscall(SYS_setresgid, NOBODY, NOBODY, NOBODY) < 0 ||
+	    syscall(SYS_setresuid, NOBODY, NOBODY, NOBODY) < 0 ||
+	    syscall(SYS_capset, &header, data) < 0)
+		return 1;
+
+	if (read_target(target, buf, region, 8) >= 0 || errno != EPERM)
+		return 1;
+	return 0;
+}
+
+static int run_as_nobody(void)
+{
+	int status;
+	pid_t pid;
+
+	pid = fork();
+	if (pid < 0)
+		return -1;
+	if (pid == 0)
+		_exit(read_as_nobody());
+
+	if (waitpid(pid, &status, 0) < 0)
+		return -1;
+	return WIFEXITED(status) ? WEXITSTATUS(status) : -1;
+}
+
+FN_TEST(eperm)
+{
+	TEST_RES(run_as_nobody(), _ret == 0);
+}
+END_TEST()
+
+FN_SETUP(kill_target)
+{
+	CHECK(kill(target, SIGKILL));
+	CHECK_WITH(waitpid(target, NULL, 0), _ret == target);
+}
+END_SETUP()
@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <linux/fs.h>
+#include <sys/stat.h>
+#include <sys/syscall.h>
+#include <unistd.h>
+
+#include "../network/test.h"
+
+#ifndef RAMFS_DIR
+#define RAMFS_DIR "/tmp/renameat2"
+#endif
+
+#ifndef EXT2_DIR
+#define EXT2_DIR "/ext2/renameat2"
+#endif
+
+static char buf[16];
+static struct stat st;
+static ino_t ino;
+
+static int sys_renameat2(const char *oldpath, const char *newpath,
+			 unsigned int flags)
+{
+	return syscall(SYS_renameat2, AT_FDCWD, oldpath, AT_FDCWD, newpath,
+		       flags);
+}
+
+static int create_file(const char *path, const char *content)
+{
+	int fd;
+
+	fd = open(path, O_WRONLY | O_CREAT | O_TRUNC, 0644);
+	if (fd < 0)
+		return -1;
+	if (write(fd, content, strlen(content)) != strlen(content)) {
+		close(fd);
+		return -1;
+	}
+	return close(fd);
+}
+
+static int read_file(const char *path)
+{
+	int fd;
+	int len;
+
+	fd = open(path, O_RDONLY);
+	if (fd < 0)
+		return -1;
+	memset(buf, 0, sizeof(buf));
+	len = read(fd, buf, sizeof(buf) - 1);
+	close(fd);
+	return len;
+}
+
+FN_SETUP(files)
+{
+	CHECK(mkdir(RAMFS_DIR, 0755));
+	CHECK(create_file(RAMFS_DIR "/a", "aaa"));
+	CHECK(create_file(RAMFS_DIR "/b", "bb"));
+
+	CHECK(mkdir(EXT2_DIR, 0755));
+	CHECK(create_file(EXT2_DIR "/a", "aaa"));
+	CHECK(create_file(EXT2_DIR "/b", "bb"));
+	CHECK(mkdir(EXT2_DIR "/d", 0755));
+	CHECK(mkdir(EXT2_DIR "/sub", 0755));
+	CHECK(create_file(EXT2_DIR "/sub/c", "c"));
+	CHECK(create_file(EXT2_DIR "/sub/f", "f"));
+}
+END_SETUP()
+
+FN_TEST(noreplace)
+{
+	TEST_ERRNO(sys_renameat2(RAMFS_DIR "/a", RAMFS_DIR "/b",
+				 RENAME_NOREPLACE),
+		   EEXIST);
+	TEST_ERRNO(sys_renameat2(RAMFS_DIR "/a", RAMFS_DIR "/a",
+				 RENAME_NOREPLACE),
+		   EEXIST);
+	TEST_SUCC(sys_renameat2(RAMFS_DIR "/a", RAMFS_DIR "/n",
+				RENAME_NOREPLACE));
+	TEST_RES(read_file(RAMFS_DIR "/n"), _ret == 3 && !strcmp(buf, "aaa"));
+	TEST_SUCC(sys_renameat2(RAMFS_DIR "/n", RAMFS_DIR "/a",
+				RENAME_NOREPLACE));
+
+	TEST_ERRNO(sys_renameat2(EXT2_DIR "/a", EXT2_DIR "/b",
+				 RENAME_NOREPLACE),
+		   EEXIST);
+	TEST_ERRNO(sys_renameat2(EXT2_DIR "/a", EXT2_DIR "/sub/c",
+				 RENAME_NOREPLACE),
+		   EEXIST);
+	TEST_SUCC(sys_renameat2(EXT2_DIR "/a", EXT2_DIR "/sub/n",
+				RENAME_NOREPLACE));
+	TEST_RES(read_file(EXT2_DIR "/sub/n"),
+		 _ret == 3 && !strcmp(buf, "aaa"));
+	TEST_SUCC(sys_renameat2(EXT2_DIR "/sub/n", EXT2_DIR "/a",
+				RENAME_NOREPLACE));
+}
+END_TEST()
+
+FN_TEST(exchange)
+{
+	TEST_SUCC(sys_renameat2(RAMFS_DIR "/a", RAMFS_DIR "/b",
+				RENAME_EXCHANGE));
+	TEST_RES(read_file(RAMFS_DIR "/a"), _ret == 2 && !strcmp(buf, "bb"));
+	TEST_RES(read_file(RAMFS_DIR "/b"), _ret == 3 && !strcmp(buf, "aaa"));
+	TEST_ERRNO(sys_renameat2(RAMFS_DIR "/a", RAMFS_DIR "/n",
+				 RENAME_EXCHANGE),
+		   ENOENT);
+	TEST_ERRNO(sys_renameat2(RAMFS_DIR "/a", RAMFS_DIR "/b",
+				 RENAME_EXCHANGE | RENAME_NOREPLACE),
+		   EINVAL);
+
+	TEST_SUCC(sys_renameat2(EXT2_DIR "/a", EXT2_DIR "/b",
+				RENAME_EXCHANGE));
+	TEST_RES(read_file(EXT2_DIR "/a"), _ret == 2 && !strcmp(buf, "bb"));
+	TEST_RES(read_file(EXT2_DIR "/b"), _ret == 3 && !strcmp(buf, "aaa"));
+	TEST_SUCC(sys_renameat2(EXT2_DIR "/a", EXT2_DIR "/sub/c",
+				RENAME_EXCHANGE));
+	TEST_RES(read_file(EXT2_DIR "/a"), _ret == 1 && !strcmp(buf, "c"));
+	TEST_RES(read_file(EXT2_DIR "/sub/c"),
+		 _ret == 2 && !strcmp(buf, "bb"));
+	TEST_ERRNO(sys_renameat2(EXT2_DIR "/a", EXT2_DIR "/sub/n",
+				 RENAME_EXCHANGE),
+		   ENOENT);
+}
+END_TEST()
+
+FN_TEST(exchange_dir)
+{
+	TEST_SUCC(sys_renameat2(EXT2_DIR "/d", EXT2_DIR "/sub/f",
+				RENAME_EXCHANGE));
+	TEST_RES(read_file(EXT2_DIR "/d"), _ret == 1 && !strcmp(buf, "f"));
+
+	// The links of the parent directories must follow the moved directory
+	TEST_RES(stat(EXT2_DIR, &st), st.st_nlink == 3);
+	TEST_RES(stat(EXT2_DIR "/sub", &st), st.st_nlink == 3);
+	ino = st.st_ino;
+	TEST_RES(stat(EXT2_DIR "/sub/f/..", &st),
+		 S_ISDIR(st.st_mode) && st.st_ino == ino);
+}
+END_TEST()
+
+FN_TEST(whiteout)
+{
+	TEST_SUCC(sys_renameat2(RAMFS_DIR "/a", RAMFS_DIR "/w",
+				RENAME_WHITEOUT));
+	TEST_RES(read_file(RAMFS_DIR "/w"), _ret == 2 && !strcmp(buf, "bb"));
+	TEST_RES(stat(RAMFS_DIR "/a", &st),
+		 S_ISCHR(st.st_mode) && st.st_rdev == 0);
+	TEST_ERRNO(open(RAMFS_DIR "/a", O_RDONLY), ENXIO);
+	TEST_ERRNO(sys_renameat2(RAMFS_DIR "/w", RAMFS_DIR "/b",
+				 RENAME_EXCHANGE | RENAME_WHITEOUT),
+		   EINVAL);
+
+	TEST_ERRNO(sys_renameat2(EXT2_DIR "/a", EXT2_DIR "/w",
+				 RENAME_WHITEOUT),
+		   EINVAL);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(unlink(RAMFS_DIR "/a"));
+	CHECK(unlink(RAMFS_DIR "/b"));
+	CHECK(unlink(RAMFS_DIR "/w"));
+	CHECK(rmdir(RAMFS_DIR));
+
+	CHECK(unlink(EXT2_DIR "/a"));
+	CHECK(unlink(EXT2_DIR "/b"));
+	CHECK(unlink(EXT2_DIR "/d"));
+	CHECK(unlink(EXT2_DIR "/sub/c"));
+	CHECK(rmdir(EXT2_DIR "/sub/f"));
+	CHECK(rmdir(EXT2_DIR "/sub"));
+	CHECK(rmdir(EXT2_DIR));
+}
+END_SETUP()
@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <sched.h>
+#include <stdint.h>
+#include <string.h>
+#include <sys/resource.h>
+#include <sys/syscall.h>
+#include <unistd.h>
+
+#include "../network/test.h"
+
+// The same as `struct sched_attr` in Linux.
+struct sched_attr_v1 {
+	uint32_t size;
+	uint32_t sched_policy;
+	uint64_t sched_flags;
+	int32_t sched_nice;
+	uint32_t sched_priority;
+	uint64_t sched_runtime;
+	uint64_t sched_deadline;
+	uint64_t sched_period;
+	uint32_t sched_util_min;
+	uint32_t sched_util_max;
+};
+
+#define SCHED_ATTR_SIZE_VER0 48
+
+static struct sched_attr_v1 attr;
+
+static int sched_getattr(pid_t pid, unsigned int size, unsigned int flags)
+{
+	return syscall(SYS_sched_getattr, pid, &attr, size, flags);
+}
+
+FN_SETUP(nice)
+{
+	CHECK(setpriority(PRIO_PROCESS, 0, 5));
+}
+END_SETUP()
+
+FN_TEST(getattr)
+{
+	memset(&attr, 0xff, sizeof(attr));
+	TEST_RES(sched_getattr(0, sizeof(attr), 0),
+		 attr.size == sizeof(attr) &&
+			 attr.sched_policy == SCHED_OTHER &&
+			 attr.sched_nice == 5 && attr.sched_priority == 0);
+
+	memset(&attr, 0xff, sizeof(attr));
+	TEST_RES(sched_getattr(getpid(), sizeof(attr), 0),
+		 attr.size == sizeof(attr) && attr.sched_nice == 5);
+}
+END_TEST()
+
+FN_TEST(old_size)
+{
+	memset(&attr, 0xff, sizeof(attr));
+	TEST_RES(sched_getattr(0, SCHED_ATTR_SIZE_VER0, 0),
+		 attr.size == SCHED_ATTR_SIZE_VER0 && attr.sched_nice == 5 &&
+			 attr.sched_util_min == UINT32_MAX &&
+			 attr.sched_util_max == UINT32_MAX);
+}
+END_TEST()
+
+FN_TEST(invalid)
+{
+	TEST_ERRNO(sched_getattr(0, SCHED_ATTR_SIZE_VER0 - 1, 0), EINVAL);
+	TEST_ERRNO(sched_getattr(0, 4097, 0), EINVAL);
+	TEST_ERRNO(sched_getattr(0, sizeof(attr), 1), EINVAL);
+	TEST_ERRNO(sched_getattr(-1, sizeof(attr), 0), EINVAL);
+	TEST_ERRNO(syscall(SYS_sched_getattr, 0, NULL, sizeof(attr), 0),
+		   EINVAL);
+	TEST_ERRNO(sched_getattr(0x7fffffff, sizeof(attr), 0), ESRCH);
+}
+END_TEST()
@@ -53,10 +53,66 @@ test_fdatasync() {
     rm -f /exfat/test_fdatasync.txt
 }
 
+test_aio() {
+    aio/aio
+}
+
+test_renameat2() {
+    renameat2/renameat2
+}
+
+test_utimensat() {
+    utimensat/utimensat
+}
+
+test_pipe_size() {
+    pipe/pipe_size
+}
+
+test_splice() {
+    pipe/splice
+}
+
+test_mount_flags() {
+    mount/mount_flags
+}
+
+test_openat2() {
+    openat2/openat2
+}
+
 echo "Start ext2 fs test......"
 test_ext2 "/ext2" "test_file.txt"
 echo "All ext2 fs test passed."
 
 echo "Start fdatasync test......"
 test_fdatasync
-echo "All fdatasync test passed."
\ No newline at end of file
+echo "All fdatasync test passed."
+
+echo "Start aio test......"
+test_aio
+echo "All aio test passed."
+
+echo "Start renameat2 test......"
+test_renameat2
+echo "All renameat2 test passed."
+
+echo "Start utimensat test......"
+test_utimensat
+echo "All utimensat test passed."
+
+echo "Start pipe size test......"
+test_pipe_size
+echo "All pipe size test passed."
+
+echo "Start splice test......"
+test_splice
+echo "All splice test passed."
+
+echo "Start mount flags test......"
+test_mount_flags
+echo "All mount flags test passed."
+
+echo "Start openat2 test......"
+test_openat2
+echo "All openat2 test passed."
@@ -23,5 +23,7 @@ echo "Start network test......"
 ./http_client
 ./tcp_err
 ./udp_err
+./mmsg
+./udp_sockopt
 
 echo "All network test passed"
@@ -11,6 +11,8 @@ echo "Start process test......"
 # These test programs are sorted by name.
 tests="
 clone3/clone_process
+close_range/close_range
+dup/dup
 execve/execve
 eventfd2/eventfd2
 fork/fork
@@ -21,8 +23,10 @@ hello_world/hello_world
 itimer/setitimer
 itimer/timer_create
 mmap/mmap_and_fork
+process_vm/process_vm
 pthread/pthread_test
 pty/open_pty
+sched_getattr/sched_getattr
 signal_c/parent_death_signal
 signal_c/signal_test
 "
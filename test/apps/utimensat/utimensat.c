@@ -0,0 +1,125 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <sys/stat.h>
+#include <time.h>
+#include <unistd.h>
+
+#include "../network/test.h"
+
+#ifndef FILE_PATH
+#define FILE_PATH "/ext2/test_utimensat.txt"
+#endif
+
+#define NOBODY 65534
+
+static struct stat st;
+static struct stat old_st;
+static struct timespec now;
+
+static int set_times(long atime_sec, long atime_nsec, long mtime_sec,
+		     long mtime_nsec)
+{
+	struct timespec times[2] = {
+		{ .tv_sec = atime_sec, .tv_nsec = atime_nsec },
+		{ .tv_sec = mtime_sec, .tv_nsec = mtime_nsec },
+	};
+
+	return utimensat(AT_FDCWD, FILE_PATH, times, 0);
+}
+
+static int is_same_time(const struct timespec *a, const struct timespec *b)
+{
+	return a->tv_sec == b->tv_sec && a->tv_nsec == b->tv_nsec;
+}
+
+FN_SETUP(file)
+{
+	CHECK(creat(FILE_PATH, 0644));
+}
+END_SETUP()
+
+FN_TEST(explicit_times)
+{
+	TEST_SUCC(set_times(1, 123456789, 2, 987654321));
+	TEST_RES(stat(FILE_PATH, &st),
+		 st.st_atim.tv_sec == 1 && st.st_atim.tv_nsec == 123456789 &&
+			 st.st_mtim.tv_sec == 2 &&
+			 st.st_mtim.tv_nsec == 987654321);
+
+	TEST_ERRNO(set_times(1, 1000000000, 2, 0), EINVAL);
+	TEST_ERRNO(set_times(1, -1, 2, 0), EINVAL);
+}
+END_TEST()
+
+FN_TEST(omit_and_now)
+{
+	CHECK(stat(FILE_PATH, &old_st));
+	// The file times are taken from the coarse clock
+	CHECK(clock_gettime(CLOCK_REALTIME_COARSE, &now));
+
+	TEST_SUCC(set_times(0, UTIME_OMIT, 0, UTIME_NOW));
+	TEST_RES(stat(FILE_PATH, &st),
+		 is_same_time(&st.st_atim, &old_st.st_atim) &&
+			 st.st_mtim.tv_sec >= now.tv_sec &&
+			 st.st_ctim.tv_sec >= now.tv_sec);
+
+	TEST_SUCC(set_times(0, UTIME_NOW, 5, 0));
+	TEST_RES(stat(FILE_PATH, &st),
+		 st.st_atim.tv_sec >= now.tv_sec && st.st_mtim.tv_sec == 5 &&
+			 st.st_mtim.tv_nsec == 0);
+}
+END_TEST()
+
+FN_TEST(omit_both)
+{
+	CHECK(stat(FILE_PATH, &old_st));
+
+	// Nothing is changed, not even the ctime
+	TEST_SUCC(set_times(0, UTIME_OMIT, 0, UTIME_OMIT));
+	TEST_RES(stat(FILE_PATH, &st),
+		 is_same_time(&st.st_atim, &old_st.st_atim) &&
+			 is_same_time(&st.st_mtim, &old_st.st_mtim) &&
+			 is_same_time(&st.st_ctim, &old_st.st_ctim));
+
+	// The path is not even looked up
+	struct timespec times[2] = {
+		{ .tv_nsec = UTIME_OMIT },
+		{ .tv_nsec = UTIME_OMIT },
+	};
+	TEST_SUCC(utimensat(AT_FDCWD, "/nonexistent", times, 0));
+}
+END_TEST()
+
+FN_TEST(not_owner)
+{
+	CHECK(chmod(FILE_PATH, 0644));
+	CHECK(seteuid(NOBODY));
+
+	TEST_ERRNO(set_times(1, 0, 2, 0), EPERM);
+	TEST_ERRNO(set_times(0, UTIME_NOW, 0, UTIME_NOW), EACCES);
+	TEST_ERRNO(utimensat(AT_FDCWD, FILE_PATH, NULL, 0), EACCES);
+	// Omitting both times does not need any permission
+	TEST_SUCC(set_times(0, UTIME_OMIT, 0, UTIME_OMIT));
+
+	CHECK(seteuid(0));
+	CHECK(chmod(FILE_PATH, 0666));
+	CHECK(seteuid(NOBODY));
+
+	TEST_SUCC(set_times(0, UTIME_NOW, 0, UTIME_NOW));
+	TEST_SUCC(utimensat(AT_FDCWD, FILE_PATH, NULL, 0));
+	// Only both times can be set to the current time
+	TEST_ERRNO(set_times(0, UTIME_NOW, 0, UTIME_OMIT), EPERM);
+	TEST_ERRNO(set_times(0, UTIME_NOW, 2, 0), EPERM);
+
+	CHECK(seteuid(0));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(unlink(FILE_PATH));
+}
+END_SETUP()